@@ -0,0 +1,30 @@
+use std::fs;
+
+use anyhow::{Context, Result};
+use mmb_lib::core::{
+    backtesting::launcher::run_configured_backtest,
+    config::{load_settings, CONFIG_PATH, CREDENTIALS_PATH},
+    lifecycle::launcher::EngineBuildConfig,
+    logger::init_logger,
+    settings::ConfiguredStrategySettings,
+};
+
+const USAGE: &str = "Usage: backtest <recorded raw messages file> <report file>";
+
+/// Backtest the first strategy from config on raw messages recorded from its exchange
+/// and write the report as JSON
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let raw_messages_path = args.next().context(USAGE)?;
+    let report_path = args.next().context(USAGE)?;
+
+    init_logger();
+
+    let settings = load_settings::<ConfiguredStrategySettings>(CONFIG_PATH, CREDENTIALS_PATH)?;
+    let report =
+        run_configured_backtest(EngineBuildConfig::standard(), settings, &raw_messages_path)?;
+
+    let report = serde_json::to_string_pretty(&report).context("Unable to serialize report")?;
+    fs::write(&report_path, report)
+        .with_context(|| format!("Unable to write report to {}", report_path))
+}
@@ -0,0 +1,657 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use log::{error, warn};
+use rust_decimal_macros::dec;
+use tokio::sync::broadcast::error::TryRecvError;
+use tokio::sync::{broadcast, oneshot};
+
+use super::backtest_report::{BacktestFill, BacktestReport};
+use crate::core::balance_manager::balance_manager::BalanceManager;
+use crate::core::disposition_execution::executor::DispositionExecutor;
+use crate::core::disposition_execution::{PriceSlotId, TradingContext};
+use crate::core::exchanges::common::{Amount, Price, TradePlaceAccount};
+use crate::core::exchanges::events::{
+    ExchangeBalance, ExchangeBalancesAndPositions, ExchangeEvent, ExchangeEvents,
+    CHANNEL_MAX_EVENTS_COUNT,
+};
+use crate::core::exchanges::exchange_blocker::ExchangeBlocker;
+use crate::core::exchanges::general::currency_pair_metadata::CurrencyPairMetadata;
+use crate::core::exchanges::general::currency_pair_to_metadata_converter::CurrencyPairToMetadataConverter;
+use crate::core::exchanges::general::exchange::{BoxExchangeClient, Exchange};
+use crate::core::exchanges::paper::paper_exchange::{
+    paper_exchange_features, PaperExchange, PaperExchangeSettings, PaperMarketDataHandler,
+};
+use crate::core::exchanges::timeouts::requests_timeout_manager_factory::{
+    RequestTimeoutArguments, RequestsTimeoutManagerFactory,
+};
+use crate::core::exchanges::timeouts::timeout_manager::TimeoutManager;
+use crate::core::exchanges::traits::ExchangeClientBuilder;
+use crate::core::lifecycle::application_manager::ApplicationManager;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::lifecycle::trading_engine::EngineContext;
+use crate::core::misc::time_manager::{time_manager, VirtualClock};
+use crate::core::order_book::local_snapshot_service::LocalSnapshotsService;
+use crate::core::orders::event::OrderEventType;
+use crate::core::orders::order::OrderStatus;
+use crate::core::service_configuration::configuration_descriptor::ConfigurationDescriptor;
+use crate::core::settings::{CoreSettings, ExchangeSettings};
+use crate::core::statistic_service::{StatisticEventHandler, StatisticService};
+use crate::core::DateTime;
+use crate::strategies::disposition_strategy::DispositionStrategy;
+
+static BACKTEST_ENGINE: &str = "BacktestEngine";
+/// Max count of passes over spawned futures to wait for completion of requests to paper exchange after an event
+const MAX_SETTLING_ITERATIONS: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct BacktestSettings {
+    pub app_settings: CoreSettings,
+    pub exchange_settings: ExchangeSettings,
+    pub currency_pair_metadata: Arc<CurrencyPairMetadata>,
+    /// Max amount of orders by side which strategy is allowed to place
+    pub max_amount: Amount,
+    pub price_slots: Vec<PriceSlotId>,
+    /// Simulation parameters of paper exchange including commission and initial balances
+    pub paper_settings: PaperExchangeSettings,
+}
+
+pub type CreateStrategy =
+    Box<dyn FnOnce(Arc<EngineContext>) -> Result<Box<dyn DispositionStrategy>>>;
+
+/// Replays recorded market data through `DispositionExecutor` with `DispositionStrategy`
+/// and orders of `PaperExchange`, so orders are handled in the same way as in live trading.
+/// Events have to be sorted by time, because time of each event is used as `time_manager::now()`
+pub struct BacktestEngine {
+    settings: BacktestSettings,
+    market_data_builder: Box<dyn ExchangeClientBuilder>,
+    create_strategy: CreateStrategy,
+}
+
+impl BacktestEngine {
+    /// `market_data_builder` creates client of the exchange which market data are replayed.
+    /// `create_strategy` is called with context of the backtest engine like strategy builders in live trading
+    pub fn new(
+        settings: BacktestSettings,
+        market_data_builder: Box<dyn ExchangeClientBuilder>,
+        create_strategy: CreateStrategy,
+    ) -> Self {
+        BacktestEngine {
+            settings,
+            market_data_builder,
+            create_strategy,
+        }
+    }
+
+    /// Backtest is run on its own single threaded runtime, so virtual time is used by all spawned futures.
+    /// It shouldn't be called from async context
+    pub fn run(self, events: impl IntoIterator<Item = ExchangeEvent>) -> Result<BacktestReport> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .expect("Unable to build runtime for backtesting");
+
+        runtime.block_on(async move {
+            let mut events = events.into_iter().filter_map(|event| {
+                let time = event_time(&event)?;
+                Some((time, event))
+            });
+
+            let (start_time, first_event) = match events.next() {
+                Some(first_event) => first_event,
+                None => {
+                    let statistics = StatisticService::new();
+                    let currency_pair_metadata = &self.settings.currency_pair_metadata;
+                    return Ok(BacktestReport::new(statistics, currency_pair_metadata));
+                }
+            };
+
+            let virtual_clock = VirtualClock::start(start_time);
+            let mut backtest = Backtest::new(
+                self.settings,
+                self.market_data_builder,
+                self.create_strategy,
+            )?;
+
+            backtest.handle_event(first_event, start_time).await;
+            for (time, event) in events {
+                virtual_clock.set_now(time);
+                backtest.handle_event(event, time).await;
+            }
+
+            Ok(backtest.finish())
+        })
+    }
+}
+
+fn event_time(event: &ExchangeEvent) -> Option<DateTime> {
+    match event {
+        ExchangeEvent::OrderBookEvent(order_book_event) => Some(order_book_event.creation_time),
+        ExchangeEvent::Trades(trades_event) => Some(trades_event.receipt_time),
+        _ => None,
+    }
+}
+
+struct Backtest {
+    exchange: Arc<Exchange>,
+    market_data_handler: PaperMarketDataHandler,
+    executor: DispositionExecutor,
+    trading_context: Option<TradingContext>,
+    events_receiver: broadcast::Receiver<ExchangeEvent>,
+    statistic_event_handler: StatisticEventHandler,
+    // Used to calculate PnL by middle price of the order book
+    local_snapshots_service: LocalSnapshotsService,
+    currency_pair_metadata: Arc<CurrencyPairMetadata>,
+    report: BacktestReport,
+}
+
+impl Backtest {
+    fn new(
+        settings: BacktestSettings,
+        market_data_builder: Box<dyn ExchangeClientBuilder>,
+        create_strategy: CreateStrategy,
+    ) -> Result<Self> {
+        let exchange_account_id = settings.exchange_settings.exchange_account_id.clone();
+        let currency_pair_metadata = settings.currency_pair_metadata.clone();
+        let application_manager = ApplicationManager::new(CancellationToken::new());
+        let (events_sender, events_receiver) = broadcast::channel(CHANNEL_MAX_EVENTS_COUNT);
+
+        let market_data_client = market_data_builder
+            .create_exchange_client(
+                settings.exchange_settings.clone(),
+                events_sender.clone(),
+                application_manager.clone(),
+            )
+            .client;
+        let (paper_exchange, market_data_handler) = PaperExchange::with_market_data_handler(
+            exchange_account_id.clone(),
+            settings.exchange_settings.clone(),
+            settings.paper_settings.clone(),
+            market_data_client,
+        );
+        paper_exchange.add_symbol(currency_pair_metadata.clone());
+
+        // There are no real requests in backtesting
+        let timeout_manager = TimeoutManager::new(HashMap::from([(
+            exchange_account_id.clone(),
+            RequestsTimeoutManagerFactory::from_requests_per_period(
+                RequestTimeoutArguments::unlimited(),
+                exchange_account_id.clone(),
+                None,
+            ),
+        )]));
+        let exchange_blocker = ExchangeBlocker::new(vec![exchange_account_id.clone()]);
+
+        let exchange = Exchange::new(
+            exchange_account_id.clone(),
+            Box::new(paper_exchange) as BoxExchangeClient,
+            paper_exchange_features(),
+            RequestTimeoutArguments::unlimited(),
+            events_sender.clone(),
+            application_manager.clone(),
+            timeout_manager.clone(),
+            settings.paper_settings.commission.clone(),
+            None,
+            Some(exchange_blocker.clone()),
+        );
+        exchange.set_known_symbols(vec![currency_pair_metadata.clone()]);
+
+        let exchanges_by_id = HashMap::from([(exchange_account_id.clone(), exchange.clone())]);
+        let balance_manager = BalanceManager::new(
+            exchanges_by_id.clone(),
+            CurrencyPairToMetadataConverter::new(exchanges_by_id),
+            None,
+        );
+        let initial_balances = ExchangeBalancesAndPositions {
+            balances: settings
+                .paper_settings
+                .balances
+                .iter()
+                .map(|(currency_code, balance)| ExchangeBalance {
+                    currency_code: currency_code.clone(),
+                    balance: *balance,
+                })
+                .collect(),
+            positions: None,
+        };
+        if let Err(error) = balance_manager
+            .lock()
+            .update_exchange_balance(&exchange_account_id, &initial_balances)
+        {
+            error!(
+                "Unable to set initial balances for backtesting: {:?}",
+                error
+            );
+        }
+
+        let engine_ctx = EngineContext::new(
+            settings.app_settings,
+            vec![(exchange_account_id.clone(), exchange.clone())]
+                .into_iter()
+                .collect(),
+            ExchangeEvents::new(events_sender),
+            oneshot::channel().0,
+            timeout_manager,
+            application_manager,
+            None,
+            balance_manager,
+            exchange_blocker,
+        );
+        let strategy = create_strategy(engine_ctx.clone())?;
+
+        let statistics = StatisticService::new();
+        let currency_pair = currency_pair_metadata.currency_pair();
+        let executor = DispositionExecutor::new(
+            engine_ctx,
+            // Events are passed to the executor directly after handling them by the paper exchange
+            broadcast::channel(1).1,
+            LocalSnapshotsService::default(),
+            exchange_account_id.clone(),
+            currency_pair.clone(),
+            settings.max_amount,
+            &settings.price_slots,
+            Arc::new(ConfigurationDescriptor::new(
+                BACKTEST_ENGINE.to_owned(),
+                format!("{};{}", exchange_account_id, currency_pair),
+            )),
+            strategy,
+            oneshot::channel().0,
+            CancellationToken::new(),
+            statistics.clone(),
+        );
+
+        Ok(Backtest {
+            exchange,
+            market_data_handler,
+            executor,
+            trading_context: None,
+            events_receiver,
+            statistic_event_handler: StatisticEventHandler {
+                stats: statistics.clone(),
+            },
+            local_snapshots_service: LocalSnapshotsService::default(),
+            report: BacktestReport::new(statistics, &currency_pair_metadata),
+            currency_pair_metadata,
+        })
+    }
+
+    async fn handle_event(&mut self, event: ExchangeEvent, now: DateTime) {
+        if let ExchangeEvent::OrderBookEvent(order_book_event) = &event {
+            let _ = self
+                .local_snapshots_service
+                .update(order_book_event.clone());
+        }
+
+        // Orders are matched before the strategy sees the event like on the real exchange
+        self.market_data_handler.handle_event(event.clone());
+        let fills_count = self.report.fills.len();
+        self.handle_order_events().await;
+
+        if let Err(error) = self.executor.handle_event(event, &mut self.trading_context) {
+            error!(
+                "Failed to handle event by DispositionExecutor in backtesting: {:?}",
+                error
+            );
+        }
+        self.handle_order_events().await;
+
+        if self.report.fills.len() > fills_count {
+            let middle_price = self.middle_price();
+            self.report.add_inventory_point(now, middle_price);
+        }
+    }
+
+    /// Handle events of orders until requests to paper exchange spawned by executor and strategy are completed
+    async fn handle_order_events(&mut self) {
+        for _ in 0..MAX_SETTLING_ITERATIONS {
+            let _ = tokio::task::yield_now().await;
+
+            let mut has_events = false;
+            loop {
+                match self.events_receiver.try_recv() {
+                    Ok(event) => {
+                        has_events = true;
+                        self.handle_order_event(event);
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Lagged(skipped_count)) => {
+                        error!("Backtest skipped {} events of orders", skipped_count)
+                    }
+                    Err(TryRecvError::Closed) => return,
+                }
+            }
+
+            if !has_events && !self.has_requests_in_progress() {
+                return;
+            }
+        }
+
+        warn!("Backtest didn't wait for completion of requests to paper exchange");
+    }
+
+    fn handle_order_event(&mut self, event: ExchangeEvent) {
+        if let ExchangeEvent::OrderEvent(order_event) = &event {
+            if let OrderEventType::OrderFilled { cloned_order } = &order_event.event_type {
+                if let Some(fill) = cloned_order.fills.fills.last() {
+                    self.report.add_fill(BacktestFill {
+                        time: time_manager::now(),
+                        client_order_id: cloned_order.header.client_order_id.clone(),
+                        side: cloned_order.header.side,
+                        role: fill.role().into(),
+                        price: fill.price(),
+                        amount: fill.amount(),
+                        commission_currency_code: fill.commission_currency_code().clone(),
+                        commission: fill.commission_amount(),
+                    });
+                }
+            }
+        }
+
+        if let Err(error) = self.statistic_event_handler.handle_event(event.clone()) {
+            error!(
+                "Failed to handle event by StatisticEventHandler in backtesting: {:?}",
+                error
+            );
+        }
+
+        if let Err(error) = self.executor.handle_event(event, &mut self.trading_context) {
+            error!(
+                "Failed to handle event by DispositionExecutor in backtesting: {:?}",
+                error
+            );
+        }
+    }
+
+    fn has_requests_in_progress(&self) -> bool {
+        self.exchange
+            .orders
+            .not_finished
+            .iter()
+            .any(|x| matches!(x.status(), OrderStatus::Creating | OrderStatus::Canceling))
+    }
+
+    fn middle_price(&self) -> Option<Price> {
+        let trade_place = TradePlaceAccount::new(
+            self.exchange.exchange_account_id.clone(),
+            self.currency_pair_metadata.currency_pair(),
+        )
+        .trade_place();
+        let top_prices = self
+            .local_snapshots_service
+            .get_snapshot(&trade_place)?
+            .get_top_prices();
+
+        Some((top_prices.top_ask? + top_prices.top_bid?) * dec!(0.5))
+    }
+
+    fn finish(mut self) -> BacktestReport {
+        let middle_price = self.middle_price();
+        self.report.finish(middle_price);
+        self.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::balance_manager::tests::balance_manager_base::BalanceManagerBase;
+    use crate::core::disposition_execution::{
+        PriceSlot, TradeCycle, TradeDisposition, TradingContextBySide,
+    };
+    use crate::core::exchanges::binance::binance::BinanceBuilder;
+    use crate::core::exchanges::common::ExchangeAccountId;
+    use crate::core::exchanges::events::{TickDirection, Trade, TradeId, TradesEvent};
+    use crate::core::exchanges::general::currency_pair_metadata::Precision;
+    use crate::core::explanation::{Explanation, WithExplanation};
+    use crate::core::order_book::event::{EventType, OrderBookEvent};
+    use crate::core::orders::order::{OrderRole, OrderSide, OrderSnapshot};
+    use crate::order_book_data;
+    use chrono::{Duration, Utc};
+    use parking_lot::Mutex;
+    use rust_decimal::Decimal;
+
+    const STRATEGY_NAME: &str = "TopOfBookStrategy";
+
+    /// Places orders at the top of the order book on both sides
+    struct TopOfBookStrategy {
+        trade_place_account: TradePlaceAccount,
+        handled_fills: Arc<Mutex<Vec<(OrderSide, Amount)>>>,
+    }
+
+    impl TopOfBookStrategy {
+        fn context_by_side(
+            &self,
+            side: OrderSide,
+            max_amount: Amount,
+            local_snapshots_service: &LocalSnapshotsService,
+        ) -> Option<TradingContextBySide> {
+            let price = local_snapshots_service
+                .get_snapshot(&self.trade_place_account.trade_place())?
+                .get_top(side)?
+                .0;
+
+            Some(TradingContextBySide {
+                max_amount,
+                estimating: vec![WithExplanation {
+                    value: Some(TradeCycle {
+                        order_role: OrderRole::Maker,
+                        strategy_name: STRATEGY_NAME.to_owned(),
+                        disposition: TradeDisposition::new(
+                            self.trade_place_account.clone(),
+                            side,
+                            price,
+                            max_amount,
+                        ),
+                    }),
+                    explanation: Explanation::default(),
+                }],
+            })
+        }
+    }
+
+    impl DispositionStrategy for TopOfBookStrategy {
+        fn calculate_trading_context(
+            &mut self,
+            max_amount: Decimal,
+            _now: DateTime,
+            local_snapshots_service: &LocalSnapshotsService,
+            _explanation: &mut Explanation,
+        ) -> Option<TradingContext> {
+            Some(TradingContext::new(
+                self.context_by_side(OrderSide::Buy, max_amount, local_snapshots_service)?,
+                self.context_by_side(OrderSide::Sell, max_amount, local_snapshots_service)?,
+            ))
+        }
+
+        fn handle_order_fill(
+            &self,
+            cloned_order: &Arc<OrderSnapshot>,
+            _price_slot: &PriceSlot,
+            _target_eai: &ExchangeAccountId,
+            _cancellation_token: CancellationToken,
+        ) -> Result<()> {
+            self.handled_fills
+                .lock()
+                .push((cloned_order.header.side, cloned_order.fills.filled_amount));
+            Ok(())
+        }
+    }
+
+    fn exchange_account_id() -> ExchangeAccountId {
+        "Binance0".parse().expect("in test")
+    }
+
+    fn currency_pair_metadata() -> Arc<CurrencyPairMetadata> {
+        Arc::new(CurrencyPairMetadata::new(
+            true,
+            false,
+            "PHB".into(),
+            "phb".into(),
+            "BTC".into(),
+            "btc".into(),
+            None,
+            None,
+            Some(dec!(0.001)),
+            None,
+            None,
+            "phb".into(),
+            None,
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0.001) },
+        ))
+    }
+
+    fn settings() -> BacktestSettings {
+        let exchange_account_id = exchange_account_id();
+        let mut paper_settings = PaperExchangeSettings::default();
+        paper_settings.balances =
+            HashMap::from([("phb".into(), dec!(10)), ("btc".into(), dec!(10))]);
+
+        BacktestSettings {
+            app_settings: CoreSettings::default(),
+            exchange_settings: ExchangeSettings::new_short(
+                exchange_account_id,
+                "".to_owned(),
+                "".to_owned(),
+                false,
+            ),
+            currency_pair_metadata: currency_pair_metadata(),
+            max_amount: dec!(2),
+            price_slots: vec![PriceSlotId::new(STRATEGY_NAME.to_owned(), 0)],
+            paper_settings,
+        }
+    }
+
+    fn snapshot_event(time: DateTime, ask: Price, bid: Price) -> ExchangeEvent {
+        let mut order_book_data = order_book_data!();
+        order_book_data.asks.insert(ask, dec!(10));
+        order_book_data.bids.insert(bid, dec!(10));
+
+        ExchangeEvent::OrderBookEvent(OrderBookEvent::new(
+            time,
+            exchange_account_id(),
+            currency_pair_metadata().currency_pair(),
+            "".to_owned(),
+            EventType::Snapshot,
+            order_book_data,
+        ))
+    }
+
+    fn trade_event(
+        time: DateTime,
+        side: OrderSide,
+        price: Price,
+        quantity: Amount,
+    ) -> ExchangeEvent {
+        ExchangeEvent::Trades(TradesEvent {
+            exchange_account_id: exchange_account_id(),
+            currency_pair: currency_pair_metadata().currency_pair(),
+            trades: vec![Trade {
+                trade_id: TradeId::Number(1),
+                price,
+                quantity,
+                side,
+                transaction_time: time,
+                tick_direction: TickDirection::None,
+            }],
+            receipt_time: time,
+        })
+    }
+
+    #[test]
+    fn round_trip_is_reported_with_pnl_and_statistics() {
+        // Keeps mocked time used by balance reservations
+        let _balance_manager_base = BalanceManagerBase::new();
+
+        let handled_fills = Arc::new(Mutex::new(Vec::new()));
+        let strategy = TopOfBookStrategy {
+            trade_place_account: TradePlaceAccount::new(
+                exchange_account_id(),
+                currency_pair_metadata().currency_pair(),
+            ),
+            handled_fills: handled_fills.clone(),
+        };
+        let engine = BacktestEngine::new(
+            settings(),
+            Box::new(BinanceBuilder),
+            Box::new(move |_| Ok(Box::new(strategy))),
+        );
+
+        let start_time = Utc::now();
+        let time = |seconds| start_time + Duration::seconds(seconds);
+        let report = engine
+            .run(vec![
+                snapshot_event(time(0), dec!(1.2), dec!(1.0)),
+                trade_event(time(1), OrderSide::Sell, dec!(1.0), dec!(2)),
+                snapshot_event(time(2), dec!(1.2), dec!(1.1)),
+                trade_event(time(3), OrderSide::Buy, dec!(1.2), dec!(1)),
+                snapshot_event(time(4), dec!(1.3), dec!(1.1)),
+            ])
+            .expect("in test");
+
+        assert_eq!(report.fills.len(), 2);
+        assert_eq!(
+            (
+                report.fills[0].side,
+                report.fills[0].price,
+                report.fills[0].amount
+            ),
+            (OrderSide::Buy, dec!(1.0), dec!(2))
+        );
+        assert_eq!(
+            (
+                report.fills[1].side,
+                report.fills[1].price,
+                report.fills[1].amount
+            ),
+            (OrderSide::Sell, dec!(1.2), dec!(1))
+        );
+        assert!(report.fills.iter().all(|x| x.role == OrderRole::Maker));
+
+        // Fills and completion of the buy order are handled by strategy through DispositionExecutor
+        assert_eq!(
+            *handled_fills.lock(),
+            vec![
+                (OrderSide::Buy, dec!(2)),
+                (OrderSide::Buy, dec!(2)),
+                (OrderSide::Sell, dec!(1))
+            ]
+        );
+
+        // Commission 0.1% is charged in received currency like on paper exchange
+        assert_eq!(report.fills[0].commission_currency_code, "phb".into());
+        assert_eq!(report.fills[0].commission, dec!(0.002));
+        assert_eq!(report.fills[1].commission_currency_code, "btc".into());
+        assert_eq!(report.fills[1].commission, dec!(0.0012));
+        assert_eq!(report.total_commission, dec!(0.0032));
+        assert_eq!(report.base_amount, dec!(0.998));
+        assert_eq!(report.quote_amount, dec!(-0.8012));
+        // Remaining 0.998 phb is valued by the last middle price 1.2
+        assert_eq!(report.pnl, Some(dec!(0.3964)));
+
+        assert_eq!(report.inventory.len(), 2);
+        assert_eq!(report.inventory[0].time, time(1));
+        assert_eq!(report.inventory[0].base_amount, dec!(1.998));
+        assert_eq!(report.inventory[1].time, time(3));
+        assert_eq!(report.inventory[1].base_amount, dec!(0.998));
+
+        let statistics =
+            serde_json::to_value(&report.statistics.statistic_service_state).expect("in test");
+        let trade_place_statistics = &statistics["trade_place_stats"]
+            .as_object()
+            .expect("in test")
+            .values()
+            .next()
+            .expect("in test")
+            .clone();
+        // Initial orders and buy order after fill. Sell order for the new price isn't created
+        // until cancellation of the previous one is finished
+        assert_eq!(trade_place_statistics["opened_orders_count"], 3);
+        assert_eq!(trade_place_statistics["canceled_orders_count"], 1);
+        // Sell order filled by 1 of 2 was canceled on price change, and canceled orders
+        // aren't counted as partially filled anymore
+        assert_eq!(trade_place_statistics["partially_filled_orders_count"], 0);
+        assert_eq!(trade_place_statistics["fully_filled_orders_count"], 1);
+    }
+}
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+use serde::{Serialize, Serializer};
+
+use crate::core::exchanges::common::{Amount, CurrencyCode, Price};
+use crate::core::exchanges::general::currency_pair_metadata::CurrencyPairMetadata;
+use crate::core::orders::order::{ClientOrderId, OrderRole, OrderSide};
+use crate::core::statistic_service::StatisticService;
+use crate::core::DateTime;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BacktestFill {
+    pub time: DateTime,
+    pub client_order_id: ClientOrderId,
+    pub side: OrderSide,
+    pub role: OrderRole,
+    pub price: Price,
+    pub amount: Amount,
+    /// Commission is charged by paper exchange in the currency received from the trade
+    pub commission_currency_code: CurrencyCode,
+    pub commission: Amount,
+}
+
+/// Position of the strategy after handling of fills at the specified time
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InventoryPoint {
+    pub time: DateTime,
+    pub base_amount: Amount,
+    pub quote_amount: Amount,
+    /// Middle price of the order book at the specified time
+    pub middle_price: Option<Price>,
+    pub pnl: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BacktestReport {
+    pub fills: Vec<BacktestFill>,
+    pub inventory: Vec<InventoryPoint>,
+    /// Change of balance in base currency since the start of backtest including commission
+    pub base_amount: Amount,
+    /// Change of balance in quote currency since the start of backtest including commission
+    pub quote_amount: Amount,
+    /// Commission of all fills in quote currency converted by prices of the fills
+    pub total_commission: Amount,
+    /// Profit in quote currency calculated by the last middle price of the order book
+    pub pnl: Option<Decimal>,
+    #[serde(serialize_with = "serialize_statistics")]
+    pub statistics: Arc<StatisticService>,
+    #[serde(skip)]
+    base_currency_code: CurrencyCode,
+}
+
+fn serialize_statistics<S>(
+    statistics: &Arc<StatisticService>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    statistics.statistic_service_state.serialize(serializer)
+}
+
+impl BacktestReport {
+    pub(crate) fn new(
+        statistics: Arc<StatisticService>,
+        currency_pair_metadata: &CurrencyPairMetadata,
+    ) -> Self {
+        BacktestReport {
+            fills: Vec::new(),
+            inventory: Vec::new(),
+            base_amount: Decimal::ZERO,
+            quote_amount: Decimal::ZERO,
+            total_commission: Decimal::ZERO,
+            pnl: None,
+            statistics,
+            base_currency_code: currency_pair_metadata.base_currency_code.clone(),
+        }
+    }
+
+    pub(crate) fn add_fill(&mut self, fill: BacktestFill) {
+        let cost = fill.price * fill.amount;
+        match fill.side {
+            OrderSide::Buy => {
+                self.base_amount += fill.amount;
+                self.quote_amount -= cost;
+            }
+            OrderSide::Sell => {
+                self.base_amount -= fill.amount;
+                self.quote_amount += cost;
+            }
+        }
+
+        if fill.commission_currency_code == self.base_currency_code {
+            self.base_amount -= fill.commission;
+            self.total_commission += fill.commission * fill.price;
+        } else {
+            self.quote_amount -= fill.commission;
+            self.total_commission += fill.commission;
+        }
+
+        self.fills.push(fill);
+    }
+
+    pub(crate) fn add_inventory_point(&mut self, time: DateTime, middle_price: Option<Price>) {
+        self.inventory.push(InventoryPoint {
+            time,
+            base_amount: self.base_amount,
+            quote_amount: self.quote_amount,
+            middle_price,
+            pnl: self.calculate_pnl(middle_price),
+        });
+    }
+
+    pub(crate) fn finish(&mut self, middle_price: Option<Price>) {
+        self.pnl = self.calculate_pnl(middle_price);
+    }
+
+    fn calculate_pnl(&self, middle_price: Option<Price>) -> Option<Decimal> {
+        if self.base_amount.is_zero() {
+            return Some(self.quote_amount);
+        }
+
+        middle_price.map(|price| self.quote_amount + self.base_amount * price)
+    }
+}
@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::backtest_engine::{BacktestEngine, BacktestSettings};
+use super::backtest_report::BacktestReport;
+use super::recorded_market_data::RecordedMarketData;
+use crate::core::exchanges::paper::paper_exchange::PaperExchangeSettings;
+use crate::core::lifecycle::launcher::EngineBuildConfig;
+use crate::core::settings::{AppSettings, ConfiguredStrategySettings};
+
+/// Backtest the first strategy from `settings` on market data from file written by `RawMessagesRecorder`.
+/// Exchange of the strategy should be the exchange which messages were recorded (e.g. `Binance`, not `PaperBinance`).
+/// Paper trading settings of the exchange are used for simulation of orders
+pub fn run_configured_backtest(
+    mut build_settings: EngineBuildConfig,
+    settings: AppSettings<ConfiguredStrategySettings>,
+    raw_messages_path: impl AsRef<Path>,
+) -> Result<BacktestReport> {
+    let strategy_settings = settings
+        .strategies
+        .first()
+        .context("There are no strategies to backtest in settings")?
+        .settings
+        .clone();
+    let exchange_account_id = strategy_settings.exchange_account_id.clone();
+    let exchange_settings = settings
+        .core
+        .exchanges
+        .iter()
+        .find(|x| x.exchange_account_id == exchange_account_id)
+        .with_context(|| format!("There are no settings of exchange {}", exchange_account_id))?
+        .clone();
+
+    let market_data_builder = build_settings
+        .supported_exchange_clients
+        .remove(&exchange_account_id.exchange_id)
+        .with_context(|| {
+            format!(
+                "Exchange {} isn't supported",
+                exchange_account_id.exchange_id
+            )
+        })?;
+    let strategy_builder = build_settings
+        .supported_strategies
+        .remove(&strategy_settings.strategy_type)
+        .with_context(|| {
+            format!(
+                "Strategy type {} isn't supported",
+                strategy_settings.strategy_type
+            )
+        })?;
+    let price_slots = strategy_builder.get_price_slots(&strategy_settings)?;
+
+    let market_data = RecordedMarketData::load(
+        raw_messages_path,
+        &exchange_settings,
+        market_data_builder.as_ref(),
+    )?;
+    let currency_pair_metadata = market_data
+        .symbols
+        .iter()
+        .find(|x| x.currency_pair() == strategy_settings.currency_pair)
+        .cloned()
+        .with_context(|| {
+            format!(
+                "There is no recorded metadata for {} on {}",
+                strategy_settings.currency_pair, exchange_account_id
+            )
+        })?;
+
+    let paper_settings = exchange_settings
+        .paper_trading
+        .as_ref()
+        .map(PaperExchangeSettings::from)
+        .unwrap_or_default();
+    let backtest_settings = BacktestSettings {
+        app_settings: settings.core,
+        exchange_settings,
+        currency_pair_metadata,
+        max_amount: strategy_settings.max_amount,
+        price_slots,
+        paper_settings,
+    };
+
+    let engine = BacktestEngine::new(
+        backtest_settings,
+        market_data_builder,
+        Box::new(move |engine_context| {
+            strategy_builder.create_strategy(&strategy_settings, engine_context)
+        }),
+    );
+    engine.run(market_data.events)
+}
@@ -0,0 +1,4 @@
+pub mod backtest_engine;
+pub mod backtest_report;
+pub mod launcher;
+pub mod recorded_market_data;
@@ -0,0 +1,251 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use awc::http::StatusCode;
+use log::warn;
+use parking_lot::Mutex;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::TryRecvError;
+
+use crate::core::exchanges::common::{CurrencyPair, RestRequestOutcome};
+use crate::core::exchanges::events::{
+    ExchangeEvent, TickDirection, Trade, TradesEvent, CHANNEL_MAX_EVENTS_COUNT,
+};
+use crate::core::exchanges::general::currency_pair_metadata::CurrencyPairMetadata;
+use crate::core::exchanges::general::request_type::RequestType;
+use crate::core::exchanges::recording::raw_messages_recorder::{RawMessage, RawMessageKind};
+use crate::core::exchanges::recording::raw_messages_replayer::RawMessagesReplayer;
+use crate::core::exchanges::traits::{ExchangeClientBuilder, Support};
+use crate::core::lifecycle::application_manager::ApplicationManager;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::settings::ExchangeSettings;
+use crate::core::DateTime;
+
+/// Market data events restored from the file written by `RawMessagesRecorder`
+pub struct RecordedMarketData {
+    /// Metadata from the first successful recorded metadata response
+    pub symbols: Vec<Arc<CurrencyPairMetadata>>,
+    /// Order book and trades events sorted by receive time of their raw messages
+    pub events: Vec<ExchangeEvent>,
+}
+
+impl RecordedMarketData {
+    /// Recorded messages are parsed by exchange client from `market_data_builder` like in live trading.
+    /// Time of each event is receive time of its raw message.
+    /// Recorded order book snapshot responses are applied to snapshot requests of the client in the order of recording
+    pub fn load(
+        file_path: impl AsRef<Path>,
+        exchange_settings: &ExchangeSettings,
+        market_data_builder: &dyn ExchangeClientBuilder,
+    ) -> Result<Self> {
+        let replayer = RawMessagesReplayer::open(file_path)?;
+
+        let mut exchange_settings = exchange_settings.clone();
+        exchange_settings.subscribe_to_market_data = true;
+        let (events_sender, mut events_receiver) = broadcast::channel(CHANNEL_MAX_EVENTS_COUNT);
+        let client = market_data_builder
+            .create_exchange_client(
+                exchange_settings.clone(),
+                events_sender,
+                ApplicationManager::new(CancellationToken::new()),
+            )
+            .client;
+
+        let trades = Arc::new(Mutex::new(Vec::new()));
+        let trades_for_callback = trades.clone();
+        client.set_handle_trade_callback(Box::new(
+            move |currency_pair, trade_id, price, quantity, side, transaction_time| {
+                trades_for_callback.lock().push((
+                    currency_pair.clone(),
+                    Trade {
+                        trade_id,
+                        price,
+                        quantity,
+                        side,
+                        transaction_time,
+                        tick_direction: TickDirection::None,
+                    },
+                ))
+            },
+        ));
+
+        let requested_snapshots = Arc::new(Mutex::new(Vec::new()));
+        let requested_snapshots_for_callback = requested_snapshots.clone();
+        client.set_order_book_snapshot_requested_callback(Box::new(move |currency_pair| {
+            requested_snapshots_for_callback.lock().push(currency_pair)
+        }));
+
+        let mut symbols = Vec::new();
+        let mut events = Vec::new();
+        for message in replayer.messages() {
+            if let Err(error) =
+                handle_message(client.as_ref(), message, &mut symbols, &requested_snapshots)
+            {
+                warn!(
+                    "Unable to handle raw message received at {}: {:?}",
+                    message.receive_time, error
+                );
+            }
+
+            loop {
+                match events_receiver.try_recv() {
+                    Ok(ExchangeEvent::OrderBookEvent(mut order_book_event)) => {
+                        order_book_event.creation_time = message.receive_time;
+                        events.push(ExchangeEvent::OrderBookEvent(order_book_event));
+                    }
+                    Ok(_) => {}
+                    Err(TryRecvError::Lagged(skipped_count)) => {
+                        bail!("{} recorded market data events were skipped", skipped_count)
+                    }
+                    Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+                }
+            }
+
+            events.extend(trades.lock().drain(..).map(|(currency_pair, trade)| {
+                trades_event(
+                    &exchange_settings,
+                    currency_pair,
+                    trade,
+                    message.receive_time,
+                )
+            }));
+        }
+
+        if symbols.is_empty() {
+            bail!(
+                "There is no metadata response for {} in recorded raw messages",
+                exchange_settings.exchange_account_id
+            );
+        }
+
+        Ok(RecordedMarketData { symbols, events })
+    }
+}
+
+fn handle_message(
+    client: &dyn Support,
+    message: &RawMessage,
+    symbols: &mut Vec<Arc<CurrencyPairMetadata>>,
+    requested_snapshots: &Mutex<Vec<CurrencyPair>>,
+) -> Result<()> {
+    let (request_type, status) = match &message.kind {
+        RawMessageKind::WebSocket => return client.on_websocket_message(&message.content),
+        RawMessageKind::Rest {
+            request_type,
+            status,
+        } => (request_type, *status),
+    };
+
+    let status = StatusCode::from_u16(status).context("Unable to parse status of response")?;
+    if !status.is_success() {
+        return Ok(());
+    }
+
+    let response = RestRequestOutcome::new(message.content.clone(), status);
+    match request_type {
+        RequestType::GetMarkets if symbols.is_empty() => {
+            *symbols = client.parse_metadata(&response)?;
+            client.on_connecting()
+        }
+        RequestType::GetOrderBook => {
+            let mut requested_snapshots = requested_snapshots.lock();
+            if requested_snapshots.is_empty() {
+                return Ok(());
+            }
+
+            let currency_pair = requested_snapshots.remove(0);
+            client.handle_order_book_snapshot_response(&currency_pair, &response)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn trades_event(
+    exchange_settings: &ExchangeSettings,
+    currency_pair: CurrencyPair,
+    trade: Trade,
+    receipt_time: DateTime,
+) -> ExchangeEvent {
+    ExchangeEvent::Trades(TradesEvent {
+        exchange_account_id: exchange_settings.exchange_account_id.clone(),
+        currency_pair,
+        trades: vec![trade],
+        receipt_time,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exchanges::binance::binance::BinanceBuilder;
+    use crate::core::exchanges::common::ExchangeAccountId;
+    use crate::core::exchanges::recording::raw_messages_recorder::RawMessagesRecorder;
+    use crate::core::order_book::event::EventType;
+    use crate::core::orders::order::OrderSide;
+    use rust_decimal_macros::dec;
+    use std::fs;
+
+    const METADATA: &str = r#"{"symbols":[{"symbol":"EOSBTC","status":"TRADING","baseAsset":"EOS","quoteAsset":"BTC","filters":[
+        {"filterType":"PRICE_FILTER","minPrice":"0.0000001","maxPrice":"1000","tickSize":"0.0000001"},
+        {"filterType":"LOT_SIZE","minQty":"0.01","maxQty":"90000000","stepSize":"0.01"}]}]}"#;
+    const ORDER_BOOK: &str = r#"{"stream":"eosbtc@depth20","data":{"lastUpdateId":1,
+        "bids":[["0.0001","10"]],"asks":[["0.0002","5"]]}}"#;
+    const TRADE: &str = r#"{"stream":"eosbtc@trade","data":{"t":1,"p":"0.0002","q":"3","m":false,"T":1600000000000}}"#;
+
+    #[tokio::test]
+    async fn events_are_restored_with_receive_time_of_messages() {
+        let directory =
+            std::env::temp_dir().join(format!("mmb_recorded_market_data_{}", std::process::id()));
+        let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+        let recorder =
+            RawMessagesRecorder::open(&directory, exchange_account_id.clone()).expect("in test");
+        // Market data received before metadata can't be parsed
+        recorder.record_websocket_message(TRADE);
+        recorder.record_rest_response(
+            RequestType::GetMarkets,
+            &RestRequestOutcome::new(METADATA.to_owned(), StatusCode::OK),
+        );
+        recorder.record_websocket_message(ORDER_BOOK);
+        recorder.record_websocket_message(TRADE);
+        recorder.stop().await.expect("in test").expect("in test");
+
+        let exchange_settings =
+            ExchangeSettings::new_short(exchange_account_id, "".to_owned(), "".to_owned(), false);
+        let market_data =
+            RecordedMarketData::load(recorder.file_path(), &exchange_settings, &BinanceBuilder)
+                .expect("in test");
+
+        assert_eq!(market_data.symbols.len(), 1);
+        let currency_pair = market_data.symbols[0].currency_pair();
+
+        let messages = RawMessagesReplayer::open(recorder.file_path()).expect("in test");
+        let messages = messages.messages();
+        assert_eq!(market_data.events.len(), 2);
+        match &market_data.events[0] {
+            ExchangeEvent::OrderBookEvent(order_book_event) => {
+                assert_eq!(order_book_event.currency_pair, currency_pair);
+                assert!(matches!(order_book_event.event_type(), EventType::Snapshot));
+                assert_eq!(order_book_event.creation_time, messages[2].receive_time);
+            }
+            event => panic!("Unexpected event {:?}", event),
+        }
+        match &market_data.events[1] {
+            ExchangeEvent::Trades(trades_event) => {
+                assert_eq!(trades_event.currency_pair, currency_pair);
+                assert_eq!(trades_event.receipt_time, messages[3].receive_time);
+                assert_eq!(
+                    (
+                        trades_event.trades[0].side,
+                        trades_event.trades[0].price,
+                        trades_event.trades[0].quantity
+                    ),
+                    (OrderSide::Buy, dec!(0.0002), dec!(3))
+                );
+            }
+            event => panic!("Unexpected event {:?}", event),
+        }
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
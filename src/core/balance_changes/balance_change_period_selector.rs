@@ -120,6 +120,13 @@ impl BalanceChangePeriodSelector {
         &mut self,
         trade_place: &TradePlaceAccount,
     ) -> Vec<ProfitLossBalanceChange> {
+        if !self
+            .balance_changes_queues_by_trade_place
+            .contains_key(trade_place)
+        {
+            return Vec::new();
+        }
+
         self.get_items_core(trade_place, None)
     }
 
@@ -1,8 +1,9 @@
 use crate::core::exchanges::common::Amount;
 use crate::core::orders::order::ClientOrderId;
 use crate::core::DateTime;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ApprovedPart {
     approve_time: DateTime,
     client_order_id: ClientOrderId,
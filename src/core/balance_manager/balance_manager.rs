@@ -4,6 +4,7 @@ use std::sync::Arc;
 use crate::core::balance_manager::balance_reservation::BalanceReservation;
 use crate::core::balance_manager::position_change::PositionChange;
 use crate::core::balances::balance_reservation_manager::BalanceReservationManager;
+use crate::core::data_recorder::recorder::{DataRecordType, DataRecorder};
 use crate::core::exchanges::common::{Amount, Price};
 use crate::core::exchanges::common::{CurrencyCode, CurrencyPair, TradePlaceAccount};
 use crate::core::exchanges::events::ExchangeBalancesAndPositions;
@@ -23,10 +24,12 @@ use crate::core::DateTime;
 use crate::core::{balance_manager::balances::Balances, exchanges::common::ExchangeAccountId};
 
 use anyhow::{bail, Context, Result};
+use chrono::Utc;
 use itertools::Itertools;
 use parking_lot::Mutex;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use serde::Serialize;
 
 /// The entity for getting information about account balances for selected exchanges
 #[derive(Clone)]
@@ -34,12 +37,22 @@ pub struct BalanceManager {
     exchange_id_with_restored_positions: HashSet<ExchangeAccountId>,
     balance_reservation_manager: BalanceReservationManager,
     last_order_fills: HashMap<TradePlaceAccount, OrderFill>,
+    data_recorder: Option<Arc<DataRecorder>>,
+}
+
+#[derive(Serialize)]
+struct BalanceUpdate<'a> {
+    time: DateTime,
+    reservations: &'a HashMap<ReservationId, BalanceReservation>,
+    whole_balance_before: HashMap<ExchangeAccountId, HashMap<CurrencyCode, Amount>>,
+    whole_balance_after: HashMap<ExchangeAccountId, HashMap<CurrencyCode, Amount>>,
 }
 
 impl BalanceManager {
     pub fn new(
         exchanges_by_id: HashMap<ExchangeAccountId, Arc<Exchange>>,
         currency_pair_to_metadata_converter: CurrencyPairToMetadataConverter,
+        data_recorder: Option<Arc<DataRecorder>>,
     ) -> Arc<Mutex<Self>> {
         Arc::new(Mutex::new(Self {
             exchange_id_with_restored_positions: HashSet::new(),
@@ -48,6 +61,7 @@ impl BalanceManager {
                 currency_pair_to_metadata_converter,
             ),
             last_order_fills: HashMap::new(),
+            data_recorder,
         }))
     }
 
@@ -142,13 +156,12 @@ impl BalanceManager {
     }
 
     fn save_balances(&mut self) {
-        // TODO: fix me when DataRecorder will be added
-        // if self.data_recorder.is_none() {
-        //     return ()
-        // }
+        let data_recorder = match &self.data_recorder {
+            Some(data_recorder) => data_recorder,
+            None => return,
+        };
 
-        let _balances = self.get_balances();
-        // self.data_recorder.save(balances);
+        data_recorder.save(DataRecordType::Balances, &self.get_balances());
     }
 
     pub fn get_balances(&self) -> Balances {
@@ -159,28 +172,25 @@ impl BalanceManager {
 
     fn save_balance_update(
         &self,
-        _whole_balance_before: HashMap<ExchangeAccountId, HashMap<CurrencyCode, Amount>>,
-        _whole_balance_after: HashMap<ExchangeAccountId, HashMap<CurrencyCode, Amount>>,
+        whole_balance_before: HashMap<ExchangeAccountId, HashMap<CurrencyCode, Amount>>,
+        whole_balance_after: HashMap<ExchangeAccountId, HashMap<CurrencyCode, Amount>>,
     ) {
-        // TODO: fix me when DataRecorder will be added
-        // if self.data_recorder.is_none()
-        // {
-        //     return;
-        // }
-
-        let _reservation_clone = self
-            .balance_reservation_manager
-            .balance_reservation_storage
-            .get_all_raw_reservations()
-            .clone();
+        let data_recorder = match &self.data_recorder {
+            Some(data_recorder) => data_recorder,
+            None => return,
+        };
 
-        // var balanceUpdate = new BalanceUpdate(
-        //     _dateTimeService.UtcNow,
-        //     reservationsClone,
-        //     wholeBalanceBefore,
-        //     wholeBalanceAfter);
+        let balance_update = BalanceUpdate {
+            time: Utc::now(),
+            reservations: self
+                .balance_reservation_manager
+                .balance_reservation_storage
+                .get_all_raw_reservations(),
+            whole_balance_before,
+            whole_balance_after,
+        };
 
-        // _dataRecorder.Save(balanceUpdate);
+        data_recorder.save(DataRecordType::BalanceUpdates, &balance_update);
     }
 
     fn restore_fill_amount_position(
@@ -463,9 +473,11 @@ impl BalanceManager {
         let this_locked = this.lock();
         let balances = this_locked.get_balances();
         let exchanges_by_id = &this_locked.balance_reservation_manager.exchanges_by_id;
+        // Changes of the clone are used for calculations only and shouldn't be recorded
         let new_balance_manager = Self::new(
             exchanges_by_id.clone(),
             CurrencyPairToMetadataConverter::new(exchanges_by_id.clone()),
+            None,
         );
         drop(this_locked);
 
@@ -7,8 +7,9 @@ use crate::core::DateTime;
 
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BalancePositionByFillAmount {
     /// TradePlace -> AmountInAmountCurrency
     position_by_fill_amount: HashMap<TradePlaceAccount, Decimal>,
@@ -14,8 +14,9 @@ use crate::core::service_configuration::configuration_descriptor::ConfigurationD
 use anyhow::{bail, Result};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BalanceReservation {
     pub configuration_descriptor: Arc<ConfigurationDescriptor>,
     pub exchange_account_id: ExchangeAccountId,
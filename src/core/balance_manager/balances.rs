@@ -13,7 +13,9 @@ use crate::core::orders::order::ReservationId;
 use crate::core::DateTime;
 
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 
+#[derive(Serialize, Deserialize)]
 pub struct Balances {
     pub version: usize,
     pub init_time: DateTime,
@@ -2,8 +2,9 @@ use crate::core::orders::order::ClientOrderFillId;
 use crate::core::DateTime;
 
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PositionChange {
     pub(crate) client_order_fill_id: ClientOrderFillId,
     pub(crate) change_time: DateTime,
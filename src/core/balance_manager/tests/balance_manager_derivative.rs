@@ -67,8 +67,11 @@ impl BalanceManagerDerivative {
         let currency_pair_to_metadata_converter =
             CurrencyPairToMetadataConverter::new(exchanges_by_id.clone());
 
-        let balance_manager =
-            BalanceManager::new(exchanges_by_id.clone(), currency_pair_to_metadata_converter);
+        let balance_manager = BalanceManager::new(
+            exchanges_by_id.clone(),
+            currency_pair_to_metadata_converter,
+            None,
+        );
         (currency_pair_metadata, balance_manager, exchanges_by_id)
     }
 
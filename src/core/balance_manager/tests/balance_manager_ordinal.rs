@@ -43,8 +43,11 @@ impl BalanceManagerOrdinal {
         let currency_pair_to_metadata_converter =
             CurrencyPairToMetadataConverter::new(exchanges_by_id.clone());
 
-        let balance_manager =
-            BalanceManager::new(exchanges_by_id.clone(), currency_pair_to_metadata_converter);
+        let balance_manager = BalanceManager::new(
+            exchanges_by_id.clone(),
+            currency_pair_to_metadata_converter,
+            None,
+        );
         (currency_pair_metadata, balance_manager)
     }
 
@@ -4477,6 +4480,7 @@ mod tests {
         let balance_manager = BalanceManager::new(
             exchanges_by_id.clone(),
             currency_pair_to_metadata_converter.clone(),
+            None,
         );
 
         let exchange_account_id = &test_object
@@ -4522,6 +4526,7 @@ mod tests {
             .set_balance_manager(BalanceManager::new(
                 exchanges_by_id,
                 currency_pair_to_metadata_converter,
+                None,
             ));
 
         test_object
@@ -11,6 +11,7 @@ use anyhow::Result;
 use futures::Future;
 use log::{error, info, log, trace, warn, Level};
 use parking_lot::Mutex;
+use rand::Rng;
 use std::pin::Pin;
 use std::{
     borrow::Borrow,
@@ -18,9 +19,44 @@ use std::{
     sync::{Arc, Weak},
 };
 use tokio::sync::broadcast;
+use tokio::time::{sleep, Duration};
 
 pub const MAX_RETRY_CONNECT_COUNT: u32 = 3;
 
+/// Jittered exponential backoff between attempts of automatic reconnection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectBackoff {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl ReconnectBackoff {
+    pub fn new(initial_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            initial_delay,
+            max_delay,
+        }
+    }
+
+    /// Delay before reconnection attempt. It's chosen randomly from the upper half of exponential delay,
+    /// so exchange accounts disconnected at the same time don't reconnect simultaneously
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .initial_delay
+            .checked_mul(2u32.saturating_pow(attempt))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        rand::thread_rng().gen_range(delay / 2..=delay)
+    }
+}
+
+impl Default for ReconnectBackoff {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(60))
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum WebSocketRole {
     Main,
@@ -86,6 +122,12 @@ pub struct ConnectivityManager {
     callback_connected: Mutex<Callback0>,
     callback_disconnected: Mutex<Callback1<bool, ()>>,
     callback_msg_received: Mutex<WSMessageReceived>,
+
+    is_enabled_secondary_websocket: Mutex<bool>,
+    // Automatic reconnection is disabled if it's None
+    reconnect_backoff: Mutex<Option<(ReconnectBackoff, CancellationToken)>>,
+    is_disconnect_requested: Mutex<bool>,
+    is_reconnecting: Mutex<bool>,
 }
 
 impl ConnectivityManager {
@@ -107,6 +149,11 @@ impl ConnectivityManager {
             callback_msg_received: Mutex::new(Box::new(|_| {
                 panic!("callback_msg_received has to be set during ConnectivityManager::connect()")
             })),
+
+            is_enabled_secondary_websocket: Mutex::new(false),
+            reconnect_backoff: Mutex::new(None),
+            is_disconnect_requested: Mutex::new(false),
+            is_reconnecting: Mutex::new(false),
         })
     }
 
@@ -126,6 +173,15 @@ impl ConnectivityManager {
         *self.callback_msg_received.lock() = msg_received;
     }
 
+    /// Reconnect automatically when websocket connection is closed not by disconnect() until stop token is cancelled
+    pub fn enable_reconnect(&self, backoff: ReconnectBackoff, stop_token: CancellationToken) {
+        *self.reconnect_backoff.lock() = Some((backoff, stop_token));
+    }
+
+    pub fn is_reconnecting(&self) -> bool {
+        *self.is_reconnecting.lock()
+    }
+
     fn set_callback_ws_params(&self, get_websocket_params: GetWSParamsCallback) {
         *self.callback_get_ws_params.lock() = get_websocket_params;
     }
@@ -141,32 +197,40 @@ impl ConnectivityManager {
         );
 
         self.set_callback_ws_params(get_websocket_params);
+        *self.is_enabled_secondary_websocket.lock() = is_enabled_secondary_websocket;
+        *self.is_disconnect_requested.lock() = false;
 
         self.callback_connecting.lock().as_mut()();
 
+        let is_connected = self.open_websocket_connections(false).await;
+        if is_connected {
+            self.callback_connected.lock().as_mut()();
+        }
+
+        is_connected
+    }
+
+    async fn open_websocket_connections(self: &Arc<Self>, is_reconnecting: bool) -> bool {
         let main_websocket_connection_opened = self
-            .clone()
-            .open_websocket_connection(WebSocketRole::Main)
+            .open_websocket_connection(WebSocketRole::Main, is_reconnecting)
             .await;
 
-        let secondary_websocket_connection_opened = if is_enabled_secondary_websocket {
-            self.clone()
-                .open_websocket_connection(WebSocketRole::Secondary)
+        let secondary_websocket_connection_opened = if *self.is_enabled_secondary_websocket.lock() {
+            self.open_websocket_connection(WebSocketRole::Secondary, is_reconnecting)
                 .await
         } else {
             true
         };
 
-        let is_connected =
-            main_websocket_connection_opened && secondary_websocket_connection_opened;
-        if is_connected {
-            self.callback_connected.lock().as_mut()();
-        }
-
-        is_connected
+        main_websocket_connection_opened && secondary_websocket_connection_opened
     }
 
     pub async fn disconnect(self: Arc<Self>) {
+        *self.is_disconnect_requested.lock() = true;
+        self.disconnect_websockets().await;
+    }
+
+    async fn disconnect_websockets(&self) {
         Self::disconnect_for_websocket(&self.websockets.main).await;
         Self::disconnect_for_websocket(&self.websockets.secondary).await;
     }
@@ -239,7 +303,7 @@ impl ConnectivityManager {
         let _ = finished_sender.send(());
     }
 
-    pub fn notify_connection_closed(&self, websocket_role: WebSocketRole) {
+    pub fn notify_connection_closed(self: &Arc<Self>, websocket_role: WebSocketRole) {
         {
             let websocket_connectivity_arc = self.websockets.get_websocket_state(websocket_role);
             let mut websocket_state_guard = websocket_connectivity_arc.lock();
@@ -250,17 +314,93 @@ impl ConnectivityManager {
                     ..
                 } = websocket_state_guard.borrow().state
                 {
-                    let _ = finished_sender.send(()).expect("Can't send finish message in ConnectivityManager::notify_connection_closed");
+                    // There are no receivers if connection was closed by the server side
+                    let _ = finished_sender.send(());
                 }
             }
 
             websocket_state_guard.deref_mut().state = Disconnected;
         }
 
-        self.callback_disconnected.lock().as_mut()(false);
+        let is_reconnect_needed = self.is_reconnect_needed();
+        self.callback_disconnected.lock().as_mut()(is_reconnect_needed);
+
+        if is_reconnect_needed {
+            actix::spawn(self.clone().reconnect());
+        }
     }
 
-    pub async fn open_websocket_connection(self: &Arc<Self>, role: WebSocketRole) -> bool {
+    fn is_reconnect_needed(&self) -> bool {
+        let is_enabled = match &*self.reconnect_backoff.lock() {
+            Some((_, stop_token)) => !stop_token.is_cancellation_requested(),
+            None => false,
+        };
+
+        is_enabled && !*self.is_disconnect_requested.lock() && !self.is_reconnecting()
+    }
+
+    /// Reopen all websocket connections, so both public and private streams are subscribed again
+    async fn reconnect(self: Arc<Self>) {
+        let (backoff, stop_token) = match *self.reconnect_backoff.lock() {
+            Some((backoff, ref stop_token)) => (backoff, stop_token.clone()),
+            None => return,
+        };
+
+        {
+            let mut is_reconnecting = self.is_reconnecting.lock();
+            if *is_reconnecting {
+                return;
+            }
+            *is_reconnecting = true;
+        }
+
+        self.callback_connecting.lock().as_mut()();
+
+        let mut attempt = 0;
+        loop {
+            let delay = backoff.delay(attempt);
+            info!(
+                "Reconnecting websockets on {} in {:?}, attempt {}",
+                self.exchange_account_id,
+                delay,
+                attempt + 1
+            );
+
+            tokio::select! {
+                _ = sleep(delay) => {}
+                _ = stop_token.when_cancelled() => break,
+            }
+
+            if *self.is_disconnect_requested.lock() {
+                break;
+            }
+
+            // Websocket which is still alive is closed too, because streams are resubscribed on opening
+            self.disconnect_websockets().await;
+
+            if self.open_websocket_connections(true).await {
+                info!(
+                    "Websockets on {} are reconnected after {} attempts",
+                    self.exchange_account_id,
+                    attempt + 1
+                );
+                self.callback_connected.lock().as_mut()();
+                break;
+            }
+
+            attempt = attempt.saturating_add(1);
+        }
+
+        *self.is_reconnecting.lock() = false;
+    }
+
+    /// Tries to open connection several times. It panics if all attempts failed, except reconnection,
+    /// which is retried with backoff
+    pub async fn open_websocket_connection(
+        self: &Arc<Self>,
+        role: WebSocketRole,
+        is_reconnecting: bool,
+    ) -> bool {
         let (finished_sender, _) = broadcast::channel(50);
 
         let cancel_websocket_connecting = CancellationToken::new();
@@ -342,6 +482,10 @@ impl ConnectivityManager {
                     );
 
                     if attempt == MAX_RETRY_CONNECT_COUNT {
+                        if is_reconnecting {
+                            break;
+                        }
+
                         panic!(
                             "Can't open websocket connection on {}",
                             self.exchange_account_id
@@ -425,3 +569,22 @@ impl Default for ConnectivityManagerNotifier {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconnect_delay_grows_exponentially_up_to_max() {
+        let backoff = ReconnectBackoff::new(Duration::from_secs(1), Duration::from_secs(10));
+
+        let delay = backoff.delay(0);
+        assert!(Duration::from_millis(500) <= delay && delay <= Duration::from_secs(1));
+
+        let delay = backoff.delay(2);
+        assert!(Duration::from_secs(2) <= delay && delay <= Duration::from_secs(4));
+
+        let delay = backoff.delay(100);
+        assert!(Duration::from_secs(5) <= delay && delay <= Duration::from_secs(10));
+    }
+}
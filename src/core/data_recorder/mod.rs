@@ -0,0 +1,2 @@
+pub mod recorder;
+mod sqlite_storage;
@@ -0,0 +1,399 @@
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use futures::FutureExt;
+use log::{error, info, warn};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::sync::oneshot;
+
+use super::sqlite_storage::SqliteStorage;
+use crate::core::infrastructure::spawn_future;
+use crate::core::lifecycle::trading_engine::Service;
+use crate::core::orders::order::OrderSnapshot;
+use crate::core::settings::DataRecorderSettings;
+use crate::core::DateTime;
+
+// Max count of records written to the database in single transaction
+const MAX_BATCH_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataRecordType {
+    Orders,
+    Fills,
+    Balances,
+    BalanceUpdates,
+    PriceSources,
+    RequestReservations,
+    Explanations,
+}
+
+impl DataRecordType {
+    pub const ALL: [DataRecordType; 7] = [
+        DataRecordType::Orders,
+        DataRecordType::Fills,
+        DataRecordType::Balances,
+        DataRecordType::BalanceUpdates,
+        DataRecordType::PriceSources,
+        DataRecordType::RequestReservations,
+        DataRecordType::Explanations,
+    ];
+
+    pub fn table_name(self) -> &'static str {
+        match self {
+            DataRecordType::Orders => "orders",
+            DataRecordType::Fills => "fills",
+            DataRecordType::Balances => "balances",
+            DataRecordType::BalanceUpdates => "balance_updates",
+            DataRecordType::PriceSources => "price_sources",
+            DataRecordType::RequestReservations => "request_reservations",
+            DataRecordType::Explanations => "explanations",
+        }
+    }
+}
+
+pub(super) struct RecordToSave {
+    pub record_type: DataRecordType,
+    pub insert_time: DateTime,
+    pub data: String,
+}
+
+enum WriterCommand {
+    Save(RecordToSave),
+    Flush(oneshot::Sender<Result<()>>),
+}
+
+/// Service for persisting of orders, fills, balances and other trading data.
+/// Records are serialized to JSON on the calling thread and written to the database
+/// in batches by separate writer thread, so `save` never waits for the database
+pub struct DataRecorder {
+    sender: Mutex<Option<Sender<WriterCommand>>>,
+    writer_thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl DataRecorder {
+    pub fn new(settings: &DataRecorderSettings) -> Result<Arc<Self>> {
+        let database_path = PathBuf::from(&settings.database_path);
+        let storage = SqliteStorage::open(&database_path)?;
+
+        let (sender, receiver) = mpsc::channel();
+        let writer_thread = thread::Builder::new()
+            .name("data_recorder".to_owned())
+            .spawn(move || write_records(storage, receiver))
+            .context("Unable to start DataRecorder writer thread")?;
+
+        info!(
+            "DataRecorder started with database {}",
+            database_path.display()
+        );
+
+        Ok(Arc::new(DataRecorder {
+            sender: Mutex::new(Some(sender)),
+            writer_thread: Mutex::new(Some(writer_thread)),
+        }))
+    }
+
+    pub fn save(&self, record_type: DataRecordType, record: &impl Serialize) {
+        let data = match serde_json::to_string(record) {
+            Ok(data) => data,
+            Err(error) => {
+                error!(
+                    "Unable to serialize record for DataRecorder table {}: {:?}",
+                    record_type.table_name(),
+                    error
+                );
+                return;
+            }
+        };
+
+        self.send(WriterCommand::Save(RecordToSave {
+            record_type,
+            insert_time: Utc::now(),
+            data,
+        }));
+    }
+
+    /// Returns receiver which is notified when all records saved before this call are written to the database
+    pub fn flush(&self) -> oneshot::Receiver<Result<()>> {
+        let (flushed_sender, flushed_receiver) = oneshot::channel();
+        self.send(WriterCommand::Flush(flushed_sender));
+        flushed_receiver
+    }
+
+    /// Load last saved snapshots of not finished orders from the database of previous launches
+    pub fn load_order_snapshots(settings: &DataRecorderSettings) -> Result<Vec<OrderSnapshot>> {
+        let database_path = PathBuf::from(&settings.database_path);
+        let records = SqliteStorage::open(&database_path)?.read_not_finished_orders()?;
+
+        Ok(records
+            .into_iter()
+            .filter_map(|data| match serde_json::from_str(&data) {
+                Ok(snapshot) => Some(snapshot),
+                Err(error) => {
+                    warn!(
+                        "Unable to deserialize persisted order {}: {:?}",
+                        data, error
+                    );
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Stop accepting records and wait until the writer thread writes all saved ones and finishes
+    fn stop(&self) -> oneshot::Receiver<Result<()>> {
+        // Closing of the channel lets the writer thread write remaining records and finish
+        drop(self.sender.lock().take());
+
+        let writer_thread = self.writer_thread.lock().take();
+        // Joining blocks, so it shouldn't be done on async runtime threads
+        let join_handle = tokio::task::spawn_blocking(move || match writer_thread {
+            Some(writer_thread) => writer_thread
+                .join()
+                .map_err(|_| anyhow!("DataRecorder writer thread panicked")),
+            None => Ok(()),
+        });
+
+        let (stopped_sender, stopped_receiver) = oneshot::channel();
+        let action = async move {
+            let result = match join_handle.await {
+                Ok(result) => result,
+                Err(error) => Err(anyhow!(
+                    "Joining of DataRecorder writer thread failed: {}",
+                    error
+                )),
+            };
+            let _ = stopped_sender.send(result);
+            Ok(())
+        };
+        spawn_future("Stop DataRecorder", true, action.boxed());
+
+        stopped_receiver
+    }
+
+    fn send(&self, command: WriterCommand) {
+        match &*self.sender.lock() {
+            Some(sender) => {
+                if sender.send(command).is_err() {
+                    error!("DataRecorder writer thread is stopped so record is lost");
+                }
+            }
+            None => warn!("DataRecorder is stopped so record is lost"),
+        }
+    }
+}
+
+fn write_records(mut storage: SqliteStorage, receiver: Receiver<WriterCommand>) {
+    let mut batch = Vec::with_capacity(MAX_BATCH_SIZE);
+
+    // Blocking wait for the first command, then take all already received ones into the same batch
+    while let Ok(command) = receiver.recv() {
+        handle_command(command, &mut storage, &mut batch);
+        while let Ok(command) = receiver.try_recv() {
+            handle_command(command, &mut storage, &mut batch);
+        }
+
+        write_batch(&mut storage, &mut batch);
+    }
+
+    info!("DataRecorder writer thread finished");
+}
+
+fn handle_command(
+    command: WriterCommand,
+    storage: &mut SqliteStorage,
+    batch: &mut Vec<RecordToSave>,
+) {
+    match command {
+        WriterCommand::Save(record) => {
+            batch.push(record);
+            if batch.len() >= MAX_BATCH_SIZE {
+                write_batch(storage, batch);
+            }
+        }
+        WriterCommand::Flush(flushed_sender) => {
+            let result = storage.write(batch);
+            batch.clear();
+            let _ = flushed_sender.send(result);
+        }
+    }
+}
+
+fn write_batch(storage: &mut SqliteStorage, batch: &mut Vec<RecordToSave>) {
+    if let Err(error) = storage.write(batch) {
+        error!(
+            "Unable to write {} records in DataRecorder: {:?}",
+            batch.len(),
+            error
+        );
+    }
+    batch.clear();
+}
+
+impl Service for DataRecorder {
+    fn name(&self) -> &str {
+        "DataRecorder"
+    }
+
+    fn graceful_shutdown(self: Arc<Self>) -> Option<oneshot::Receiver<Result<()>>> {
+        // Orders are cancelled before services shutdown, so records of the cancellation are written too
+        Some(self.stop())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exchanges::common::{CurrencyPair, ExchangeAccountId};
+    use crate::core::orders::order::{ClientOrderId, OrderSide, OrderStatus, OrderType};
+    use crate::core::settings::RestoredOrdersPolicy;
+    use rusqlite::Connection;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    fn settings(database_path: &PathBuf) -> DataRecorderSettings {
+        DataRecorderSettings {
+            database_path: database_path.display().to_string(),
+            restored_orders_policy: RestoredOrdersPolicy::Cancel,
+        }
+    }
+
+    fn temp_database_path(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("mmb_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        directory.join("data_recorder.db")
+    }
+
+    fn records_count(database_path: &PathBuf, record_type: DataRecordType) -> i64 {
+        Connection::open(database_path)
+            .expect("in test")
+            .query_row(
+                &format!("SELECT COUNT(*) FROM {}", record_type.table_name()),
+                rusqlite::NO_PARAMS,
+                |row| row.get(0),
+            )
+            .expect("in test")
+    }
+
+    #[tokio::test]
+    async fn records_are_written_on_flush() {
+        let database_path = temp_database_path("data_recorder_flush");
+        let data_recorder = DataRecorder::new(&settings(&database_path)).expect("in test");
+
+        data_recorder.save(DataRecordType::Orders, &json!({ "client_order_id": "1" }));
+        data_recorder.save(DataRecordType::Orders, &json!({ "client_order_id": "2" }));
+        data_recorder.save(DataRecordType::Fills, &json!({ "amount": "0.1" }));
+
+        data_recorder
+            .flush()
+            .await
+            .expect("in test")
+            .expect("in test");
+
+        assert_eq!(records_count(&database_path, DataRecordType::Orders), 2);
+        assert_eq!(records_count(&database_path, DataRecordType::Fills), 1);
+        assert_eq!(records_count(&database_path, DataRecordType::Balances), 0);
+
+        let _ = std::fs::remove_dir_all(database_path.parent().expect("in test"));
+    }
+
+    async fn stop(data_recorder: Arc<DataRecorder>) {
+        data_recorder
+            .graceful_shutdown()
+            .expect("in test")
+            .await
+            .expect("in test")
+            .expect("in test");
+    }
+
+    #[tokio::test]
+    async fn remaining_records_are_written_on_graceful_shutdown() {
+        let database_path = temp_database_path("data_recorder_shutdown");
+        let data_recorder = DataRecorder::new(&settings(&database_path)).expect("in test");
+
+        for i in 0..(MAX_BATCH_SIZE + 10) {
+            data_recorder.save(DataRecordType::PriceSources, &json!({ "bid": i }));
+        }
+        stop(data_recorder.clone()).await;
+
+        // Records after shutdown are lost
+        data_recorder.save(DataRecordType::PriceSources, &json!({ "bid": 0 }));
+
+        assert_eq!(
+            records_count(&database_path, DataRecordType::PriceSources),
+            (MAX_BATCH_SIZE + 10) as i64
+        );
+
+        let _ = std::fs::remove_dir_all(database_path.parent().expect("in test"));
+    }
+
+    #[tokio::test]
+    async fn last_snapshots_of_not_finished_orders_are_loaded() {
+        let database_path = temp_database_path("data_recorder_load_orders");
+        let data_recorder = DataRecorder::new(&settings(&database_path)).expect("in test");
+
+        let mut first_order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderType::Limit,
+            None,
+            ExchangeAccountId::new("Binance".into(), 0),
+            CurrencyPair::from_codes(&"phb".into(), &"btc".into()),
+            dec!(0.2),
+            dec!(5),
+            OrderSide::Buy,
+            None,
+            "test",
+        );
+        let second_order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderType::Limit,
+            None,
+            ExchangeAccountId::new("Binance".into(), 0),
+            CurrencyPair::from_codes(&"phb".into(), &"btc".into()),
+            dec!(0.3),
+            dec!(2),
+            OrderSide::Sell,
+            None,
+            "test",
+        );
+
+        let mut cancelled_order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderType::Limit,
+            None,
+            ExchangeAccountId::new("Binance".into(), 0),
+            CurrencyPair::from_codes(&"phb".into(), &"btc".into()),
+            dec!(0.4),
+            dec!(1),
+            OrderSide::Sell,
+            None,
+            "test",
+        );
+
+        data_recorder.save(DataRecordType::Orders, &first_order);
+        data_recorder.save(DataRecordType::Orders, &second_order);
+        data_recorder.save(DataRecordType::Orders, &cancelled_order);
+        first_order.set_status(OrderStatus::Created, Utc::now());
+        data_recorder.save(DataRecordType::Orders, &first_order);
+        cancelled_order.set_status(OrderStatus::Canceled, Utc::now());
+        data_recorder.save(DataRecordType::Orders, &cancelled_order);
+        stop(data_recorder).await;
+
+        let snapshots =
+            DataRecorder::load_order_snapshots(&settings(&database_path)).expect("in test");
+
+        assert_eq!(snapshots.len(), 2);
+        let loaded_first_order = snapshots
+            .iter()
+            .find(|x| x.header.client_order_id == first_order.header.client_order_id)
+            .expect("in test");
+        assert_eq!(loaded_first_order.status(), OrderStatus::Created);
+        assert_eq!(loaded_first_order.price(), dec!(0.2));
+
+        let _ = std::fs::remove_dir_all(database_path.parent().expect("in test"));
+    }
+}
@@ -0,0 +1,111 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection};
+
+use super::recorder::{DataRecordType, RecordToSave};
+use crate::core::orders::order::OrderStatus;
+
+/// Embedded storage for `DataRecorder`. Records of every type are kept in separate table
+/// as JSON with insertion time
+pub(super) struct SqliteStorage {
+    connection: Connection,
+}
+
+impl SqliteStorage {
+    pub fn open(database_path: &Path) -> Result<Self> {
+        if let Some(directory) = database_path.parent() {
+            std::fs::create_dir_all(directory).with_context(|| {
+                format!(
+                    "Unable to create directory {} for DataRecorder database",
+                    directory.display()
+                )
+            })?;
+        }
+
+        let connection = Connection::open(database_path).with_context(|| {
+            format!(
+                "Unable to open DataRecorder database {}",
+                database_path.display()
+            )
+        })?;
+
+        for record_type in DataRecordType::ALL.iter() {
+            let table_name = record_type.table_name();
+            connection
+                .execute_batch(&format!(
+                    "CREATE TABLE IF NOT EXISTS {} (
+                        id INTEGER PRIMARY KEY AUTOINCREMENT,
+                        insert_time TEXT NOT NULL,
+                        data TEXT NOT NULL
+                    );",
+                    table_name
+                ))
+                .with_context(|| format!("Unable to create table {}", table_name))?;
+        }
+
+        Ok(SqliteStorage { connection })
+    }
+
+    /// Write records in single transaction
+    pub fn write(&mut self, records: &[RecordToSave]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let transaction = self
+            .connection
+            .transaction()
+            .context("Unable to start transaction")?;
+
+        for record in records {
+            let table_name = record.record_type.table_name();
+            transaction
+                .prepare_cached(&format!(
+                    "INSERT INTO {} (insert_time, data) VALUES (?1, ?2)",
+                    table_name
+                ))
+                .and_then(|mut statement| {
+                    statement.execute(params![record.insert_time.to_rfc3339(), record.data])
+                })
+                .with_context(|| format!("Unable to insert record into {}", table_name))?;
+        }
+
+        transaction.commit().context("Unable to commit transaction")
+    }
+
+    /// Read data of the last snapshots of orders which aren't finished in order of insertion.
+    /// Snapshots are selected in database, so history of finished orders isn't loaded
+    pub fn read_not_finished_orders(&self) -> Result<Vec<String>> {
+        let table_name = DataRecordType::Orders.table_name();
+        let finished_statuses = [
+            OrderStatus::FailedToCreate,
+            OrderStatus::Canceled,
+            OrderStatus::Completed,
+        ]
+        .iter()
+        .map(|status| match serde_json::to_value(status) {
+            Ok(serde_json::Value::String(status)) => Ok(status),
+            _ => Err(anyhow!("Unable to serialize order status {:?}", status)),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+        let mut statement = self
+            .connection
+            .prepare(&format!(
+                "SELECT data FROM {table} WHERE id IN (
+                    SELECT MAX(id) FROM {table} GROUP BY json_extract(data, '$.header.client_order_id')
+                ) AND json_extract(data, '$.props.status') NOT IN (?1, ?2, ?3)
+                ORDER BY id",
+                table = table_name
+            ))
+            .with_context(|| format!("Unable to prepare reading from {}", table_name))?;
+
+        let rows = statement
+            .query_map(&finished_statuses, |row| row.get(0))
+            .with_context(|| format!("Unable to read records from {}", table_name))?;
+
+        rows.collect::<rusqlite::Result<Vec<String>>>()
+            .with_context(|| format!("Unable to read record from {}", table_name))
+    }
+}
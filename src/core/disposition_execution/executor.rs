@@ -1,27 +1,37 @@
+use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
 use anyhow::{anyhow, bail, Context, Result};
-use chrono::Utc;
 use futures::FutureExt;
 use itertools::Itertools;
 use log::{error, trace, warn};
 use parking_lot::Mutex;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::{broadcast, oneshot};
 
+use crate::core::data_recorder::recorder::DataRecordType;
+use crate::core::disposition_execution::order_protection::{
+    check_order_protection, check_price_band,
+};
+use crate::core::disposition_execution::self_trade_prevention::find_crossed_orders;
 use crate::core::disposition_execution::trading_context_calculation::calculate_trading_context;
 use crate::core::exchanges::common::{
-    Amount, CurrencyPair, ExchangeAccountId, Price, TradePlaceAccount,
+    Amount, CurrencyPair, ExchangeAccountId, Price, TradePlace, TradePlaceAccount,
 };
 use crate::core::exchanges::events::ExchangeEvent;
 use crate::core::exchanges::general::currency_pair_metadata::CurrencyPairMetadata;
 use crate::core::exchanges::general::exchange::Exchange;
 use crate::core::exchanges::general::request_type::RequestType;
+use crate::core::exchanges::timeouts::requests_timeout_manager::RequestGroupId;
 use crate::core::explanation::{Explanation, WithExplanation};
 use crate::core::lifecycle::cancellation_token::CancellationToken;
 use crate::core::lifecycle::trading_engine::{EngineContext, Service};
+use crate::core::misc::reserve_parameters::ReserveParameters;
+use crate::core::misc::time_manager::time_manager;
 use crate::core::order_book::local_snapshot_service::LocalSnapshotsService;
 use crate::core::orders::event::OrderEventType;
 use crate::core::orders::order::{
@@ -29,12 +39,15 @@ use crate::core::orders::order::{
     OrderStatus, OrderType, ReservationId,
 };
 use crate::core::orders::pool::OrderRef;
+use crate::core::service_configuration::configuration_descriptor::ConfigurationDescriptor;
+use crate::core::settings::{PriceBandAction, SelfTradePreventionMode};
 use crate::core::{
     disposition_execution::trade_limit::is_enough_amount_and_cost, infrastructure::spawn_future,
 };
 use crate::core::{
     disposition_execution::{
-        CompositeOrder, OrderRecord, OrdersState, PriceSlot, TradeCycle, TradingContext,
+        CompositeOrder, OrderRecord, OrdersState, PriceSlot, PriceSlotId, TakerOrderRecord,
+        TradeCycle, TradeDisposition, TradingContext,
     },
     statistic_service::StatisticService,
 };
@@ -42,7 +55,7 @@ use crate::core::{nothing_to_do, DateTime};
 use crate::strategies::disposition_strategy::DispositionStrategy;
 use chrono::Duration;
 
-static DISPOSITION_EXECUTOR: &str = "DispositionExecutor";
+pub(crate) static DISPOSITION_EXECUTOR: &str = "DispositionExecutor";
 static DISPOSITION_EXECUTOR_REQUESTS_GROUP: &str = "DispositionExecutorRG";
 const ALLOWED_AMOUNT_DEVIATION_RATE: Decimal = dec!(0.001);
 const GROUP_REQUESTS_COUNT: usize = 4;
@@ -70,9 +83,12 @@ impl DispositionExecutorService {
         exchange_account_id: ExchangeAccountId,
         currency_pair: CurrencyPair,
         max_amount: Amount,
+        price_slots: Vec<PriceSlotId>,
+        configuration_descriptor: Arc<ConfigurationDescriptor>,
         strategy: Box<dyn DispositionStrategy>,
         cancellation_token: CancellationToken,
         statistics: Arc<StatisticService>,
+        restored_orders: Vec<OrderRef>,
     ) -> Arc<Self> {
         let (work_finished_sender, receiver) = oneshot::channel();
 
@@ -84,11 +100,14 @@ impl DispositionExecutorService {
                 exchange_account_id,
                 currency_pair,
                 max_amount,
+                &price_slots,
+                configuration_descriptor,
                 strategy,
                 work_finished_sender,
                 cancellation_token,
                 statistics,
             );
+            disposition_executor.adopt_restored_orders(restored_orders);
 
             disposition_executor.start().await
         };
@@ -115,28 +134,48 @@ impl Service for DispositionExecutorService {
     }
 }
 
-struct DispositionExecutor {
+#[derive(Serialize)]
+struct ExplanationRecord<'a> {
+    time: DateTime,
+    exchange_account_id: &'a ExchangeAccountId,
+    currency_pair: &'a CurrencyPair,
+    side: OrderSide,
+    level_index: usize,
+    explanation: &'a Explanation,
+}
+
+pub(crate) struct DispositionExecutor {
     engine_ctx: Arc<EngineContext>,
     exchange_account_id: ExchangeAccountId,
     currency_pair_metadata: Arc<CurrencyPairMetadata>,
     max_amount: Amount,
+    /// Descriptor of strategy instance used for reservation of balances
+    configuration_descriptor: Arc<ConfigurationDescriptor>,
     events_receiver: broadcast::Receiver<ExchangeEvent>,
+    // None if market data watchdog is disabled
+    stale_trade_places_receiver: Option<broadcast::Receiver<TradePlace>>,
     local_snapshots_service: LocalSnapshotsService,
     orders_state: OrdersState,
     strategy: Box<dyn DispositionStrategy>,
     work_finished_sender: Option<oneshot::Sender<Result<()>>>,
     cancellation_token: CancellationToken,
     statistics: Arc<StatisticService>,
+    /// Resting orders which are cancelled by self-trade prevention, but their cancellation isn't finished yet
+    self_trade_cancellations: Arc<Mutex<HashSet<ClientOrderId>>>,
+    /// Taker orders requested by strategy which wait for unblocking of exchange or fresh market data
+    postponed_taker_orders: Vec<TradeCycle>,
 }
 
 impl DispositionExecutor {
-    pub fn new(
+    pub(crate) fn new(
         engine_ctx: Arc<EngineContext>,
         events_receiver: broadcast::Receiver<ExchangeEvent>,
         local_snapshots_service: LocalSnapshotsService,
         exchange_account_id: ExchangeAccountId,
         currency_pair: CurrencyPair,
         max_amount: Amount,
+        price_slots: &[PriceSlotId],
+        configuration_descriptor: Arc<ConfigurationDescriptor>,
         strategy: Box<dyn DispositionStrategy>,
         work_finished_sender: oneshot::Sender<Result<()>>,
         cancellation_token: CancellationToken,
@@ -148,28 +187,164 @@ impl DispositionExecutor {
             .expect("Target exchange should exists")
             .get_currency_pair_metadata(&currency_pair)
             .expect("Currency pair metadata should exists for target trading place");
+        let stale_trade_places_receiver = engine_ctx
+            .market_data_watchdog
+            .as_ref()
+            .map(|x| x.subscribe_stale_trade_places());
 
         DispositionExecutor {
             engine_ctx,
             events_receiver,
+            stale_trade_places_receiver,
             local_snapshots_service,
             exchange_account_id,
             currency_pair_metadata,
             max_amount,
-            orders_state: OrdersState::new(),
+            configuration_descriptor,
+            orders_state: OrdersState::new(price_slots),
             strategy,
             work_finished_sender: Some(work_finished_sender),
             cancellation_token,
             statistics,
+            self_trade_cancellations: Default::default(),
+            postponed_taker_orders: Vec::new(),
+        }
+    }
+
+    /// Add orders of previous launches to price slots with the same side and level, so they are managed
+    /// like orders created by this executor. Orders without suitable price slot are cancelled
+    fn adopt_restored_orders(&self, restored_orders: Vec<OrderRef>) {
+        for order in restored_orders {
+            let side = order.side();
+            let signal_id = order.fn_ref(|x| x.header.signal_id.clone());
+            let price_slot = self.orders_state.by_side[side]
+                .slots
+                .iter()
+                .find(|x| signal_id.as_deref() == Some(x.id.to_string().as_str()));
+            let price_slot = match price_slot {
+                Some(price_slot) => price_slot,
+                None => {
+                    warn!(
+                        "Restored order {} {} doesn't belong to any price slot of {} so it is cancelled",
+                        order.client_order_id(),
+                        side,
+                        self.configuration_descriptor.service_configuration_key
+                    );
+                    self.cancel_restored_order(order);
+                    continue;
+                }
+            };
+
+            let requests_group_id = match self.engine_ctx.timeout_manager.try_reserve_group(
+                &order.exchange_account_id(),
+                GROUP_REQUESTS_COUNT,
+                DISPOSITION_EXECUTOR_REQUESTS_GROUP.to_string(),
+            ) {
+                Ok(Some(requests_group_id)) => requests_group_id,
+                Ok(None) => {
+                    error!(
+                        "Can't reserve requests group for restored order {} so it is cancelled",
+                        order.client_order_id()
+                    );
+                    self.cancel_restored_order(order);
+                    continue;
+                }
+                Err(error) => {
+                    error!(
+                        "Failed to reserve requests group for restored order {} so it is cancelled: {:?}",
+                        order.client_order_id(),
+                        error
+                    );
+                    self.cancel_restored_order(order);
+                    continue;
+                }
+            };
+
+            if let Err(error) = self.reserve_restored_order(&order) {
+                error!(
+                    "Failed to reserve balance for restored order {} so it is cancelled: {:?}",
+                    order.client_order_id(),
+                    error
+                );
+                let _ = self
+                    .engine_ctx
+                    .timeout_manager
+                    .remove_group(&order.exchange_account_id(), requests_group_id);
+                self.cancel_restored_order(order);
+                continue;
+            }
+
+            // Adopted order is repriced or cancelled on the next synchronization of its price slot
+            trace!(
+                "Adopting restored order {} to price slot {} {}",
+                order.client_order_id(),
+                price_slot.id,
+                side
+            );
+            price_slot.add_order(side, order.price(), order, requests_group_id);
         }
     }
 
+    /// Reservation of previous launch isn't valid anymore, so a fresh one is created for the rest
+    /// of order amount. It's released on fills and finishing of order like for a new order
+    fn reserve_restored_order(&self, order: &OrderRef) -> Result<()> {
+        let (client_order_id, side, price, remaining_amount) = order.fn_ref(|x| {
+            (
+                x.header.client_order_id.clone(),
+                x.header.side,
+                x.price(),
+                x.amount() - x.filled_amount(),
+            )
+        });
+
+        let reserve_parameters = ReserveParameters::new(
+            self.configuration_descriptor.clone(),
+            order.exchange_account_id(),
+            self.currency_pair_metadata.clone(),
+            side,
+            price,
+            remaining_amount,
+        );
+        let mut balance_manager = self.engine_ctx.balance_manager.lock();
+        let reservation_id = balance_manager
+            .try_reserve(&reserve_parameters, &mut None)
+            .with_context(|| format!("Not enough balance to reserve {}", remaining_amount))?;
+
+        order.fn_mut(|x| {
+            let mut header = (*x.header).clone();
+            header.reservation_id = Some(reservation_id);
+            x.header = Arc::new(header);
+        });
+        balance_manager.approve_reservation(reservation_id, &client_order_id, remaining_amount);
+
+        Ok(())
+    }
+
+    fn cancel_restored_order(&self, order: OrderRef) {
+        let exchange = self.exchange(&order.exchange_account_id());
+        let cancellation_token = self.cancellation_token.clone();
+        let action = async move {
+            exchange
+                .wait_cancel_order(order, None, false, cancellation_token)
+                .await
+        };
+        spawn_future(
+            "Start wait_cancel_order from DispositionExecutor::cancel_restored_order()",
+            true,
+            action.boxed(),
+        );
+    }
+
     pub async fn start(&mut self) -> Result<()> {
         let mut trading_context: Option<TradingContext> = None;
 
         loop {
             let event = tokio::select! {
                 event_res = self.events_receiver.recv() => event_res.context("Error during receiving event in DispositionExecutor::start()")?,
+                trade_place = recv_stale_trade_place(&mut self.stale_trade_places_receiver) => {
+                    self.cancel_orders_on_stale_trade_place(&trade_place);
+                    continue;
+                }
                 _ = self.cancellation_token.when_cancelled() => {
                     let _ = self.work_finished_sender.take().ok_or(anyhow!("Can't take `work_finished_sender` in DispositionExecutor"))?.send(Ok(()));
                     return Ok(());
@@ -180,7 +355,26 @@ impl DispositionExecutor {
         }
     }
 
-    fn handle_event(
+    /// Quotes are cancelled as soon as trade place becomes stale, because order book events
+    /// which trigger synchronization of price slots aren't received from it
+    fn cancel_orders_on_stale_trade_place(&self, trade_place: &TradePlace) {
+        for (_, state_by_side) in self.orders_state.by_side.iter() {
+            for price_slot in &state_by_side.slots {
+                if !self.is_price_slot_market_data_stale(&None, price_slot) {
+                    continue;
+                }
+
+                let mut explanation = Explanation::default();
+                self.start_cancelling_all_orders(
+                    &format!("market data on {:?} is stale", trade_place),
+                    &mut price_slot.order.borrow_mut(),
+                    &mut explanation,
+                );
+            }
+        }
+    }
+
+    pub(crate) fn handle_event(
         &mut self,
         event: ExchangeEvent,
         last_trading_context: &mut Option<TradingContext>,
@@ -198,91 +392,20 @@ impl DispositionExecutor {
                 }
 
                 let order = &order_event.order;
-                match order_event.event_type {
-                    OrderEventType::CreateOrderSucceeded => nothing_to_do(),
-                    OrderEventType::CreateOrderFailed => {
-                        let client_order_id = order.client_order_id();
-                        trace!(
-                            "Started handling event CreateOrderFailed {} in DispositionExecutor",
-                            client_order_id
-                        );
-                        let price_slot = self.get_price_slot(order);
-                        let price_slot = match price_slot {
-                            None => return Ok(()),
-                            Some(v) => v,
-                        };
-
-                        self.finish_order(order, price_slot)?;
-                        trace!(
-                            "Finished handling event CreateOrderFailed {} in DispositionExecutor",
-                            client_order_id
-                        );
-                    }
-                    OrderEventType::OrderFilled { ref cloned_order } => {
-                        trace!(
-                            "Started handling event OrderFilled {} in DispositionExecutor",
-                            cloned_order.header.client_order_id
-                        );
-                        let price_slot = self.get_price_slot(order);
-                        if let Some(price_slot) = price_slot {
-                            // TODO recalculate balances on order fill when BalanceManager will be implemented
-                            if cloned_order.status() == OrderStatus::Completed {
-                                return Ok(());
-                            }
-
-                            self.handle_order_fill(cloned_order, price_slot)?;
-                        }
-                        trace!(
-                            "Finished handling event OrderFilled {} in DispositionExecutor",
-                            cloned_order.header.client_order_id
-                        );
-                    }
-                    OrderEventType::OrderCompleted { ref cloned_order } => {
-                        trace!(
-                            "Started handling event OrderCompleted {} in DispositionExecutor",
-                            cloned_order.header.client_order_id
-                        );
-                        let price_slot = self.get_price_slot(order);
-                        if let Some(price_slot) = price_slot {
-                            self.handle_order_fill(cloned_order, price_slot)?;
-                            self.finish_order(order, price_slot)?;
-                        }
-                        trace!(
-                            "Finished handling event OrderCompleted {} in DispositionExecutor",
-                            cloned_order.header.client_order_id
-                        );
-                    }
-                    OrderEventType::CancelOrderSucceeded => {
-                        let client_order_id = order.client_order_id();
-                        trace!(
-                            "Started handling event CancelOrderSucceeded {} in DispositionExecutor",
-                            client_order_id
-                        );
-
-                        let price_slot = self.get_price_slot(order);
-                        let price_slot = match price_slot {
-                            None => return Ok(()),
-                            Some(v) => v,
-                        };
-
-                        self.finish_order(order, price_slot)?;
-                        trace!(
-                            "Finished handling event CancelOrderSucceeded {} in DispositionExecutor",
-                            client_order_id
-                        );
-                    }
-                    OrderEventType::CancelOrderFailed => {
-                        //We should use WaitCancelOrder everywhere, so we don't need to
-                        //manually call CancelOrder if CancelOrderFailed
-                        //like we used to in a event-driven approach
-
-                        // TODO save state to Database
-                    }
+                if self.orders_state.is_taker_order(order) {
+                    self.handle_taker_order_event(order, &order_event.event_type, now)?;
+                } else if let Some(price_slot) =
+                    self.orders_state.by_side[order.side()].find_price_slot(order)
+                {
+                    self.handle_price_slot_order_event(order, &order_event.event_type, price_slot)?;
                 }
+                // Other orders belong to other strategy instances of the engine
             }
             _ => nothing_to_do(),
         };
 
+        self.create_taker_orders(now)?;
+
         let mut new_trading_context = estimate_trading_context(
             need_recalculate_trading_context,
             self.max_amount,
@@ -301,6 +424,130 @@ impl DispositionExecutor {
         Ok(())
     }
 
+    fn handle_price_slot_order_event(
+        &self,
+        order: &OrderRef,
+        event_type: &OrderEventType,
+        price_slot: &PriceSlot,
+    ) -> Result<()> {
+        match event_type {
+            OrderEventType::CreateOrderSucceeded => nothing_to_do(),
+            OrderEventType::CreateOrderFailed => {
+                let client_order_id = order.client_order_id();
+                trace!(
+                    "Started handling event CreateOrderFailed {} in DispositionExecutor",
+                    client_order_id
+                );
+                self.finish_order(order, price_slot)?;
+                trace!(
+                    "Finished handling event CreateOrderFailed {} in DispositionExecutor",
+                    client_order_id
+                );
+            }
+            OrderEventType::OrderFilled { cloned_order } => {
+                trace!(
+                    "Started handling event OrderFilled {} in DispositionExecutor",
+                    cloned_order.header.client_order_id
+                );
+                self.recalculate_balances_on_fill(cloned_order)?;
+
+                if cloned_order.status() == OrderStatus::Completed {
+                    return Ok(());
+                }
+
+                self.handle_order_fill(cloned_order, price_slot)?;
+                trace!(
+                    "Finished handling event OrderFilled {} in DispositionExecutor",
+                    cloned_order.header.client_order_id
+                );
+            }
+            OrderEventType::OrderCompleted { cloned_order } => {
+                trace!(
+                    "Started handling event OrderCompleted {} in DispositionExecutor",
+                    cloned_order.header.client_order_id
+                );
+                self.handle_order_fill(cloned_order, price_slot)?;
+                self.finish_order(order, price_slot)?;
+                trace!(
+                    "Finished handling event OrderCompleted {} in DispositionExecutor",
+                    cloned_order.header.client_order_id
+                );
+            }
+            OrderEventType::CancelOrderSucceeded => {
+                let client_order_id = order.client_order_id();
+                trace!(
+                    "Started handling event CancelOrderSucceeded {} in DispositionExecutor",
+                    client_order_id
+                );
+                self.finish_order(order, price_slot)?;
+                trace!(
+                    "Finished handling event CancelOrderSucceeded {} in DispositionExecutor",
+                    client_order_id
+                );
+            }
+            OrderEventType::CancelOrderFailed => {
+                //We should use WaitCancelOrder everywhere, so we don't need to
+                //manually call CancelOrder if CancelOrderFailed
+                //like we used to in a event-driven approach
+
+                // TODO save state to Database
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fills of taker orders are applied to balances as soon as they are received.
+    /// Strategy is notified about finished taker order with its not filled amount
+    fn handle_taker_order_event(
+        &mut self,
+        order: &OrderRef,
+        event_type: &OrderEventType,
+        now: DateTime,
+    ) -> Result<()> {
+        match event_type {
+            OrderEventType::OrderFilled { cloned_order } => {
+                self.recalculate_balances_on_fill(cloned_order)
+            }
+            OrderEventType::CreateOrderFailed
+            | OrderEventType::OrderCompleted { .. }
+            | OrderEventType::CancelOrderSucceeded => self.finish_taker_order(order, now),
+            OrderEventType::CreateOrderSucceeded | OrderEventType::CancelOrderFailed => Ok(()),
+        }
+    }
+
+    fn finish_taker_order(&mut self, order: &OrderRef, now: DateTime) -> Result<()> {
+        let client_order_id = order.client_order_id();
+        let taker_order_record = match self
+            .orders_state
+            .taker_orders
+            .borrow_mut()
+            .remove(&client_order_id)
+        {
+            None => return Ok(()),
+            Some(v) => v,
+        };
+        trace!(
+            "Finishing taker order {} in DispositionExecutor",
+            client_order_id
+        );
+
+        self.unreserve_order_amount(order)?;
+        let _ = self.engine_ctx.timeout_manager.remove_group(
+            &order.exchange_account_id(),
+            taker_order_record.request_group_id,
+        )?;
+
+        let unfilled_amount = order.fn_ref(|x| x.amount() - x.filled_amount());
+        self.strategy.handle_taker_order_finished(
+            &taker_order_record.trade_cycle,
+            unfilled_amount,
+            now,
+        );
+
+        Ok(())
+    }
+
     fn synchronize_price_slots_for_trading_context(
         &mut self,
         trading_context: &mut Option<TradingContext>,
@@ -321,10 +568,42 @@ impl DispositionExecutor {
             )?
         }
 
-        // TODO save explanations
+        self.save_explanations(trading_context, now);
+
         Ok(())
     }
 
+    fn save_explanations(&self, trading_context: &Option<TradingContext>, now: DateTime) {
+        let data_recorder = match &self.engine_ctx.data_recorder {
+            Some(data_recorder) => data_recorder,
+            None => return,
+        };
+
+        let trading_context = match trading_context {
+            Some(trading_context) => trading_context,
+            None => return,
+        };
+
+        let currency_pair = self.currency_pair_metadata.currency_pair();
+        for (side, trading_context_by_side) in trading_context.by_side.iter() {
+            for (level_index, with_explanation) in
+                trading_context_by_side.estimating.iter().enumerate()
+            {
+                data_recorder.save(
+                    DataRecordType::Explanations,
+                    &ExplanationRecord {
+                        time: now,
+                        exchange_account_id: &self.exchange_account_id,
+                        currency_pair: &currency_pair,
+                        side,
+                        level_index,
+                        explanation: &with_explanation.explanation,
+                    },
+                );
+            }
+        }
+    }
+
     fn synchronize_price_slots_for_list(
         &self,
         slots: &[PriceSlot],
@@ -332,22 +611,104 @@ impl DispositionExecutor {
         max_amount: Decimal,
         now: DateTime,
     ) -> Result<()> {
-        if slots.len() != estimating.len() {
-            bail!("ExchangeAccountId {} slots count is different is trading context ({}) and DispositionExecutor state ({})", self.exchange_account_id, estimating.len(), slots.len());
+        if estimating.len() > slots.len() {
+            bail!("ExchangeAccountId {} levels count in trading context ({}) is greater than price slots count in DispositionExecutor state ({})", self.exchange_account_id, estimating.len(), slots.len());
+        }
+
+        for price_slot in slots {
+            let level_index = price_slot.id.level_index;
+            match estimating.get_mut(level_index) {
+                Some(with_explanation) => {
+                    let (trade_cycle, explanation) = with_explanation.as_mut_all();
+                    self.clamp_order_price(trade_cycle, explanation)?;
+                    self.synchronize_price_slot(
+                        trade_cycle,
+                        price_slot,
+                        max_amount,
+                        now,
+                        explanation,
+                    )?;
+                }
+                None => {
+                    // Strategy doesn't estimate this level, so orders of the slot aren't needed
+                    let mut explanation = Explanation::default();
+                    explanation.add_reason(format!(
+                        "There is no estimation for level {} in trading context",
+                        level_index
+                    ));
+                    self.synchronize_price_slot(
+                        &None,
+                        price_slot,
+                        max_amount,
+                        now,
+                        &mut explanation,
+                    )?;
+                }
+            }
         }
 
-        for level_index in 0..slots.len() {
-            let price_slot = &slots[level_index];
-            let with_explanation = &mut estimating[level_index];
+        Ok(())
+    }
+
+    /// Clamp price of estimated order to price band, so existing orders are compared with the price of new one.
+    /// Pre-trade checks of new orders are in `check_new_order`
+    fn clamp_order_price(
+        &self,
+        trade_cycle: &mut Option<TradeCycle>,
+        explanation: &mut Explanation,
+    ) -> Result<()> {
+        let settings = match &self.engine_ctx.app_settings.order_protection {
+            Some(settings) if settings.price_band_action == PriceBandAction::Clamp => settings,
+            _ => return Ok(()),
+        };
 
-            let (trade_cycle, explanation) = with_explanation.as_mut_all();
+        let disposition = match trade_cycle {
+            Some(trade_cycle) => &mut trade_cycle.disposition,
+            None => return Ok(()),
+        };
 
-            self.synchronize_price_slot(trade_cycle, price_slot, max_amount, now, explanation)?;
+        let trade_place_account = disposition.trade_place_account();
+        let currency_pair_metadata = self.get_currency_pair_metadata(&trade_place_account)?;
+        let reference_price = self.reference_price(&trade_place_account);
+        if let Ok(price) = check_price_band(
+            settings,
+            disposition.price(),
+            reference_price,
+            &currency_pair_metadata,
+        ) {
+            if price != disposition.order.price {
+                explanation.add_reason(format!(
+                    "Order price {} is clamped to {} by price band",
+                    disposition.order.price, price
+                ));
+                disposition.order.price = price;
+            }
         }
 
         Ok(())
     }
 
+    /// Mid price of order book or price of last trade if order book is empty
+    fn reference_price(&self, trade_place_account: &TradePlaceAccount) -> Option<Price> {
+        let trade_place = trade_place_account.trade_place();
+        let mid_price = self
+            .local_snapshots_service
+            .get_snapshot(&trade_place)
+            .and_then(
+                |snapshot| match (snapshot.get_top_ask(), snapshot.get_top_bid()) {
+                    (Some((ask, _)), Some((bid, _))) => Some((ask + bid) / dec!(2)),
+                    _ => None,
+                },
+            );
+
+        mid_price.or_else(|| {
+            self.exchange(&trade_place_account.exchange_account_id)
+                .last_trades
+                .get(&trade_place)
+                .map(|x| x.price)
+        })
+    }
+
     fn synchronize_price_slot(
         &self,
         new_estimating: &Option<TradeCycle>,
@@ -363,11 +724,7 @@ impl DispositionExecutor {
             composite_order.borrow().side
         );
 
-        if self
-            .engine_ctx
-            .exchange_blocker
-            .is_blocked(&self.exchange_account_id)
-        {
+        if self.is_price_slot_exchange_blocked(new_estimating, price_slot) {
             self.start_cancelling_all_orders(
                 "target exchange is locked",
                 &mut composite_order.borrow_mut(),
@@ -377,6 +734,16 @@ impl DispositionExecutor {
             return Ok(());
         }
 
+        if self.is_price_slot_market_data_stale(new_estimating, price_slot) {
+            self.start_cancelling_all_orders(
+                "market data is stale",
+                &mut composite_order.borrow_mut(),
+                explanation,
+            );
+
+            return Ok(());
+        }
+
         // TODO close position if needed
 
         let new_estimating = match new_estimating {
@@ -553,7 +920,7 @@ impl DispositionExecutor {
 
         let client_order_id = order.client_order_id();
         let request_group_id = order_record.request_group_id.clone();
-        let exchange = self.exchange();
+        let exchange = self.exchange(&order.exchange_account_id());
         let cancellation_token = self.cancellation_token.clone();
 
         let action = async move {
@@ -598,97 +965,46 @@ impl DispositionExecutor {
 
         let side = price_slot.order.borrow().side;
         let new_disposition = &new_estimating.disposition;
-
-        let new_price = new_disposition.order.price;
-        let found = self.find_new_order_crossing_existing_orders(new_price, side);
-        if let Some(crossed_order) = found {
-            let msg = format!("Finished `try_create_order` because there is order {} with price {} that crossing current price {}", crossed_order.client_order_id(),
-                                 crossed_order.price(),
-                                 new_price
-            );
-            return log_trace(msg, explanation);
-        }
+        let trade_place_account = new_disposition.trade_place_account();
 
         let new_order_amount = self.calculate_new_order_amount(
-            new_disposition.trade_place_account(),
+            trade_place_account.clone(),
             side,
             desired_amount,
             max_amount,
             explanation,
         );
 
-        if let Err(reason) = is_enough_amount_and_cost(
-            new_disposition,
-            new_order_amount,
-            true,
-            &self.currency_pair_metadata,
-        ) {
-            return log_trace(
-                format!("Finished `try_create_order` by reason: {}", reason),
-                explanation,
-            );
+        if !self.check_new_order(new_disposition, new_order_amount, explanation)? {
+            return Ok(());
         }
 
-        let new_client_order_id = ClientOrderId::unique_id();
-
-        let requests_group_id = self.engine_ctx.timeout_manager.try_reserve_group(
-            &self.exchange_account_id,
-            GROUP_REQUESTS_COUNT,
-            DISPOSITION_EXECUTOR_REQUESTS_GROUP.to_string(),
-        )?;
-
-        let requests_group_id = match requests_group_id {
-            None => {
-                return log_trace(
-                    "Finished `try_create_order` because can't reserve reservation group",
-                    explanation,
-                )
-            }
-            Some(v) => v,
-        };
-
-        // TODO reserve balances
-
-        if !self.engine_ctx.timeout_manager.try_reserve_group_instant(
-            &self.exchange_account_id,
-            RequestType::CancelOrder,
-            Some(requests_group_id),
-        )? {
-            // TODO unreserve balances
-
-            let _ = self
-                .engine_ctx
-                .timeout_manager
-                .remove_group(&self.exchange_account_id, requests_group_id)?;
-
-            return log_trace(
-                "Finished `try_create_order` because can't reserve requests",
-                explanation,
-            );
-        }
+        let (reservation_id, requests_group_id) =
+            match self.reserve_new_order(new_disposition, new_order_amount, explanation)? {
+                None => return Ok(()),
+                Some(v) => v,
+            };
 
         *price_slot.estimating.borrow_mut() = Some(Box::new(new_estimating.clone()));
 
         let new_order_header = OrderHeader::new(
-            new_client_order_id.clone(),
+            ClientOrderId::unique_id(),
             now,
-            self.exchange_account_id.clone(),
-            self.currency_pair_metadata.currency_pair(),
+            trade_place_account.exchange_account_id.clone(),
+            trade_place_account.currency_pair.clone(),
             OrderType::Limit,
             new_disposition.side(),
             new_order_amount,
             OrderExecutionType::MakerOnly,
-            // TODO fix after implementation balances reservation
-            Some(ReservationId::generate()),
-            None,
+            Some(reservation_id),
+            // Price slot of the order is kept to adopt it by the same price slot after restart
+            Some(price_slot.id.to_string()),
             new_estimating.strategy_name.clone(),
         );
+        let new_client_order_id = new_order_header.client_order_id.clone();
 
-        let exchange = self.exchange();
-
-        let new_order = exchange
-            .orders
-            .add_simple_initial(new_order_header.clone(), Some(new_disposition.price()));
+        let new_order =
+            self.add_new_order(&new_order_header, new_disposition.price(), reservation_id);
 
         price_slot.add_order(
             new_disposition.side(),
@@ -699,73 +1015,522 @@ impl DispositionExecutor {
 
         explanation.add_reason(format!("Creating order {}", new_client_order_id));
 
-        self.cancellation_token.error_if_cancellation_requested()?;
-
-        {
-            let new_client_order_id = new_client_order_id.clone();
-            let cancellation_token = self.cancellation_token.clone();
-
-            let action = async move {
-                trace!("Begin create_order {}", new_client_order_id);
+        self.start_creating_order(new_order_header, new_disposition.price())?;
 
-                let order_creating = OrderCreating {
-                    header: new_order_header,
-                    price: new_price,
-                };
+        trace!("Finished try_create_order {}", new_client_order_id);
+        Ok(())
+    }
 
-                exchange
-                    .create_order(&order_creating, None, cancellation_token)
-                    .await?;
+    /// Create orders which strategy requested to execute immediately (e.g. hedges of maker fills).
+    /// Orders for blocked exchange or stale market data are postponed and checked again on the next event,
+    /// so they wait for unblocking instead of being rejected.
+    /// Strategy is notified about orders which aren't created, so it can retry them
+    fn create_taker_orders(&mut self, now: DateTime) -> Result<()> {
+        let mut taker_orders = std::mem::take(&mut self.postponed_taker_orders);
+        taker_orders.extend(self.strategy.take_taker_orders(now));
+
+        for trade_cycle in taker_orders {
+            if let Some(reason) = self.taker_order_postponing_reason(&trade_cycle) {
+                trace!(
+                    "Taker order {} {} {} on {} is postponed because {}",
+                    trade_cycle.disposition.side(),
+                    trade_cycle.disposition.amount(),
+                    trade_cycle.disposition.currency_pair(),
+                    trade_cycle.disposition.exchange_account_id(),
+                    reason
+                );
+                self.postponed_taker_orders.push(trade_cycle);
+                continue;
+            }
 
-                trace!("Finished create_order {}", new_client_order_id);
+            let mut explanation = Explanation::default();
+            if self.try_create_taker_order(&trade_cycle, now, &mut explanation)? {
+                continue;
+            }
 
-                Ok(())
-            };
-            spawn_future(
-                "wait_cancel_order in blocking cancel_order",
-                true,
-                action.boxed(),
+            warn!(
+                "Taker order {} {} {} on {} isn't created: {:?}",
+                trade_cycle.disposition.side(),
+                trade_cycle.disposition.amount(),
+                trade_cycle.disposition.currency_pair(),
+                trade_cycle.disposition.exchange_account_id(),
+                explanation
+            );
+            self.strategy.handle_taker_order_finished(
+                &trade_cycle,
+                trade_cycle.disposition.amount(),
+                now,
             );
         }
 
-        trace!("Begin try_create_order {}", new_client_order_id);
         Ok(())
     }
 
-    fn find_new_order_crossing_existing_orders(
-        &self,
-        new_order_price: Price,
-        side: OrderSide,
-    ) -> Option<OrderRef> {
-        let buy_comparator = &|order: &OrderRef| order.price() <= new_order_price;
-        let sell_comparator = &|order: &OrderRef| new_order_price <= order.price();
-
-        let is_crossing: &dyn Fn(&OrderRef) -> bool = match side {
-            OrderSide::Buy => buy_comparator,
-            OrderSide::Sell => sell_comparator,
-        };
+    fn taker_order_postponing_reason(&self, trade_cycle: &TradeCycle) -> Option<&'static str> {
+        let disposition = &trade_cycle.disposition;
+        if self
+            .engine_ctx
+            .exchange_blocker
+            .is_blocked(&disposition.exchange_account_id())
+        {
+            return Some("exchange is blocked");
+        }
 
-        for slot in &self.orders_state.by_side[side.change_side()].slots {
-            for (_, order_record) in &slot.order.borrow().orders {
-                let order = &order_record.order;
-                if order.is_finished() && is_crossing(order) {
-                    return Some(order.clone());
-                }
-            }
+        let is_market_data_stale = self
+            .engine_ctx
+            .market_data_watchdog
+            .as_ref()
+            .is_some_and(|x| x.is_stale(&disposition.trade_place()));
+        if is_market_data_stale {
+            return Some("market data is stale");
         }
 
-        return None;
+        None
     }
 
-    fn calculate_new_order_amount(
+    /// Taker order is created by market order which isn't bound to any price slot,
+    /// but passes the same checks and reservations as orders of price slots.
+    /// Returns false if order isn't created
+    fn try_create_taker_order(
         &self,
-        _trade_place_account: TradePlaceAccount,
-        side: OrderSide,
-        desired_amount: Decimal,
-        max_amount: Decimal,
+        trade_cycle: &TradeCycle,
+        now: DateTime,
         explanation: &mut Explanation,
-    ) -> Decimal {
-        let total_remaining_amount = self.orders_state.by_side[side].calc_total_remaining_amount();
+    ) -> Result<bool> {
+        let disposition = &trade_cycle.disposition;
+        let amount = disposition.amount();
+
+        if !self.check_new_order(disposition, amount, explanation)? {
+            return Ok(false);
+        }
+
+        let (reservation_id, requests_group_id) =
+            match self.reserve_new_order(disposition, amount, explanation)? {
+                None => return Ok(false),
+                Some(v) => v,
+            };
+
+        let header = OrderHeader::new(
+            ClientOrderId::unique_id(),
+            now,
+            disposition.exchange_account_id(),
+            disposition.currency_pair(),
+            OrderType::Market,
+            disposition.side(),
+            amount,
+            OrderExecutionType::None,
+            Some(reservation_id),
+            None,
+            trade_cycle.strategy_name.clone(),
+        );
+        let client_order_id = header.client_order_id.clone();
+
+        // Price of disposition is estimation of market order price used for balance reservation
+        let order = self.add_new_order(&header, disposition.price(), reservation_id);
+        self.orders_state.taker_orders.borrow_mut().insert(
+            client_order_id.clone(),
+            TakerOrderRecord::new(order, trade_cycle.clone(), requests_group_id),
+        );
+
+        trace!("Creating taker order {}", client_order_id);
+        self.start_creating_order(header, disposition.price())?;
+
+        Ok(true)
+    }
+
+    /// Pre-trade checks of new order with specified amount. Returns false if order shouldn't be created now
+    fn check_new_order(
+        &self,
+        disposition: &TradeDisposition,
+        amount: Amount,
+        explanation: &mut Explanation,
+    ) -> Result<bool> {
+        let trade_place_account = disposition.trade_place_account();
+        let side = disposition.side();
+        let price = disposition.price();
+        let currency_pair_metadata = self.get_currency_pair_metadata(&trade_place_account)?;
+
+        let found = self.find_new_order_crossing_existing_orders(price, side, &trade_place_account);
+        if let Some(crossed_order) = found {
+            let msg = format!(
+                "Order isn't created because there is order {} with price {} that crossing current price {}",
+                crossed_order.client_order_id(),
+                crossed_order.price(),
+                price
+            );
+            log_trace(msg, explanation)?;
+            return Ok(false);
+        }
+
+        if let Some(self_trade_prevention) = &self.engine_ctx.app_settings.self_trade_prevention {
+            if !self.prevent_self_trade(
+                self_trade_prevention.mode,
+                &trade_place_account,
+                side,
+                price,
+                explanation,
+            ) {
+                log_trace(
+                    "Order isn't created because it would cross own resting orders",
+                    explanation,
+                )?;
+                return Ok(false);
+            }
+        }
+
+        if let Err(reason) =
+            is_enough_amount_and_cost(disposition, amount, true, &currency_pair_metadata)
+        {
+            log_trace(
+                format!("Order isn't created by reason: {}", reason),
+                explanation,
+            )?;
+            return Ok(false);
+        }
+
+        if let Some(settings) = &self.engine_ctx.app_settings.order_protection {
+            if let Err(reason) = check_order_protection(
+                settings,
+                disposition,
+                amount,
+                self.reference_price(&trade_place_account),
+                &currency_pair_metadata,
+            ) {
+                explanation.add_reason(format!("Order is rejected by pre-trade check: {}", reason));
+                self.statistics
+                    .register_rejected_order(&trade_place_account);
+                log_trace(
+                    "Order isn't created because it's rejected by pre-trade check",
+                    explanation,
+                )?;
+                return Ok(false);
+            }
+        }
+
+        if let Some(risk_manager) = &self.engine_ctx.risk_manager {
+            let open_orders = self
+                .exchange(&trade_place_account.exchange_account_id)
+                .orders
+                .not_finished
+                .iter()
+                .filter(|x| x.trade_place_account() == trade_place_account)
+                .map(|x| x.value().clone())
+                .collect_vec();
+
+            if !risk_manager.check_order(
+                &trade_place_account,
+                side,
+                price,
+                amount,
+                &open_orders,
+                explanation,
+            ) {
+                log_trace(
+                    "Order isn't created because it's rejected by risk manager",
+                    explanation,
+                )?;
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Reserve requests group with cancellation request and balance for new order.
+    /// Returns None if anything can't be reserved
+    fn reserve_new_order(
+        &self,
+        disposition: &TradeDisposition,
+        amount: Amount,
+        explanation: &mut Explanation,
+    ) -> Result<Option<(ReservationId, RequestGroupId)>> {
+        let trade_place_account = disposition.trade_place_account();
+        let exchange_account_id = &trade_place_account.exchange_account_id;
+        let currency_pair_metadata = self.get_currency_pair_metadata(&trade_place_account)?;
+
+        let requests_group_id = self.engine_ctx.timeout_manager.try_reserve_group(
+            exchange_account_id,
+            GROUP_REQUESTS_COUNT,
+            DISPOSITION_EXECUTOR_REQUESTS_GROUP.to_string(),
+        )?;
+
+        let requests_group_id = match requests_group_id {
+            None => {
+                log_trace(
+                    "Order isn't created because can't reserve reservation group",
+                    explanation,
+                )?;
+                return Ok(None);
+            }
+            Some(v) => v,
+        };
+
+        let reserve_parameters = ReserveParameters::new(
+            self.configuration_descriptor.clone(),
+            exchange_account_id.clone(),
+            currency_pair_metadata,
+            disposition.side(),
+            disposition.price(),
+            amount,
+        );
+        let mut balance_explanation = Some(std::mem::take(explanation));
+        let reservation_id = self
+            .engine_ctx
+            .balance_manager
+            .lock()
+            .try_reserve(&reserve_parameters, &mut balance_explanation);
+        *explanation = balance_explanation.unwrap_or_default();
+
+        let reservation_id = match reservation_id {
+            None => {
+                let _ = self
+                    .engine_ctx
+                    .timeout_manager
+                    .remove_group(exchange_account_id, requests_group_id)?;
+
+                log_trace(
+                    "Order isn't created because can't reserve balance",
+                    explanation,
+                )?;
+                return Ok(None);
+            }
+            Some(v) => v,
+        };
+
+        if !self.engine_ctx.timeout_manager.try_reserve_group_instant(
+            exchange_account_id,
+            RequestType::CancelOrder,
+            Some(requests_group_id),
+        )? {
+            self.engine_ctx
+                .balance_manager
+                .lock()
+                .unreserve_rest(reservation_id)?;
+
+            let _ = self
+                .engine_ctx
+                .timeout_manager
+                .remove_group(exchange_account_id, requests_group_id)?;
+
+            log_trace(
+                "Order isn't created because can't reserve requests",
+                explanation,
+            )?;
+            return Ok(None);
+        }
+
+        Ok(Some((reservation_id, requests_group_id)))
+    }
+
+    /// Add new order to the pool and approve its reservation
+    fn add_new_order(
+        &self,
+        header: &Arc<OrderHeader>,
+        price: Price,
+        reservation_id: ReservationId,
+    ) -> OrderRef {
+        let new_order = self
+            .exchange(&header.exchange_account_id)
+            .orders
+            .add_simple_initial(header.clone(), Some(price));
+
+        // Approve right after adding order to the pool, because fills can be received
+        // before the response on order creation
+        self.engine_ctx.balance_manager.lock().approve_reservation(
+            reservation_id,
+            &header.client_order_id,
+            header.amount,
+        );
+
+        new_order
+    }
+
+    fn start_creating_order(&self, header: Arc<OrderHeader>, price: Price) -> Result<()> {
+        self.cancellation_token.error_if_cancellation_requested()?;
+
+        let exchange = self.exchange(&header.exchange_account_id);
+        let cancellation_token = self.cancellation_token.clone();
+        let action = async move {
+            let client_order_id = header.client_order_id.clone();
+            trace!("Begin create_order {}", client_order_id);
+
+            let order_creating = OrderCreating { header, price };
+            exchange
+                .create_order(&order_creating, None, cancellation_token)
+                .await?;
+
+            trace!("Finished create_order {}", client_order_id);
+
+            Ok(())
+        };
+        spawn_future(
+            "Start create_order from DispositionExecutor",
+            true,
+            action.boxed(),
+        );
+
+        Ok(())
+    }
+
+    fn find_new_order_crossing_existing_orders(
+        &self,
+        new_order_price: Price,
+        side: OrderSide,
+        trade_place_account: &TradePlaceAccount,
+    ) -> Option<OrderRef> {
+        let buy_comparator = &|order: &OrderRef| order.price() <= new_order_price;
+        let sell_comparator = &|order: &OrderRef| new_order_price <= order.price();
+
+        let is_crossing: &dyn Fn(&OrderRef) -> bool = match side {
+            OrderSide::Buy => buy_comparator,
+            OrderSide::Sell => sell_comparator,
+        };
+
+        for slot in &self.orders_state.by_side[side.change_side()].slots {
+            for (_, order_record) in &slot.order.borrow().orders {
+                let order = &order_record.order;
+                let is_same_trade_place = order.exchange_account_id()
+                    == trade_place_account.exchange_account_id
+                    && order.currency_pair() == trade_place_account.currency_pair;
+                if is_same_trade_place && order.is_finished() && is_crossing(order) {
+                    return Some(order.clone());
+                }
+            }
+        }
+
+        return None;
+    }
+
+    /// Check new order against open orders of all strategies and accounts on the same trade place.
+    /// Returns false if new order shouldn't be created now
+    fn prevent_self_trade(
+        &self,
+        mode: SelfTradePreventionMode,
+        trade_place_account: &TradePlaceAccount,
+        side: OrderSide,
+        price: Price,
+        explanation: &mut Explanation,
+    ) -> bool {
+        let exchange_account_id = &trade_place_account.exchange_account_id;
+        let is_native = mode == SelfTradePreventionMode::Native
+            && self
+                .exchange(exchange_account_id)
+                .supports_self_trade_prevention();
+
+        let crossed_orders = find_crossed_orders(
+            &self.engine_ctx.exchanges,
+            &trade_place_account.trade_place(),
+            side,
+            price,
+        )
+        .into_iter()
+        // Exchange prevents self-trades only within the same account
+        .filter(|x| !is_native || &x.exchange_account_id() != exchange_account_id)
+        .collect_vec();
+
+        if crossed_orders.is_empty() {
+            return true;
+        }
+
+        let display_orders = |orders: &[OrderRef]| {
+            orders
+                .iter()
+                .map(|x| format!("{} {}", x.client_order_id(), x.exchange_account_id()))
+                .join(", ")
+        };
+
+        if mode != SelfTradePreventionMode::CancelResting {
+            explanation.add_reason(format!(
+                "Self-trade prevention skipped new order with price {} crossing orders {}",
+                price,
+                display_orders(&crossed_orders)
+            ));
+            self.statistics.register_rejected_order(trade_place_account);
+            return false;
+        }
+
+        // New order will be created on next iterations after cancellation of resting ones
+        let (cancelling_orders, orders_to_cancel): (Vec<_>, Vec<_>) = crossed_orders
+            .into_iter()
+            .partition(|x| self.is_self_trade_cancellation_started(x));
+        if !cancelling_orders.is_empty() {
+            explanation.add_reason(format!(
+                "Self-trade prevention is waiting for cancellation of orders {}",
+                display_orders(&cancelling_orders)
+            ));
+        }
+
+        if orders_to_cancel.is_empty() {
+            return false;
+        }
+
+        explanation.add_reason(format!(
+            "Self-trade prevention is cancelling orders {} crossed by new order with price {}",
+            display_orders(&orders_to_cancel),
+            price
+        ));
+        for order in orders_to_cancel {
+            let client_order_id = order.client_order_id();
+            let _ = self
+                .self_trade_cancellations
+                .lock()
+                .insert(client_order_id.clone());
+
+            let exchange = self.exchange(&order.exchange_account_id());
+            let self_trade_cancellations = self.self_trade_cancellations.clone();
+            let cancellation_token = self.cancellation_token.clone();
+            let action = async move {
+                let outcome = exchange
+                    .wait_cancel_order(order, None, false, cancellation_token)
+                    .await;
+                // Cancellation can be started again if it's failed
+                let _ = self_trade_cancellations.lock().remove(&client_order_id);
+                outcome
+            };
+            spawn_future(
+                "Start wait_cancel_order from DispositionExecutor::prevent_self_trade()",
+                true,
+                action.boxed(),
+            );
+        }
+
+        false
+    }
+
+    /// Whether cancellation of crossed order is requested already by this executor or somewhere else
+    fn is_self_trade_cancellation_started(&self, order: &OrderRef) -> bool {
+        if order.is_finished()
+            || order.status() == OrderStatus::Canceling
+            || self
+                .self_trade_cancellations
+                .lock()
+                .contains(&order.client_order_id())
+        {
+            return true;
+        }
+
+        self.orders_state.by_side[order.side()]
+            .find_price_slot(order)
+            .and_then(|price_slot| {
+                price_slot
+                    .order
+                    .borrow()
+                    .orders
+                    .get(&order.client_order_id())
+                    .map(|x| x.is_cancellation_requested)
+            })
+            .unwrap_or(false)
+    }
+
+    fn calculate_new_order_amount(
+        &self,
+        _trade_place_account: TradePlaceAccount,
+        side: OrderSide,
+        desired_amount: Decimal,
+        max_amount: Decimal,
+        explanation: &mut Explanation,
+    ) -> Decimal {
+        let total_remaining_amount = self.orders_state.by_side[side].calc_total_remaining_amount();
         // TODO is needed high priority amount?
         let high_priority_amount = dec!(0);
         let balance_quota = max_amount - total_remaining_amount;
@@ -778,28 +1543,13 @@ impl DispositionExecutor {
         new_amount
     }
 
-    fn get_price_slot(&self, order: &OrderRef) -> Option<&PriceSlot> {
-        let side = order.side();
-        let price_slot = self.orders_state.by_side[side].find_price_slot(order);
-        if price_slot.is_some() {
-            return price_slot;
-        }
-
-        error!(
-            "Can't find order with client_order_id {} {} in orders state of DispositionExecutor",
-            order.client_order_id(),
-            self.exchange_account_id
-        );
-        return None;
-    }
-
     fn finish_order(&self, order: &OrderRef, price_slot: &PriceSlot) -> Result<()> {
         let client_order_id = order.client_order_id();
         trace!(
             "Started DispositionExecutor::finish_order {}",
             client_order_id
         );
-        self.unreserve_order_amount(order, price_slot);
+        self.unreserve_order_amount(order)?;
         self.remove_request_group(order, price_slot)?;
 
         price_slot.remove_order(order);
@@ -810,9 +1560,70 @@ impl DispositionExecutor {
         );
         Ok(())
     }
-    fn unreserve_order_amount(&self, _order: &OrderRef, _price_slot: &PriceSlot) {
-        // TODO needed implementation after BalanceManager
+
+    /// Release the rest of order reservation which wasn't spent by fills
+    fn unreserve_order_amount(&self, order: &OrderRef) -> Result<()> {
+        let (client_order_id, reservation_id) =
+            order.fn_ref(|x| (x.header.client_order_id.clone(), x.header.reservation_id));
+        let reservation_id = match reservation_id {
+            None => return Ok(()),
+            Some(v) => v,
+        };
+
+        let mut balance_manager = self.engine_ctx.balance_manager.lock();
+        let approved_amount = match balance_manager.try_get_reservation(&reservation_id) {
+            None => return Ok(()),
+            Some(reservation) => reservation
+                .approved_parts
+                .get(&client_order_id)
+                .map(|approved_part| approved_part.unreserved_amount),
+        };
+
+        if let Some(approved_amount) = approved_amount {
+            balance_manager.unreserve_by_client_order_id(
+                reservation_id,
+                client_order_id,
+                approved_amount,
+            )?;
+        }
+
+        if balance_manager
+            .try_get_reservation(&reservation_id)
+            .is_some()
+        {
+            balance_manager.unreserve_rest(reservation_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Apply order fill to balances and release filled amount from order reservation
+    fn recalculate_balances_on_fill(&self, cloned_order: &OrderSnapshot) -> Result<()> {
+        let header = &cloned_order.header;
+        let fill_amount = match cloned_order.fills.fills.last() {
+            None => return Ok(()),
+            Some(order_fill) => order_fill.amount(),
+        };
+
+        let mut balance_manager = self.engine_ctx.balance_manager.lock();
+        balance_manager.order_was_filled(self.configuration_descriptor.clone(), cloned_order);
+
+        if let Some(reservation_id) = header.reservation_id {
+            if balance_manager
+                .try_get_reservation(&reservation_id)
+                .is_some()
+            {
+                balance_manager.unreserve_by_client_order_id(
+                    reservation_id,
+                    header.client_order_id.clone(),
+                    fill_amount,
+                )?;
+            }
+        }
+
+        Ok(())
     }
+
     fn remove_request_group(&self, order: &OrderRef, price_slot: &PriceSlot) -> Result<()> {
         let request_group_id =
             price_slot.order.borrow().orders[&order.client_order_id()].request_group_id;
@@ -820,7 +1631,7 @@ impl DispositionExecutor {
         let _ = self
             .engine_ctx
             .timeout_manager
-            .remove_group(&self.exchange_account_id, request_group_id)?;
+            .remove_group(&order.exchange_account_id(), request_group_id)?;
         Ok(())
     }
 
@@ -842,15 +1653,70 @@ impl DispositionExecutor {
         result
     }
 
-    fn exchange(&self) -> Arc<Exchange> {
+    fn exchange(&self, exchange_account_id: &ExchangeAccountId) -> Arc<Exchange> {
         self.engine_ctx
             .exchanges
-            .get(&self.exchange_account_id)
-            .expect("Target exchange for strategy should exists")
+            .get(exchange_account_id)
+            .expect("Exchange of orders for strategy should exists")
             .value()
             .clone()
     }
 
+    fn get_currency_pair_metadata(
+        &self,
+        trade_place_account: &TradePlaceAccount,
+    ) -> Result<Arc<CurrencyPairMetadata>> {
+        self.engine_ctx
+            .exchanges
+            .get(&trade_place_account.exchange_account_id)
+            .with_context(|| {
+                format!(
+                    "Exchange {} of disposition should exists",
+                    trade_place_account.exchange_account_id
+                )
+            })?
+            .get_currency_pair_metadata(&trade_place_account.currency_pair)
+    }
+
+    /// Orders of price slot should be cancelled if exchange of any of them or of new estimation is blocked
+    fn is_price_slot_exchange_blocked(
+        &self,
+        new_estimating: &Option<TradeCycle>,
+        price_slot: &PriceSlot,
+    ) -> bool {
+        let exchange_blocker = &self.engine_ctx.exchange_blocker;
+
+        new_estimating
+            .iter()
+            .any(|x| exchange_blocker.is_blocked(&x.disposition.exchange_account_id()))
+            || price_slot
+                .order
+                .borrow()
+                .orders
+                .values()
+                .any(|x| exchange_blocker.is_blocked(&x.order.exchange_account_id()))
+    }
+
+    /// Orders of price slot shouldn't be kept or created if order book of trade place of any of them
+    /// or of new estimation is stale
+    fn is_price_slot_market_data_stale(
+        &self,
+        new_estimating: &Option<TradeCycle>,
+        price_slot: &PriceSlot,
+    ) -> bool {
+        let market_data_watchdog = match &self.engine_ctx.market_data_watchdog {
+            Some(market_data_watchdog) => market_data_watchdog,
+            None => return false,
+        };
+
+        new_estimating
+            .iter()
+            .any(|x| market_data_watchdog.is_stale(&x.disposition.trade_place()))
+            || price_slot.order.borrow().orders.values().any(|x| {
+                market_data_watchdog.is_stale(&x.order.trade_place_account().trade_place())
+            })
+    }
+
     fn prepare_estimate_trading_context(&self, event: &ExchangeEvent, now: DateTime) -> bool {
         let event_time = match event {
             ExchangeEvent::OrderBookEvent(order_book_event) => order_book_event.creation_time,
@@ -891,6 +1757,24 @@ fn estimate_trading_context(
     ))
 }
 
+/// Waits for trade place which became stale. Never completes if market data watchdog is disabled
+async fn recv_stale_trade_place(
+    stale_trade_places_receiver: &mut Option<broadcast::Receiver<TradePlace>>,
+) -> TradePlace {
+    if let Some(receiver) = stale_trade_places_receiver {
+        loop {
+            match receiver.recv().await {
+                Ok(trade_place) => return trade_place,
+                // Missed trade places are handled on the next synchronization of price slots
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+
+    futures::future::pending().await
+}
+
 fn get_cancelling_orders<'a>(
     order_records: impl Iterator<Item = &'a mut OrderRecord>,
     desired_amount: Amount,
@@ -933,7 +1817,7 @@ fn get_cancelling_orders<'a>(
 }
 
 fn now() -> DateTime {
-    Utc::now()
+    time_manager::now()
 }
 
 #[inline(always)]
@@ -945,3 +1829,838 @@ fn log_trace<'a>(msg: impl AsRef<str>, explanation: &mut Explanation) -> Result<
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use parking_lot::MutexGuard;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::core::balance_manager::balance_manager::BalanceManager;
+    use crate::core::balance_manager::tests::balance_manager_base::BalanceManagerBase;
+    use crate::core::disposition_execution::{PriceSlot, TradeDisposition};
+    use crate::core::exchanges::binance::binance::BinanceBuilder;
+    use crate::core::exchanges::block_reasons::EXCHANGE_UNAVAILABLE;
+    use crate::core::exchanges::events::{
+        ExchangeBalance, ExchangeBalancesAndPositions, ExchangeEvents, TickDirection, Trade,
+        TradeId,
+    };
+    use crate::core::exchanges::exchange_blocker::{BlockType, ExchangeBlocker};
+    use crate::core::exchanges::general::currency_pair_metadata::Precision;
+    use crate::core::exchanges::general::currency_pair_to_metadata_converter::CurrencyPairToMetadataConverter;
+    use crate::core::exchanges::general::features::OrderFeatures;
+    use crate::core::exchanges::general::test_helper::{
+        get_test_exchange_with_currency_pair_metadata_and_id, get_test_exchange_with_order_features,
+    };
+    use crate::core::exchanges::timeouts::requests_timeout_manager_factory::RequestsTimeoutManagerFactory;
+    use crate::core::exchanges::timeouts::timeout_manager::TimeoutManager;
+    use crate::core::exchanges::traits::ExchangeClientBuilder;
+    use crate::core::lifecycle::application_manager::ApplicationManager;
+    use crate::core::orders::event::OrderEvent;
+    use crate::core::orders::fill::{OrderFill, OrderFillType};
+    use crate::core::orders::order::{ExchangeOrderId, OrderFillRole, OrderRole, ReservationId};
+    use crate::core::settings::{CoreSettings, MaxOrderAmountSettings, OrderProtectionSettings};
+
+    const PRICE_SLOT_STRATEGY_NAME: &str = "test";
+
+    struct NoQuotesStrategy;
+
+    impl DispositionStrategy for NoQuotesStrategy {
+        fn calculate_trading_context(
+            &mut self,
+            _max_amount: Decimal,
+            _now: DateTime,
+            _local_snapshots_service: &LocalSnapshotsService,
+            _explanation: &mut Explanation,
+        ) -> Option<TradingContext> {
+            None
+        }
+
+        fn handle_order_fill(
+            &self,
+            _cloned_order: &Arc<OrderSnapshot>,
+            _price_slot: &PriceSlot,
+            _target_eai: &ExchangeAccountId,
+            _cancellation_token: CancellationToken,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Strategy which requests specified taker orders once and keeps their outcomes
+    struct TakerOrdersStrategy {
+        taker_orders: Vec<TradeCycle>,
+        finished_taker_orders: Arc<Mutex<Vec<(TradeCycle, Amount)>>>,
+    }
+
+    impl DispositionStrategy for TakerOrdersStrategy {
+        fn calculate_trading_context(
+            &mut self,
+            _max_amount: Decimal,
+            _now: DateTime,
+            _local_snapshots_service: &LocalSnapshotsService,
+            _explanation: &mut Explanation,
+        ) -> Option<TradingContext> {
+            None
+        }
+
+        fn handle_order_fill(
+            &self,
+            _cloned_order: &Arc<OrderSnapshot>,
+            _price_slot: &PriceSlot,
+            _target_eai: &ExchangeAccountId,
+            _cancellation_token: CancellationToken,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn take_taker_orders(&mut self, _now: DateTime) -> Vec<TradeCycle> {
+            std::mem::take(&mut self.taker_orders)
+        }
+
+        fn handle_taker_order_finished(
+            &mut self,
+            trade_cycle: &TradeCycle,
+            unfilled_amount: Amount,
+            _now: DateTime,
+        ) {
+            self.finished_taker_orders
+                .lock()
+                .push((trade_cycle.clone(), unfilled_amount));
+        }
+    }
+
+    struct TestContext {
+        executor: DispositionExecutor,
+        exchange: Arc<Exchange>,
+        // Keeps mocked time used by balance reservations
+        _balance_manager_base: BalanceManagerBase,
+    }
+
+    impl TestContext {
+        fn new() -> Self {
+            Self::with_settings(CoreSettings::default(), OrderFeatures::default())
+        }
+
+        fn with_settings(settings: CoreSettings, order_features: OrderFeatures) -> Self {
+            let balance_manager_base = BalanceManagerBase::new();
+            let exchange_account_id: ExchangeAccountId =
+                ExchangeAccountId::new(BalanceManagerBase::exchange_name().as_str().into(), 0);
+            let base_currency_code = BalanceManagerBase::eth();
+            let quote_currency_code = BalanceManagerBase::btc();
+            let currency_pair_metadata = Arc::new(CurrencyPairMetadata::new(
+                false,
+                false,
+                base_currency_code.as_str().into(),
+                base_currency_code.clone(),
+                quote_currency_code.as_str().into(),
+                quote_currency_code.clone(),
+                None,
+                None,
+                Some(dec!(0.001)),
+                None,
+                None,
+                base_currency_code.clone(),
+                Some(base_currency_code.clone()),
+                Precision::ByTick { tick: dec!(0.1) },
+                Precision::ByTick { tick: dec!(0.001) },
+            ));
+            let currency_pair = currency_pair_metadata.currency_pair();
+
+            let (exchange, _) = get_test_exchange_with_order_features(
+                currency_pair_metadata,
+                &exchange_account_id,
+                order_features,
+            );
+            let exchanges_by_id = HashMap::from([(exchange_account_id.clone(), exchange.clone())]);
+
+            let balance_manager = BalanceManager::new(
+                exchanges_by_id.clone(),
+                CurrencyPairToMetadataConverter::new(exchanges_by_id),
+                None,
+            );
+            balance_manager
+                .lock()
+                .update_exchange_balance(
+                    &exchange_account_id,
+                    &ExchangeBalancesAndPositions {
+                        balances: vec![
+                            ExchangeBalance {
+                                currency_code: base_currency_code,
+                                balance: dec!(100),
+                            },
+                            ExchangeBalance {
+                                currency_code: quote_currency_code,
+                                balance: dec!(100),
+                            },
+                        ],
+                        positions: None,
+                    },
+                )
+                .expect("in test");
+
+            let timeout_manager = TimeoutManager::new(HashMap::from([(
+                exchange_account_id.clone(),
+                RequestsTimeoutManagerFactory::from_requests_per_period(
+                    BinanceBuilder.get_timeout_argments(),
+                    exchange_account_id.clone(),
+                    None,
+                ),
+            )]));
+
+            let (events_sender, events_receiver) = broadcast::channel(10);
+            let engine_ctx = EngineContext::new(
+                settings,
+                vec![(exchange_account_id.clone(), exchange.clone())]
+                    .into_iter()
+                    .collect(),
+                ExchangeEvents::new(events_sender),
+                oneshot::channel().0,
+                timeout_manager,
+                ApplicationManager::new(CancellationToken::new()),
+                None,
+                balance_manager,
+                ExchangeBlocker::new(vec![exchange_account_id.clone()]),
+            );
+
+            let executor = DispositionExecutor::new(
+                engine_ctx,
+                events_receiver,
+                LocalSnapshotsService::default(),
+                exchange_account_id.clone(),
+                currency_pair.clone(),
+                dec!(10),
+                &[PriceSlotId::new(PRICE_SLOT_STRATEGY_NAME.to_owned(), 0)],
+                Arc::new(ConfigurationDescriptor::new(
+                    "test".to_owned(),
+                    format!("{};{}", exchange_account_id, currency_pair),
+                )),
+                Box::new(NoQuotesStrategy),
+                oneshot::channel().0,
+                CancellationToken::new(),
+                StatisticService::new(),
+            );
+
+            TestContext {
+                executor,
+                exchange,
+                _balance_manager_base: balance_manager_base,
+            }
+        }
+
+        fn balance_manager(&self) -> MutexGuard<'_, BalanceManager> {
+            self.executor.engine_ctx.balance_manager.lock()
+        }
+
+        fn price_slot(&self, side: OrderSide) -> &PriceSlot {
+            &self.executor.orders_state.by_side[side].slots[0]
+        }
+
+        fn price_slot_id() -> String {
+            PriceSlotId::new(PRICE_SLOT_STRATEGY_NAME.to_owned(), 0).to_string()
+        }
+
+        /// Reserve balance and add order to price slot like it's done on order creation
+        fn place_order(
+            &self,
+            side: OrderSide,
+            price: Price,
+            amount: Amount,
+        ) -> (OrderRef, ReservationId) {
+            let reserve_parameters = ReserveParameters::new(
+                self.executor.configuration_descriptor.clone(),
+                self.executor.exchange_account_id.clone(),
+                self.executor.currency_pair_metadata.clone(),
+                side,
+                price,
+                amount,
+            );
+            let reservation_id = self
+                .balance_manager()
+                .try_reserve(&reserve_parameters, &mut None)
+                .expect("in test");
+
+            let header = OrderHeader::new(
+                ClientOrderId::unique_id(),
+                now(),
+                self.executor.exchange_account_id.clone(),
+                self.executor.currency_pair_metadata.currency_pair(),
+                OrderType::Limit,
+                side,
+                amount,
+                OrderExecutionType::MakerOnly,
+                Some(reservation_id),
+                Some(Self::price_slot_id()),
+                "test".to_owned(),
+            );
+            let order = self.exchange.orders.add_simple_initial(header, Some(price));
+            self.balance_manager().approve_reservation(
+                reservation_id,
+                &order.client_order_id(),
+                amount,
+            );
+
+            let requests_group_id = self
+                .executor
+                .engine_ctx
+                .timeout_manager
+                .try_reserve_group(
+                    &self.executor.exchange_account_id,
+                    GROUP_REQUESTS_COUNT,
+                    DISPOSITION_EXECUTOR_REQUESTS_GROUP.to_string(),
+                )
+                .expect("in test")
+                .expect("in test");
+            self.price_slot(side)
+                .add_order(side, price, order.clone(), requests_group_id);
+
+            (order, reservation_id)
+        }
+
+        fn handle_order_event(&mut self, order: &OrderRef, event_type: OrderEventType) {
+            let event = ExchangeEvent::OrderEvent(OrderEvent::new(order.clone(), event_type));
+            self.executor
+                .handle_event(event, &mut None)
+                .expect("in test");
+        }
+
+        fn fill_order(&mut self, order: &OrderRef, amount: Amount) {
+            let cloned_order = Self::add_fill(order, amount);
+            self.handle_order_event(order, OrderEventType::OrderFilled { cloned_order });
+        }
+
+        fn add_fill(order: &OrderRef, amount: Amount) -> Arc<OrderSnapshot> {
+            let price = order.price();
+            let fill = OrderFill::new(
+                Uuid::new_v4(),
+                None,
+                now(),
+                OrderFillType::UserTrade,
+                None,
+                price,
+                amount,
+                price * amount,
+                OrderFillRole::Maker,
+                BalanceManagerBase::btc(),
+                dec!(0),
+                dec!(0),
+                BalanceManagerBase::btc(),
+                dec!(0),
+                dec!(0),
+                false,
+                None,
+                None,
+            );
+            order.fn_mut(|x| {
+                x.add_fill(fill.clone());
+                Arc::new(x.clone())
+            })
+        }
+
+        fn cancel_order(&mut self, order: &OrderRef) {
+            order.fn_mut(|x| x.set_status(OrderStatus::Canceled, now()));
+            self.handle_order_event(order, OrderEventType::CancelOrderSucceeded);
+        }
+
+        /// Exchange of another account on the same exchange
+        fn add_other_account_exchange(&self) -> Arc<Exchange> {
+            let exchange_account_id =
+                ExchangeAccountId::new(BalanceManagerBase::exchange_name().as_str().into(), 1);
+            let (exchange, _) = get_test_exchange_with_currency_pair_metadata_and_id(
+                self.executor.currency_pair_metadata.clone(),
+                &exchange_account_id,
+            );
+            let _ = self
+                .executor
+                .engine_ctx
+                .exchanges
+                .insert(exchange_account_id, exchange.clone());
+            exchange
+        }
+
+        /// Open order which doesn't belong to price slots of executor
+        fn add_resting_order(
+            &self,
+            exchange: &Exchange,
+            side: OrderSide,
+            price: Price,
+        ) -> OrderRef {
+            let header = OrderHeader::new(
+                ClientOrderId::unique_id(),
+                now(),
+                exchange.exchange_account_id.clone(),
+                self.executor.currency_pair_metadata.currency_pair(),
+                OrderType::Limit,
+                side,
+                dec!(1),
+                OrderExecutionType::MakerOnly,
+                None,
+                None,
+                "other".to_owned(),
+            );
+            let order = exchange.orders.add_simple_initial(header, Some(price));
+            order.fn_mut(|x| x.set_status(OrderStatus::Created, now()));
+            order
+        }
+
+        fn prevent_self_trade(
+            &self,
+            mode: SelfTradePreventionMode,
+            explanation: &mut Explanation,
+        ) -> bool {
+            self.executor.prevent_self_trade(
+                mode,
+                &self.trade_place_account(),
+                OrderSide::Sell,
+                dec!(0.2),
+                explanation,
+            )
+        }
+
+        fn trade_place_account(&self) -> TradePlaceAccount {
+            TradePlaceAccount::new(
+                self.executor.exchange_account_id.clone(),
+                self.executor.currency_pair_metadata.currency_pair(),
+            )
+        }
+
+        /// Replace strategy of executor by one which requests taker order with specified amount once.
+        /// Returns outcomes of finished taker orders
+        fn request_taker_order(&mut self, amount: Amount) -> Arc<Mutex<Vec<(TradeCycle, Amount)>>> {
+            let finished_taker_orders = Arc::new(Mutex::new(Vec::new()));
+            self.executor.strategy = Box::new(TakerOrdersStrategy {
+                taker_orders: vec![self.taker_trade_cycle(amount)],
+                finished_taker_orders: finished_taker_orders.clone(),
+            });
+            finished_taker_orders
+        }
+
+        fn taker_trade_cycle(&self, amount: Amount) -> TradeCycle {
+            TradeCycle {
+                order_role: OrderRole::Taker,
+                strategy_name: "test".to_owned(),
+                disposition: TradeDisposition::new(
+                    self.trade_place_account(),
+                    OrderSide::Buy,
+                    dec!(0.2),
+                    amount,
+                ),
+            }
+        }
+
+        fn rejected_orders_count(&self) -> u64 {
+            let statistics =
+                serde_json::to_value(&self.executor.statistics.statistic_service_state)
+                    .expect("in test");
+            statistics["trade_place_stats"]
+                .as_object()
+                .and_then(|x| x.values().next())
+                .and_then(|x| x["rejected_orders_count"].as_u64())
+                .unwrap_or_default()
+        }
+
+        fn approved_unreserved_amount(
+            &self,
+            reservation_id: ReservationId,
+            order: &OrderRef,
+        ) -> Option<Amount> {
+            self.balance_manager()
+                .try_get_reservation(&reservation_id)
+                .and_then(|x| x.approved_parts.get(&order.client_order_id()).cloned())
+                .map(|x| x.unreserved_amount)
+        }
+    }
+
+    #[tokio::test]
+    async fn reservation_is_released_by_fills_and_cancellation() {
+        let mut context = TestContext::new();
+
+        let (order, reservation_id) = context.place_order(OrderSide::Sell, dec!(0.2), dec!(5));
+        assert_eq!(
+            context.approved_unreserved_amount(reservation_id, &order),
+            Some(dec!(5))
+        );
+
+        context.fill_order(&order, dec!(2));
+        assert_eq!(
+            context.approved_unreserved_amount(reservation_id, &order),
+            Some(dec!(3))
+        );
+
+        context.cancel_order(&order);
+        assert!(context
+            .balance_manager()
+            .try_get_reservation(&reservation_id)
+            .is_none());
+        assert!(!context.price_slot(OrderSide::Sell).contains(&order));
+    }
+
+    #[tokio::test]
+    async fn adopted_restored_order_reserves_remaining_amount() {
+        let mut context = TestContext::new();
+
+        let mut persisted_order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderType::Limit,
+            None,
+            context.executor.exchange_account_id.clone(),
+            context.executor.currency_pair_metadata.currency_pair(),
+            dec!(0.3),
+            dec!(4),
+            OrderSide::Sell,
+            None,
+            "test",
+        );
+        let mut header = (*persisted_order.header).clone();
+        header.signal_id = Some(TestContext::price_slot_id());
+        persisted_order.header = Arc::new(header);
+        persisted_order.props.exchange_order_id = Some(ExchangeOrderId::new("restored".into()));
+        persisted_order.set_status(OrderStatus::Created, now());
+
+        let restored_orders = context.exchange.add_persisted_orders(vec![persisted_order]);
+        let restored_order = restored_orders[0].clone();
+        // Fill of previous launch
+        let _ = TestContext::add_fill(&restored_order, dec!(1));
+
+        context.executor.adopt_restored_orders(restored_orders);
+        let reservation_id = restored_order
+            .reservation_id()
+            .expect("restored order should be reserved on adoption");
+        assert_eq!(
+            context.approved_unreserved_amount(reservation_id, &restored_order),
+            Some(dec!(3))
+        );
+
+        context.fill_order(&restored_order, dec!(1));
+        assert_eq!(
+            context.approved_unreserved_amount(reservation_id, &restored_order),
+            Some(dec!(2))
+        );
+
+        context.cancel_order(&restored_order);
+        assert!(context
+            .balance_manager()
+            .try_get_reservation(&reservation_id)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn restored_order_does_not_release_reservation_with_the_same_id() {
+        let mut context = TestContext::new();
+        let (order, reservation_id) = context.place_order(OrderSide::Sell, dec!(0.2), dec!(5));
+
+        // Reservation ids of previous launch can collide with reservations of current one
+        let mut persisted_order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderType::Limit,
+            None,
+            context.executor.exchange_account_id.clone(),
+            context.executor.currency_pair_metadata.currency_pair(),
+            dec!(0.3),
+            dec!(4),
+            OrderSide::Sell,
+            Some(reservation_id),
+            "test",
+        );
+        let mut header = (*persisted_order.header).clone();
+        header.signal_id = Some(TestContext::price_slot_id());
+        persisted_order.header = Arc::new(header);
+        persisted_order.props.exchange_order_id = Some(ExchangeOrderId::new("restored".into()));
+        persisted_order.set_status(OrderStatus::Created, now());
+
+        let restored_orders = context.exchange.add_persisted_orders(vec![persisted_order]);
+        assert_eq!(restored_orders[0].reservation_id(), None);
+
+        let restored_order = restored_orders[0].clone();
+        context.executor.adopt_restored_orders(restored_orders);
+        assert!(context
+            .price_slot(OrderSide::Sell)
+            .contains(&restored_order));
+
+        context.fill_order(&restored_order, dec!(1));
+        context.cancel_order(&restored_order);
+
+        assert_eq!(
+            context.approved_unreserved_amount(reservation_id, &order),
+            Some(dec!(5))
+        );
+        assert!(!context
+            .price_slot(OrderSide::Sell)
+            .contains(&restored_order));
+    }
+
+    #[tokio::test]
+    async fn order_protection_checks_amount_of_new_order() {
+        let mut settings = CoreSettings::default();
+        settings.order_protection = Some(OrderProtectionSettings {
+            max_price_deviation_percent: dec!(5),
+            price_band_action: PriceBandAction::Reject,
+            max_order_amounts: vec![MaxOrderAmountSettings {
+                exchange_account_id: ExchangeAccountId::new(
+                    BalanceManagerBase::exchange_name().as_str().into(),
+                    0,
+                ),
+                currency_pair: CurrencyPair::from_codes(
+                    &BalanceManagerBase::eth(),
+                    &BalanceManagerBase::btc(),
+                ),
+                max_amount: dec!(2),
+            }],
+        });
+        let context = TestContext::with_settings(settings, OrderFeatures::default());
+
+        let trade_place_account = context.trade_place_account();
+        let _ = context.exchange.last_trades.insert(
+            trade_place_account.trade_place(),
+            Trade {
+                trade_id: TradeId::Number(1),
+                price: dec!(0.2),
+                quantity: dec!(1),
+                side: OrderSide::Buy,
+                transaction_time: now(),
+                tick_direction: TickDirection::None,
+            },
+        );
+
+        // Amount of disposition is allowed, but amount of new order isn't
+        let trade_cycle = TradeCycle {
+            order_role: OrderRole::Maker,
+            strategy_name: "test".to_owned(),
+            disposition: TradeDisposition::new(
+                trade_place_account,
+                OrderSide::Sell,
+                dec!(0.2),
+                dec!(1),
+            ),
+        };
+        let mut explanation = Explanation::default();
+        context
+            .executor
+            .try_create_order(
+                dec!(3),
+                context.price_slot(OrderSide::Sell),
+                &trade_cycle,
+                dec!(10),
+                now(),
+                &mut explanation,
+            )
+            .expect("in test");
+
+        assert_eq!(context.rejected_orders_count(), 1);
+        assert!(context
+            .price_slot(OrderSide::Sell)
+            .order
+            .borrow()
+            .orders
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn taker_order_reservation_is_released_by_fills_and_completion() {
+        let mut context = TestContext::new();
+        let finished_taker_orders = context.request_taker_order(dec!(2));
+
+        context
+            .executor
+            .create_taker_orders(now())
+            .expect("in test");
+
+        let order = context
+            .executor
+            .orders_state
+            .taker_orders
+            .borrow()
+            .values()
+            .map(|x| x.order.clone())
+            .exactly_one()
+            .expect("in test");
+        assert_eq!(order.order_type(), OrderType::Market);
+        let reservation_id = order.reservation_id().expect("in test");
+        assert_eq!(
+            context.approved_unreserved_amount(reservation_id, &order),
+            Some(dec!(2))
+        );
+
+        context.fill_order(&order, dec!(0.5));
+        assert_eq!(
+            context.approved_unreserved_amount(reservation_id, &order),
+            Some(dec!(1.5))
+        );
+
+        context.fill_order(&order, dec!(1.5));
+        let cloned_order = order.fn_mut(|x| {
+            x.set_status(OrderStatus::Completed, now());
+            Arc::new(x.clone())
+        });
+        context.handle_order_event(&order, OrderEventType::OrderCompleted { cloned_order });
+
+        assert!(context
+            .balance_manager()
+            .try_get_reservation(&reservation_id)
+            .is_none());
+        assert!(!context.executor.orders_state.is_taker_order(&order));
+        assert_eq!(
+            *finished_taker_orders.lock(),
+            vec![(context.taker_trade_cycle(dec!(2)), dec!(0))]
+        );
+    }
+
+    #[tokio::test]
+    async fn taker_order_rejected_by_order_protection_is_reported_to_strategy() {
+        let mut settings = CoreSettings::default();
+        settings.order_protection = Some(OrderProtectionSettings {
+            max_price_deviation_percent: dec!(5),
+            price_band_action: PriceBandAction::Reject,
+            max_order_amounts: vec![MaxOrderAmountSettings {
+                exchange_account_id: ExchangeAccountId::new(
+                    BalanceManagerBase::exchange_name().as_str().into(),
+                    0,
+                ),
+                currency_pair: CurrencyPair::from_codes(
+                    &BalanceManagerBase::eth(),
+                    &BalanceManagerBase::btc(),
+                ),
+                max_amount: dec!(1),
+            }],
+        });
+        let mut context = TestContext::with_settings(settings, OrderFeatures::default());
+        let finished_taker_orders = context.request_taker_order(dec!(2));
+
+        context
+            .executor
+            .create_taker_orders(now())
+            .expect("in test");
+
+        assert!(context
+            .executor
+            .orders_state
+            .taker_orders
+            .borrow()
+            .is_empty());
+        assert_eq!(context.rejected_orders_count(), 1);
+        assert_eq!(
+            *finished_taker_orders.lock(),
+            vec![(context.taker_trade_cycle(dec!(2)), dec!(2))]
+        );
+    }
+
+    #[tokio::test]
+    async fn taker_order_waits_for_unblocking_of_exchange() {
+        let mut context = TestContext::new();
+        let finished_taker_orders = context.request_taker_order(dec!(2));
+        let exchange_blocker = context.executor.engine_ctx.exchange_blocker.clone();
+        let exchange_account_id = context.exchange.exchange_account_id.clone();
+        exchange_blocker.block(
+            &exchange_account_id,
+            EXCHANGE_UNAVAILABLE,
+            BlockType::Manual,
+        );
+
+        context
+            .executor
+            .create_taker_orders(now())
+            .expect("in test");
+
+        assert!(context
+            .executor
+            .orders_state
+            .taker_orders
+            .borrow()
+            .is_empty());
+        assert_eq!(context.executor.postponed_taker_orders.len(), 1);
+        assert!(finished_taker_orders.lock().is_empty());
+
+        exchange_blocker.unblock(&exchange_account_id, EXCHANGE_UNAVAILABLE);
+        exchange_blocker
+            .wait_unblock(exchange_account_id, CancellationToken::new())
+            .await;
+        context
+            .executor
+            .create_taker_orders(now())
+            .expect("in test");
+
+        assert_eq!(context.executor.orders_state.taker_orders.borrow().len(), 1);
+        assert!(context.executor.postponed_taker_orders.is_empty());
+        assert!(finished_taker_orders.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn self_trade_prevention_skips_new_order() {
+        let context = TestContext::new();
+        let other_exchange = context.add_other_account_exchange();
+
+        let mut explanation = Explanation::default();
+        assert!(context.prevent_self_trade(SelfTradePreventionMode::SkipNew, &mut explanation));
+
+        let _ = context.add_resting_order(&other_exchange, OrderSide::Buy, dec!(0.1));
+        assert!(context.prevent_self_trade(SelfTradePreventionMode::SkipNew, &mut explanation));
+
+        let crossed_order = context.add_resting_order(&other_exchange, OrderSide::Buy, dec!(0.3));
+        assert!(!context.prevent_self_trade(SelfTradePreventionMode::SkipNew, &mut explanation));
+        assert_eq!(context.rejected_orders_count(), 1);
+        assert_eq!(crossed_order.status(), OrderStatus::Created);
+        assert!(context.executor.self_trade_cancellations.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn self_trade_prevention_cancels_resting_orders_once() {
+        let context = TestContext::new();
+        let other_exchange = context.add_other_account_exchange();
+        let crossed_order = context.add_resting_order(&context.exchange, OrderSide::Buy, dec!(0.3));
+        let cancelling_order =
+            context.add_resting_order(&other_exchange, OrderSide::Buy, dec!(0.3));
+        cancelling_order.fn_mut(|x| x.set_status(OrderStatus::Canceling, now()));
+
+        let mut explanation = Explanation::default();
+        assert!(
+            !context.prevent_self_trade(SelfTradePreventionMode::CancelResting, &mut explanation)
+        );
+        assert_eq!(
+            *context.executor.self_trade_cancellations.lock(),
+            HashSet::from([crossed_order.client_order_id()])
+        );
+
+        // Cancellation isn't requested again on next iterations
+        let mut explanation = Explanation::default();
+        assert!(
+            !context.prevent_self_trade(SelfTradePreventionMode::CancelResting, &mut explanation)
+        );
+        assert_eq!(context.executor.self_trade_cancellations.lock().len(), 1);
+        assert!(format!("{:?}", explanation).contains("is waiting for cancellation"));
+        assert!(!format!("{:?}", explanation).contains("is cancelling orders"));
+        assert_eq!(context.rejected_orders_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn native_self_trade_prevention_skips_new_order_crossing_other_accounts() {
+        let context = TestContext::with_settings(
+            CoreSettings::default(),
+            OrderFeatures {
+                supports_self_trade_prevention: true,
+                ..OrderFeatures::default()
+            },
+        );
+        let other_exchange = context.add_other_account_exchange();
+
+        // Exchange prevents self-trades within the same account
+        let _ = context.add_resting_order(&context.exchange, OrderSide::Buy, dec!(0.3));
+        let mut explanation = Explanation::default();
+        assert!(context.prevent_self_trade(SelfTradePreventionMode::Native, &mut explanation));
+
+        let _ = context.add_resting_order(&other_exchange, OrderSide::Buy, dec!(0.3));
+        assert!(!context.prevent_self_trade(SelfTradePreventionMode::Native, &mut explanation));
+        assert_eq!(context.rejected_orders_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn native_self_trade_prevention_without_exchange_support() {
+        let context = TestContext::new();
+        let _ = context.add_resting_order(&context.exchange, OrderSide::Buy, dec!(0.3));
+
+        let mut explanation = Explanation::default();
+        assert!(!context.prevent_self_trade(SelfTradePreventionMode::Native, &mut explanation));
+        assert_eq!(context.rejected_orders_count(), 1);
+    }
+}
@@ -1,16 +1,21 @@
 pub mod executor;
+pub mod order_protection;
+pub mod self_trade_prevention;
 pub mod trade_limit;
-mod trading_context_calculation;
+pub(crate) mod trading_context_calculation;
 
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
+use anyhow::{bail, Result};
 use derive_getters::Getters;
 use enum_map::{enum_map, EnumMap};
+use itertools::Itertools;
 use log::{error, info};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
 
 use crate::core::exchanges::common::{
     Amount, CurrencyPair, ExchangeAccountId, Price, TradePlace, TradePlaceAccount,
@@ -150,7 +155,7 @@ impl TradingContext {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize)]
 pub struct PriceSlotId {
     pub strategy_name: String,
     pub level_index: usize,
@@ -171,6 +176,27 @@ impl Display for PriceSlotId {
     }
 }
 
+/// Check that price slots from strategy settings can be mapped onto levels of trading context
+pub(crate) fn validate_price_slots(price_slots: &[PriceSlotId]) -> Result<()> {
+    if price_slots.is_empty() {
+        bail!("At least one price slot should be specified in strategy settings");
+    }
+
+    if let Some(level_index) = price_slots
+        .iter()
+        .map(|x| x.level_index)
+        .duplicates()
+        .next()
+    {
+        bail!(
+            "Level index {} is specified for several price slots in strategy settings",
+            level_index
+        );
+    }
+
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct OrderRecord {
     pub order: OrderRef,
@@ -188,6 +214,25 @@ impl OrderRecord {
     }
 }
 
+/// Market order created for taker trade cycle of strategy (e.g. hedge of maker fill),
+/// which isn't bound to any price slot
+#[derive(Debug)]
+pub struct TakerOrderRecord {
+    pub order: OrderRef,
+    pub trade_cycle: TradeCycle,
+    pub request_group_id: RequestGroupId,
+}
+
+impl TakerOrderRecord {
+    fn new(order: OrderRef, trade_cycle: TradeCycle, request_group_id: RequestGroupId) -> Self {
+        TakerOrderRecord {
+            order,
+            trade_cycle,
+            request_group_id,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct CompositeOrder {
     pub orders: HashMap<ClientOrderId, OrderRecord>,
@@ -299,14 +344,13 @@ struct OrdersStateBySide {
 }
 
 impl OrdersStateBySide {
-    pub fn new(side: OrderSide) -> Self {
+    pub fn new(side: OrderSide, price_slots: &[PriceSlotId]) -> Self {
         OrdersStateBySide {
             side,
-            // TODO create list of PriceSlots by config
-            slots: vec![PriceSlot::new(
-                PriceSlotId::new("PriceSlotId".into(), 0),
-                side,
-            )],
+            slots: price_slots
+                .iter()
+                .map(|id| PriceSlot::new(id.clone(), side))
+                .collect(),
         }
     }
 
@@ -329,14 +373,58 @@ impl OrdersStateBySide {
 #[derive(Debug)]
 struct OrdersState {
     by_side: EnumMap<OrderSide, OrdersStateBySide>,
+    taker_orders: RefCell<HashMap<ClientOrderId, TakerOrderRecord>>,
 }
 
 impl OrdersState {
-    pub fn new() -> Self {
+    pub fn new(price_slots: &[PriceSlotId]) -> Self {
         OrdersState {
             by_side: enum_map! {
-                side => OrdersStateBySide::new(side),
+                side => OrdersStateBySide::new(side, price_slots),
             },
+            taker_orders: Default::default(),
         }
     }
+
+    pub fn is_taker_order(&self, order: &OrderRef) -> bool {
+        self.taker_orders
+            .borrow()
+            .contains_key(&order.client_order_id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price_slot_id(level_index: usize) -> PriceSlotId {
+        PriceSlotId::new("TestStrategy".into(), level_index)
+    }
+
+    #[test]
+    fn orders_state_has_price_slots_for_every_side() {
+        let price_slots = vec![price_slot_id(0), price_slot_id(1), price_slot_id(2)];
+
+        let orders_state = OrdersState::new(&price_slots);
+
+        for (side, state_by_side) in orders_state.by_side.iter() {
+            let ids = state_by_side
+                .slots
+                .iter()
+                .map(|x| x.id.clone())
+                .collect_vec();
+            assert_eq!(ids, price_slots);
+            assert!(state_by_side
+                .slots
+                .iter()
+                .all(|x| x.order.borrow().side == side));
+        }
+    }
+
+    #[test]
+    fn price_slots_validation() {
+        assert!(validate_price_slots(&[price_slot_id(0), price_slot_id(1)]).is_ok());
+        assert!(validate_price_slots(&[]).is_err());
+        assert!(validate_price_slots(&[price_slot_id(1), price_slot_id(1)]).is_err());
+    }
 }
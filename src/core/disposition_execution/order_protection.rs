@@ -0,0 +1,194 @@
+use rust_decimal_macros::dec;
+
+use crate::core::disposition_execution::TradeDisposition;
+use crate::core::exchanges::common::{Amount, Price};
+use crate::core::exchanges::general::currency_pair_metadata::{CurrencyPairMetadata, Round};
+use crate::core::settings::{OrderProtectionSettings, PriceBandAction};
+
+/// Check order of disposition with specified amount against price band around reference price and max order amount of its currency pair.
+/// Returns price for order, which differs from disposition price if it's clamped, or reason of rejection
+pub fn check_order_protection(
+    settings: &OrderProtectionSettings,
+    disposition: &TradeDisposition,
+    amount: Amount,
+    reference_price: Option<Price>,
+    currency_pair_metadata: &CurrencyPairMetadata,
+) -> Result<Price, String> {
+    let max_order_amount = settings.max_order_amounts.iter().find(|x| {
+        x.exchange_account_id == disposition.direction.exchange_account_id
+            && x.currency_pair == disposition.direction.currency_pair
+    });
+    if let Some(max_order_amount) = max_order_amount {
+        if amount > max_order_amount.max_amount {
+            return Err(format!(
+                "amount {} exceeds max order amount {}",
+                amount, max_order_amount.max_amount
+            ));
+        }
+    }
+
+    check_price_band(
+        settings,
+        disposition.price(),
+        reference_price,
+        currency_pair_metadata,
+    )
+}
+
+/// Returns price inside band around reference price, which differs from specified one if it's clamped, or reason of rejection
+pub fn check_price_band(
+    settings: &OrderProtectionSettings,
+    price: Price,
+    reference_price: Option<Price>,
+    currency_pair_metadata: &CurrencyPairMetadata,
+) -> Result<Price, String> {
+    let reference_price = match reference_price {
+        Some(reference_price) => reference_price,
+        None => return Err("there is neither order book nor last trade for price band".to_owned()),
+    };
+
+    let max_deviation = reference_price * settings.max_price_deviation_percent / dec!(100);
+    let min_price = reference_price - max_deviation;
+    let max_price = reference_price + max_deviation;
+
+    if price >= min_price && price <= max_price {
+        return Ok(price);
+    }
+
+    match settings.price_band_action {
+        PriceBandAction::Reject => Err(format!(
+            "price {} is out of band [{}, {}] around reference price {}",
+            price, min_price, max_price, reference_price
+        )),
+        PriceBandAction::Clamp => {
+            // Rounding to the inside of band
+            let clamped_price = match price > max_price {
+                true => currency_pair_metadata.price_round(max_price, Round::Floor),
+                false => currency_pair_metadata.price_round(min_price, Round::Ceiling),
+            };
+            clamped_price.map_err(|error| format!("unable to clamp price {}: {:?}", price, error))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exchanges::common::{
+        Amount, CurrencyCode, ExchangeAccountId, TradePlaceAccount,
+    };
+    use crate::core::exchanges::general::currency_pair_metadata::Precision;
+    use crate::core::orders::order::OrderSide;
+    use crate::core::settings::MaxOrderAmountSettings;
+
+    fn currency_pair_metadata() -> CurrencyPairMetadata {
+        let base_currency_code: CurrencyCode = "eth".into();
+        let quote_currency_code: CurrencyCode = "btc".into();
+        CurrencyPairMetadata::new(
+            false,
+            false,
+            base_currency_code.as_str().into(),
+            base_currency_code.clone(),
+            quote_currency_code.as_str().into(),
+            quote_currency_code,
+            None,
+            None,
+            None,
+            None,
+            None,
+            base_currency_code.clone(),
+            Some(base_currency_code),
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0.001) },
+        )
+    }
+
+    fn disposition(side: OrderSide, price: Price, amount: Amount) -> TradeDisposition {
+        let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+        let trade_place_account = TradePlaceAccount::new(
+            exchange_account_id,
+            currency_pair_metadata().currency_pair(),
+        );
+        TradeDisposition::new(trade_place_account, side, price, amount)
+    }
+
+    fn settings(price_band_action: PriceBandAction) -> OrderProtectionSettings {
+        OrderProtectionSettings {
+            max_price_deviation_percent: dec!(5),
+            price_band_action,
+            max_order_amounts: vec![MaxOrderAmountSettings {
+                exchange_account_id: "Binance0".parse().expect("in test"),
+                currency_pair: currency_pair_metadata().currency_pair(),
+                max_amount: dec!(2),
+            }],
+        }
+    }
+
+    fn check(
+        price_band_action: PriceBandAction,
+        disposition: TradeDisposition,
+        reference_price: Option<Price>,
+    ) -> Result<Price, String> {
+        check_order_protection(
+            &settings(price_band_action),
+            &disposition,
+            disposition.amount(),
+            reference_price,
+            &currency_pair_metadata(),
+        )
+    }
+
+    #[test]
+    fn approve_price_inside_band() {
+        let outcome = check(
+            PriceBandAction::Reject,
+            disposition(OrderSide::Buy, dec!(104), dec!(1)),
+            Some(dec!(100)),
+        );
+        assert_eq!(outcome, Ok(dec!(104)));
+    }
+
+    #[test]
+    fn reject_price_outside_band() {
+        let outcome = check(
+            PriceBandAction::Reject,
+            disposition(OrderSide::Buy, dec!(106), dec!(1)),
+            Some(dec!(100)),
+        );
+        assert!(outcome.is_err());
+
+        let outcome = check(
+            PriceBandAction::Reject,
+            disposition(OrderSide::Buy, dec!(100), dec!(1)),
+            None,
+        );
+        assert!(outcome.is_err());
+    }
+
+    #[test]
+    fn clamp_price_to_band() {
+        let outcome = check(
+            PriceBandAction::Clamp,
+            disposition(OrderSide::Buy, dec!(110), dec!(1)),
+            Some(dec!(100.01)),
+        );
+        assert_eq!(outcome, Ok(dec!(105)));
+
+        let outcome = check(
+            PriceBandAction::Clamp,
+            disposition(OrderSide::Sell, dec!(90), dec!(1)),
+            Some(dec!(100.01)),
+        );
+        assert_eq!(outcome, Ok(dec!(95.1)));
+    }
+
+    #[test]
+    fn reject_amount_above_max() {
+        let outcome = check(
+            PriceBandAction::Clamp,
+            disposition(OrderSide::Sell, dec!(100), dec!(3)),
+            Some(dec!(100)),
+        );
+        assert!(outcome.is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use dashmap::DashMap;
+
+use crate::core::exchanges::common::{ExchangeAccountId, Price, TradePlace};
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::orders::order::OrderSide;
+use crate::core::orders::pool::OrderRef;
+
+/// Whether new order would be matched with resting order
+fn is_crossing(
+    side: OrderSide,
+    price: Price,
+    resting_order_side: OrderSide,
+    resting_order_price: Price,
+) -> bool {
+    if side == resting_order_side {
+        return false;
+    }
+
+    match side {
+        OrderSide::Buy => resting_order_price <= price,
+        OrderSide::Sell => price <= resting_order_price,
+    }
+}
+
+/// Find open orders of all strategies and exchange accounts on trade place which would be matched with new order
+pub fn find_crossed_orders(
+    exchanges: &DashMap<ExchangeAccountId, Arc<Exchange>>,
+    trade_place: &TradePlace,
+    side: OrderSide,
+    price: Price,
+) -> Vec<OrderRef> {
+    exchanges
+        .iter()
+        .filter(|x| x.key().exchange_id == trade_place.exchange_id)
+        .flat_map(|exchange| {
+            exchange
+                .orders
+                .not_finished
+                .iter()
+                .map(|x| x.value().clone())
+                .collect::<Vec<_>>()
+        })
+        .filter(|order| {
+            order.currency_pair() == trade_place.currency_pair
+                && !order.is_finished()
+                && is_crossing(side, price, order.side(), order.price())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn crossing_only_opposite_side() {
+        assert!(is_crossing(
+            OrderSide::Buy,
+            dec!(10),
+            OrderSide::Sell,
+            dec!(10)
+        ));
+        assert!(is_crossing(
+            OrderSide::Buy,
+            dec!(10),
+            OrderSide::Sell,
+            dec!(9)
+        ));
+        assert!(!is_crossing(
+            OrderSide::Buy,
+            dec!(10),
+            OrderSide::Sell,
+            dec!(11)
+        ));
+        assert!(!is_crossing(
+            OrderSide::Buy,
+            dec!(10),
+            OrderSide::Buy,
+            dec!(9)
+        ));
+
+        assert!(is_crossing(
+            OrderSide::Sell,
+            dec!(10),
+            OrderSide::Buy,
+            dec!(11)
+        ));
+        assert!(!is_crossing(
+            OrderSide::Sell,
+            dec!(10),
+            OrderSide::Buy,
+            dec!(9)
+        ));
+    }
+}
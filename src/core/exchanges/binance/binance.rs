@@ -11,6 +11,7 @@ use serde_json::Value;
 use sha2::Sha256;
 use tokio::sync::broadcast;
 
+use super::order_book_sync::OrderBookSync;
 use super::support::BinanceOrderInfo;
 use crate::core::exchanges::common::{Amount, Price};
 use crate::core::exchanges::events::{ExchangeEvent, TradeId};
@@ -22,6 +23,8 @@ use crate::core::exchanges::traits::ExchangeClientBuilderResult;
 use crate::core::exchanges::{
     common::CurrencyCode,
     general::features::{ExchangeFeatures, OpenOrdersType},
+    general::request_type::RequestType,
+    timeouts::rate_limit::{RateLimit, RateLimitKind},
     timeouts::requests_timeout_manager_factory::RequestTimeoutArguments,
 };
 use crate::core::exchanges::{common::CurrencyId, general::exchange::BoxExchangeClient};
@@ -36,6 +39,7 @@ use crate::core::settings::{ExchangeSettings, Hosts};
 use crate::core::DateTime;
 use crate::core::{exchanges::traits::ExchangeClientBuilder, orders::fill::OrderFillType};
 use crate::core::{lifecycle::application_manager::ApplicationManager, utils};
+use crate::hashmap;
 
 pub struct Binance {
     pub settings: ExchangeSettings,
@@ -56,6 +60,13 @@ pub struct Binance {
     // Currencies used for trading according to user settings
     pub traded_specific_currencies: Mutex<Vec<SpecificCurrencyPair>>,
     pub(super) last_trade_ids: DashMap<CurrencyPair, TradeId>,
+    // Balances from the last account request updated by user data events,
+    // because outboundAccountPosition event contains only changed balances
+    pub(super) last_balances: Mutex<Option<HashMap<CurrencyCode, Amount>>>,
+    // Local order books built from diff depth stream
+    pub(super) order_book_sync: Arc<Mutex<OrderBookSync>>,
+    pub(super) order_book_snapshot_requested_callback:
+        Mutex<Box<dyn FnMut(CurrencyPair) + Send + Sync>>,
 
     pub(super) application_manager: Arc<ApplicationManager>,
 
@@ -104,6 +115,9 @@ impl Binance {
             supported_currencies: Default::default(),
             traded_specific_currencies: Default::default(),
             last_trade_ids: Default::default(),
+            last_balances: Default::default(),
+            order_book_sync: Default::default(),
+            order_book_snapshot_requested_callback: Mutex::new(Box::new(|_| {})),
             subscribe_to_market_data: settings.subscribe_to_market_data,
             is_reducing_market_data,
             settings,
@@ -127,10 +141,6 @@ impl Binance {
             .await
     }
 
-    pub async fn reconnect(&mut self) {
-        todo!("reconnect")
-    }
-
     pub(super) fn get_stream_name(
         specific_currency_pair: &SpecificCurrencyPair,
         channel: &str,
@@ -138,10 +148,6 @@ impl Binance {
         format!("{}@{}", specific_currency_pair.as_str(), channel)
     }
 
-    fn is_websocket_reconnecting(&self) -> bool {
-        todo!("is_websocket_reconnecting")
-    }
-
     pub(super) fn to_server_order_side(side: OrderSide) -> String {
         match side {
             OrderSide::Buy => "BUY".to_owned(),
@@ -409,6 +415,7 @@ impl ExchangeClientBuilder for BinanceBuilder {
         application_manager: Arc<ApplicationManager>,
     ) -> ExchangeClientBuilderResult {
         let exchange_account_id = exchange_settings.exchange_account_id.clone();
+        let is_margin_trading = exchange_settings.is_margin_trading;
 
         ExchangeClientBuilderResult {
             client: Box::new(Binance::new(
@@ -421,7 +428,12 @@ impl ExchangeClientBuilder for BinanceBuilder {
             features: ExchangeFeatures::new(
                 OpenOrdersType::AllCurrencyPair,
                 RestFillsFeatures::new(RestFillsType::None),
-                OrderFeatures::default(),
+                OrderFeatures {
+                    supports_cancel_all_orders: true,
+                    supports_cancel_all_orders_countdown: is_margin_trading,
+                    supports_self_trade_prevention: true,
+                    ..OrderFeatures::default()
+                },
                 OrderTradeOption::default(),
                 WebSocketOptions::default(),
                 false,
@@ -433,14 +445,44 @@ impl ExchangeClientBuilder for BinanceBuilder {
     }
 
     fn get_timeout_argments(&self) -> RequestTimeoutArguments {
+        // Weights and limits of spot API
         RequestTimeoutArguments::from_requests_per_minute(1200)
+            .with_request_weights(hashmap![
+                RequestType::GetOrderInfo => 2,
+                RequestType::GetOpenOrders => 40,
+                RequestType::GetBalance => 10,
+                RequestType::GetMarkets => 10,
+                RequestType::GetMyTrades => 10,
+                RequestType::GetOrderBook => 10
+            ])
+            .with_additional_limit(RateLimit::new(
+                RateLimitKind::OrdersCount,
+                50,
+                chrono::Duration::seconds(10),
+            ))
+            .with_additional_limit(RateLimit::new(
+                RateLimitKind::OrdersCount,
+                160_000,
+                chrono::Duration::days(1),
+            ))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::exchanges::binance::order_book_sync::{DepthUpdate, DepthUpdateAction};
+    use crate::core::exchanges::binance::test_helper::{
+        get_binance_with_synced_order_book, get_test_currency_pair, get_test_exchange_settings,
+    };
+    use crate::core::exchanges::common::RestRequestOutcome;
+    use crate::core::exchanges::timeouts::rate_limit::RateLimitUsage;
+    use crate::core::exchanges::traits::Support;
     use crate::core::lifecycle::cancellation_token::CancellationToken;
+    use crate::core::order_book::event::EventType;
+    use crate::order_book_data;
+    use awc::http::StatusCode;
+    use rust_decimal_macros::dec;
 
     #[test]
     fn generate_signature() {
@@ -469,6 +511,170 @@ mod tests {
         assert_eq!(result, right_value);
     }
 
+    #[test]
+    fn balances_are_updated_by_account_position_event() {
+        let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+        let settings = ExchangeSettings::new_short(
+            exchange_account_id.clone(),
+            "test_api_key".into(),
+            "test_secret_key".into(),
+            false,
+        );
+
+        let (tx, mut rx) = broadcast::channel(10);
+        let binance = Binance::new(
+            exchange_account_id.clone(),
+            settings,
+            tx,
+            ApplicationManager::new(CancellationToken::default()),
+            false,
+        );
+        for currency in &["BTC", "ETH"] {
+            let _ = binance
+                .supported_currencies
+                .insert((*currency).into(), currency.to_lowercase().as_str().into());
+        }
+
+        let account_position = r#"{"e":"outboundAccountPosition","E":1564034571105,"u":1564034571073,"B":[{"a":"ETH","f":"5","l":"0.5"}]}"#;
+        binance
+            .on_websocket_message(account_position)
+            .expect("in test");
+        assert!(rx.try_recv().is_err());
+
+        let account = r#"{"balances":[{"asset":"BTC","free":"1.5","locked":"0.1"},{"asset":"ETH","free":"10","locked":"0"},{"asset":"XYZ","free":"7","locked":"0"}]}"#;
+        let response = RestRequestOutcome::new(account.to_owned(), StatusCode::OK);
+        let balances = binance.parse_balance(&response).expect("in test");
+        assert_eq!(balances.balances.len(), 2);
+
+        binance
+            .on_websocket_message(account_position)
+            .expect("in test");
+        let balance_update = match rx.try_recv().expect("in test") {
+            ExchangeEvent::BalanceUpdate(balance_update) => balance_update,
+            _ => panic!("BalanceUpdate event expected"),
+        };
+        assert_eq!(balance_update.exchange_account_id, exchange_account_id);
+
+        let balances: HashMap<_, _> = balance_update
+            .balances_and_positions
+            .balances
+            .into_iter()
+            .map(|x| (x.currency_code, x.balance))
+            .collect();
+        assert_eq!(balances.len(), 2);
+        assert_eq!(balances[&"btc".into()], dec!(1.6));
+        assert_eq!(balances[&"eth".into()], dec!(5.5));
+    }
+
+    #[test]
+    fn rate_limits_usage_from_headers() {
+        let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+        let settings = ExchangeSettings::new_short(
+            exchange_account_id.clone(),
+            "test_api_key".into(),
+            "test_secret_key".into(),
+            false,
+        );
+        let (tx, _) = broadcast::channel(10);
+        let binance = Binance::new(
+            exchange_account_id,
+            settings,
+            tx,
+            ApplicationManager::new(CancellationToken::default()),
+            false,
+        );
+
+        let mut response = RestRequestOutcome::new("{}".to_owned(), StatusCode::OK);
+        for (name, value) in &[
+            ("X-MBX-USED-WEIGHT", "30"),
+            ("X-MBX-USED-WEIGHT-1M", "30"),
+            ("X-MBX-ORDER-COUNT-10S", "2"),
+            ("X-MBX-ORDER-COUNT-1D", "100"),
+            ("Content-Type", "application/json"),
+        ] {
+            let _ = response
+                .headers
+                .insert(*name, value.parse().expect("in test"));
+        }
+
+        let mut rate_limits_usage = binance.parse_rate_limits_usage(&response);
+        rate_limits_usage.sort_by_key(|x| x.period);
+        assert_eq!(
+            rate_limits_usage,
+            vec![
+                RateLimitUsage::new(RateLimitKind::OrdersCount, chrono::Duration::seconds(10), 2),
+                RateLimitUsage::new(
+                    RateLimitKind::RequestWeight,
+                    chrono::Duration::minutes(1),
+                    30
+                ),
+                RateLimitUsage::new(RateLimitKind::OrdersCount, chrono::Duration::days(1), 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn order_book_resync_requests_new_snapshot() {
+        let (tx, _) = broadcast::channel(10);
+        let application_manager = ApplicationManager::new(CancellationToken::default());
+        let (binance, is_snapshot_requested) =
+            get_binance_with_synced_order_book(tx, application_manager);
+
+        binance.request_order_book_resync(&get_test_currency_pair());
+        assert!(is_snapshot_requested());
+    }
+
+    #[test]
+    fn order_book_snapshot_response_is_applied_with_buffered_updates() {
+        let settings = get_test_exchange_settings();
+        let (tx, mut rx) = broadcast::channel(10);
+        let binance = Binance::new(
+            settings.exchange_account_id.clone(),
+            settings,
+            tx,
+            ApplicationManager::new(CancellationToken::default()),
+            false,
+        );
+
+        let currency_pair = get_test_currency_pair();
+        let depth_update = |first_update_id, last_update_id| DepthUpdate {
+            first_update_id,
+            last_update_id,
+            data: order_book_data![dec!(1) => dec!(2), ;],
+        };
+        let _ = binance
+            .order_book_sync
+            .lock()
+            .on_update(&currency_pair, depth_update(4, 6));
+
+        let invalid_response = RestRequestOutcome::new("{}".to_owned(), StatusCode::OK);
+        assert!(binance
+            .handle_order_book_snapshot_response(&currency_pair, &invalid_response)
+            .is_err());
+        // Snapshot is requested again after failure
+        assert!(matches!(
+            binance
+                .order_book_sync
+                .lock()
+                .on_update(&currency_pair, depth_update(4, 6)),
+            DepthUpdateAction::RequestSnapshot
+        ));
+
+        let snapshot = r#"{"lastUpdateId":5,"asks":[["1","1"]],"bids":[["0.5","3"]]}"#;
+        let response = RestRequestOutcome::new(snapshot.to_owned(), StatusCode::OK);
+        binance
+            .handle_order_book_snapshot_response(&currency_pair, &response)
+            .expect("in test");
+
+        let order_book_event = match rx.try_recv().expect("in test") {
+            ExchangeEvent::OrderBookEvent(order_book_event) => order_book_event,
+            _ => panic!("OrderBookEvent event expected"),
+        };
+        assert!(matches!(order_book_event.event_type(), EventType::Snapshot));
+        assert_eq!(order_book_event.data().asks[&dec!(1)], dec!(2));
+        assert_eq!(order_book_event.data().bids[&dec!(0.5)], dec!(3));
+    }
+
     #[test]
     fn to_http_string() {
         let parameters: rest_client::HttpParams = vec![
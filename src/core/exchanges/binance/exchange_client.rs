@@ -8,8 +8,12 @@ use crate::core::{
     exchanges::common::{CurrencyPair, RestRequestOutcome},
     orders::pool::OrderRef,
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_trait::async_trait;
+use tokio::time::Duration;
+
+/// Max count of levels on each side of order book snapshot
+const ORDER_BOOK_SNAPSHOT_LIMIT: &str = "1000";
 
 #[async_trait]
 impl ExchangeClient for Binance {
@@ -50,6 +54,13 @@ impl ExchangeClient for Binance {
         } else if order.header.execution_type == OrderExecutionType::MakerOnly {
             http_params.push(("timeInForce".to_owned(), "GTX".to_owned()));
         }
+        if self.settings.native_self_trade_prevention {
+            // Resting order is expired when crossed by new order of the same account
+            http_params.push((
+                "selfTradePreventionMode".to_owned(),
+                "EXPIRE_MAKER".to_owned(),
+            ));
+        }
         self.add_authentification_headers(&mut http_params)?;
 
         let url_path = match self.settings.is_margin_trading {
@@ -116,6 +127,37 @@ impl ExchangeClient for Binance {
         Ok(())
     }
 
+    async fn request_cancel_all_orders_countdown(
+        &self,
+        currency_pair: CurrencyPair,
+        countdown: Duration,
+    ) -> Result<RestRequestOutcome> {
+        if !self.settings.is_margin_trading {
+            bail!("Countdown cancellation of all orders is supported only for Binance futures");
+        }
+
+        let specific_currency_pair = self.get_specific_currency_pair(&currency_pair);
+
+        let mut http_params = vec![
+            (
+                "symbol".to_owned(),
+                specific_currency_pair.as_str().to_owned(),
+            ),
+            (
+                "countdownTime".to_owned(),
+                countdown.as_millis().to_string(),
+            ),
+        ];
+        self.add_authentification_headers(&mut http_params)?;
+
+        let url_path = "/fapi/v1/countdownCancelAll";
+        let full_url = rest_client::build_uri(&self.hosts.rest_host, url_path, &vec![])?;
+
+        self.rest_client
+            .post(full_url, &self.settings.api_key, &http_params)
+            .await
+    }
+
     async fn request_open_orders(&self) -> Result<RestRequestOutcome> {
         let mut http_params = rest_client::HttpParams::new();
         self.add_authentification_headers(&mut http_params)?;
@@ -186,4 +228,37 @@ impl ExchangeClient for Binance {
         let full_url = rest_client::build_uri(&self.hosts.rest_host, url_path, &http_params)?;
         self.rest_client.get(full_url, &self.settings.api_key).await
     }
+
+    async fn request_balance(&self) -> Result<RestRequestOutcome> {
+        // In currenct versions works only with Spot market
+        let url_path = "/api/v3/account";
+
+        let mut http_params = rest_client::HttpParams::new();
+        self.add_authentification_headers(&mut http_params)?;
+
+        let full_url = rest_client::build_uri(&self.hosts.rest_host, url_path, &http_params)?;
+        self.rest_client.get(full_url, &self.settings.api_key).await
+    }
+
+    async fn request_order_book_snapshot(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> Result<RestRequestOutcome> {
+        let specific_currency_pair = self.get_specific_currency_pair(&currency_pair);
+        let http_params = vec![
+            (
+                "symbol".to_owned(),
+                specific_currency_pair.as_str().to_owned(),
+            ),
+            ("limit".to_owned(), ORDER_BOOK_SNAPSHOT_LIMIT.to_owned()),
+        ];
+
+        let url_path = match self.settings.is_margin_trading {
+            true => "/fapi/v1/depth",
+            false => "/api/v3/depth",
+        };
+
+        let full_url = rest_client::build_uri(&self.hosts.rest_host, url_path, &http_params)?;
+        self.rest_client.get(full_url, &self.settings.api_key).await
+    }
 }
@@ -1,3 +1,6 @@
 pub mod binance;
 pub mod exchange_client;
+mod order_book_sync;
 pub mod support;
+#[cfg(test)]
+pub mod test_helper;
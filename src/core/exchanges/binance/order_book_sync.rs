@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use log::warn;
+
+use crate::core::exchanges::common::CurrencyPair;
+use crate::core::order_book::order_book_data::OrderBookData;
+
+/// Event of `<symbol>@depth` stream
+#[derive(Debug, Clone)]
+pub(super) struct DepthUpdate {
+    /// First update id in event (`U`)
+    pub(super) first_update_id: u64,
+    /// Final update id in event (`u`)
+    pub(super) last_update_id: u64,
+    pub(super) data: OrderBookData,
+}
+
+impl DepthUpdate {
+    /// Whether update follows order book state with specified last update id without gaps
+    fn is_continuation_of(&self, last_update_id: u64) -> bool {
+        self.first_update_id <= last_update_id + 1
+    }
+}
+
+#[derive(Debug)]
+enum SyncState {
+    /// Updates are buffered until order book snapshot is received from REST
+    Buffering(Vec<DepthUpdate>),
+    Synced {
+        last_update_id: u64,
+    },
+}
+
+#[derive(Debug)]
+pub(super) enum DepthUpdateAction {
+    /// Order book isn't synced yet, update is buffered
+    Skip,
+    /// Order book isn't synced or has a gap, so snapshot should be requested from REST
+    RequestSnapshot,
+    Apply(OrderBookData),
+}
+
+/// Local order books maintenance according to Binance procedure: diff depth events are buffered
+/// until REST snapshot is received, then events with continuous update ids are applied to it.
+/// Any gap in update ids leads to resynchronization.
+/// Order book events should be sent under the same lock as the sync is changed to keep their order
+#[derive(Default)]
+pub(super) struct OrderBookSync {
+    states: HashMap<CurrencyPair, SyncState>,
+}
+
+impl OrderBookSync {
+    pub(super) fn reset(&mut self) {
+        self.states.clear();
+    }
+
+    /// Snapshot will be requested on the next update
+    pub(super) fn resync(&mut self, currency_pair: &CurrencyPair) {
+        let _ = self.states.remove(currency_pair);
+    }
+
+    pub(super) fn on_update(
+        &mut self,
+        currency_pair: &CurrencyPair,
+        update: DepthUpdate,
+    ) -> DepthUpdateAction {
+        let state = match self.states.get_mut(currency_pair) {
+            Some(state) => state,
+            None => {
+                let _ = self
+                    .states
+                    .insert(currency_pair.clone(), SyncState::Buffering(vec![update]));
+                return DepthUpdateAction::RequestSnapshot;
+            }
+        };
+
+        match state {
+            SyncState::Buffering(updates) => {
+                updates.push(update);
+                DepthUpdateAction::Skip
+            }
+            SyncState::Synced { last_update_id } => {
+                if update.last_update_id <= *last_update_id {
+                    return DepthUpdateAction::Skip;
+                }
+
+                if !update.is_continuation_of(*last_update_id) {
+                    warn!(
+                        "Gap in Binance order book updates for {}: last update id {}, next first update id {}. Order book is resynchronized",
+                        currency_pair, last_update_id, update.first_update_id
+                    );
+                    *state = SyncState::Buffering(vec![update]);
+                    return DepthUpdateAction::RequestSnapshot;
+                }
+
+                *last_update_id = update.last_update_id;
+                DepthUpdateAction::Apply(update.data)
+            }
+        }
+    }
+
+    /// Returns last update id and buffered updates which should be applied to snapshot.
+    /// Returns None if snapshot is outdated or order book is synced already
+    pub(super) fn on_snapshot(
+        &mut self,
+        currency_pair: &CurrencyPair,
+        snapshot_last_update_id: u64,
+    ) -> Option<(u64, Vec<OrderBookData>)> {
+        let buffered_updates = match self.states.get_mut(currency_pair) {
+            Some(SyncState::Buffering(updates)) => std::mem::take(updates),
+            Some(SyncState::Synced { .. }) | None => return None,
+        };
+
+        let mut last_update_id = snapshot_last_update_id;
+        let mut updates = Vec::new();
+        for update in buffered_updates
+            .into_iter()
+            .filter(|x| x.last_update_id > snapshot_last_update_id)
+        {
+            if !update.is_continuation_of(last_update_id) {
+                // Snapshot is older than buffered updates, so it should be requested again
+                let _ = self.states.remove(currency_pair);
+                return None;
+            }
+
+            last_update_id = update.last_update_id;
+            updates.push(update.data);
+        }
+
+        let _ = self
+            .states
+            .insert(currency_pair.clone(), SyncState::Synced { last_update_id });
+        Some((last_update_id, updates))
+    }
+
+    /// Snapshot will be requested again on the next update
+    pub(super) fn on_snapshot_failed(&mut self, currency_pair: &CurrencyPair) {
+        if let Some(SyncState::Buffering(_)) = self.states.get(currency_pair) {
+            let _ = self.states.remove(currency_pair);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_book_data;
+    use rust_decimal_macros::dec;
+
+    fn update(first_update_id: u64, last_update_id: u64) -> DepthUpdate {
+        DepthUpdate {
+            first_update_id,
+            last_update_id,
+            data: order_book_data![dec!(1) => Decimal::from(last_update_id), ;],
+        }
+    }
+
+    #[test]
+    fn buffered_updates_are_applied_to_snapshot() {
+        let mut sync = OrderBookSync::default();
+        let currency_pair = CurrencyPair::from_codes(&"eth".into(), &"btc".into());
+
+        assert!(matches!(
+            sync.on_update(&currency_pair, update(1, 5)),
+            DepthUpdateAction::RequestSnapshot
+        ));
+        assert!(matches!(
+            sync.on_update(&currency_pair, update(6, 8)),
+            DepthUpdateAction::Skip
+        ));
+        assert!(matches!(
+            sync.on_update(&currency_pair, update(9, 10)),
+            DepthUpdateAction::Skip
+        ));
+
+        let (last_update_id, updates) = sync.on_snapshot(&currency_pair, 7).expect("in test");
+        assert_eq!(last_update_id, 10);
+        assert_eq!(
+            updates.iter().map(|x| x.asks[&dec!(1)]).collect::<Vec<_>>(),
+            vec![dec!(8), dec!(10)]
+        );
+
+        assert!(matches!(
+            sync.on_update(&currency_pair, update(9, 10)),
+            DepthUpdateAction::Skip
+        ));
+        assert!(matches!(
+            sync.on_update(&currency_pair, update(11, 12)),
+            DepthUpdateAction::Apply(_)
+        ));
+    }
+
+    #[test]
+    fn resync_on_gap() {
+        let mut sync = OrderBookSync::default();
+        let currency_pair = CurrencyPair::from_codes(&"eth".into(), &"btc".into());
+
+        let _ = sync.on_update(&currency_pair, update(5, 6));
+        // Snapshot is older than the first buffered update
+        assert!(sync.on_snapshot(&currency_pair, 3).is_none());
+
+        let _ = sync.on_update(&currency_pair, update(7, 8));
+        assert!(sync.on_snapshot(&currency_pair, 7).is_some());
+        assert!(matches!(
+            sync.on_update(&currency_pair, update(10, 11)),
+            DepthUpdateAction::RequestSnapshot
+        ));
+        assert!(matches!(
+            sync.on_update(&currency_pair, update(12, 13)),
+            DepthUpdateAction::Skip
+        ));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::core::infrastructure::WithExpect;
+use std::collections::HashMap;
 use std::str::FromStr;
 
 use std::sync::Arc;
@@ -10,16 +11,20 @@ use awc::http::Uri;
 use chrono::{TimeZone, Utc};
 use dashmap::DashMap;
 use itertools::Itertools;
-use log::{error, info};
+use log::{error, info, warn};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use super::binance::Binance;
+use super::order_book_sync::{DepthUpdate, DepthUpdateAction};
 use crate::core::exchanges::common::SortedOrderData;
-use crate::core::exchanges::events::{ExchangeEvent, TradeId};
+use crate::core::exchanges::events::{
+    BalanceUpdateEvent, ExchangeBalance, ExchangeBalancesAndPositions, ExchangeEvent, TradeId,
+};
 use crate::core::exchanges::general::order::get_order_trades::OrderTrade;
 use crate::core::exchanges::rest_client;
+use crate::core::exchanges::timeouts::rate_limit::{RateLimitKind, RateLimitUsage};
 use crate::core::exchanges::{
     common::CurrencyCode, common::CurrencyId,
     general::currency_pair_metadata::CurrencyPairMetadata,
@@ -162,6 +167,11 @@ impl Support for Binance {
                     self.process_snapshot_update(&currency_pair, data)?;
                     return Ok(());
                 }
+
+                if stream.ends_with("@depth") || stream.ends_with("@depth@100ms") {
+                    self.process_depth_update(&currency_pair, data)?;
+                    return Ok(());
+                }
             }
 
             return Ok(());
@@ -173,6 +183,8 @@ impl Support for Binance {
             .ok_or(anyhow!("Unable to parse event_type"))?;
         if event_type == "executionReport" {
             self.handle_order_fill(msg, data)?;
+        } else if event_type == "outboundAccountPosition" {
+            self.handle_account_position(&data)?;
         } else if false {
             // TODO something about ORDER_TRADE_UPDATE? There are no info about it in Binance docs
         } else {
@@ -183,6 +195,9 @@ impl Support for Binance {
     }
 
     fn on_connecting(&self) -> Result<()> {
+        // Updates from new connection can't be applied to order books from previous one
+        self.order_book_sync.lock().reset();
+
         self.unified_to_specific
             .read()
             .iter()
@@ -225,6 +240,13 @@ impl Support for Binance {
         *self.handle_trade_callback.lock() = callback;
     }
 
+    fn set_order_book_snapshot_requested_callback(
+        &self,
+        callback: Box<dyn FnMut(CurrencyPair) + Send + Sync>,
+    ) {
+        *self.order_book_snapshot_requested_callback.lock() = callback;
+    }
+
     fn set_traded_specific_currencies(&self, currencies: Vec<SpecificCurrencyPair>) {
         *self.traded_specific_currencies.lock() = currencies;
     }
@@ -310,7 +332,7 @@ impl Support for Binance {
         for symbol in symbols {
             let is_active = symbol["status"] == "TRADING";
 
-            // TODO There is no work with dereivatives in current version
+            // TODO There is no work with derivatives in current version
             let is_derivative = false;
             let base_currency_id = &symbol
                 .get_as_str("baseAsset")
@@ -472,11 +494,130 @@ impl Support for Binance {
             .collect()
     }
 
+    fn parse_balance(&self, response: &RestRequestOutcome) -> Result<ExchangeBalancesAndPositions> {
+        #[derive(Deserialize, Debug)]
+        struct BinanceBalance {
+            asset: CurrencyId,
+            free: Amount,
+            locked: Amount,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct BinanceAccount {
+            balances: Vec<BinanceBalance>,
+        }
+
+        let account: BinanceAccount = serde_json::from_str(&response.content)
+            .context("Unable to parse response content for get_balance request")?;
+
+        // Amount locked in open orders is included, because BalanceManager reserves it by itself
+        let balances: HashMap<_, _> = account
+            .balances
+            .into_iter()
+            .filter_map(|balance| {
+                self.get_currency_code(&balance.asset)
+                    .map(|currency_code| (currency_code, balance.free + balance.locked))
+            })
+            .collect();
+
+        let balances_and_positions = to_balances_and_positions(&balances);
+        *self.last_balances.lock() = Some(balances);
+
+        Ok(balances_and_positions)
+    }
+
+    fn parse_rate_limits_usage(&self, response: &RestRequestOutcome) -> Vec<RateLimitUsage> {
+        response
+            .headers
+            .iter()
+            .filter_map(|(name, value)| {
+                let name = name.as_str();
+                let (kind, interval) =
+                    if let Some(interval) = name.strip_prefix("x-mbx-used-weight-") {
+                        (RateLimitKind::RequestWeight, interval)
+                    } else if let Some(interval) = name.strip_prefix("x-mbx-order-count-") {
+                        (RateLimitKind::OrdersCount, interval)
+                    } else {
+                        return None;
+                    };
+
+                let period = parse_rate_limit_interval(interval)?;
+                let used = value.to_str().ok()?.parse().ok()?;
+                Some(RateLimitUsage::new(kind, period, used))
+            })
+            .collect()
+    }
+
+    fn request_order_book_resync(&self, currency_pair: &CurrencyPair) {
+        self.order_book_sync.lock().resync(currency_pair);
+    }
+
+    /// Snapshot is sent with buffered diff depth updates applied as order book snapshot event
+    fn handle_order_book_snapshot_response(
+        &self,
+        currency_pair: &CurrencyPair,
+        response: &RestRequestOutcome,
+    ) -> Result<()> {
+        let (snapshot_last_update_id, order_book_data) = match parse_order_book_snapshot(response) {
+            Ok(snapshot) => snapshot,
+            Err(error) => {
+                self.on_order_book_snapshot_failed(currency_pair);
+                return Err(error);
+            }
+        };
+
+        // Event is sent under lock, so updates can't be sent before it
+        let mut order_book_sync = self.order_book_sync.lock();
+        let (last_update_id, updates) = match order_book_sync
+            .on_snapshot(currency_pair, snapshot_last_update_id)
+        {
+            Some(synced) => synced,
+            None => {
+                info!(
+                    "Order book snapshot {} for {} on {} is skipped because it doesn't match buffered updates",
+                    snapshot_last_update_id, currency_pair, self.id
+                );
+                return Ok(());
+            }
+        };
+
+        let mut order_book_event = OrderBookEvent::new(
+            Utc::now(),
+            self.id.clone(),
+            currency_pair.clone(),
+            last_update_id.to_string(),
+            EventType::Snapshot,
+            order_book_data,
+        );
+        order_book_event.apply_data_update(updates);
+
+        self.send_event(ExchangeEvent::OrderBookEvent(order_book_event))
+    }
+
+    fn on_order_book_snapshot_failed(&self, currency_pair: &CurrencyPair) {
+        self.order_book_sync
+            .lock()
+            .on_snapshot_failed(currency_pair);
+    }
+
     fn get_settings(&self) -> &ExchangeSettings {
         &self.settings
     }
 }
 
+/// Parse interval of rate limit from header name suffix like `1m` or `10s`
+fn parse_rate_limit_interval(interval: &str) -> Option<chrono::Duration> {
+    let unit_index = interval.len().checked_sub(1)?;
+    let number: i64 = interval.get(..unit_index)?.parse().ok()?;
+    match interval.get(unit_index..)? {
+        "s" => Some(chrono::Duration::seconds(number)),
+        "m" => Some(chrono::Duration::minutes(number)),
+        "h" => Some(chrono::Duration::hours(number)),
+        "d" => Some(chrono::Duration::days(number)),
+        _ => None,
+    }
+}
+
 trait GetOrErr {
     fn get_as_str(&self, key: &str) -> Result<String>;
     fn get_as_decimal(&self, key: &str) -> Option<Decimal>;
@@ -575,6 +716,55 @@ impl Binance {
         self.handle_order_book_snapshot(currency_pair, &last_update_id, order_book_data, None)
     }
 
+    /// Diff depth event is applied to local order book only after it is synchronized with REST snapshot
+    fn process_depth_update(&self, currency_pair: &CurrencyPair, data: &Value) -> Result<()> {
+        if !self.subscribe_to_market_data {
+            return Ok(());
+        }
+
+        let update = DepthUpdate {
+            first_update_id: data["U"]
+                .as_u64()
+                .context("Unable to get u64 from 'U' field json data")?,
+            last_update_id: data["u"]
+                .as_u64()
+                .context("Unable to get u64 from 'u' field json data")?,
+            data: OrderBookData::new(
+                get_order_book_side(
+                    data["a"]
+                        .as_array()
+                        .context("Unable to parse 'a' in Binance")?,
+                )?,
+                get_order_book_side(
+                    data["b"]
+                        .as_array()
+                        .context("Unable to parse 'b' in Binance")?,
+                )?,
+            ),
+        };
+        let event_id = update.last_update_id.to_string();
+
+        // Event is sent under lock, so it can't be sent before snapshot which it's applied to
+        let mut order_book_sync = self.order_book_sync.lock();
+        match order_book_sync.on_update(currency_pair, update) {
+            DepthUpdateAction::Skip => Ok(()),
+            DepthUpdateAction::RequestSnapshot => {
+                (&self.order_book_snapshot_requested_callback).lock()(currency_pair.clone());
+                Ok(())
+            }
+            DepthUpdateAction::Apply(order_book_data) => {
+                self.send_event(ExchangeEvent::OrderBookEvent(OrderBookEvent::new(
+                    Utc::now(),
+                    self.id.clone(),
+                    currency_pair.clone(),
+                    event_id,
+                    EventType::Update,
+                    order_book_data,
+                )))
+            }
+        }
+    }
+
     fn handle_order_book_snapshot(
         &self,
         currency_pair: &CurrencyPair,
@@ -608,6 +798,49 @@ impl Binance {
         self.send_event(event)
     }
 
+    fn handle_account_position(&self, data: &Value) -> Result<()> {
+        #[derive(Deserialize, Debug)]
+        struct BinanceAccountPositionBalance {
+            #[serde(rename = "a")]
+            asset: CurrencyId,
+            #[serde(rename = "f")]
+            free: Amount,
+            #[serde(rename = "l")]
+            locked: Amount,
+        }
+
+        let changed_balances: Vec<BinanceAccountPositionBalance> =
+            serde_json::from_value(data["B"].clone())
+                .context("Unable to parse balances of outboundAccountPosition event")?;
+
+        let balances_and_positions = {
+            let mut last_balances = self.last_balances.lock();
+            let last_balances = match last_balances.as_mut() {
+                Some(last_balances) => last_balances,
+                None => {
+                    warn!(
+                        "outboundAccountPosition event is skipped on {} because balances weren't requested yet",
+                        self.id
+                    );
+                    return Ok(());
+                }
+            };
+
+            for balance in changed_balances {
+                if let Some(currency_code) = self.get_currency_code(&balance.asset) {
+                    let _ = last_balances.insert(currency_code, balance.free + balance.locked);
+                }
+            }
+
+            to_balances_and_positions(last_balances)
+        };
+
+        self.send_event(ExchangeEvent::BalanceUpdate(BalanceUpdateEvent {
+            exchange_account_id: self.id.clone(),
+            balances_and_positions,
+        }))
+    }
+
     fn currency_pair_from_web_socket(&self, currency_pair: &str) -> Result<CurrencyPair> {
         let specific_currency_pair = currency_pair.to_uppercase().as_str().into();
         self.get_unified_currency_pair(&specific_currency_pair)
@@ -677,6 +910,44 @@ impl Binance {
     }
 }
 
+fn to_balances_and_positions(
+    balances: &HashMap<CurrencyCode, Amount>,
+) -> ExchangeBalancesAndPositions {
+    ExchangeBalancesAndPositions {
+        balances: balances
+            .iter()
+            .map(|(currency_code, balance)| ExchangeBalance {
+                currency_code: currency_code.clone(),
+                balance: *balance,
+            })
+            .collect(),
+        // TODO There is no work with derivatives in current version
+        positions: None,
+    }
+}
+
+fn parse_order_book_snapshot(response: &RestRequestOutcome) -> Result<(u64, OrderBookData)> {
+    let data: Value = serde_json::from_str(&response.content)
+        .context("Unable to parse order book snapshot response")?;
+    let last_update_id = data["lastUpdateId"]
+        .as_u64()
+        .context("Unable to get u64 from 'lastUpdateId' field json data")?;
+    let order_book_data = OrderBookData::new(
+        get_order_book_side(
+            data["asks"]
+                .as_array()
+                .context("Unable to parse 'asks' in Binance")?,
+        )?,
+        get_order_book_side(
+            data["bids"]
+                .as_array()
+                .context("Unable to parse 'bids' in Binance")?,
+        )?,
+    );
+
+    Ok((last_update_id, order_book_data))
+}
+
 fn get_order_book_side(levels: &Vec<Value>) -> Result<SortedOrderData> {
     levels
         .iter()
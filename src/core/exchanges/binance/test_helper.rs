@@ -0,0 +1,68 @@
+#![cfg(test)]
+use std::sync::Arc;
+
+use tokio::sync::broadcast;
+
+use super::binance::Binance;
+use super::order_book_sync::{DepthUpdate, DepthUpdateAction};
+use crate::core::exchanges::common::{CurrencyPair, ExchangeAccountId};
+use crate::core::exchanges::events::ExchangeEvent;
+use crate::core::lifecycle::application_manager::ApplicationManager;
+use crate::core::order_book::order_book_data::OrderBookData;
+use crate::core::settings::ExchangeSettings;
+
+pub(crate) fn get_test_currency_pair() -> CurrencyPair {
+    CurrencyPair::from_codes(&"eth".into(), &"btc".into())
+}
+
+pub(crate) fn get_test_exchange_settings() -> ExchangeSettings {
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    ExchangeSettings::new_short(
+        exchange_account_id,
+        "test_api_key".into(),
+        "test_secret_key".into(),
+        false,
+    )
+}
+
+/// Binance with synced local order book of `get_test_currency_pair()`.
+/// Returned function checks if the next depth update requests a new order book snapshot
+pub(crate) fn get_binance_with_synced_order_book(
+    events_channel: broadcast::Sender<ExchangeEvent>,
+    application_manager: Arc<ApplicationManager>,
+) -> (Binance, impl Fn() -> bool) {
+    let settings = get_test_exchange_settings();
+    let binance = Binance::new(
+        settings.exchange_account_id.clone(),
+        settings,
+        events_channel,
+        application_manager,
+        false,
+    );
+
+    let currency_pair = get_test_currency_pair();
+    let depth_update = |first_update_id, last_update_id| DepthUpdate {
+        first_update_id,
+        last_update_id,
+        data: OrderBookData::new(Default::default(), Default::default()),
+    };
+    let order_book_sync = binance.order_book_sync.clone();
+    let _ = order_book_sync
+        .lock()
+        .on_update(&currency_pair, depth_update(1, 5));
+    let _ = order_book_sync
+        .lock()
+        .on_snapshot(&currency_pair, 5)
+        .expect("in test");
+
+    let is_snapshot_requested = move || {
+        matches!(
+            order_book_sync
+                .lock()
+                .on_update(&currency_pair, depth_update(6, 6)),
+            DepthUpdateAction::RequestSnapshot
+        )
+    };
+
+    (binance, is_snapshot_requested)
+}
@@ -8,3 +8,5 @@ pub static CREATE_ORDER_INSUFFICIENT_FUNDS: BlockReason =
 pub static REST_RATE_LIMIT: BlockReason = BlockReason::new("REST_RATE_LIMIT");
 pub static GRACEFUL_SHUTDOWN: BlockReason = BlockReason::new("GRACEFUL_SHUTDOWN");
 pub static EXCHANGE_UNAVAILABLE: BlockReason = BlockReason::new("EXCHANGE_UNAVAILABLE");
+pub static RISK_LIMIT_BREACH: BlockReason = BlockReason::new("RISK_LIMIT_BREACH");
+pub static DEAD_MAN_SWITCH: BlockReason = BlockReason::new("DEAD_MAN_SWITCH");
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use awc::http::StatusCode;
+use hyper::HeaderMap;
 use itertools::Itertools;
 use regex::Regex;
 use rust_decimal::*;
@@ -212,7 +213,7 @@ impl From<&str> for CurrencyCode {
 }
 
 impl Display for CurrencyCode {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.as_str())
     }
 }
@@ -236,7 +237,7 @@ impl CurrencyPair {
 }
 
 impl Display for CurrencyPair {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.as_str())
     }
 }
@@ -345,11 +346,16 @@ pub enum RestRequestError {
 pub struct RestRequestOutcome {
     pub content: String,
     pub status: StatusCode,
+    pub headers: HeaderMap,
 }
 
 impl RestRequestOutcome {
     pub fn new(content: String, status: StatusCode) -> Self {
-        Self { content, status }
+        Self {
+            content,
+            status,
+            headers: HeaderMap::new(),
+        }
     }
 }
 
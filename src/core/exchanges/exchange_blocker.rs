@@ -414,7 +414,7 @@ impl ExchangeBlockerEventsProcessor {
 
     async fn stop_processing(&self) {
         self.cancellation_token.cancel();
-        tokio::task::yield_now().await;
+        let _ = tokio::task::yield_now().await;
 
         let processing_handle = match self.processing_handle.lock().take() {
             None => {
@@ -861,7 +861,7 @@ mod tests {
             },
         );
 
-        tokio::task::yield_now().await;
+        let _ = tokio::task::yield_now().await;
         assert_eq!(*signal.lock(), false);
 
         exchange_blocker.unblock(&exchange_account_id(), reason);
@@ -870,7 +870,7 @@ mod tests {
             .await;
         assert_eq!(exchange_blocker.is_blocked(&exchange_account_id()), false);
 
-        tokio::task::yield_now().await;
+        let _ = tokio::task::yield_now().await;
         assert_eq!(*signal.lock(), true);
     }
 
@@ -1116,7 +1116,7 @@ mod tests {
             let reason = gen_reason(index);
 
             exchange_blocker.block(&exchange_account_id(), reason, Manual);
-            tokio::task::yield_now().await;
+            let _ = tokio::task::yield_now().await;
             exchange_blocker.unblock(&exchange_account_id(), reason);
             exchange_blocker
                 .wait_unblock_with_reason(
@@ -1179,7 +1179,7 @@ mod tests {
             let reason = gen_reason(index);
 
             exchange_blocker.block(&exchange_account_id(), reason, Manual);
-            tokio::task::yield_now().await;
+            let _ = tokio::task::yield_now().await;
             exchange_blocker.unblock(&exchange_account_id(), reason);
         }
 
@@ -1231,7 +1231,7 @@ mod tests {
             let reason = gen_reason(index);
 
             exchange_blocker.block(&exchange_account_id(), reason, Manual);
-            tokio::task::yield_now().await;
+            let _ = tokio::task::yield_now().await;
             exchange_blocker.unblock(&exchange_account_id(), reason);
         }
 
@@ -1271,7 +1271,7 @@ mod tests {
                         .boxed(),
                     );
                     if i % REASONS_COUNT == 0 {
-                        tokio::task::yield_now().await;
+                        let _ = tokio::task::yield_now().await;
                     }
                 }
 
@@ -1389,7 +1389,7 @@ mod tests {
             .boxed(),
         );
 
-        tokio::task::yield_now().await;
+        let _ = tokio::task::yield_now().await;
         assert_eq!(*wait_completed.lock(), false);
 
         // reblock reason1
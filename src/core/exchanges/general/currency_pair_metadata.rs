@@ -4,6 +4,7 @@ use std::sync::Arc;
 use anyhow::{bail, Context, Result};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
 
 use crate::core::{
     exchanges::common::Amount,
@@ -34,7 +35,7 @@ pub enum BeforeAfter {
 /// ```ignore
 /// Precision::ByTick { tick: dec!(0.001) } // for AmountPrecision = 3 equal pow(0.1, 3)
 /// ```
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Precision {
     // Rounding is performed to a number divisible to the specified tick
     // Look at round_by_tick test below
@@ -44,7 +45,7 @@ pub enum Precision {
     ByMantissa { precision: i8 },
 }
 
-#[derive(Debug, Clone, Hash, Eq)]
+#[derive(Debug, Clone, Hash, Eq, Serialize, Deserialize)]
 pub struct CurrencyPairMetadata {
     pub is_active: bool,
     pub is_derivative: bool,
@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use futures::future::join_all;
+use itertools::Itertools;
+use log::{error, info, warn};
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+use tokio::time::{interval, Duration, Instant};
+
+use crate::core::exchanges::block_reasons::DEAD_MAN_SWITCH;
+use crate::core::exchanges::exchange_blocker::{BlockType, ExchangeBlocker};
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::exchanges::general::order::cancel_all::OrdersCancellationOutcome;
+use crate::core::exchanges::general::request_type::RequestType;
+use crate::core::internal_events_loop::InternalEventsLoop;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::lifecycle::trading_engine::Service;
+use crate::core::nothing_to_do;
+use crate::core::settings::{DeadManSwitchSettings, OrdersCancellationSettings};
+
+// Countdown is refreshed several times per its duration, so a single failed request doesn't lead to cancellation
+const COUNTDOWN_REFRESHES_PER_COUNTDOWN: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeadManSwitchTrigger {
+    EventsLoopStalled,
+    ConnectivityOutage,
+}
+
+fn detect_trigger(
+    time_since_events_loop_heartbeat: Duration,
+    connectivity_outage: Option<Duration>,
+    timeout: Duration,
+) -> Option<DeadManSwitchTrigger> {
+    if time_since_events_loop_heartbeat >= timeout {
+        return Some(DeadManSwitchTrigger::EventsLoopStalled);
+    }
+
+    match connectivity_outage {
+        Some(outage) if outage >= timeout => Some(DeadManSwitchTrigger::ConnectivityOutage),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeadManSwitchAction {
+    CancelAllOrders(DeadManSwitchTrigger),
+    RefreshCountdown,
+}
+
+#[derive(Debug, Default)]
+struct ExchangeSwitchState {
+    // Orders on exchange are cancelled once per failure, next time only after recovering
+    is_triggered: bool,
+    last_countdown_refresh: Option<Instant>,
+}
+
+impl ExchangeSwitchState {
+    fn next_action(
+        &mut self,
+        trigger: Option<DeadManSwitchTrigger>,
+        supports_countdown: bool,
+        countdown: Duration,
+        now: Instant,
+    ) -> Option<DeadManSwitchAction> {
+        if let Some(trigger) = trigger {
+            if self.is_triggered {
+                return None;
+            }
+
+            self.is_triggered = true;
+            return Some(DeadManSwitchAction::CancelAllOrders(trigger));
+        }
+
+        self.is_triggered = false;
+
+        // Countdown isn't refreshed during failure, so exchange cancels orders itself as well
+        let refresh_interval = countdown / COUNTDOWN_REFRESHES_PER_COUNTDOWN;
+        let is_refresh_needed = match self.last_countdown_refresh {
+            Some(last_refresh) => now.duration_since(last_refresh) >= refresh_interval,
+            None => true,
+        };
+        if supports_countdown && is_refresh_needed {
+            Some(DeadManSwitchAction::RefreshCountdown)
+        } else {
+            None
+        }
+    }
+}
+
+/// Exchange is blocked when dead man's switch is triggered and unblocked when it's reset
+fn update_exchange_block(
+    exchange_blocker: &Arc<ExchangeBlocker>,
+    exchange: &Exchange,
+    was_triggered: bool,
+    is_triggered: bool,
+) {
+    let exchange_account_id = &exchange.exchange_account_id;
+    match (was_triggered, is_triggered) {
+        (false, true) => {
+            exchange_blocker.block(exchange_account_id, DEAD_MAN_SWITCH, BlockType::Manual)
+        }
+        (true, false) => {
+            info!("Dead man's switch on {} is reset", exchange_account_id);
+            exchange_blocker.unblock(exchange_account_id, DEAD_MAN_SWITCH);
+        }
+        _ => nothing_to_do(),
+    }
+}
+
+impl Exchange {
+    /// Duration of websocket connectivity outage. None if exchange is connected.
+    /// Outage is counted since exchange creation until the first connection
+    pub fn connectivity_outage_duration(&self) -> Option<Duration> {
+        self.connectivity_lost_at.lock().map(|x| x.elapsed())
+    }
+
+    /// Restart countdown after which exchange cancels all orders for traded currency pairs
+    pub async fn refresh_cancel_all_orders_countdown(&self, countdown: Duration) -> Result<()> {
+        let currency_pairs = self.symbols.iter().map(|x| x.key().clone()).collect_vec();
+        for currency_pair in currency_pairs {
+            self.timeout_manager
+                .reserve_when_available(
+                    &self.exchange_account_id,
+                    RequestType::SetCancelAllOrdersCountdown,
+                    None,
+                    CancellationToken::default(),
+                )?
+                .await
+                .into_result()?;
+
+            self.wait_rest_rate_limit_unblock(self.application_manager.stop_token())
+                .await;
+            let response = self
+                .exchange_client
+                .request_cancel_all_orders_countdown(currency_pair.clone(), countdown)
+                .await?;
+
+            if let Some(error) = self.get_rest_error(&response) {
+                Err(error).with_context(|| {
+                    format!(
+                        "From refreshing of cancel all orders countdown for {} on {}",
+                        currency_pair, self.exchange_account_id
+                    )
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Watchdog which cancels all orders if events loop is stalled or exchange connectivity is lost for too long.
+/// Exchange is blocked until the failure is over, so no new orders are created instead of cancelled ones.
+/// On exchanges with countdown cancellation of all orders the countdown is refreshed periodically too,
+/// so orders are cancelled by exchange even if the whole engine hangs
+pub(crate) struct DeadManSwitch {
+    settings: DeadManSwitchSettings,
+    orders_cancellation_settings: OrdersCancellationSettings,
+    exchanges: Vec<Arc<Exchange>>,
+    internal_events_loop: Arc<InternalEventsLoop>,
+    exchange_blocker: Arc<ExchangeBlocker>,
+    work_finished_receiver: Mutex<Option<oneshot::Receiver<Result<()>>>>,
+}
+
+impl DeadManSwitch {
+    pub(crate) fn new(
+        settings: DeadManSwitchSettings,
+        orders_cancellation_settings: OrdersCancellationSettings,
+        exchanges: Vec<Arc<Exchange>>,
+        internal_events_loop: Arc<InternalEventsLoop>,
+        exchange_blocker: Arc<ExchangeBlocker>,
+    ) -> Result<Arc<Self>> {
+        if settings.check_interval_ms >= settings.timeout_secs * 1000 {
+            bail!(
+                "Dead man's switch check interval {}ms should be less than timeout {}s",
+                settings.check_interval_ms,
+                settings.timeout_secs
+            );
+        }
+
+        Ok(Arc::new(DeadManSwitch {
+            settings,
+            orders_cancellation_settings,
+            exchanges,
+            internal_events_loop,
+            exchange_blocker,
+            work_finished_receiver: Default::default(),
+        }))
+    }
+
+    pub(crate) async fn start(
+        self: Arc<Self>,
+        cancellation_token: CancellationToken,
+    ) -> Result<()> {
+        let (work_finished_sender, receiver) = oneshot::channel();
+        *self.work_finished_receiver.lock() = Some(receiver);
+
+        let timeout = Duration::from_secs(self.settings.timeout_secs);
+        let mut check_interval = interval(Duration::from_millis(self.settings.check_interval_ms));
+
+        let mut states: HashMap<_, ExchangeSwitchState> = HashMap::new();
+        loop {
+            tokio::select! {
+                _ = check_interval.tick() => {}
+                _ = cancellation_token.when_cancelled() => {
+                    let _ = work_finished_sender.send(Ok(()));
+                    return Ok(());
+                }
+            }
+
+            let time_since_events_loop_heartbeat = self.internal_events_loop.time_since_heartbeat();
+            let now = Instant::now();
+            let orders_cancellation_settings = &self.orders_cancellation_settings;
+            let actions = self
+                .exchanges
+                .iter()
+                .filter_map(|exchange| {
+                    let trigger = detect_trigger(
+                        time_since_events_loop_heartbeat,
+                        exchange.connectivity_outage_duration(),
+                        timeout,
+                    );
+                    let state = states
+                        .entry(exchange.exchange_account_id.clone())
+                        .or_default();
+                    let was_triggered = state.is_triggered;
+                    let action = state.next_action(
+                        trigger,
+                        exchange
+                            .features
+                            .order_features
+                            .supports_cancel_all_orders_countdown,
+                        timeout,
+                        now,
+                    );
+
+                    update_exchange_block(
+                        &self.exchange_blocker,
+                        exchange,
+                        was_triggered,
+                        state.is_triggered,
+                    );
+
+                    action.map(|action| async move {
+                        match action {
+                            DeadManSwitchAction::CancelAllOrders(trigger) => {
+                                Self::cancel_all_orders(
+                                    exchange,
+                                    trigger,
+                                    orders_cancellation_settings,
+                                )
+                                .await;
+                                None
+                            }
+                            DeadManSwitchAction::RefreshCountdown => {
+                                Self::refresh_countdown(exchange, timeout)
+                                    .await
+                                    .then(|| exchange.exchange_account_id.clone())
+                            }
+                        }
+                    })
+                })
+                .collect_vec();
+
+            for exchange_account_id in join_all(actions).await.into_iter().flatten() {
+                if let Some(state) = states.get_mut(&exchange_account_id) {
+                    state.last_countdown_refresh = Some(now);
+                }
+            }
+        }
+    }
+
+    async fn cancel_all_orders(
+        exchange: &Exchange,
+        trigger: DeadManSwitchTrigger,
+        orders_cancellation_settings: &OrdersCancellationSettings,
+    ) {
+        error!(
+            "Dead man's switch is triggered on {} by {:?}. Cancelling all orders",
+            exchange.exchange_account_id, trigger
+        );
+
+        let deadline =
+            Instant::now() + Duration::from_secs(orders_cancellation_settings.deadline_secs);
+        let retry_interval = Duration::from_millis(orders_cancellation_settings.retry_interval_ms);
+        let outcome = exchange
+            .cancel_all_orders_until_deadline(deadline, retry_interval)
+            .await;
+
+        if outcome == OrdersCancellationOutcome::Cancelled {
+            info!(
+                "Dead man's switch cancelled all orders on {}",
+                exchange.exchange_account_id
+            );
+        }
+    }
+
+    /// Returns true if countdown is refreshed
+    async fn refresh_countdown(exchange: &Exchange, countdown: Duration) -> bool {
+        match exchange
+            .refresh_cancel_all_orders_countdown(countdown)
+            .await
+        {
+            Ok(()) => true,
+            Err(error) => {
+                warn!(
+                    "Unable to refresh cancel all orders countdown on {}: {:?}",
+                    exchange.exchange_account_id, error
+                );
+                false
+            }
+        }
+    }
+}
+
+impl Service for DeadManSwitch {
+    fn name(&self) -> &str {
+        "DeadManSwitch"
+    }
+
+    fn graceful_shutdown(self: Arc<Self>) -> Option<oneshot::Receiver<Result<()>>> {
+        let work_finished_receiver = self.work_finished_receiver.lock().take();
+        if work_finished_receiver.is_none() {
+            warn!("'work_finished_receiver' wasn't created when started graceful shutdown in DeadManSwitch");
+        }
+
+        work_finished_receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exchanges::general::test_helper::get_test_exchange;
+
+    #[test]
+    fn detect_trigger_by_timeout() {
+        let timeout = Duration::from_secs(10);
+        let short = Duration::from_secs(1);
+        let long = Duration::from_secs(11);
+
+        assert_eq!(detect_trigger(short, None, timeout), None);
+        assert_eq!(detect_trigger(short, Some(short), timeout), None);
+        assert_eq!(
+            detect_trigger(long, None, timeout),
+            Some(DeadManSwitchTrigger::EventsLoopStalled)
+        );
+        assert_eq!(
+            detect_trigger(short, Some(long), timeout),
+            Some(DeadManSwitchTrigger::ConnectivityOutage)
+        );
+        assert_eq!(
+            detect_trigger(long, Some(long), timeout),
+            Some(DeadManSwitchTrigger::EventsLoopStalled)
+        );
+    }
+
+    #[test]
+    fn refresh_countdown_a_few_times_per_countdown() {
+        let countdown = Duration::from_secs(9);
+        let now = Instant::now();
+        let mut state = ExchangeSwitchState::default();
+
+        assert_eq!(state.next_action(None, false, countdown, now), None);
+        assert_eq!(
+            state.next_action(None, true, countdown, now),
+            Some(DeadManSwitchAction::RefreshCountdown)
+        );
+
+        state.last_countdown_refresh = Some(now);
+        assert_eq!(
+            state.next_action(None, true, countdown, now + Duration::from_secs(1)),
+            None
+        );
+        assert_eq!(
+            state.next_action(None, true, countdown, now + Duration::from_secs(3)),
+            Some(DeadManSwitchAction::RefreshCountdown)
+        );
+    }
+
+    #[test]
+    fn cancel_all_orders_once_per_failure() {
+        let countdown = Duration::from_secs(9);
+        let now = Instant::now();
+        let trigger = Some(DeadManSwitchTrigger::ConnectivityOutage);
+        let mut state = ExchangeSwitchState::default();
+
+        assert_eq!(
+            state.next_action(trigger, true, countdown, now),
+            Some(DeadManSwitchAction::CancelAllOrders(
+                DeadManSwitchTrigger::ConnectivityOutage
+            ))
+        );
+        // Neither repeated cancellation nor countdown refreshing during failure
+        assert_eq!(state.next_action(trigger, true, countdown, now), None);
+
+        assert_eq!(
+            state.next_action(None, true, countdown, now),
+            Some(DeadManSwitchAction::RefreshCountdown)
+        );
+        assert_eq!(
+            state.next_action(trigger, true, countdown, now),
+            Some(DeadManSwitchAction::CancelAllOrders(
+                DeadManSwitchTrigger::ConnectivityOutage
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn exchange_is_blocked_until_switch_is_reset() {
+        let (exchange, _) = get_test_exchange(false);
+        let exchange_account_id = exchange.exchange_account_id.clone();
+        let exchange_blocker = ExchangeBlocker::new(vec![exchange_account_id.clone()]);
+
+        update_exchange_block(&exchange_blocker, &exchange, false, true);
+        assert!(exchange_blocker.is_blocked_by_reason(&exchange_account_id, DEAD_MAN_SWITCH));
+
+        // Exchange stays blocked while failure isn't over
+        update_exchange_block(&exchange_blocker, &exchange, true, true);
+        assert!(exchange_blocker.is_blocked_by_reason(&exchange_account_id, DEAD_MAN_SWITCH));
+
+        update_exchange_block(&exchange_blocker, &exchange, true, false);
+        exchange_blocker
+            .wait_unblock(exchange_account_id.clone(), CancellationToken::new())
+            .await;
+        assert!(!exchange_blocker.is_blocked(&exchange_account_id));
+    }
+
+    #[test]
+    fn connectivity_outage_if_exchange_never_connected() {
+        let (exchange, _) = get_test_exchange(false);
+
+        // Outage is counted until the first connection
+        assert!(exchange.connectivity_outage_duration().is_some());
+    }
+}
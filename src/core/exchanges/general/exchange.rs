@@ -8,21 +8,28 @@ use itertools::Itertools;
 use log::{error, info, warn, Level};
 use parking_lot::Mutex;
 use rust_decimal::Decimal;
+use serde::Serialize;
 use serde_json::Value;
 use tokio::sync::{broadcast, oneshot};
+use tokio::time::Instant;
 
 use super::commission::Commission;
 use super::currency_pair_metadata::CurrencyPairMetadata;
 use super::polling_timeout_manager::PollingTimeoutManager;
+use super::request_type::RequestType;
 use crate::core::connectivity::connectivity_manager::GetWSParamsCallback;
+use crate::core::data_recorder::recorder::{DataRecordType, DataRecorder};
 use crate::core::exchanges::common::TradePlace;
-use crate::core::exchanges::events::{ExchangeEvent, Trade};
+use crate::core::exchanges::events::{ExchangeBalancesAndPositions, ExchangeEvent, Trade};
+use crate::core::exchanges::exchange_blocker::ExchangeBlocker;
 use crate::core::exchanges::general::features::ExchangeFeatures;
 use crate::core::exchanges::general::order::cancel::CancelOrderResult;
 use crate::core::exchanges::general::order::create::CreateOrderResult;
+use crate::core::exchanges::general::rest_rate_limit::RestRateLimitBans;
 use crate::core::exchanges::timeouts::requests_timeout_manager_factory::RequestTimeoutArguments;
 use crate::core::exchanges::timeouts::timeout_manager::TimeoutManager;
 use crate::core::orders::event::OrderEventType;
+use crate::core::orders::fill::OrderFill;
 use crate::core::orders::order::{OrderHeader, OrderSide};
 use crate::core::orders::pool::OrdersPool;
 use crate::core::orders::{order::ExchangeOrderId, pool::OrderRef};
@@ -40,7 +47,10 @@ use crate::core::{
 };
 
 use crate::core::{
-    connectivity::{connectivity_manager::ConnectivityManager, websocket_actor::WebSocketParams},
+    connectivity::{
+        connectivity_manager::{ConnectivityManager, ReconnectBackoff},
+        websocket_actor::WebSocketParams,
+    },
     orders::order::ClientOrderId,
 };
 use crate::core::{
@@ -86,7 +96,12 @@ pub struct Exchange {
     pub exchange_account_id: ExchangeAccountId,
     pub(super) exchange_client: Box<dyn ExchangeClient>,
     pub orders: Arc<OrdersPool>,
-    connectivity_manager: Arc<ConnectivityManager>,
+    pub(super) connectivity_manager: Arc<ConnectivityManager>,
+    // Time when websocket connection was lost, None if exchange is connected.
+    // Until the first connection it's time of exchange creation
+    pub(super) connectivity_lost_at: Mutex<Option<Instant>>,
+    // Events since start of websockets reconnection, order book snapshots over new connections are awaited in them
+    pub(super) reconnection_events_receiver: Mutex<Option<broadcast::Receiver<ExchangeEvent>>>,
 
     // It allows to send and receive notification about event in websocket channel
     // Websocket event is main source detecting order creation result
@@ -124,6 +139,17 @@ pub struct Exchange {
     pub(crate) leverage_by_currency_pair: DashMap<CurrencyPair, Decimal>,
     pub(crate) last_trades_update_time: DashMap<TradePlace, DateTime>,
     pub(crate) last_trades: DashMap<TradePlace, Trade>,
+    pub(crate) data_recorder: Option<Arc<DataRecorder>>,
+    pub(super) exchange_blocker: Option<Arc<ExchangeBlocker>>,
+    pub(super) rest_rate_limit_bans: Mutex<RestRateLimitBans>,
+}
+
+#[derive(Serialize)]
+struct FillRecord<'a> {
+    exchange_account_id: &'a ExchangeAccountId,
+    client_order_id: ClientOrderId,
+    currency_pair: CurrencyPair,
+    fill: &'a OrderFill,
 }
 
 pub type BoxExchangeClient = Box<dyn ExchangeClient + Send + Sync + 'static>;
@@ -138,6 +164,8 @@ impl Exchange {
         application_manager: Arc<ApplicationManager>,
         timeout_manager: Arc<TimeoutManager>,
         commission: Commission,
+        data_recorder: Option<Arc<DataRecorder>>,
+        exchange_blocker: Option<Arc<ExchangeBlocker>>,
     ) -> Arc<Self> {
         let connectivity_manager = ConnectivityManager::new(exchange_account_id.clone());
         let polling_timeout_manager = PollingTimeoutManager::new(timeout_arguments);
@@ -147,6 +175,8 @@ impl Exchange {
             exchange_client,
             orders: OrdersPool::new(),
             connectivity_manager,
+            connectivity_lost_at: Mutex::new(Some(Instant::now())),
+            reconnection_events_receiver: Mutex::new(None),
             order_creation_events: DashMap::new(),
             order_cancellation_events: DashMap::new(),
             supported_symbols: Default::default(),
@@ -167,6 +197,9 @@ impl Exchange {
             leverage_by_currency_pair: DashMap::new(),
             last_trades_update_time: DashMap::new(),
             last_trades: DashMap::new(),
+            data_recorder,
+            exchange_blocker,
+            rest_rate_limit_bans: Default::default(),
         });
 
         exchange.clone().setup_connectivity_manager();
@@ -175,6 +208,26 @@ impl Exchange {
         exchange
     }
 
+    pub(crate) fn save_order(&self, order: &OrderRef) {
+        if let Some(data_recorder) = &self.data_recorder {
+            order.fn_ref(|order| data_recorder.save(DataRecordType::Orders, order));
+        }
+    }
+
+    pub(crate) fn save_fill(&self, order: &OrderRef, fill: &OrderFill) {
+        if let Some(data_recorder) = &self.data_recorder {
+            data_recorder.save(
+                DataRecordType::Fills,
+                &FillRecord {
+                    exchange_account_id: &self.exchange_account_id,
+                    client_order_id: order.client_order_id(),
+                    currency_pair: order.currency_pair(),
+                    fill,
+                },
+            );
+        }
+    }
+
     fn setup_connectivity_manager(self: Arc<Self>) {
         let exchange_weak = Arc::downgrade(&self);
         self.connectivity_manager
@@ -189,6 +242,25 @@ impl Exchange {
                 Some(exchange) => exchange.on_connecting(),
                 None => info!("Unable to upgrade weak reference to Exchange instance"),
             }));
+
+        let exchange_weak = Arc::downgrade(&self);
+        self.connectivity_manager
+            .set_callback_connected(Box::new(move || match exchange_weak.upgrade() {
+                Some(exchange) => {
+                    *exchange.connectivity_lost_at.lock() = None;
+                    if exchange.is_websocket_reconnecting() {
+                        exchange.on_reconnected();
+                    }
+                }
+                None => info!("Unable to upgrade weak reference to Exchange instance"),
+            }));
+
+        let exchange_weak = Arc::downgrade(&self);
+        self.connectivity_manager
+            .set_callback_disconnected(Box::new(move |_| match exchange_weak.upgrade() {
+                Some(exchange) => exchange.on_disconnected(),
+                None => info!("Unable to upgrade weak reference to Exchange instance"),
+            }));
     }
 
     fn setup_exchange_client(self: Arc<Self>) {
@@ -273,6 +345,15 @@ impl Exchange {
                 }
             },
         ));
+
+        let exchange_weak = Arc::downgrade(&self);
+        self.exchange_client
+            .set_order_book_snapshot_requested_callback(Box::new(move |currency_pair| {
+                match exchange_weak.upgrade() {
+                    Some(exchange) => exchange.on_order_book_snapshot_requested(currency_pair),
+                    None => info!("Unable to upgrade weak reference to Exchange instance",),
+                }
+            }));
     }
 
     fn on_websocket_message(&self, msg: &str) {
@@ -306,6 +387,10 @@ impl Exchange {
             return;
         }
 
+        if self.is_websocket_reconnecting() {
+            self.on_reconnecting();
+        }
+
         let callback_outcome = self.exchange_client.on_connecting();
         if let Err(error) = callback_outcome {
             warn!(
@@ -315,6 +400,27 @@ impl Exchange {
         }
     }
 
+    fn on_disconnected(&self) {
+        let mut connectivity_lost_at = self.connectivity_lost_at.lock();
+        if connectivity_lost_at.is_none() {
+            warn!(
+                "Websocket connection on {} is lost",
+                self.exchange_account_id
+            );
+            *connectivity_lost_at = Some(Instant::now());
+        }
+    }
+
+    /// Local order book for currency pair is invalid, so it should be received from exchange again
+    pub fn request_order_book_resync(&self, currency_pair: &CurrencyPair) {
+        info!(
+            "Order book resync is requested for {} on {}",
+            currency_pair, self.exchange_account_id
+        );
+        self.exchange_client
+            .request_order_book_resync(currency_pair);
+    }
+
     fn log_websocket_message(&self, msg: &str) {
         info!(
             "Websocket message from {}: {}",
@@ -323,8 +429,11 @@ impl Exchange {
     }
 
     pub async fn connect(self: Arc<Self>) {
-        self.try_connect().await;
-        // TODO Reconnect
+        self.clone().try_connect().await;
+        self.connectivity_manager.enable_reconnect(
+            ReconnectBackoff::default(),
+            self.application_manager.stop_token(),
+        );
     }
 
     async fn try_connect(self: Arc<Self>) {
@@ -383,6 +492,9 @@ impl Exchange {
     ) -> Option<ExchangeError> {
         use ExchangeErrorType::*;
 
+        // Every REST response is checked here, so it's the place to catch up with exchange counters
+        self.sync_rate_limits_usage(response);
+
         let error = match response.status {
             StatusCode::UNAUTHORIZED => {
                 ExchangeError::new(Authentication, response.content.clone(), None)
@@ -390,7 +502,7 @@ impl Exchange {
             StatusCode::GATEWAY_TIMEOUT | StatusCode::SERVICE_UNAVAILABLE => {
                 ExchangeError::new(ServiceUnavailable, response.content.clone(), None)
             }
-            StatusCode::TOO_MANY_REQUESTS => {
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::IM_A_TEAPOT => {
                 ExchangeError::new(RateLimit, response.content.clone(), None)
             }
             _ => match Self::check_content(&response.content) {
@@ -416,6 +528,10 @@ impl Exchange {
             },
         };
 
+        if error.error_type == RateLimit {
+            self.block_on_rest_rate_limit(response);
+        }
+
         let extra_data_len = 512; // just apriori estimation
         let mut msg = String::with_capacity(error.message.len() + extra_data_len);
         write!(
@@ -471,6 +587,8 @@ impl Exchange {
     }
 
     pub async fn cancel_all_orders(&self, currency_pair: CurrencyPair) -> Result<()> {
+        self.wait_rest_rate_limit_unblock(self.application_manager.stop_token())
+            .await;
         self.exchange_client
             .cancel_all_orders(currency_pair)
             .await?;
@@ -478,6 +596,31 @@ impl Exchange {
         Ok(())
     }
 
+    pub async fn get_balance(
+        &self,
+        cancellation_token: CancellationToken,
+    ) -> Result<ExchangeBalancesAndPositions> {
+        self.timeout_manager
+            .reserve_when_available(
+                &self.exchange_account_id,
+                RequestType::GetBalance,
+                None,
+                cancellation_token.clone(),
+            )?
+            .await
+            .into_result()?;
+        self.wait_rest_rate_limit_unblock(cancellation_token).await;
+        let response = self.exchange_client.request_balance().await?;
+
+        if let Some(error) = self.get_rest_error(&response) {
+            Err(error).context("From request get_balance")?;
+        }
+
+        self.exchange_client
+            .parse_balance(&response)
+            .with_context(|| format!("Unable to parse balance on {}", self.exchange_account_id))
+    }
+
     pub(super) fn handle_parse_error(
         &self,
         error: Error,
@@ -579,6 +722,31 @@ impl Exchange {
         }
     }
 
+    pub fn commission(&self) -> &Commission {
+        &self.commission
+    }
+
+    fn sync_rate_limits_usage(&self, response: &RestRequestOutcome) {
+        let rate_limits_usage = self.exchange_client.parse_rate_limits_usage(response);
+        if rate_limits_usage.is_empty() {
+            return;
+        }
+
+        if let Err(error) = self
+            .timeout_manager
+            .sync_rate_limits_usage(&self.exchange_account_id, &rate_limits_usage)
+        {
+            warn!(
+                "Unable to synchronise rate limits usage on {}: {:?}",
+                self.exchange_account_id, error
+            );
+        }
+    }
+
+    pub fn supports_self_trade_prevention(&self) -> bool {
+        self.features.order_features.supports_self_trade_prevention
+    }
+
     pub fn get_balance_reservation_currency_code(
         &self,
         currency_pair_metadata: Arc<CurrencyPairMetadata>,
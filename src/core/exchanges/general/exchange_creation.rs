@@ -5,7 +5,11 @@ use log::error;
 use tokio::sync::broadcast;
 
 use super::{commission::Commission, currency_pair_metadata::CurrencyPairMetadata};
+use crate::core::data_recorder::recorder::DataRecorder;
 use crate::core::exchanges::events::ExchangeEvent;
+use crate::core::exchanges::exchange_blocker::ExchangeBlocker;
+use crate::core::exchanges::recording::raw_messages_recorder::RawMessagesRecorder;
+use crate::core::exchanges::recording::recording_exchange_client::RecordingExchangeClient;
 use crate::core::lifecycle::application_manager::ApplicationManager;
 use crate::core::lifecycle::launcher::EngineBuildConfig;
 use crate::core::settings::{CurrencyPairSetting, ExchangeSettings};
@@ -21,6 +25,7 @@ use crate::core::{
 pub fn create_timeout_manager(
     core_settings: &CoreSettings,
     build_settings: &EngineBuildConfig,
+    data_recorder: &Option<Arc<DataRecorder>>,
 ) -> Arc<TimeoutManager> {
     let request_timeout_managers = core_settings
         .exchanges
@@ -34,6 +39,7 @@ pub fn create_timeout_manager(
             let request_timeout_manager = RequestsTimeoutManagerFactory::from_requests_per_period(
                 timeout_arguments,
                 exchange_account_id.clone(),
+                data_recorder.clone(),
             );
 
             (exchange_account_id, request_timeout_manager)
@@ -43,13 +49,16 @@ pub fn create_timeout_manager(
     TimeoutManager::new(request_timeout_managers)
 }
 
+/// Returns raw messages recorder of the exchange too, so it can be finished on graceful shutdown
 pub async fn create_exchange(
     user_settings: &ExchangeSettings,
     build_settings: &EngineBuildConfig,
     events_channel: broadcast::Sender<ExchangeEvent>,
     application_manager: Arc<ApplicationManager>,
     timeout_manager: Arc<TimeoutManager>,
-) -> Arc<Exchange> {
+    data_recorder: Option<Arc<DataRecorder>>,
+    exchange_blocker: Arc<ExchangeBlocker>,
+) -> (Arc<Exchange>, Option<Arc<RawMessagesRecorder>>) {
     let exchange_client_builder =
         &build_settings.supported_exchange_clients[&user_settings.exchange_account_id.exchange_id];
 
@@ -59,15 +68,35 @@ pub async fn create_exchange(
         application_manager.clone(),
     );
 
+    let mut client = exchange_client.client;
+    let mut raw_messages_recorder = None;
+    if let Some(raw_messages_directory) = &user_settings.raw_messages_directory {
+        match RawMessagesRecorder::open(
+            raw_messages_directory,
+            user_settings.exchange_account_id.clone(),
+        ) {
+            Ok(recorder) => {
+                client = Box::new(RecordingExchangeClient::new(client, recorder.clone()));
+                raw_messages_recorder = Some(recorder);
+            }
+            Err(error) => error!(
+                "Raw messages recording is disabled for {}: {:?}",
+                user_settings.exchange_account_id, error
+            ),
+        }
+    }
+
     let exchange = Exchange::new(
         user_settings.exchange_account_id.clone(),
-        exchange_client.client,
+        client,
         exchange_client.features,
         exchange_client_builder.get_timeout_argments(),
         events_channel,
         application_manager,
         timeout_manager.clone(),
         Commission::default(),
+        data_recorder,
+        Some(exchange_blocker),
     );
 
     exchange.build_metadata().await;
@@ -78,7 +107,7 @@ pub async fn create_exchange(
 
     exchange.clone().connect().await;
 
-    exchange
+    (exchange, raw_messages_recorder)
 }
 
 pub fn get_symbols(
@@ -48,6 +48,27 @@ impl Exchange {
         *self.supported_symbols.lock() = symbols;
     }
 
+    /// Use metadata known in advance instead of requested one. It's used when market data are replayed
+    /// (e.g. in backtesting), so there is no connection to the exchange
+    pub(crate) fn set_known_symbols(&self, symbols: Vec<Arc<CurrencyPairMetadata>>) {
+        let supported_currencies = Self::get_supported_currencies(&symbols[..]);
+        self.set_supported_currencies(supported_currencies);
+
+        let mut currencies = symbols
+            .iter()
+            .flat_map(|x| vec![x.base_currency_code.clone(), x.quote_currency_code.clone()])
+            .collect_vec();
+        currencies.dedup();
+        *self.currencies.lock() = currencies;
+
+        for symbol in &symbols {
+            self.leverage_by_currency_pair
+                .insert(symbol.currency_pair(), dec!(1));
+            self.symbols.insert(symbol.currency_pair(), symbol.clone());
+        }
+        *self.supported_symbols.lock() = symbols;
+    }
+
     fn set_supported_currencies(&self, supported_currencies: DashMap<CurrencyCode, CurrencyId>) {
         for (currency_code, currency_id) in supported_currencies {
             self.exchange_client
@@ -57,6 +78,8 @@ impl Exchange {
     }
 
     async fn build_metadata_core(&self) -> Result<Vec<Arc<CurrencyPairMetadata>>> {
+        self.wait_rest_rate_limit_unblock(self.application_manager.stop_token())
+            .await;
         let response = &self.exchange_client.request_metadata().await?;
 
         if let Some(error) = self.get_rest_error(response) {
@@ -66,6 +66,9 @@ pub struct OrderFeatures {
     pub order_was_completed_error_for_cancellation: bool,
     pub supports_already_cancelled_order: bool,
     pub supports_stop_loss_order: bool,
+    pub supports_cancel_all_orders: bool,
+    pub supports_cancel_all_orders_countdown: bool,
+    pub supports_self_trade_prevention: bool,
 }
 
 impl OrderFeatures {
@@ -77,6 +80,9 @@ impl OrderFeatures {
         order_was_completed_error_for_cancellation: bool,
         supports_already_cancelled_order: bool,
         supports_stop_loss_order: bool,
+        supports_cancel_all_orders: bool,
+        supports_cancel_all_orders_countdown: bool,
+        supports_self_trade_prevention: bool,
     ) -> Self {
         Self {
             maker_only,
@@ -86,6 +92,9 @@ impl OrderFeatures {
             order_was_completed_error_for_cancellation,
             supports_already_cancelled_order,
             supports_stop_loss_order,
+            supports_cancel_all_orders,
+            supports_cancel_all_orders_countdown,
+            supports_self_trade_prevention,
         }
     }
 }
@@ -125,7 +125,7 @@ impl Exchange {
                     error.message
                 );
 
-                // TODO DataRecorder.save()
+                self.save_order(&order);
             }
         }
 
@@ -136,7 +136,7 @@ impl Exchange {
             client_order_id, exchange_order_id, self.exchange_account_id
         );
 
-        // TODO DataRecorder.save(order_ref)
+        self.save_order(order_ref);
 
         Ok(())
     }
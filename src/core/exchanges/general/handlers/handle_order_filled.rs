@@ -670,7 +670,8 @@ impl Exchange {
 
         self.react_if_order_completed(order_filled_amount, order_ref)?;
 
-        // TODO DataRecorder.save(order)
+        self.save_fill(order_ref, &order_fill);
+        self.save_order(order_ref);
 
         Ok(())
     }
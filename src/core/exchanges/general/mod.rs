@@ -1,13 +1,17 @@
 pub mod commission;
 pub mod currency_pair_metadata;
 pub mod currency_pair_to_metadata_converter;
+pub mod dead_man_switch;
 pub mod exchange;
 pub mod exchange_creation;
 pub mod exchange_metadata;
 pub mod features;
 pub mod handlers;
 pub mod order;
+pub mod order_book_snapshot;
 pub mod polling_timeout_manager;
 pub mod request_type;
+pub mod rest_rate_limit;
 #[cfg(test)]
 pub mod test_helper;
+pub mod websocket_reconnect;
@@ -152,6 +152,12 @@ impl Exchange {
         self.order_cancellation_events
             .insert(exchange_order_id.clone(), (tx, None));
 
+        self.wait_rest_rate_limit_unblock(cancellation_token.clone())
+            .await;
+        if cancellation_token.is_cancellation_requested() {
+            return None;
+        }
+
         let order_cancel_future = self.exchange_client.request_cancel_order(&order);
 
         tokio::select! {
@@ -0,0 +1,131 @@
+use itertools::Itertools;
+use log::{error, info, warn};
+use serde::Serialize;
+use tokio::time::{sleep, timeout_at, Duration, Instant};
+
+use crate::core::{
+    exchanges::general::exchange::Exchange, lifecycle::cancellation_token::CancellationToken,
+    orders::order::ClientOrderId,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum OrdersCancellationOutcome {
+    /// There are no open orders on exchange
+    Cancelled,
+    /// Orders are still open on exchange when deadline is reached
+    OpenOrdersRemain(Vec<ClientOrderId>),
+    /// Open orders weren't received from exchange until deadline
+    Unverified,
+}
+
+impl Exchange {
+    /// Cancel all not finished orders and check with open orders from exchange that nothing is left.
+    /// Cancellation is repeated until there are no open orders or deadline is reached
+    pub async fn cancel_all_orders_until_deadline(
+        &self,
+        deadline: Instant,
+        retry_interval: Duration,
+    ) -> OrdersCancellationOutcome {
+        let mut attempt = 1;
+        loop {
+            self.cancel_all_orders_by_currency_pairs(deadline).await;
+
+            let outcome = match timeout_at(deadline, self.get_open_orders(true)).await {
+                Ok(Ok(open_orders)) if open_orders.is_empty() => {
+                    info!(
+                        "All orders on {} are cancelled after {} attempts",
+                        self.exchange_account_id, attempt
+                    );
+                    return OrdersCancellationOutcome::Cancelled;
+                }
+                Ok(Ok(open_orders)) => {
+                    let mut client_order_ids = open_orders
+                        .iter()
+                        .map(|x| x.client_order_id.clone())
+                        .collect_vec();
+
+                    // Fallback for exchanges without cancellation of all orders and for orders which were left by it
+                    let cancellation_token = CancellationToken::new();
+                    if timeout_at(
+                        deadline,
+                        self.cancel_orders(open_orders, cancellation_token.clone()),
+                    )
+                    .await
+                    .is_err()
+                    {
+                        cancellation_token.cancel();
+                    } else if let Ok(Ok(open_orders)) =
+                        timeout_at(deadline, self.get_open_orders(true)).await
+                    {
+                        if open_orders.is_empty() {
+                            info!(
+                                "All orders on {} are cancelled one by one after {} attempts",
+                                self.exchange_account_id, attempt
+                            );
+                            return OrdersCancellationOutcome::Cancelled;
+                        }
+
+                        client_order_ids = open_orders
+                            .into_iter()
+                            .map(|x| x.client_order_id)
+                            .collect_vec();
+                    }
+
+                    OrdersCancellationOutcome::OpenOrdersRemain(client_order_ids)
+                }
+                Ok(Err(error)) => {
+                    warn!(
+                        "Unable to get open orders on {} for cancellation check: {:?}",
+                        self.exchange_account_id, error
+                    );
+                    OrdersCancellationOutcome::Unverified
+                }
+                Err(_) => OrdersCancellationOutcome::Unverified,
+            };
+
+            if Instant::now() + retry_interval >= deadline {
+                error!(
+                    "Orders on {} aren't cancelled until deadline: {:?}",
+                    self.exchange_account_id, outcome
+                );
+                return outcome;
+            }
+
+            warn!(
+                "Orders on {} aren't cancelled on attempt {}: {:?}",
+                self.exchange_account_id, attempt, outcome
+            );
+            attempt += 1;
+            sleep(retry_interval).await;
+        }
+    }
+
+    async fn cancel_all_orders_by_currency_pairs(&self, deadline: Instant) {
+        if !self.features.order_features.supports_cancel_all_orders {
+            return;
+        }
+
+        let currency_pairs = self
+            .orders
+            .not_finished
+            .iter()
+            .map(|x| x.currency_pair())
+            .unique()
+            .collect_vec();
+
+        for currency_pair in currency_pairs {
+            let result = timeout_at(deadline, self.cancel_all_orders(currency_pair.clone())).await;
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(error)) => warn!(
+                    "Unable to cancel all orders for {} on {}: {:?}",
+                    currency_pair, self.exchange_account_id, error
+                ),
+                Err(_) => warn!(
+                    "Cancellation of all orders for {} on {} isn't finished until deadline",
+                    currency_pair, self.exchange_account_id
+                ),
+            }
+        }
+    }
+}
@@ -118,9 +118,6 @@ impl Exchange {
                     );
                 }
 
-                // TODO DataRecorder.Save(order); Do we really need it here?
-                // Cause it's already performed in handle_create_order_succeeded
-
                 info!(
                     "Order was submitted {} {:?} {:?} on {}",
                     result_order.client_order_id(),
@@ -255,7 +252,7 @@ impl Exchange {
 
                 self.add_event_on_order_change(order_ref, OrderEventType::CreateOrderFailed)?;
 
-                // TODO DataRecorder.Save(order)
+                self.save_order(order_ref);
 
                 warn!(
                     "Order creation failed {:?}, with error: {:?}",
@@ -384,8 +381,7 @@ impl Exchange {
                 // TODO if BufferedFillsManager.TryGetFills(...)
                 // TODO if BufferedCanceledOrdersManager.TryGetOrder(...)
 
-                // TODO DataRecorder.Save(order); Do we really need it here?
-                // Cause it's already performed in handle_create_order_succeeded
+                self.save_order(order_ref);
 
                 info!("Order was created: {:?}", args_to_log);
 
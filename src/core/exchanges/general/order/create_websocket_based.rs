@@ -30,6 +30,12 @@ impl Exchange {
         self.order_creation_events
             .insert(client_order_id.clone(), (tx, None));
 
+        self.wait_rest_rate_limit_unblock(cancellation_token.clone())
+            .await;
+        if cancellation_token.is_cancellation_requested() {
+            return None;
+        }
+
         let order_create_future = self.exchange_client.create_order(&order);
 
         tokio::select! {
@@ -29,6 +29,8 @@ impl Exchange {
             order.exchange_order_id(),
             self.exchange_account_id
         );
+        self.wait_rest_rate_limit_unblock(self.application_manager.stop_token())
+            .await;
         let request_outcome = self.exchange_client.request_order_info(order).await;
 
         match request_outcome {
@@ -54,6 +54,8 @@ impl Exchange {
             )?
             .await
             .into_result()?;
+        self.wait_rest_rate_limit_unblock(self.application_manager.stop_token())
+            .await;
         self.exchange_client
             .request_open_orders_by_currency_pair(currency_pair)
             .await
@@ -76,6 +78,8 @@ impl Exchange {
                     )?
                     .await
                     .into_result()?;
+                self.wait_rest_rate_limit_unblock(self.application_manager.stop_token())
+                    .await;
                 let response = self.exchange_client.request_open_orders().await?;
 
                 info!(
@@ -134,7 +138,7 @@ impl Exchange {
         Ok(open_orders)
     }
 
-    fn add_missing_open_orders(&self, open_orders: &Vec<OrderInfo>) {
+    pub(super) fn add_missing_open_orders(&self, open_orders: &Vec<OrderInfo>) {
         for order in open_orders {
             if order.client_order_id.as_str().is_empty()
                 && self
@@ -99,6 +99,8 @@ impl Exchange {
         last_date_time: Option<DateTime>,
     ) -> Result<RequestResult<Vec<OrderTrade>>> {
         // TODO Add metric UseTimeMetric(RequestType::GetMyTrades)
+        self.wait_rest_rate_limit_unblock(self.application_manager.stop_token())
+            .await;
         let response = self
             .exchange_client
             .request_my_trades(currency_pair_metadata, last_date_time)
@@ -1,8 +1,10 @@
 pub mod cancel;
+pub mod cancel_all;
 pub mod create;
 pub mod create_websocket_based;
 pub mod get_info;
 pub mod get_open_orders;
 pub mod get_order_trades;
+pub mod restore_orders;
 pub mod wait_cancel;
 pub mod wait_finish;
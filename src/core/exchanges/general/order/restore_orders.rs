@@ -0,0 +1,430 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use itertools::Itertools;
+use log::{info, warn};
+use parking_lot::RwLock;
+
+use super::get_order_trades::OrderTrade;
+use crate::core::exchanges::general::exchange::{Exchange, RequestResult};
+use crate::core::exchanges::general::request_type::RequestType;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::orders::fill::EventSourceType;
+use crate::core::orders::order::{OrderInfo, OrderSnapshot, OrderStatus};
+use crate::core::orders::pool::OrderRef;
+
+impl Exchange {
+    /// Restore orders of previous launches and reconcile them with the exchange:
+    /// missed fills are applied, orders which are not open anymore are finished
+    /// and unknown open orders are added to the pool.
+    /// Returns orders which are still open on the exchange
+    pub async fn restore_orders(
+        &self,
+        persisted_orders: Vec<OrderSnapshot>,
+    ) -> Result<Vec<OrderRef>> {
+        let restored_orders = self.add_persisted_orders(persisted_orders);
+
+        let open_orders = self.get_open_orders(false).await?;
+        let my_trades = self.get_my_trades_for_orders(&restored_orders).await?;
+
+        self.reconcile_restored_orders(&restored_orders, &open_orders, &my_trades)
+    }
+
+    /// Reservation ids are unique only within a launch, so reservations of restored orders are dropped.
+    /// Fresh reservations are created when orders are adopted by disposition executor
+    pub(crate) fn add_persisted_orders(
+        &self,
+        persisted_orders: Vec<OrderSnapshot>,
+    ) -> Vec<OrderRef> {
+        persisted_orders
+            .into_iter()
+            .filter(|snapshot| {
+                snapshot.header.exchange_account_id == self.exchange_account_id
+                    && !snapshot.props.is_finished()
+                    && !self
+                        .orders
+                        .cache_by_client_id
+                        .contains_key(&snapshot.header.client_order_id)
+            })
+            .map(|mut snapshot| {
+                if snapshot.header.reservation_id.is_some() {
+                    let mut header = (*snapshot.header).clone();
+                    header.reservation_id = None;
+                    snapshot.header = Arc::new(header);
+                }
+
+                let order = self
+                    .orders
+                    .add_snapshot_initial(Arc::new(RwLock::new(snapshot)));
+
+                if let Some(exchange_order_id) = order.exchange_order_id() {
+                    let _ = self
+                        .orders
+                        .cache_by_exchange_id
+                        .insert(exchange_order_id, order.clone());
+                }
+
+                info!(
+                    "Restored order {} {:?} with status {:?} on {}",
+                    order.client_order_id(),
+                    order.exchange_order_id(),
+                    order.status(),
+                    self.exchange_account_id
+                );
+
+                order
+            })
+            .collect()
+    }
+
+    /// Trades are requested for currency pairs of all restored orders, because orders without
+    /// exchange order id get it from open orders during reconciliation before their fills are applied
+    async fn get_my_trades_for_orders(&self, orders: &[OrderRef]) -> Result<Vec<OrderTrade>> {
+        let currency_pairs = orders
+            .iter()
+            .map(|order| order.currency_pair())
+            .unique()
+            .collect_vec();
+
+        let mut my_trades = Vec::new();
+        for currency_pair in currency_pairs {
+            let currency_pair_metadata = self
+                .symbols
+                .get(&currency_pair)
+                .with_context(|| {
+                    format!(
+                        "No such currency_pair_metadata for given currency_pair {}",
+                        currency_pair
+                    )
+                })?
+                .clone();
+
+            self.timeout_manager
+                .reserve_when_available(
+                    &self.exchange_account_id,
+                    RequestType::GetMyTrades,
+                    None,
+                    CancellationToken::default(),
+                )?
+                .await
+                .into_result()?;
+
+            match self.get_my_trades(&currency_pair_metadata, None).await? {
+                RequestResult::Success(ref mut trades) => my_trades.append(trades),
+                RequestResult::Error(error) => bail!(
+                    "Unable to get trades for {} on {} while restoring orders: {:?}",
+                    currency_pair,
+                    self.exchange_account_id,
+                    error
+                ),
+            }
+        }
+
+        Ok(my_trades)
+    }
+
+    pub(crate) fn reconcile_restored_orders(
+        &self,
+        restored_orders: &[OrderRef],
+        open_orders: &[OrderInfo],
+        my_trades: &[OrderTrade],
+    ) -> Result<Vec<OrderRef>> {
+        for order in restored_orders {
+            let open_order = find_open_order(order, open_orders);
+            if let Some(open_order) = open_order {
+                self.update_by_open_order(order, open_order);
+            }
+
+            self.apply_missed_fills(order, my_trades)?;
+
+            if open_order.is_none() && !order.is_finished() {
+                self.finish_closed_order(order)?;
+            }
+        }
+
+        self.add_missing_open_orders(&open_orders.to_vec());
+
+        Ok(self
+            .orders
+            .not_finished
+            .iter()
+            .map(|x| x.value().clone())
+            .filter(|order| {
+                !order.is_finished()
+                    && open_orders.iter().any(|open_order| {
+                        Some(&open_order.exchange_order_id) == order.exchange_order_id().as_ref()
+                    })
+            })
+            .collect())
+    }
+
+    fn update_by_open_order(&self, order: &OrderRef, open_order: &OrderInfo) {
+        if order.exchange_order_id().is_none() {
+            order
+                .fn_mut(|x| x.props.exchange_order_id = Some(open_order.exchange_order_id.clone()));
+            let _ = self
+                .orders
+                .cache_by_exchange_id
+                .insert(open_order.exchange_order_id.clone(), order.clone());
+        }
+
+        if order.status() == OrderStatus::Creating {
+            order.fn_mut(|x| x.set_status(OrderStatus::Created, Utc::now()));
+            self.save_order(order);
+        }
+    }
+
+    fn apply_missed_fills(&self, order: &OrderRef, my_trades: &[OrderTrade]) -> Result<()> {
+        let exchange_order_id = match order.exchange_order_id() {
+            Some(exchange_order_id) => exchange_order_id,
+            None => return Ok(()),
+        };
+
+        for order_trade in my_trades
+            .iter()
+            .filter(|x| x.exchange_order_id == exchange_order_id)
+        {
+            let is_fill_known = order.get_fills().0.iter().any(|order_fill| {
+                order_fill
+                    .trade_id()
+                    .map(|fill_trade_id| fill_trade_id == &order_trade.trade_id)
+                    .unwrap_or(false)
+            });
+            if is_fill_known {
+                continue;
+            }
+
+            info!(
+                "Applying missed trade {} for restored order {} on {}",
+                order_trade.trade_id,
+                order.client_order_id(),
+                self.exchange_account_id
+            );
+            self.handle_order_filled_for_restfallback(order, order_trade)?;
+        }
+
+        Ok(())
+    }
+
+    fn finish_closed_order(&self, order: &OrderRef) -> Result<()> {
+        let client_order_id = order.client_order_id();
+        match order.exchange_order_id() {
+            Some(exchange_order_id) => {
+                info!(
+                    "Restored order {} {} is not open anymore on {} so it is considered canceled",
+                    client_order_id, exchange_order_id, self.exchange_account_id
+                );
+                self.handle_cancel_order_succeeded(
+                    Some(&client_order_id),
+                    &exchange_order_id,
+                    None,
+                    EventSourceType::RestFallback,
+                )
+            }
+            None => {
+                // Order creation wasn't confirmed before restart and the order isn't open on the exchange
+                warn!(
+                    "Restored order {} without exchange_order_id is not open on {} so it is considered failed to create",
+                    client_order_id, self.exchange_account_id
+                );
+                order.fn_mut(|x| x.set_status(OrderStatus::FailedToCreate, Utc::now()));
+                self.save_order(order);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn find_open_order<'a>(order: &OrderRef, open_orders: &'a [OrderInfo]) -> Option<&'a OrderInfo> {
+    let client_order_id = order.client_order_id();
+    let exchange_order_id = order.exchange_order_id();
+    open_orders.iter().find(|open_order| {
+        Some(&open_order.exchange_order_id) == exchange_order_id.as_ref()
+            || open_order.client_order_id == client_order_id
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exchanges::common::{CurrencyPair, ExchangeAccountId};
+    use crate::core::exchanges::general::test_helper::get_test_exchange;
+    use crate::core::orders::fill::OrderFillType;
+    use crate::core::orders::order::{
+        ClientOrderId, ExchangeOrderId, OrderRole, OrderSide, OrderType,
+    };
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    fn persisted_order(
+        exchange_account_id: &ExchangeAccountId,
+        currency_pair: &CurrencyPair,
+        exchange_order_id: Option<&str>,
+        status: OrderStatus,
+    ) -> OrderSnapshot {
+        let mut snapshot = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderType::Limit,
+            None,
+            exchange_account_id.clone(),
+            currency_pair.clone(),
+            dec!(0.2),
+            dec!(10),
+            OrderSide::Buy,
+            None,
+            "StrategyInUnitTests",
+        );
+        snapshot.props.exchange_order_id =
+            exchange_order_id.map(|x| ExchangeOrderId::new(x.into()));
+        snapshot.set_status(status, Utc::now());
+        snapshot
+    }
+
+    fn open_order(
+        currency_pair: &CurrencyPair,
+        exchange_order_id: &str,
+        client_order_id: ClientOrderId,
+    ) -> OrderInfo {
+        OrderInfo::new(
+            currency_pair.clone(),
+            ExchangeOrderId::new(exchange_order_id.into()),
+            client_order_id,
+            OrderSide::Buy,
+            OrderStatus::Created,
+            dec!(0.2),
+            dec!(10),
+            dec!(0),
+            dec!(0),
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn order_trade(exchange_order_id: &str, trade_id: &str, amount: Decimal) -> OrderTrade {
+        OrderTrade {
+            exchange_order_id: ExchangeOrderId::new(exchange_order_id.into()),
+            trade_id: trade_id.to_owned(),
+            datetime: Utc::now(),
+            price: dec!(0.2),
+            amount,
+            order_role: OrderRole::Maker,
+            fee_currency_code: "BTC".into(),
+            fee_rate: None,
+            fee_amount: None,
+            fill_type: OrderFillType::UserTrade,
+        }
+    }
+
+    #[test]
+    fn restored_orders_are_reconciled_with_exchange() {
+        let (exchange, _event_receiver) = get_test_exchange(false);
+        let exchange_account_id = exchange.exchange_account_id.clone();
+        let currency_pair = CurrencyPair::from_codes(&"PHB".into(), &"BTC".into());
+
+        let still_open = persisted_order(
+            &exchange_account_id,
+            &currency_pair,
+            Some("still_open"),
+            OrderStatus::Created,
+        );
+        let closed = persisted_order(
+            &exchange_account_id,
+            &currency_pair,
+            Some("closed"),
+            OrderStatus::Created,
+        );
+        let creating = persisted_order(
+            &exchange_account_id,
+            &currency_pair,
+            None,
+            OrderStatus::Creating,
+        );
+        let never_created = persisted_order(
+            &exchange_account_id,
+            &currency_pair,
+            None,
+            OrderStatus::Creating,
+        );
+        let already_canceled = persisted_order(
+            &exchange_account_id,
+            &currency_pair,
+            Some("already_canceled"),
+            OrderStatus::Canceled,
+        );
+
+        let still_open_id = still_open.header.client_order_id.clone();
+        let closed_id = closed.header.client_order_id.clone();
+        let creating_id = creating.header.client_order_id.clone();
+        let never_created_id = never_created.header.client_order_id.clone();
+        let already_canceled_id = already_canceled.header.client_order_id.clone();
+
+        let restored_orders = exchange.add_persisted_orders(vec![
+            still_open,
+            closed,
+            creating,
+            never_created,
+            already_canceled,
+        ]);
+        assert_eq!(restored_orders.len(), 4);
+        assert!(!exchange
+            .orders
+            .cache_by_client_id
+            .contains_key(&already_canceled_id));
+
+        let open_orders = vec![
+            open_order(&currency_pair, "still_open", still_open_id.clone()),
+            open_order(&currency_pair, "created", creating_id.clone()),
+            open_order(&currency_pair, "unknown", ClientOrderId::unique_id()),
+        ];
+        let my_trades = vec![
+            order_trade("still_open", "trade_1", dec!(3)),
+            order_trade("closed", "trade_2", dec!(10)),
+            // Trade of order which exchange order id was unknown before restart
+            order_trade("created", "trade_3", dec!(2)),
+        ];
+
+        let surviving_orders = exchange
+            .reconcile_restored_orders(&restored_orders, &open_orders, &my_trades)
+            .expect("in test");
+
+        let get_order = |client_order_id: &ClientOrderId| {
+            exchange
+                .orders
+                .cache_by_client_id
+                .get(client_order_id)
+                .expect("in test")
+                .clone()
+        };
+
+        let still_open = get_order(&still_open_id);
+        assert_eq!(still_open.status(), OrderStatus::Created);
+        assert_eq!(still_open.filled_amount(), dec!(3));
+
+        assert_eq!(get_order(&closed_id).status(), OrderStatus::Completed);
+
+        let creating = get_order(&creating_id);
+        assert_eq!(creating.status(), OrderStatus::Created);
+        assert_eq!(
+            creating.exchange_order_id(),
+            Some(ExchangeOrderId::new("created".into()))
+        );
+        assert_eq!(creating.filled_amount(), dec!(2));
+
+        assert_eq!(
+            get_order(&never_created_id).status(),
+            OrderStatus::FailedToCreate
+        );
+
+        let mut surviving_exchange_order_ids = surviving_orders
+            .iter()
+            .map(|x| x.exchange_order_id().expect("in test").as_str().to_owned())
+            .collect_vec();
+        surviving_exchange_order_ids.sort();
+        assert_eq!(
+            surviving_exchange_order_ids,
+            vec!["created", "still_open", "unknown"]
+        );
+    }
+}
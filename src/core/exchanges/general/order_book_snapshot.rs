@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use futures::FutureExt;
+
+use crate::core::exchanges::common::{CurrencyPair, RestRequestOutcome};
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::exchanges::general::request_type::RequestType;
+use crate::core::infrastructure::spawn_future;
+
+impl Exchange {
+    /// Order book snapshot is requested in background and handed over to exchange client,
+    /// which synchronizes it with websocket updates. Exchange client requests it again on failure
+    pub(super) fn on_order_book_snapshot_requested(self: Arc<Self>, currency_pair: CurrencyPair) {
+        let action = async move {
+            match self.request_order_book_snapshot(&currency_pair).await {
+                Ok(response) => self
+                    .exchange_client
+                    .handle_order_book_snapshot_response(&currency_pair, &response),
+                Err(error) => {
+                    self.exchange_client
+                        .on_order_book_snapshot_failed(&currency_pair);
+                    Err(error)
+                }
+            }
+            .with_context(|| {
+                format!(
+                    "Unable to get order book snapshot for {} on {}",
+                    currency_pair, self.exchange_account_id
+                )
+            })
+        };
+
+        spawn_future("Request order book snapshot", false, action.boxed());
+    }
+
+    async fn request_order_book_snapshot(
+        &self,
+        currency_pair: &CurrencyPair,
+    ) -> Result<RestRequestOutcome> {
+        let cancellation_token = self.application_manager.stop_token();
+
+        self.timeout_manager
+            .reserve_when_available(
+                &self.exchange_account_id,
+                RequestType::GetOrderBook,
+                None,
+                cancellation_token.clone(),
+            )?
+            .await
+            .into_result()?;
+        self.wait_rest_rate_limit_unblock(cancellation_token).await;
+        let response = self
+            .exchange_client
+            .request_order_book_snapshot(currency_pair.clone())
+            .await?;
+
+        if let Some(error) = self.get_rest_error(&response) {
+            Err(error).context("From request get_order_book_snapshot")?;
+        }
+
+        Ok(response)
+    }
+}
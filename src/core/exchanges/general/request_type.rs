@@ -1,7 +1,10 @@
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum RequestType {
     CreateOrder,
     CancelOrder,
+    CancelAllOrders,
     GetOrderInfo,
     GetBalance,
     GetOpenOrders,
@@ -22,4 +25,7 @@ pub enum RequestType {
     GetProfileId,
     GetMyTrades,
     SetLeverage,
+    SetCancelAllOrdersCountdown,
+    /// Usage of rate limit reported by exchange which isn't accounted by reserved requests
+    UnaccountedUsage,
 }
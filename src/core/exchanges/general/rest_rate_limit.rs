@@ -0,0 +1,178 @@
+use chrono::Utc;
+use log::error;
+use tokio::time::{Duration, Instant};
+
+use crate::core::exchanges::block_reasons;
+use crate::core::exchanges::common::RestRequestOutcome;
+use crate::core::exchanges::exchange_blocker::BlockType;
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+
+/// Block duration if exchange doesn't specify Retry-After
+const DEFAULT_BLOCK_DURATION: Duration = Duration::from_secs(10);
+const MAX_BLOCK_DURATION: Duration = Duration::from_secs(60 * 60);
+/// Ban is considered repeated if it happens earlier than this interval after the end of previous block
+const REPEATED_BAN_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Block duration doubles for each repeated ban, but it's never shorter than Retry-After
+fn block_duration(retry_after: Option<Duration>, repeated_bans_count: u32) -> Duration {
+    let base_duration = retry_after.unwrap_or(DEFAULT_BLOCK_DURATION);
+    let escalated_duration = base_duration
+        .checked_mul(2u32.saturating_pow(repeated_bans_count))
+        .unwrap_or(MAX_BLOCK_DURATION)
+        .min(MAX_BLOCK_DURATION);
+
+    escalated_duration.max(base_duration)
+}
+
+fn parse_retry_after(response: &RestRequestOutcome) -> Option<Duration> {
+    let retry_after = response
+        .headers
+        .get(hyper::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+
+    if let Ok(seconds) = retry_after.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let retry_time = chrono::DateTime::parse_from_rfc2822(retry_after).ok()?;
+    (retry_time.with_timezone(&Utc) - Utc::now()).to_std().ok()
+}
+
+#[derive(Default)]
+pub(super) struct RestRateLimitBans {
+    repeated_bans_count: u32,
+    last_block_end: Option<Instant>,
+}
+
+impl RestRateLimitBans {
+    /// Returns None if exchange is blocked already, because responses of requests sent before blocking shouldn't escalate it
+    fn next_block_duration(
+        &mut self,
+        retry_after: Option<Duration>,
+        now: Instant,
+    ) -> Option<Duration> {
+        if let Some(last_block_end) = self.last_block_end {
+            if now < last_block_end {
+                return None;
+            }
+
+            self.repeated_bans_count = match now < last_block_end + REPEATED_BAN_INTERVAL {
+                true => self.repeated_bans_count.saturating_add(1),
+                false => 0,
+            };
+        }
+
+        let duration = block_duration(retry_after, self.repeated_bans_count);
+        self.last_block_end = Some(now + duration);
+
+        Some(duration)
+    }
+}
+
+impl Exchange {
+    /// REST requests wait until the end of rate limit ban, because requests during the ban prolong it.
+    /// Waiting is finished earlier if `cancellation_token` is cancelled
+    pub(crate) async fn wait_rest_rate_limit_unblock(&self, cancellation_token: CancellationToken) {
+        if let Some(exchange_blocker) = &self.exchange_blocker {
+            exchange_blocker
+                .wait_unblock_with_reason(
+                    self.exchange_account_id.clone(),
+                    block_reasons::REST_RATE_LIMIT,
+                    cancellation_token,
+                )
+                .await;
+        }
+    }
+
+    /// Block exchange for duration requested in response. Repeated bans escalate block duration
+    pub(super) fn block_on_rest_rate_limit(&self, response: &RestRequestOutcome) {
+        let exchange_blocker = match &self.exchange_blocker {
+            Some(exchange_blocker) => exchange_blocker,
+            None => return,
+        };
+
+        let retry_after = parse_retry_after(response);
+        let block_duration = match self
+            .rest_rate_limit_bans
+            .lock()
+            .next_block_duration(retry_after, Instant::now())
+        {
+            Some(block_duration) => block_duration,
+            None => return,
+        };
+
+        error!(
+            "REST rate limit is exceeded on {}, Retry-After {:?}. Exchange is blocked for {:?}",
+            self.exchange_account_id, retry_after, block_duration
+        );
+
+        exchange_blocker.block(
+            &self.exchange_account_id,
+            block_reasons::REST_RATE_LIMIT,
+            BlockType::Timed(block_duration),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use awc::http::StatusCode;
+
+    #[test]
+    fn retry_after_in_seconds() {
+        let mut response = RestRequestOutcome::new("".to_owned(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(parse_retry_after(&response), None);
+
+        let _ = response
+            .headers
+            .insert(hyper::header::RETRY_AFTER, "120".parse().expect("in test"));
+        assert_eq!(parse_retry_after(&response), Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn repeated_bans_escalate_block_duration() {
+        let mut bans = RestRateLimitBans::default();
+        let now = Instant::now();
+        let retry_after = Some(Duration::from_secs(30));
+
+        assert_eq!(
+            bans.next_block_duration(retry_after, now),
+            Some(Duration::from_secs(30))
+        );
+        // Responses of requests which were in flight during blocking are ignored
+        assert_eq!(
+            bans.next_block_duration(retry_after, now + Duration::from_secs(1)),
+            None
+        );
+
+        let now = now + Duration::from_secs(31);
+        assert_eq!(
+            bans.next_block_duration(retry_after, now),
+            Some(Duration::from_secs(60))
+        );
+        let now = now + Duration::from_secs(61);
+        assert_eq!(
+            bans.next_block_duration(None, now),
+            Some(Duration::from_secs(40))
+        );
+
+        let now = now + Duration::from_secs(40) + REPEATED_BAN_INTERVAL;
+        assert_eq!(
+            bans.next_block_duration(retry_after, now),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn block_duration_is_limited() {
+        assert_eq!(block_duration(None, 100), MAX_BLOCK_DURATION);
+        assert_eq!(
+            block_duration(Some(MAX_BLOCK_DURATION * 2), 1),
+            MAX_BLOCK_DURATION * 2
+        );
+    }
+}
@@ -91,6 +91,18 @@ pub(crate) fn get_test_exchange_with_currency_pair_metadata(
 pub(crate) fn get_test_exchange_with_currency_pair_metadata_and_id(
     currency_pair_metadata: Arc<CurrencyPairMetadata>,
     exchange_account_id: &ExchangeAccountId,
+) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
+    get_test_exchange_with_order_features(
+        currency_pair_metadata,
+        exchange_account_id,
+        OrderFeatures::default(),
+    )
+}
+
+pub(crate) fn get_test_exchange_with_order_features(
+    currency_pair_metadata: Arc<CurrencyPairMetadata>,
+    exchange_account_id: &ExchangeAccountId,
+    order_features: OrderFeatures,
 ) -> (Arc<Exchange>, broadcast::Receiver<ExchangeEvent>) {
     let settings = settings::ExchangeSettings::new_short(
         exchange_account_id.clone(),
@@ -121,7 +133,7 @@ pub(crate) fn get_test_exchange_with_currency_pair_metadata_and_id(
         ExchangeFeatures::new(
             OpenOrdersType::AllCurrencyPair,
             RestFillsFeatures::default(),
-            OrderFeatures::default(),
+            order_features,
             OrderTradeOption::default(),
             WebSocketOptions::default(),
             false,
@@ -134,6 +146,8 @@ pub(crate) fn get_test_exchange_with_currency_pair_metadata_and_id(
         application_manager,
         TimeoutManager::new(HashMap::new()),
         commission,
+        None,
+        None,
     );
 
     exchange
@@ -0,0 +1,215 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use futures::FutureExt;
+use itertools::Itertools;
+use log::{info, warn};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{timeout, Duration};
+
+use crate::core::exchanges::block_reasons;
+use crate::core::exchanges::common::{CurrencyPair, ExchangeAccountId};
+use crate::core::exchanges::events::ExchangeEvent;
+use crate::core::exchanges::exchange_blocker::BlockType;
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::infrastructure::spawn_future;
+use crate::core::order_book::event::EventType;
+
+/// Order book snapshots are requested again if they aren't received for this time after reconnection
+const DEFAULT_ORDER_BOOK_SNAPSHOTS_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Wait until order book snapshots for all currency pairs are received, currency pairs with received snapshots are removed.
+/// Returns false if events channel is closed
+async fn wait_order_book_snapshots(
+    exchange_account_id: &ExchangeAccountId,
+    currency_pairs: &mut HashSet<CurrencyPair>,
+    events_receiver: &mut broadcast::Receiver<ExchangeEvent>,
+) -> bool {
+    while !currency_pairs.is_empty() {
+        match events_receiver.recv().await {
+            Ok(ExchangeEvent::OrderBookEvent(order_book_event)) => {
+                if order_book_event.exchange_account_id == *exchange_account_id
+                    && matches!(order_book_event.event_type(), EventType::Snapshot)
+                {
+                    let _ = currency_pairs.remove(&order_book_event.currency_pair);
+                }
+            }
+            Ok(_) | Err(RecvError::Lagged(_)) => {}
+            Err(RecvError::Closed) => return false,
+        }
+    }
+
+    true
+}
+
+impl Exchange {
+    pub fn is_websocket_reconnecting(&self) -> bool {
+        self.connectivity_manager.is_reconnecting()
+    }
+
+    /// Trading is blocked since reconnection start because orders and order books aren't tracked without websockets
+    pub(super) fn on_reconnecting(&self) {
+        if let Some(exchange_blocker) = &self.exchange_blocker {
+            warn!(
+                "Exchange {} is blocked while websockets are reconnecting",
+                self.exchange_account_id
+            );
+            exchange_blocker.block(
+                &self.exchange_account_id,
+                block_reasons::CONNECTIVITY_MANAGER_RECONNECT,
+                BlockType::Manual,
+            );
+
+            // Subscription before reconnection, so snapshots received over new connections aren't missed
+            *self.reconnection_events_receiver.lock() = Some(self.events_channel.subscribe());
+        }
+    }
+
+    /// Unblock exchange after order book snapshots for all traded currency pairs are received over new connections.
+    /// Snapshots which aren't received in time are requested again and exchange stays blocked until they are received
+    pub(super) fn on_reconnected(self: Arc<Self>) {
+        let exchange_blocker = match &self.exchange_blocker {
+            Some(exchange_blocker) => exchange_blocker.clone(),
+            None => return,
+        };
+
+        let settings = self.exchange_client.get_settings();
+        let mut currency_pairs: HashSet<_> = match settings.subscribe_to_market_data {
+            true => self.symbols.iter().map(|x| x.key().clone()).collect(),
+            false => HashSet::new(),
+        };
+        let snapshots_timeout = settings
+            .order_book_snapshots_timeout_secs
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_ORDER_BOOK_SNAPSHOTS_TIMEOUT);
+        let mut events_receiver = self
+            .reconnection_events_receiver
+            .lock()
+            .take()
+            .unwrap_or_else(|| self.events_channel.subscribe());
+        let exchange_account_id = self.exchange_account_id.clone();
+        let cancellation_token = self.application_manager.stop_token();
+        let exchange_weak = Arc::downgrade(&self);
+
+        let action = async move {
+            loop {
+                let snapshots_received = tokio::select! {
+                    snapshots_received = timeout(
+                        snapshots_timeout,
+                        wait_order_book_snapshots(&exchange_account_id, &mut currency_pairs, &mut events_receiver),
+                    ) => snapshots_received,
+                    _ = cancellation_token.when_cancelled() => return Ok(()),
+                };
+
+                match snapshots_received {
+                    Ok(true) => break,
+                    Ok(false) => return Ok(()),
+                    Err(_) => {
+                        warn!(
+                            "Order book snapshots for {} aren't received on {} in {:?} after reconnection, so they are requested again",
+                            currency_pairs.iter().join(", "),
+                            exchange_account_id,
+                            snapshots_timeout
+                        );
+
+                        let exchange = match exchange_weak.upgrade() {
+                            Some(exchange) => exchange,
+                            None => return Ok(()),
+                        };
+                        for currency_pair in &currency_pairs {
+                            exchange.request_order_book_resync(currency_pair);
+                        }
+                    }
+                }
+            }
+
+            info!(
+                "Order book snapshots are received on {} after reconnection",
+                exchange_account_id
+            );
+            exchange_blocker.unblock(
+                &exchange_account_id,
+                block_reasons::CONNECTIVITY_MANAGER_RECONNECT,
+            );
+
+            Ok(())
+        };
+
+        spawn_future(
+            "Unblock exchange after websockets reconnection",
+            false,
+            action.boxed(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::order_book::event::OrderBookEvent;
+    use crate::core::order_book::order_book_data::OrderBookData;
+    use chrono::Utc;
+
+    fn order_book_event(
+        exchange_account_id: &ExchangeAccountId,
+        currency_pair: &CurrencyPair,
+        event_type: EventType,
+    ) -> ExchangeEvent {
+        ExchangeEvent::OrderBookEvent(OrderBookEvent::new(
+            Utc::now(),
+            exchange_account_id.clone(),
+            currency_pair.clone(),
+            "".to_owned(),
+            event_type,
+            OrderBookData::new(Default::default(), Default::default()),
+        ))
+    }
+
+    #[tokio::test]
+    async fn wait_snapshots_for_all_currency_pairs() {
+        let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+        let other_exchange_account_id: ExchangeAccountId = "Binance1".parse().expect("in test");
+        let eth_btc = CurrencyPair::from_codes(&"eth".into(), &"btc".into());
+        let ltc_btc = CurrencyPair::from_codes(&"ltc".into(), &"btc".into());
+
+        let (events_sender, events_receiver) = broadcast::channel(10);
+        let send = |exchange_account_id, currency_pair, event_type| {
+            let _ = events_sender
+                .send(order_book_event(
+                    exchange_account_id,
+                    currency_pair,
+                    event_type,
+                ))
+                .expect("in test");
+        };
+        send(&exchange_account_id, &eth_btc, EventType::Snapshot);
+        send(&exchange_account_id, &ltc_btc, EventType::Update);
+        send(&other_exchange_account_id, &ltc_btc, EventType::Snapshot);
+
+        let mut currency_pairs = vec![eth_btc.clone(), ltc_btc.clone()].into_iter().collect();
+        let mut events_receiver = events_receiver;
+        {
+            let waiting = wait_order_book_snapshots(
+                &exchange_account_id,
+                &mut currency_pairs,
+                &mut events_receiver,
+            );
+            futures::pin_mut!(waiting);
+            assert!(futures::poll!(waiting.as_mut()).is_pending());
+        }
+        // Currency pairs without snapshots are kept for next waiting
+        assert_eq!(currency_pairs, vec![ltc_btc.clone()].into_iter().collect());
+
+        send(&exchange_account_id, &ltc_btc, EventType::Snapshot);
+        assert!(
+            wait_order_book_snapshots(
+                &exchange_account_id,
+                &mut currency_pairs,
+                &mut events_receiver
+            )
+            .await
+        );
+        assert!(currency_pairs.is_empty());
+    }
+}
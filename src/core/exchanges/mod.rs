@@ -4,6 +4,8 @@ pub mod common;
 pub mod events;
 pub mod exchange_blocker;
 pub mod general;
+pub mod paper;
+pub mod recording;
 pub mod rest_client;
 pub mod timeouts;
 pub mod traits;
@@ -0,0 +1,260 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::time::Duration;
+
+use super::order_matcher::unknown_order_error;
+use super::paper_exchange::PaperExchange;
+use crate::core::exchanges::general::currency_pair_metadata::CurrencyPairMetadata;
+use crate::core::exchanges::traits::ExchangeClient;
+use crate::core::orders::fill::EventSourceType;
+use crate::core::orders::order::*;
+use crate::core::DateTime;
+use crate::core::{
+    exchanges::common::{CurrencyPair, RestRequestOutcome},
+    orders::pool::OrderRef,
+};
+
+#[async_trait]
+impl ExchangeClient for PaperExchange {
+    async fn request_metadata(&self) -> Result<RestRequestOutcome> {
+        self.market_data_client.request_metadata().await
+    }
+
+    async fn create_order(&self, order: &OrderCreating) -> Result<RestRequestOutcome> {
+        self.simulate_latency().await;
+
+        let add_order_outcome = {
+            let mut order_matcher = self.order_matcher.lock();
+            self.check_balance(&order_matcher, order)
+                .and_then(|_| order_matcher.add_order(order))
+        };
+        match add_order_outcome {
+            Ok(exchange_order_id) => {
+                self.order_created_callback.lock()(
+                    order.header.client_order_id.clone(),
+                    exchange_order_id.clone(),
+                    EventSourceType::WebSocket,
+                );
+
+                Ok(Self::success_outcome(
+                    json!({ "orderId": exchange_order_id.as_str() }),
+                ))
+            }
+            Err(error) => Ok(Self::error_outcome(&error)),
+        }
+    }
+
+    async fn request_cancel_order(&self, order: &OrderCancelling) -> Result<RestRequestOutcome> {
+        self.simulate_latency().await;
+
+        let cancel_order_outcome = self
+            .order_matcher
+            .lock()
+            .cancel_order(&order.header.client_order_id);
+        match cancel_order_outcome {
+            Ok(exchange_order_id) => {
+                self.order_cancelled_callback.lock()(
+                    order.header.client_order_id.clone(),
+                    exchange_order_id.clone(),
+                    EventSourceType::WebSocket,
+                );
+
+                Ok(Self::success_outcome(
+                    json!({ "orderId": exchange_order_id.as_str() }),
+                ))
+            }
+            Err(error) => Ok(Self::error_outcome(&error)),
+        }
+    }
+
+    async fn cancel_all_orders(&self, currency_pair: CurrencyPair) -> Result<()> {
+        self.simulate_latency().await;
+
+        let open_orders = self.order_matcher.lock().open_orders(Some(&currency_pair));
+        for order in open_orders {
+            let cancel_order_outcome = self
+                .order_matcher
+                .lock()
+                .cancel_order(&order.client_order_id);
+            if let Ok(exchange_order_id) = cancel_order_outcome {
+                self.order_cancelled_callback.lock()(
+                    order.client_order_id,
+                    exchange_order_id,
+                    EventSourceType::WebSocket,
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn request_cancel_all_orders_countdown(
+        &self,
+        _currency_pair: CurrencyPair,
+        _countdown: Duration,
+    ) -> Result<RestRequestOutcome> {
+        Err(anyhow!(
+            "Countdown cancellation of all orders isn't supported by paper exchange"
+        ))
+    }
+
+    async fn request_open_orders(&self) -> Result<RestRequestOutcome> {
+        let open_orders = self.order_matcher.lock().open_orders(None);
+        Ok(Self::success_outcome(serde_json::to_value(open_orders)?))
+    }
+
+    async fn request_open_orders_by_currency_pair(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> Result<RestRequestOutcome> {
+        let open_orders = self.order_matcher.lock().open_orders(Some(&currency_pair));
+        Ok(Self::success_outcome(serde_json::to_value(open_orders)?))
+    }
+
+    async fn request_order_info(&self, order: &OrderRef) -> Result<RestRequestOutcome> {
+        let order_info = self
+            .order_matcher
+            .lock()
+            .order_info(&order.client_order_id());
+        match order_info {
+            Some(order_info) => Ok(Self::success_outcome(serde_json::to_value(order_info)?)),
+            None => Ok(Self::error_outcome(&unknown_order_error())),
+        }
+    }
+
+    async fn request_my_trades(
+        &self,
+        _currency_pair_metadata: &CurrencyPairMetadata,
+        _last_date_time: Option<DateTime>,
+    ) -> Result<RestRequestOutcome> {
+        // Simulated fills are delivered only through handle_order_filled_callback
+        Ok(Self::success_outcome(json!([])))
+    }
+
+    async fn request_balance(&self) -> Result<RestRequestOutcome> {
+        let balances = self.balances.lock().clone();
+        Ok(Self::success_outcome(serde_json::to_value(balances)?))
+    }
+
+    async fn request_order_book_snapshot(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> Result<RestRequestOutcome> {
+        self.market_data_client
+            .request_order_book_snapshot(currency_pair)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exchanges::binance::test_helper::{
+        get_binance_with_synced_order_book, get_test_currency_pair, get_test_exchange_settings,
+    };
+    use crate::core::exchanges::common::ExchangeErrorType;
+    use crate::core::exchanges::general::currency_pair_metadata::Precision;
+    use crate::core::exchanges::paper::paper_exchange::PaperExchangeSettings;
+    use crate::core::exchanges::traits::Support;
+    use crate::core::lifecycle::application_manager::ApplicationManager;
+    use crate::core::lifecycle::cancellation_token::CancellationToken;
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+    use tokio::sync::broadcast;
+
+    #[tokio::test]
+    async fn order_book_resync_is_forwarded_to_market_data_client() {
+        let (tx, _) = broadcast::channel(10);
+        let application_manager = ApplicationManager::new(CancellationToken::default());
+        let (binance, is_snapshot_requested) =
+            get_binance_with_synced_order_book(tx.clone(), application_manager.clone());
+
+        let settings = get_test_exchange_settings();
+        let exchange_client = PaperExchange::new(
+            settings.exchange_account_id.clone(),
+            settings,
+            PaperExchangeSettings::default(),
+            Box::new(binance),
+            tx,
+            application_manager,
+        );
+
+        exchange_client.request_order_book_resync(&get_test_currency_pair());
+        assert!(is_snapshot_requested());
+    }
+
+    #[tokio::test]
+    async fn order_is_rejected_if_balance_is_not_enough() {
+        let (tx, _) = broadcast::channel(10);
+        let application_manager = ApplicationManager::new(CancellationToken::default());
+        let (binance, _) = get_binance_with_synced_order_book(tx, application_manager);
+
+        let settings = get_test_exchange_settings();
+        let paper_settings = PaperExchangeSettings {
+            balances: vec![("btc".into(), dec!(3))].into_iter().collect(),
+            ..PaperExchangeSettings::default()
+        };
+        let (exchange_client, _) = PaperExchange::with_market_data_handler(
+            settings.exchange_account_id.clone(),
+            settings.clone(),
+            paper_settings,
+            Box::new(binance),
+        );
+        exchange_client.add_symbol(Arc::new(CurrencyPairMetadata::new(
+            true,
+            false,
+            "eth".into(),
+            "eth".into(),
+            "btc".into(),
+            "btc".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            "eth".into(),
+            None,
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(1) },
+        )));
+
+        let buy_order = |client_order_id: &str| OrderCreating {
+            header: OrderHeader::new(
+                client_order_id.into(),
+                chrono::Utc::now(),
+                settings.exchange_account_id.clone(),
+                get_test_currency_pair(),
+                OrderType::Limit,
+                OrderSide::Buy,
+                dec!(2),
+                OrderExecutionType::None,
+                None,
+                None,
+                "test".to_owned(),
+            ),
+            price: dec!(1),
+        };
+
+        let outcome = exchange_client
+            .create_order(&buy_order("first"))
+            .await
+            .expect("in test");
+        assert!(exchange_client.is_rest_error_code(&outcome).is_ok());
+
+        // Only 1 btc isn't used by the first order
+        let outcome = exchange_client
+            .create_order(&buy_order("second"))
+            .await
+            .expect("in test");
+        let mut error = exchange_client
+            .is_rest_error_code(&outcome)
+            .expect_err("in test");
+        exchange_client.clarify_error_type(&mut error);
+        assert_eq!(error.error_type, ExchangeErrorType::InsufficientFunds);
+        assert_eq!(
+            exchange_client.order_matcher.lock().open_orders(None).len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod exchange_client;
+pub mod order_matcher;
+pub mod paper_exchange;
+pub mod support;
@@ -0,0 +1,824 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+
+use crate::core::exchanges::common::{
+    Amount, CurrencyPair, ExchangeAccountId, ExchangeError, ExchangeErrorType, Price, TradePlace,
+};
+use crate::core::exchanges::events::TradesEvent;
+use crate::core::order_book::event::{EventType, OrderBookEvent};
+use crate::core::order_book::local_order_book_snapshot::LocalOrderBookSnapshot;
+use crate::core::order_book::local_snapshot_service::LocalSnapshotsService;
+use crate::core::orders::order::{
+    ClientOrderId, ExchangeOrderId, OrderCreating, OrderExecutionType, OrderHeader, OrderInfo,
+    OrderRole, OrderSide, OrderStatus, OrderType,
+};
+
+pub const UNKNOWN_ORDER_CODE: i64 = -2011;
+pub const UNKNOWN_ORDER_MSG: &str = "Unknown order sent.";
+pub const WOULD_TAKE_CODE: i64 = -2010;
+pub const WOULD_TAKE_MSG: &str = "Order would immediately match and take.";
+pub const DUPLICATE_ORDER_CODE: i64 = -2012;
+pub const DUPLICATE_ORDER_MSG: &str = "Duplicate order sent.";
+pub const INSUFFICIENT_FUNDS_CODE: i64 = -2019;
+pub const INSUFFICIENT_FUNDS_MSG: &str = "Account has insufficient balance for requested action.";
+
+pub fn unknown_order_error() -> ExchangeError {
+    ExchangeError::new(
+        ExchangeErrorType::OrderNotFound,
+        UNKNOWN_ORDER_MSG.to_owned(),
+        Some(UNKNOWN_ORDER_CODE),
+    )
+}
+
+/// Order placed on the paper exchange
+#[derive(Debug, Clone)]
+struct PaperOrder {
+    header: Arc<OrderHeader>,
+    exchange_order_id: ExchangeOrderId,
+    price: Price,
+    // Role is fixed at the moment of order placement:
+    // order crossing the book at that moment is taker for all its fills
+    role: OrderRole,
+    status: OrderStatus,
+    filled_amount: Amount,
+    filled_cost: Decimal,
+}
+
+impl PaperOrder {
+    fn remaining_amount(&self) -> Amount {
+        self.header.amount - self.filled_amount
+    }
+
+    fn is_open(&self) -> bool {
+        self.status == OrderStatus::Created
+    }
+
+    fn is_matched_by(&self, level_price: Price) -> bool {
+        if self.header.order_type == OrderType::Market {
+            return true;
+        }
+
+        match self.header.side {
+            OrderSide::Buy => level_price <= self.price,
+            OrderSide::Sell => level_price >= self.price,
+        }
+    }
+
+    fn to_order_info(&self) -> OrderInfo {
+        let average_fill_price = if self.filled_amount.is_zero() {
+            Decimal::ZERO
+        } else {
+            self.filled_cost / self.filled_amount
+        };
+
+        OrderInfo::new(
+            self.header.currency_pair.clone(),
+            self.exchange_order_id.clone(),
+            self.header.client_order_id.clone(),
+            self.header.side,
+            self.status,
+            self.price,
+            self.header.amount,
+            average_fill_price,
+            self.filled_amount,
+            None,
+            None,
+            None,
+        )
+    }
+}
+
+/// Price-time priority of paper order: orders with better price go first,
+/// orders with the same price are matched in order of their placement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct OrderPriority {
+    /// Price with the sign which makes better prices smaller for both sides
+    price_rank: Decimal,
+    sequence: u64,
+}
+
+impl OrderPriority {
+    fn new(header: &OrderHeader, price: Price, sequence: u64) -> Self {
+        let price_rank = match (header.order_type, header.side) {
+            (OrderType::Market, _) => Decimal::MIN,
+            (_, OrderSide::Buy) => -price,
+            (_, OrderSide::Sell) => price,
+        };
+
+        Self {
+            price_rank,
+            sequence,
+        }
+    }
+}
+
+/// Fill of paper order produced by matching it with the order book
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaperFill {
+    pub trade_id: String,
+    pub client_order_id: ClientOrderId,
+    pub exchange_order_id: ExchangeOrderId,
+    pub currency_pair: CurrencyPair,
+    pub side: OrderSide,
+    pub role: OrderRole,
+    pub price: Price,
+    pub amount: Amount,
+    pub total_filled_amount: Amount,
+}
+
+/// Market order which remaining amount expired because there was not enough liquidity in order book
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpiredOrder {
+    pub client_order_id: ClientOrderId,
+    pub exchange_order_id: ExchangeOrderId,
+}
+
+/// Matches orders placed on the paper exchange against order book snapshots
+/// Order book itself isn't changed by our orders, so liquidity consumed by them is tracked separately
+/// until the next snapshot or update of consumed price level
+pub struct OrderMatcher {
+    exchange_account_id: ExchangeAccountId,
+    local_snapshots_service: LocalSnapshotsService,
+    /// Amounts of price levels consumed by paper orders by book side (Buy - bids, Sell - asks)
+    consumed_amounts: HashMap<CurrencyPair, HashMap<(OrderSide, Price), Amount>>,
+    /// Open orders sorted by price-time priority, so scarce liquidity is split between them deterministically
+    orders: BTreeMap<OrderPriority, PaperOrder>,
+    order_priorities: HashMap<ClientOrderId, OrderPriority>,
+    /// Completed and canceled orders are kept only for order info requests
+    finished_orders: HashMap<ClientOrderId, PaperOrder>,
+    expired_orders: Vec<ExpiredOrder>,
+    last_order_id: u64,
+    last_trade_id: u64,
+}
+
+impl OrderMatcher {
+    pub fn new(exchange_account_id: ExchangeAccountId) -> Self {
+        Self {
+            exchange_account_id,
+            local_snapshots_service: LocalSnapshotsService::default(),
+            consumed_amounts: HashMap::new(),
+            orders: BTreeMap::new(),
+            order_priorities: HashMap::new(),
+            finished_orders: HashMap::new(),
+            expired_orders: Vec::new(),
+            last_order_id: 0,
+            last_trade_id: 0,
+        }
+    }
+
+    pub fn add_order(&mut self, order: &OrderCreating) -> Result<ExchangeOrderId, ExchangeError> {
+        let header = &order.header;
+        if self.order_priorities.contains_key(&header.client_order_id)
+            || self.finished_orders.contains_key(&header.client_order_id)
+        {
+            return Err(ExchangeError::new(
+                ExchangeErrorType::InvalidOrder,
+                DUPLICATE_ORDER_MSG.to_owned(),
+                Some(DUPLICATE_ORDER_CODE),
+            ));
+        }
+
+        let is_crossing_book = header.order_type == OrderType::Market
+            || match self.get_snapshot(&header.currency_pair) {
+                Some(snapshot) => Self::get_opposite_levels(snapshot, header.side)
+                    .first()
+                    .is_some_and(|(top_price, _)| match header.side {
+                        OrderSide::Buy => *top_price <= order.price,
+                        OrderSide::Sell => *top_price >= order.price,
+                    }),
+                None => false,
+            };
+
+        if is_crossing_book && header.execution_type == OrderExecutionType::MakerOnly {
+            return Err(ExchangeError::new(
+                ExchangeErrorType::InvalidOrder,
+                WOULD_TAKE_MSG.to_owned(),
+                Some(WOULD_TAKE_CODE),
+            ));
+        }
+
+        self.last_order_id += 1;
+        let exchange_order_id: ExchangeOrderId = self.last_order_id.to_string().as_str().into();
+
+        let paper_order = PaperOrder {
+            header: header.clone(),
+            exchange_order_id: exchange_order_id.clone(),
+            price: order.price,
+            role: match is_crossing_book {
+                true => OrderRole::Taker,
+                false => OrderRole::Maker,
+            },
+            status: OrderStatus::Created,
+            filled_amount: Decimal::ZERO,
+            filled_cost: Decimal::ZERO,
+        };
+        let priority = OrderPriority::new(header, order.price, self.last_order_id);
+        let _ = self
+            .order_priorities
+            .insert(header.client_order_id.clone(), priority);
+        let _ = self.orders.insert(priority, paper_order);
+
+        Ok(exchange_order_id)
+    }
+
+    pub fn cancel_order(
+        &mut self,
+        client_order_id: &ClientOrderId,
+    ) -> Result<ExchangeOrderId, ExchangeError> {
+        let mut order = self
+            .order_priorities
+            .remove(client_order_id)
+            .and_then(|priority| self.orders.remove(&priority))
+            .ok_or_else(unknown_order_error)?;
+
+        order.status = OrderStatus::Canceled;
+        let exchange_order_id = order.exchange_order_id.clone();
+        let _ = self.finished_orders.insert(client_order_id.clone(), order);
+
+        Ok(exchange_order_id)
+    }
+
+    /// Apply order book event and match open orders with the updated snapshot
+    pub fn handle_order_book_event(&mut self, event: OrderBookEvent) -> Vec<PaperFill> {
+        if event.exchange_account_id != self.exchange_account_id {
+            return Vec::new();
+        }
+
+        let currency_pair = event.currency_pair.clone();
+        self.reset_consumed_amounts(&event);
+        if self.local_snapshots_service.update(event).is_none() {
+            return Vec::new();
+        }
+
+        let fills = self.match_orders(&currency_pair);
+        self.remove_finished_orders();
+        fills
+    }
+
+    /// Match resting orders with trades of other market participants.
+    /// Side of the trade is the taker side, so only orders on the opposite side are filled by it
+    pub fn handle_trades_event(&mut self, event: &TradesEvent) -> Vec<PaperFill> {
+        if event.exchange_account_id != self.exchange_account_id {
+            return Vec::new();
+        }
+
+        let mut fills = Vec::new();
+        for trade in &event.trades {
+            let mut trade_remaining_amount = trade.quantity;
+
+            let matched_orders = self.orders.values_mut().filter(|order| {
+                order.is_open()
+                    && order.header.currency_pair == event.currency_pair
+                    && order.header.side != trade.side
+                    && order.is_matched_by(trade.price)
+            });
+
+            for order in matched_orders {
+                if trade_remaining_amount.is_zero() {
+                    break;
+                }
+
+                let fill_amount = order.remaining_amount().min(trade_remaining_amount);
+                trade_remaining_amount -= fill_amount;
+
+                self.last_trade_id += 1;
+                let fill_price = order.price;
+                fills.push(Self::fill_order(
+                    order,
+                    self.last_trade_id,
+                    fill_price,
+                    fill_amount,
+                ));
+            }
+        }
+
+        self.remove_finished_orders();
+        fills
+    }
+
+    /// Market orders expired since the previous call
+    pub fn take_expired_orders(&mut self) -> Vec<ExpiredOrder> {
+        std::mem::take(&mut self.expired_orders)
+    }
+
+    pub fn open_orders(&self, currency_pair: Option<&CurrencyPair>) -> Vec<OrderInfo> {
+        self.orders
+            .values()
+            .filter(|order| order.is_open())
+            .filter(|order| currency_pair.is_none_or(|x| &order.header.currency_pair == x))
+            .map(|order| order.to_order_info())
+            .collect()
+    }
+
+    pub fn order_info(&self, client_order_id: &ClientOrderId) -> Option<OrderInfo> {
+        self.order_priorities
+            .get(client_order_id)
+            .and_then(|priority| self.orders.get(priority))
+            .or_else(|| self.finished_orders.get(client_order_id))
+            .map(|order| order.to_order_info())
+    }
+
+    fn remove_finished_orders(&mut self) {
+        let finished_priorities = self
+            .orders
+            .iter()
+            .filter(|(_, order)| !order.is_open())
+            .map(|(priority, _)| *priority)
+            .collect::<Vec<_>>();
+
+        for priority in finished_priorities {
+            if let Some(order) = self.orders.remove(&priority) {
+                let client_order_id = order.header.client_order_id.clone();
+                let _ = self.order_priorities.remove(&client_order_id);
+                let _ = self.finished_orders.insert(client_order_id, order);
+            }
+        }
+    }
+
+    fn get_snapshot(&self, currency_pair: &CurrencyPair) -> Option<&LocalOrderBookSnapshot> {
+        let trade_place = TradePlace::new(
+            self.exchange_account_id.exchange_id.clone(),
+            currency_pair.clone(),
+        );
+        self.local_snapshots_service.get_snapshot(&trade_place)
+    }
+
+    /// Price levels which can be taken by an order with specified side starting from the best one
+    fn get_opposite_levels(
+        snapshot: &LocalOrderBookSnapshot,
+        side: OrderSide,
+    ) -> Vec<(Price, Amount)> {
+        match side {
+            OrderSide::Buy => snapshot
+                .get_asks_price_levels()
+                .map(|(price, amount)| (*price, *amount))
+                .collect(),
+            OrderSide::Sell => snapshot
+                .get_bids_price_levels()
+                .map(|(price, amount)| (*price, *amount))
+                .collect(),
+        }
+    }
+
+    /// New amounts of price levels from order book event already include all trades,
+    /// so liquidity consumed by paper orders is available again
+    fn reset_consumed_amounts(&mut self, event: &OrderBookEvent) {
+        let consumed_amounts = match self.consumed_amounts.get_mut(&event.currency_pair) {
+            Some(consumed_amounts) => consumed_amounts,
+            None => return,
+        };
+
+        match event.event_type() {
+            EventType::Snapshot => consumed_amounts.clear(),
+            EventType::Update => {
+                let data = event.data();
+                for price in data.asks.keys() {
+                    let _ = consumed_amounts.remove(&(OrderSide::Sell, *price));
+                }
+                for price in data.bids.keys() {
+                    let _ = consumed_amounts.remove(&(OrderSide::Buy, *price));
+                }
+            }
+        }
+    }
+
+    /// Price levels with amounts which aren't consumed by paper orders yet
+    fn get_available_levels(
+        &self,
+        snapshot: &LocalOrderBookSnapshot,
+        currency_pair: &CurrencyPair,
+        side: OrderSide,
+    ) -> Vec<(Price, Amount)> {
+        let book_side = side.change_side();
+        let consumed_amounts = self.consumed_amounts.get(currency_pair);
+        Self::get_opposite_levels(snapshot, side)
+            .into_iter()
+            .map(|(price, amount)| {
+                let consumed_amount = consumed_amounts
+                    .and_then(|x| x.get(&(book_side, price)))
+                    .cloned()
+                    .unwrap_or_default();
+                (price, (amount - consumed_amount).max(Decimal::ZERO))
+            })
+            .filter(|(_, amount)| !amount.is_zero())
+            .collect()
+    }
+
+    fn match_orders(&mut self, currency_pair: &CurrencyPair) -> Vec<PaperFill> {
+        let (mut asks, mut bids) = match self.get_snapshot(currency_pair) {
+            Some(snapshot) => (
+                self.get_available_levels(snapshot, currency_pair, OrderSide::Buy),
+                self.get_available_levels(snapshot, currency_pair, OrderSide::Sell),
+            ),
+            None => return Vec::new(),
+        };
+
+        let consumed_amounts = self
+            .consumed_amounts
+            .entry(currency_pair.clone())
+            .or_default();
+        let mut fills = Vec::new();
+        let mut last_trade_id = self.last_trade_id;
+        let expired_orders = &mut self.expired_orders;
+        for order in self
+            .orders
+            .values_mut()
+            .filter(|order| order.is_open() && &order.header.currency_pair == currency_pair)
+        {
+            let levels = match order.header.side {
+                OrderSide::Buy => &mut asks,
+                OrderSide::Sell => &mut bids,
+            };
+            let book_side = order.header.side.change_side();
+
+            for (level_price, level_amount) in levels.iter_mut() {
+                let remaining_amount = order.remaining_amount();
+                if remaining_amount.is_zero() || !order.is_matched_by(*level_price) {
+                    break;
+                }
+
+                if level_amount.is_zero() {
+                    continue;
+                }
+
+                let fill_amount = remaining_amount.min(*level_amount);
+                *level_amount -= fill_amount;
+                *consumed_amounts
+                    .entry((book_side, *level_price))
+                    .or_default() += fill_amount;
+                let fill_price = match order.role {
+                    OrderRole::Maker => order.price,
+                    OrderRole::Taker => *level_price,
+                };
+
+                last_trade_id += 1;
+                fills.push(Self::fill_order(
+                    order,
+                    last_trade_id,
+                    fill_price,
+                    fill_amount,
+                ));
+            }
+
+            // Market order takes only liquidity available at the moment like IOC one
+            if order.header.order_type == OrderType::Market && order.is_open() {
+                order.status = OrderStatus::Canceled;
+                expired_orders.push(ExpiredOrder {
+                    client_order_id: order.header.client_order_id.clone(),
+                    exchange_order_id: order.exchange_order_id.clone(),
+                });
+            }
+        }
+        self.last_trade_id = last_trade_id;
+
+        fills
+    }
+
+    fn fill_order(
+        order: &mut PaperOrder,
+        trade_id: u64,
+        fill_price: Price,
+        fill_amount: Amount,
+    ) -> PaperFill {
+        order.filled_amount += fill_amount;
+        order.filled_cost += fill_amount * fill_price;
+        if order.remaining_amount().is_zero() {
+            order.status = OrderStatus::Completed;
+        }
+
+        PaperFill {
+            trade_id: trade_id.to_string(),
+            client_order_id: order.header.client_order_id.clone(),
+            exchange_order_id: order.exchange_order_id.clone(),
+            currency_pair: order.header.currency_pair.clone(),
+            side: order.header.side,
+            role: order.role,
+            price: fill_price,
+            amount: fill_amount,
+            total_filled_amount: order.filled_amount,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exchanges::events::{TickDirection, Trade, TradeId};
+    use crate::order_book_data;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+
+    fn exchange_account_id() -> ExchangeAccountId {
+        "PaperBinance0".parse().expect("in test")
+    }
+
+    fn currency_pair() -> CurrencyPair {
+        CurrencyPair::from_codes(&"phb".into(), &"btc".into())
+    }
+
+    fn order_creating(
+        client_order_id: &str,
+        side: OrderSide,
+        price: Price,
+        amount: Amount,
+        execution_type: OrderExecutionType,
+    ) -> OrderCreating {
+        let header = OrderHeader::new(
+            client_order_id.into(),
+            Utc::now(),
+            exchange_account_id(),
+            currency_pair(),
+            OrderType::Limit,
+            side,
+            amount,
+            execution_type,
+            None,
+            None,
+            "test".to_owned(),
+        );
+
+        OrderCreating { header, price }
+    }
+
+    fn snapshot_event(asks: &[(Price, Amount)], bids: &[(Price, Amount)]) -> OrderBookEvent {
+        let mut order_book_data = order_book_data!();
+        order_book_data.asks.extend(asks.iter().cloned());
+        order_book_data.bids.extend(bids.iter().cloned());
+
+        OrderBookEvent::new(
+            Utc::now(),
+            exchange_account_id(),
+            currency_pair(),
+            "".to_owned(),
+            EventType::Snapshot,
+            order_book_data,
+        )
+    }
+
+    #[test]
+    fn resting_order_filled_as_maker_when_book_crosses_its_price() {
+        let mut matcher = OrderMatcher::new(exchange_account_id());
+        let _ = matcher.handle_order_book_event(snapshot_event(
+            &[(dec!(1.2), dec!(5))],
+            &[(dec!(1.0), dec!(5))],
+        ));
+
+        let order = order_creating(
+            "buy",
+            OrderSide::Buy,
+            dec!(1.1),
+            dec!(3),
+            OrderExecutionType::None,
+        );
+        let exchange_order_id = matcher.add_order(&order).expect("in test");
+
+        let fills = matcher.handle_order_book_event(snapshot_event(
+            &[(dec!(1.05), dec!(2)), (dec!(1.1), dec!(2))],
+            &[(dec!(1.0), dec!(5))],
+        ));
+
+        assert_eq!(fills.len(), 2);
+        assert!(fills.iter().all(|x| x.role == OrderRole::Maker));
+        assert!(fills.iter().all(|x| x.price == dec!(1.1)));
+        assert!(fills
+            .iter()
+            .all(|x| x.exchange_order_id == exchange_order_id));
+        assert_eq!(fills[0].amount, dec!(2));
+        assert_eq!(fills[1].amount, dec!(1));
+        assert_eq!(fills[1].total_filled_amount, dec!(3));
+
+        let order_info = matcher
+            .order_info(&order.header.client_order_id)
+            .expect("in test");
+        assert_eq!(order_info.order_status, OrderStatus::Completed);
+        assert!(matcher.open_orders(None).is_empty());
+    }
+
+    #[test]
+    fn crossing_order_filled_as_taker_by_book_prices() {
+        let mut matcher = OrderMatcher::new(exchange_account_id());
+        let book = snapshot_event(&[(dec!(1.2), dec!(1)), (dec!(1.3), dec!(5))], &[]);
+        let _ = matcher.handle_order_book_event(book.clone());
+
+        let order = order_creating(
+            "buy",
+            OrderSide::Buy,
+            dec!(1.3),
+            dec!(2),
+            OrderExecutionType::None,
+        );
+        let _ = matcher.add_order(&order).expect("in test");
+
+        let fills = matcher.handle_order_book_event(book);
+
+        assert_eq!(fills.len(), 2);
+        assert!(fills.iter().all(|x| x.role == OrderRole::Taker));
+        assert_eq!((fills[0].price, fills[0].amount), (dec!(1.2), dec!(1)));
+        assert_eq!((fills[1].price, fills[1].amount), (dec!(1.3), dec!(1)));
+
+        let order_info = matcher
+            .order_info(&order.header.client_order_id)
+            .expect("in test");
+        assert_eq!(order_info.average_fill_price, dec!(1.25));
+    }
+
+    #[test]
+    fn crossing_orders_consume_level_amount_until_next_snapshot() {
+        let mut matcher = OrderMatcher::new(exchange_account_id());
+        let _ = matcher.handle_order_book_event(snapshot_event(&[(dec!(1.2), dec!(3))], &[]));
+
+        let first_order = order_creating(
+            "buy1",
+            OrderSide::Buy,
+            dec!(1.2),
+            dec!(2),
+            OrderExecutionType::None,
+        );
+        let second_order = order_creating(
+            "buy2",
+            OrderSide::Buy,
+            dec!(1.2),
+            dec!(2),
+            OrderExecutionType::None,
+        );
+        let _ = matcher.add_order(&first_order).expect("in test");
+        let _ = matcher.add_order(&second_order).expect("in test");
+
+        let fills = matcher.handle_order_book_event(snapshot_event(&[(dec!(1.2), dec!(3))], &[]));
+        assert_eq!(fills.iter().map(|x| x.amount).sum::<Amount>(), dec!(3));
+        assert_eq!(matcher.open_orders(None).len(), 1);
+
+        // Consumed liquidity isn't available for other order book events until the level is updated
+        let update = OrderBookEvent::new(
+            Utc::now(),
+            exchange_account_id(),
+            currency_pair(),
+            "".to_owned(),
+            EventType::Update,
+            order_book_data![dec!(1.3) => dec!(1), ;],
+        );
+        assert!(matcher.handle_order_book_event(update).is_empty());
+
+        let fills = matcher.handle_order_book_event(snapshot_event(&[(dec!(1.2), dec!(3))], &[]));
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].amount, dec!(1));
+        assert!(matcher.open_orders(None).is_empty());
+    }
+
+    #[test]
+    fn scarce_liquidity_is_matched_by_price_time_priority() {
+        let mut matcher = OrderMatcher::new(exchange_account_id());
+        for (client_order_id, price) in &[
+            ("first", dec!(1.1)),
+            ("second", dec!(1.1)),
+            ("best", dec!(1.2)),
+        ] {
+            let order = order_creating(
+                client_order_id,
+                OrderSide::Buy,
+                *price,
+                dec!(2),
+                OrderExecutionType::None,
+            );
+            let _ = matcher.add_order(&order).expect("in test");
+        }
+
+        let fills = matcher.handle_order_book_event(snapshot_event(&[(dec!(1.0), dec!(3))], &[]));
+
+        let filled_orders = fills
+            .iter()
+            .map(|x| (x.client_order_id.as_str().to_owned(), x.amount))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            filled_orders,
+            vec![("best".to_owned(), dec!(2)), ("first".to_owned(), dec!(1))]
+        );
+    }
+
+    #[test]
+    fn maker_only_order_rejected_if_crosses_book() {
+        let mut matcher = OrderMatcher::new(exchange_account_id());
+        let _ = matcher.handle_order_book_event(snapshot_event(&[], &[(dec!(1.0), dec!(5))]));
+
+        let order = order_creating(
+            "sell",
+            OrderSide::Sell,
+            dec!(0.9),
+            dec!(1),
+            OrderExecutionType::MakerOnly,
+        );
+        let error = matcher.add_order(&order).expect_err("in test");
+
+        assert_eq!(error.code, Some(WOULD_TAKE_CODE));
+        assert!(matcher.open_orders(None).is_empty());
+    }
+
+    #[test]
+    fn resting_order_filled_by_opposite_trades() {
+        let mut matcher = OrderMatcher::new(exchange_account_id());
+        let order = order_creating(
+            "sell",
+            OrderSide::Sell,
+            dec!(1.0),
+            dec!(3),
+            OrderExecutionType::MakerOnly,
+        );
+        let _ = matcher.add_order(&order).expect("in test");
+
+        let trade = |price, quantity, side| Trade {
+            trade_id: TradeId::Number(1),
+            price,
+            quantity,
+            side,
+            transaction_time: Utc::now(),
+            tick_direction: TickDirection::None,
+        };
+        let fills = matcher.handle_trades_event(&TradesEvent {
+            exchange_account_id: exchange_account_id(),
+            currency_pair: currency_pair(),
+            trades: vec![
+                // Taker sold, so our sell order can't be filled
+                trade(dec!(1.1), dec!(1), OrderSide::Sell),
+                // Price is worse than our order one
+                trade(dec!(0.9), dec!(1), OrderSide::Buy),
+                trade(dec!(1.1), dec!(2), OrderSide::Buy),
+                trade(dec!(1.0), dec!(5), OrderSide::Buy),
+            ],
+            receipt_time: Utc::now(),
+        });
+
+        assert_eq!(fills.len(), 2);
+        assert!(fills.iter().all(|x| x.role == OrderRole::Maker));
+        assert!(fills.iter().all(|x| x.price == dec!(1.0)));
+        assert_eq!(fills[0].amount, dec!(2));
+        assert_eq!(fills[1].amount, dec!(1));
+        assert!(matcher.open_orders(None).is_empty());
+    }
+
+    #[test]
+    fn unfilled_remainder_of_market_order_expires() {
+        let mut matcher = OrderMatcher::new(exchange_account_id());
+        let mut order = order_creating(
+            "buy",
+            OrderSide::Buy,
+            dec!(0),
+            dec!(3),
+            OrderExecutionType::None,
+        );
+        Arc::make_mut(&mut order.header).order_type = OrderType::Market;
+        let exchange_order_id = matcher.add_order(&order).expect("in test");
+
+        let fills = matcher.handle_order_book_event(snapshot_event(&[(dec!(1.2), dec!(2))], &[]));
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].amount, dec!(2));
+        assert_eq!(
+            matcher.take_expired_orders(),
+            vec![ExpiredOrder {
+                client_order_id: order.header.client_order_id.clone(),
+                exchange_order_id,
+            }]
+        );
+        assert!(matcher.open_orders(None).is_empty());
+
+        // Finished order isn't matched anymore, but its info is still available
+        let order_info = matcher
+            .order_info(&order.header.client_order_id)
+            .expect("in test");
+        assert_eq!(order_info.order_status, OrderStatus::Canceled);
+        assert_eq!(order_info.filled_amount, dec!(2));
+        assert!(matcher
+            .handle_order_book_event(snapshot_event(&[(dec!(1.2), dec!(2))], &[]))
+            .is_empty());
+        assert!(matcher.take_expired_orders().is_empty());
+    }
+
+    #[test]
+    fn canceled_order_is_not_matched() {
+        let mut matcher = OrderMatcher::new(exchange_account_id());
+        let order = order_creating(
+            "sell",
+            OrderSide::Sell,
+            dec!(1.0),
+            dec!(1),
+            OrderExecutionType::None,
+        );
+        let exchange_order_id = matcher.add_order(&order).expect("in test");
+        assert_eq!(matcher.open_orders(Some(&currency_pair())).len(), 1);
+
+        let canceled_order_id = matcher
+            .cancel_order(&order.header.client_order_id)
+            .expect("in test");
+        assert_eq!(canceled_order_id, exchange_order_id);
+
+        let fills = matcher.handle_order_book_event(snapshot_event(&[], &[(dec!(1.5), dec!(5))]));
+        assert!(fills.is_empty());
+
+        let error = matcher
+            .cancel_order(&order.header.client_order_id)
+            .expect_err("in test");
+        assert_eq!(error.error_type, ExchangeErrorType::OrderNotFound);
+    }
+}
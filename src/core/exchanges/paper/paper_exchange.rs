@@ -0,0 +1,549 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use awc::http::StatusCode;
+use dashmap::DashMap;
+use futures::FutureExt;
+use log::{error, info, warn};
+use parking_lot::Mutex;
+use rust_decimal_macros::dec;
+use serde_json::json;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+use super::order_matcher::{
+    ExpiredOrder, OrderMatcher, PaperFill, INSUFFICIENT_FUNDS_CODE, INSUFFICIENT_FUNDS_MSG,
+};
+use crate::core::exchanges::common::{
+    Amount, CurrencyCode, CurrencyPair, ExchangeAccountId, ExchangeError, ExchangeErrorType,
+    RestRequestOutcome,
+};
+use crate::core::exchanges::events::{AllowedEventSourceType, ExchangeEvent};
+use crate::core::exchanges::general::commission::{Commission, CommissionForType};
+use crate::core::exchanges::general::currency_pair_metadata::CurrencyPairMetadata;
+use crate::core::exchanges::general::exchange::BoxExchangeClient;
+use crate::core::exchanges::general::features::{
+    ExchangeFeatures, OpenOrdersType, OrderFeatures, OrderTradeOption, RestFillsFeatures,
+    RestFillsType, WebSocketOptions,
+};
+use crate::core::exchanges::general::handlers::handle_order_filled::FillEventData;
+use crate::core::exchanges::timeouts::requests_timeout_manager_factory::RequestTimeoutArguments;
+use crate::core::exchanges::traits::{ExchangeClientBuilder, ExchangeClientBuilderResult};
+use crate::core::infrastructure::spawn_future;
+use crate::core::lifecycle::application_manager::ApplicationManager;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::math::ConvertPercentToRate;
+use crate::core::orders::fill::{EventSourceType, OrderFillType};
+use crate::core::orders::order::{ClientOrderId, ExchangeOrderId, OrderCreating, OrderSide};
+use crate::core::settings::{ExchangeSettings, PaperTradingSettings};
+
+pub type OrderEventCallback =
+    Box<dyn FnMut(ClientOrderId, ExchangeOrderId, EventSourceType) + Send + Sync>;
+pub type OrderFilledCallback = Box<dyn FnMut(FillEventData) + Send + Sync>;
+
+#[derive(Debug, Clone)]
+pub struct PaperExchangeSettings {
+    /// Fees applied to simulated fills
+    pub commission: Commission,
+    /// Delay before order creation or cancellation takes effect
+    pub latency: Duration,
+    /// Initial balances of the simulated account
+    pub balances: HashMap<CurrencyCode, Amount>,
+}
+
+impl Default for PaperExchangeSettings {
+    fn default() -> Self {
+        // Binance spot fees for regular users
+        let fee = CommissionForType::new(dec!(0.1), dec!(0));
+
+        Self {
+            commission: Commission::new(fee.clone(), fee),
+            latency: Duration::from_millis(0),
+            balances: HashMap::new(),
+        }
+    }
+}
+
+impl From<&PaperTradingSettings> for PaperExchangeSettings {
+    fn from(settings: &PaperTradingSettings) -> Self {
+        Self {
+            commission: Commission::new(
+                CommissionForType::new(settings.maker_fee, dec!(0)),
+                CommissionForType::new(settings.taker_fee, dec!(0)),
+            ),
+            latency: Duration::from_millis(settings.latency_ms),
+            balances: settings.balances.clone(),
+        }
+    }
+}
+
+/// Exchange client which doesn't send orders to the venue but matches them internally.
+/// Metadata and market data are taken from the wrapped client of the real exchange.
+/// Orders are matched against order book events from the events channel,
+/// so the events can be either live ones or replayed from records
+pub struct PaperExchange {
+    pub id: ExchangeAccountId,
+    pub settings: ExchangeSettings,
+    pub paper_settings: PaperExchangeSettings,
+
+    pub(super) market_data_client: BoxExchangeClient,
+    pub(super) order_matcher: Arc<Mutex<OrderMatcher>>,
+    /// Balances of the simulated account which are changed by fills starting from configured ones
+    pub(super) balances: Arc<Mutex<HashMap<CurrencyCode, Amount>>>,
+    // Filled in parse_metadata() to calculate commissions for simulated fills
+    pub(super) symbols: Arc<DashMap<CurrencyPair, Arc<CurrencyPairMetadata>>>,
+
+    pub(super) order_created_callback: Mutex<OrderEventCallback>,
+    pub(super) order_cancelled_callback: Arc<Mutex<OrderEventCallback>>,
+    pub(super) handle_order_filled_callback: Arc<Mutex<OrderFilledCallback>>,
+}
+
+impl PaperExchange {
+    pub fn new(
+        id: ExchangeAccountId,
+        settings: ExchangeSettings,
+        paper_settings: PaperExchangeSettings,
+        market_data_client: BoxExchangeClient,
+        events_channel: broadcast::Sender<ExchangeEvent>,
+        application_manager: Arc<ApplicationManager>,
+    ) -> Self {
+        let (paper_exchange, market_data_handler) =
+            Self::with_market_data_handler(id, settings, paper_settings, market_data_client);
+
+        let action = match_market_data_events(
+            events_channel.subscribe(),
+            market_data_handler,
+            application_manager.stop_token(),
+        );
+        spawn_future("PaperExchange order matching", true, action.boxed());
+
+        paper_exchange
+    }
+
+    /// Create paper exchange which orders are matched only against events passed to the returned handler
+    /// instead of ones from the events channel. It's used when market data are replayed (e.g. in backtesting)
+    pub(crate) fn with_market_data_handler(
+        id: ExchangeAccountId,
+        settings: ExchangeSettings,
+        paper_settings: PaperExchangeSettings,
+        market_data_client: BoxExchangeClient,
+    ) -> (Self, PaperMarketDataHandler) {
+        let paper_exchange = Self {
+            order_matcher: Arc::new(Mutex::new(OrderMatcher::new(id.clone()))),
+            balances: Arc::new(Mutex::new(paper_settings.balances.clone())),
+            symbols: Default::default(),
+            order_created_callback: Mutex::new(Box::new(|_, _, _| {})),
+            order_cancelled_callback: Arc::new(Mutex::new(Box::new(|_, _, _| {}))),
+            handle_order_filled_callback: Arc::new(Mutex::new(Box::new(|_| {}))),
+            id,
+            settings,
+            paper_settings,
+            market_data_client,
+        };
+
+        let market_data_handler = PaperMarketDataHandler {
+            exchange_account_id: paper_exchange.id.clone(),
+            order_matcher: paper_exchange.order_matcher.clone(),
+            symbols: paper_exchange.symbols.clone(),
+            handle_order_filled_callback: paper_exchange.handle_order_filled_callback.clone(),
+            order_cancelled_callback: paper_exchange.order_cancelled_callback.clone(),
+            commission: paper_exchange.paper_settings.commission.clone(),
+            balances: paper_exchange.balances.clone(),
+        };
+
+        (paper_exchange, market_data_handler)
+    }
+
+    /// Metadata of currency pair is required to calculate commissions of simulated fills
+    pub(crate) fn add_symbol(&self, currency_pair_metadata: Arc<CurrencyPairMetadata>) {
+        let _ = self.symbols.insert(
+            currency_pair_metadata.currency_pair(),
+            currency_pair_metadata,
+        );
+    }
+
+    /// Order is funded by balance which isn't used by other open orders. Sell order needs base currency
+    /// and buy order needs quote currency for its cost, so the check should be done under matcher lock
+    pub(super) fn check_balance(
+        &self,
+        order_matcher: &OrderMatcher,
+        order: &OrderCreating,
+    ) -> Result<(), ExchangeError> {
+        let required_balance = |currency_pair: &CurrencyPair, side, price, amount| {
+            let currency_pair_metadata = self.symbols.get(currency_pair).ok_or_else(|| {
+                ExchangeError::new(
+                    ExchangeErrorType::InvalidOrder,
+                    format!("Unknown currency pair {}", currency_pair),
+                    None,
+                )
+            })?;
+            Ok(match side {
+                OrderSide::Buy => (
+                    currency_pair_metadata.quote_currency_code.clone(),
+                    amount * price,
+                ),
+                OrderSide::Sell => (currency_pair_metadata.base_currency_code.clone(), amount),
+            })
+        };
+
+        let header = &order.header;
+        let (currency_code, amount) = required_balance(
+            &header.currency_pair,
+            header.side,
+            order.price,
+            header.amount,
+        )?;
+
+        let mut reserved_amount = dec!(0);
+        for open_order in order_matcher.open_orders(None) {
+            let (open_order_currency_code, open_order_amount) = required_balance(
+                &open_order.currency_pair,
+                open_order.order_side,
+                open_order.price,
+                open_order.amount - open_order.filled_amount,
+            )?;
+            if open_order_currency_code == currency_code {
+                reserved_amount += open_order_amount;
+            }
+        }
+
+        let balance = self
+            .balances
+            .lock()
+            .get(&currency_code)
+            .cloned()
+            .unwrap_or_default();
+        if balance - reserved_amount < amount {
+            return Err(ExchangeError::new(
+                ExchangeErrorType::InsufficientFunds,
+                INSUFFICIENT_FUNDS_MSG.to_owned(),
+                Some(INSUFFICIENT_FUNDS_CODE),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub(super) async fn simulate_latency(&self) {
+        if !self.paper_settings.latency.is_zero() {
+            tokio::time::sleep(self.paper_settings.latency).await;
+        }
+    }
+
+    pub(super) fn success_outcome(content: serde_json::Value) -> RestRequestOutcome {
+        RestRequestOutcome::new(content.to_string(), StatusCode::OK)
+    }
+
+    pub(super) fn error_outcome(error: &ExchangeError) -> RestRequestOutcome {
+        let content = json!({ "code": error.code, "msg": error.message });
+        RestRequestOutcome::new(content.to_string(), StatusCode::BAD_REQUEST)
+    }
+}
+
+fn to_fill_event_data(
+    fill: PaperFill,
+    currency_pair_metadata: &CurrencyPairMetadata,
+    commission: &Commission,
+) -> FillEventData {
+    let commission_rate = commission.get_commission(fill.role).fee.percent_to_rate();
+    // Commission is charged in the currency we get from the trade as Binance does by default
+    let commission_currency_code = currency_pair_metadata.get_commission_currency_code(fill.side);
+    let commission_amount = if commission_currency_code == currency_pair_metadata.base_currency_code
+    {
+        fill.amount * commission_rate
+    } else {
+        fill.amount * fill.price * commission_rate
+    };
+
+    FillEventData {
+        source_type: EventSourceType::WebSocket,
+        trade_id: Some(fill.trade_id),
+        client_order_id: Some(fill.client_order_id),
+        exchange_order_id: fill.exchange_order_id,
+        fill_price: fill.price,
+        fill_amount: fill.amount,
+        is_diff: true,
+        total_filled_amount: Some(fill.total_filled_amount),
+        order_role: Some(fill.role),
+        commission_currency_code: Some(commission_currency_code),
+        commission_rate: Some(commission_rate),
+        commission_amount: Some(commission_amount),
+        fill_type: OrderFillType::UserTrade,
+        trade_currency_pair: None,
+        order_side: Some(fill.side),
+        order_amount: None,
+    }
+}
+
+/// Paper account is a spot one: fill exchanges base currency for quote one
+/// and commission is taken in the currency we get from the trade
+fn apply_fill_to_balances(
+    balances: &mut HashMap<CurrencyCode, Amount>,
+    event_data: &FillEventData,
+    currency_pair_metadata: &CurrencyPairMetadata,
+) {
+    let cost = event_data.fill_amount * event_data.fill_price;
+    let (base_diff, quote_diff) = match event_data.order_side {
+        Some(OrderSide::Buy) => (event_data.fill_amount, -cost),
+        Some(OrderSide::Sell) => (-event_data.fill_amount, cost),
+        None => return,
+    };
+
+    *balances
+        .entry(currency_pair_metadata.base_currency_code.clone())
+        .or_default() += base_diff;
+    *balances
+        .entry(currency_pair_metadata.quote_currency_code.clone())
+        .or_default() += quote_diff;
+
+    if let (Some(commission_currency_code), Some(commission_amount)) = (
+        &event_data.commission_currency_code,
+        event_data.commission_amount,
+    ) {
+        *balances
+            .entry(commission_currency_code.clone())
+            .or_default() -= commission_amount;
+    }
+}
+
+/// Matches orders of paper exchange against market data events. Simulated fills are turned
+/// into fill events of the exchange and applied to paper balances
+pub(crate) struct PaperMarketDataHandler {
+    exchange_account_id: ExchangeAccountId,
+    order_matcher: Arc<Mutex<OrderMatcher>>,
+    symbols: Arc<DashMap<CurrencyPair, Arc<CurrencyPairMetadata>>>,
+    handle_order_filled_callback: Arc<Mutex<OrderFilledCallback>>,
+    order_cancelled_callback: Arc<Mutex<OrderEventCallback>>,
+    commission: Commission,
+    balances: Arc<Mutex<HashMap<CurrencyCode, Amount>>>,
+}
+
+impl PaperMarketDataHandler {
+    pub(crate) fn handle_event(&self, event: ExchangeEvent) {
+        // Fills are collected first to avoid calling exchange callbacks under the matcher lock
+        let (fills, expired_orders) = {
+            let mut order_matcher = self.order_matcher.lock();
+            let fills = match event {
+                ExchangeEvent::OrderBookEvent(order_book_event) => {
+                    order_matcher.handle_order_book_event(order_book_event)
+                }
+                ExchangeEvent::Trades(trades_event) => {
+                    order_matcher.handle_trades_event(&trades_event)
+                }
+                _ => return,
+            };
+            (fills, order_matcher.take_expired_orders())
+        };
+
+        self.handle_fills(fills);
+        self.handle_expired_orders(expired_orders);
+    }
+
+    /// Expired market orders are canceled on the exchange, so they are reported as canceled ones
+    fn handle_expired_orders(&self, expired_orders: Vec<ExpiredOrder>) {
+        for expired_order in expired_orders {
+            self.order_cancelled_callback.lock()(
+                expired_order.client_order_id,
+                expired_order.exchange_order_id,
+                EventSourceType::WebSocket,
+            );
+        }
+    }
+
+    fn handle_fills(&self, fills: Vec<PaperFill>) {
+        for fill in fills {
+            let currency_pair_metadata = match self.symbols.get(&fill.currency_pair) {
+                Some(currency_pair_metadata) => currency_pair_metadata.clone(),
+                None => {
+                    error!(
+                        "Unable to find metadata for {} on PaperExchange {}",
+                        fill.currency_pair, self.exchange_account_id
+                    );
+                    continue;
+                }
+            };
+
+            let event_data = to_fill_event_data(fill, &currency_pair_metadata, &self.commission);
+            apply_fill_to_balances(
+                &mut self.balances.lock(),
+                &event_data,
+                &currency_pair_metadata,
+            );
+            self.handle_order_filled_callback.lock()(event_data);
+        }
+    }
+}
+
+async fn match_market_data_events(
+    mut events_receiver: broadcast::Receiver<ExchangeEvent>,
+    market_data_handler: PaperMarketDataHandler,
+    cancellation_token: CancellationToken,
+) -> Result<()> {
+    let exchange_account_id = market_data_handler.exchange_account_id.clone();
+    loop {
+        let event = tokio::select! {
+            event = events_receiver.recv() => event,
+            _ = cancellation_token.when_cancelled() => return Ok(()),
+        };
+
+        match event {
+            Ok(event) => market_data_handler.handle_event(event),
+            Err(RecvError::Lagged(skipped_count)) => {
+                warn!(
+                    "PaperExchange {} skipped {} events during order matching",
+                    exchange_account_id, skipped_count
+                );
+            }
+            Err(RecvError::Closed) => {
+                info!(
+                    "Events channel was closed so PaperExchange {} stopped order matching",
+                    exchange_account_id
+                );
+                return Ok(());
+            }
+        }
+    }
+}
+
+pub(crate) fn paper_exchange_features() -> ExchangeFeatures {
+    ExchangeFeatures::new(
+        OpenOrdersType::AllCurrencyPair,
+        RestFillsFeatures::new(RestFillsType::None),
+        OrderFeatures {
+            supports_cancel_all_orders: true,
+            ..OrderFeatures::default()
+        },
+        OrderTradeOption::default(),
+        WebSocketOptions::default(),
+        true,
+        true,
+        AllowedEventSourceType::All,
+        AllowedEventSourceType::All,
+    )
+}
+
+pub struct PaperExchangeBuilder {
+    market_data_builder: Box<dyn ExchangeClientBuilder>,
+    paper_settings: PaperExchangeSettings,
+}
+
+impl PaperExchangeBuilder {
+    /// `market_data_builder` creates client of the exchange which metadata and market data are used for simulation.
+    /// `paper_settings` are used for exchanges without `paper_trading` in their settings
+    pub fn new(
+        market_data_builder: Box<dyn ExchangeClientBuilder>,
+        paper_settings: PaperExchangeSettings,
+    ) -> Self {
+        Self {
+            market_data_builder,
+            paper_settings,
+        }
+    }
+}
+
+impl ExchangeClientBuilder for PaperExchangeBuilder {
+    fn create_exchange_client(
+        &self,
+        exchange_settings: ExchangeSettings,
+        events_channel: broadcast::Sender<ExchangeEvent>,
+        application_manager: Arc<ApplicationManager>,
+    ) -> ExchangeClientBuilderResult {
+        let exchange_account_id = exchange_settings.exchange_account_id.clone();
+        let paper_settings = exchange_settings
+            .paper_trading
+            .as_ref()
+            .map(PaperExchangeSettings::from)
+            .unwrap_or_else(|| self.paper_settings.clone());
+        let market_data_client = self
+            .market_data_builder
+            .create_exchange_client(
+                exchange_settings.clone(),
+                events_channel.clone(),
+                application_manager.clone(),
+            )
+            .client;
+
+        ExchangeClientBuilderResult {
+            client: Box::new(PaperExchange::new(
+                exchange_account_id,
+                exchange_settings,
+                paper_settings,
+                market_data_client,
+                events_channel,
+                application_manager,
+            )) as BoxExchangeClient,
+            features: paper_exchange_features(),
+        }
+    }
+
+    fn get_timeout_argments(&self) -> RequestTimeoutArguments {
+        // Only requests for metadata reach the real exchange
+        self.market_data_builder.get_timeout_argments()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exchanges::general::currency_pair_metadata::Precision;
+    use crate::core::orders::order::OrderRole;
+
+    #[test]
+    fn balances_are_changed_by_fills_and_commission() {
+        let currency_pair_metadata = CurrencyPairMetadata::new(
+            true,
+            false,
+            "phb".into(),
+            "phb".into(),
+            "btc".into(),
+            "btc".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            "phb".into(),
+            None,
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(1) },
+        );
+        let commission = PaperExchangeSettings::default().commission;
+        let fill = |side, price, amount| PaperFill {
+            trade_id: "1".to_owned(),
+            client_order_id: "order".into(),
+            exchange_order_id: "1".into(),
+            currency_pair: currency_pair_metadata.currency_pair(),
+            side,
+            role: OrderRole::Maker,
+            price,
+            amount,
+            total_filled_amount: amount,
+        };
+
+        let mut balances: HashMap<CurrencyCode, Amount> =
+            vec![("btc".into(), dec!(10))].into_iter().collect();
+        let buy = to_fill_event_data(
+            fill(OrderSide::Buy, dec!(2), dec!(3)),
+            &currency_pair_metadata,
+            &commission,
+        );
+        apply_fill_to_balances(&mut balances, &buy, &currency_pair_metadata);
+
+        // Commission 0.1% is charged in received phb
+        assert_eq!(balances[&"phb".into()], dec!(2.997));
+        assert_eq!(balances[&"btc".into()], dec!(4));
+
+        let sell = to_fill_event_data(
+            fill(OrderSide::Sell, dec!(3), dec!(2)),
+            &currency_pair_metadata,
+            &commission,
+        );
+        apply_fill_to_balances(&mut balances, &sell, &currency_pair_metadata);
+
+        // Commission 0.1% is charged in received btc
+        assert_eq!(balances[&"phb".into()], dec!(0.997));
+        assert_eq!(balances[&"btc".into()], dec!(9.994));
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use awc::http::Uri;
+use dashmap::DashMap;
+use serde_json::Value;
+
+use super::order_matcher::{
+    DUPLICATE_ORDER_CODE, INSUFFICIENT_FUNDS_CODE, UNKNOWN_ORDER_CODE, WOULD_TAKE_CODE,
+};
+use super::paper_exchange::PaperExchange;
+use crate::core::connectivity::connectivity_manager::WebSocketRole;
+use crate::core::exchanges::common::{
+    Amount, CurrencyCode, CurrencyId, CurrencyPair, ExchangeError, ExchangeErrorType, Price,
+    RestRequestOutcome, SpecificCurrencyPair,
+};
+use crate::core::exchanges::events::{ExchangeBalance, ExchangeBalancesAndPositions, TradeId};
+use crate::core::exchanges::general::currency_pair_metadata::CurrencyPairMetadata;
+use crate::core::exchanges::general::handlers::handle_order_filled::FillEventData;
+use crate::core::exchanges::general::order::get_order_trades::OrderTrade;
+use crate::core::exchanges::traits::Support;
+use crate::core::orders::fill::EventSourceType;
+use crate::core::orders::order::*;
+use crate::core::settings::ExchangeSettings;
+use crate::core::DateTime;
+
+#[async_trait]
+impl Support for PaperExchange {
+    fn is_rest_error_code(&self, response: &RestRequestOutcome) -> Result<(), ExchangeError> {
+        if !response.content.contains(r#""code""#) {
+            return Ok(());
+        }
+
+        let data: Value = serde_json::from_str(&response.content).map_err(|error| {
+            ExchangeError::new(
+                ExchangeErrorType::ParsingError,
+                format!("Unable to parse response.content: {}", error),
+                None,
+            )
+        })?;
+
+        match (data["msg"].as_str(), data["code"].as_i64()) {
+            (Some(message), Some(code)) => Err(ExchangeError::new(
+                ExchangeErrorType::Unknown,
+                message.to_owned(),
+                Some(code),
+            )),
+            _ => Err(ExchangeError::new(
+                ExchangeErrorType::ParsingError,
+                "Unable to parse code or msg field".into(),
+                None,
+            )),
+        }
+    }
+
+    fn get_order_id(&self, response: &RestRequestOutcome) -> Result<ExchangeOrderId> {
+        let response: Value =
+            serde_json::from_str(&response.content).context("Unable to parse response content")?;
+        let id = response["orderId"]
+            .as_str()
+            .context("Unable to get orderId from response")?;
+        Ok(id.into())
+    }
+
+    fn clarify_error_type(&self, error: &mut ExchangeError) {
+        error.error_type = match error.code {
+            Some(UNKNOWN_ORDER_CODE) => ExchangeErrorType::OrderNotFound,
+            Some(WOULD_TAKE_CODE) | Some(DUPLICATE_ORDER_CODE) => ExchangeErrorType::InvalidOrder,
+            Some(INSUFFICIENT_FUNDS_CODE) => ExchangeErrorType::InsufficientFunds,
+            _ => ExchangeErrorType::Unknown,
+        };
+    }
+
+    fn on_websocket_message(&self, msg: &str) -> Result<()> {
+        self.market_data_client.on_websocket_message(msg)
+    }
+
+    fn on_connecting(&self) -> Result<()> {
+        self.market_data_client.on_connecting()
+    }
+
+    fn set_order_created_callback(
+        &self,
+        callback: Box<dyn FnMut(ClientOrderId, ExchangeOrderId, EventSourceType) + Send + Sync>,
+    ) {
+        *self.order_created_callback.lock() = callback;
+    }
+
+    fn set_order_cancelled_callback(
+        &self,
+        callback: Box<dyn FnMut(ClientOrderId, ExchangeOrderId, EventSourceType) + Send + Sync>,
+    ) {
+        *self.order_cancelled_callback.lock() = callback;
+    }
+
+    fn set_handle_order_filled_callback(
+        &self,
+        callback: Box<dyn FnMut(FillEventData) + Send + Sync>,
+    ) {
+        *self.handle_order_filled_callback.lock() = callback;
+    }
+
+    fn set_handle_trade_callback(
+        &self,
+        callback: Box<
+            dyn FnMut(&CurrencyPair, TradeId, Price, Amount, OrderSide, DateTime) + Send + Sync,
+        >,
+    ) {
+        self.market_data_client.set_handle_trade_callback(callback);
+    }
+
+    fn set_order_book_snapshot_requested_callback(
+        &self,
+        callback: Box<dyn FnMut(CurrencyPair) + Send + Sync>,
+    ) {
+        self.market_data_client
+            .set_order_book_snapshot_requested_callback(callback);
+    }
+
+    fn set_traded_specific_currencies(&self, currencies: Vec<SpecificCurrencyPair>) {
+        self.market_data_client
+            .set_traded_specific_currencies(currencies);
+    }
+
+    fn is_websocket_enabled(&self, role: WebSocketRole) -> bool {
+        match role {
+            WebSocketRole::Main => self.market_data_client.is_websocket_enabled(role),
+            // There are no private data on paper exchange
+            WebSocketRole::Secondary => false,
+        }
+    }
+
+    async fn create_ws_url(&self, role: WebSocketRole) -> Result<Uri> {
+        self.market_data_client.create_ws_url(role).await
+    }
+
+    fn get_specific_currency_pair(&self, currency_pair: &CurrencyPair) -> SpecificCurrencyPair {
+        self.market_data_client
+            .get_specific_currency_pair(currency_pair)
+    }
+
+    fn get_supported_currencies(&self) -> &DashMap<CurrencyId, CurrencyCode> {
+        self.market_data_client.get_supported_currencies()
+    }
+
+    fn should_log_message(&self, message: &str) -> bool {
+        self.market_data_client.should_log_message(message)
+    }
+
+    fn parse_open_orders(&self, response: &RestRequestOutcome) -> Result<Vec<OrderInfo>> {
+        serde_json::from_str(&response.content)
+            .context("Unable to parse response content for get_open_orders request")
+    }
+
+    fn parse_order_info(&self, response: &RestRequestOutcome) -> Result<OrderInfo> {
+        serde_json::from_str(&response.content)
+            .context("Unable to parse response content for get_order_info request")
+    }
+
+    fn parse_metadata(
+        &self,
+        response: &RestRequestOutcome,
+    ) -> Result<Vec<Arc<CurrencyPairMetadata>>> {
+        let symbols = self.market_data_client.parse_metadata(response)?;
+        for symbol in &symbols {
+            self.add_symbol(symbol.clone());
+        }
+
+        Ok(symbols)
+    }
+
+    fn get_balance_reservation_currency_code(
+        &self,
+        currency_pair_metadata: Arc<CurrencyPairMetadata>,
+        side: OrderSide,
+    ) -> CurrencyCode {
+        self.market_data_client
+            .get_balance_reservation_currency_code(currency_pair_metadata, side)
+    }
+
+    fn parse_get_my_trades(
+        &self,
+        _response: &RestRequestOutcome,
+        _last_date_time: Option<DateTime>,
+    ) -> Result<Vec<OrderTrade>> {
+        Ok(Vec::new())
+    }
+
+    fn parse_balance(&self, response: &RestRequestOutcome) -> Result<ExchangeBalancesAndPositions> {
+        let balances: HashMap<CurrencyCode, Amount> = serde_json::from_str(&response.content)
+            .context("Unable to parse response content for get_balance request")?;
+
+        Ok(ExchangeBalancesAndPositions {
+            balances: balances
+                .into_iter()
+                .map(|(currency_code, balance)| ExchangeBalance {
+                    currency_code,
+                    balance,
+                })
+                .collect(),
+            positions: None,
+        })
+    }
+
+    fn request_order_book_resync(&self, currency_pair: &CurrencyPair) {
+        self.market_data_client
+            .request_order_book_resync(currency_pair);
+    }
+
+    fn handle_order_book_snapshot_response(
+        &self,
+        currency_pair: &CurrencyPair,
+        response: &RestRequestOutcome,
+    ) -> Result<()> {
+        self.market_data_client
+            .handle_order_book_snapshot_response(currency_pair, response)
+    }
+
+    fn on_order_book_snapshot_failed(&self, currency_pair: &CurrencyPair) {
+        self.market_data_client
+            .on_order_book_snapshot_failed(currency_pair);
+    }
+
+    fn get_settings(&self) -> &ExchangeSettings {
+        &self.settings
+    }
+}
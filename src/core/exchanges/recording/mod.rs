@@ -0,0 +1,3 @@
+pub mod raw_messages_recorder;
+pub mod raw_messages_replayer;
+pub mod recording_exchange_client;
@@ -0,0 +1,263 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::{error, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::core::exchanges::common::{ExchangeAccountId, RestRequestOutcome};
+use crate::core::exchanges::general::request_type::RequestType;
+use crate::core::lifecycle::trading_engine::Service;
+use crate::core::DateTime;
+
+// Compressed data is flushed to the file after this count of messages
+const FLUSH_MESSAGES_COUNT: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RawMessageKind {
+    WebSocket,
+    Rest {
+        request_type: RequestType,
+        status: u16,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawMessage {
+    pub receive_time: DateTime,
+    pub kind: RawMessageKind,
+    pub content: String,
+}
+
+enum WriterCommand {
+    Record(String),
+    Flush(oneshot::Sender<Result<()>>),
+    Finish(oneshot::Sender<Result<()>>),
+}
+
+/// Writes raw messages from the exchange to `<directory>/<exchange_account_id>_<start time>.jsonl.gz`.
+/// Each message is a JSON line. Every application run writes its own file, which is finished
+/// on graceful shutdown, so the file is complete unless the application was killed.
+/// Messages are serialized on the calling thread and compressed by separate writer thread,
+/// so recording never blocks handling of exchange messages
+pub struct RawMessagesRecorder {
+    exchange_account_id: ExchangeAccountId,
+    file_path: PathBuf,
+    sender: Mutex<Option<Sender<WriterCommand>>>,
+    writer_thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl RawMessagesRecorder {
+    pub fn open(
+        directory: impl AsRef<Path>,
+        exchange_account_id: ExchangeAccountId,
+    ) -> Result<Arc<Self>> {
+        let directory = directory.as_ref();
+        fs::create_dir_all(directory).with_context(|| {
+            format!(
+                "Unable to create directory {} for raw messages",
+                directory.display()
+            )
+        })?;
+
+        let file_path = directory.join(format!(
+            "{}_{}.jsonl.gz",
+            exchange_account_id,
+            Utc::now().format("%Y%m%d_%H%M%S%.3f")
+        ));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&file_path)
+            .with_context(|| format!("Unable to create file {}", file_path.display()))?;
+
+        let (sender, receiver) = mpsc::channel();
+        let encoder = GzEncoder::new(file, Compression::default());
+        let writer_file_path = file_path.clone();
+        let writer_thread = thread::Builder::new()
+            .name(format!("raw_messages_recorder_{}", exchange_account_id))
+            .spawn(move || write_messages(encoder, writer_file_path, receiver))
+            .context("Unable to start RawMessagesRecorder writer thread")?;
+
+        Ok(Arc::new(RawMessagesRecorder {
+            exchange_account_id,
+            file_path,
+            sender: Mutex::new(Some(sender)),
+            writer_thread: Mutex::new(Some(writer_thread)),
+        }))
+    }
+
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    pub fn record_websocket_message(&self, message: &str) {
+        self.record(RawMessage {
+            receive_time: Utc::now(),
+            kind: RawMessageKind::WebSocket,
+            content: message.to_owned(),
+        });
+    }
+
+    pub fn record_rest_response(&self, request_type: RequestType, response: &RestRequestOutcome) {
+        self.record(RawMessage {
+            receive_time: Utc::now(),
+            kind: RawMessageKind::Rest {
+                request_type,
+                status: response.status.as_u16(),
+            },
+            content: response.content.clone(),
+        });
+    }
+
+    /// Record successful completion of request which response content isn't available from exchange client
+    pub fn record_rest_request_completion(&self, request_type: RequestType) {
+        self.record(RawMessage {
+            receive_time: Utc::now(),
+            kind: RawMessageKind::Rest {
+                request_type,
+                status: 200,
+            },
+            content: String::new(),
+        });
+    }
+
+    /// Returns receiver which is notified when all messages recorded before this call are written to the file
+    pub fn flush(&self) -> oneshot::Receiver<Result<()>> {
+        let (flushed_sender, flushed_receiver) = oneshot::channel();
+        self.send(WriterCommand::Flush(flushed_sender));
+        flushed_receiver
+    }
+
+    /// Finish the file after writing of all messages recorded before this call.
+    /// Messages recorded later are lost
+    pub fn stop(&self) -> oneshot::Receiver<Result<()>> {
+        let (finished_sender, finished_receiver) = oneshot::channel();
+        self.send(WriterCommand::Finish(finished_sender));
+        drop(self.sender.lock().take());
+        finished_receiver
+    }
+
+    fn record(&self, raw_message: RawMessage) {
+        match serde_json::to_string(&raw_message) {
+            Ok(mut line) => {
+                line.push('\n');
+                self.send(WriterCommand::Record(line));
+            }
+            Err(error) => error!(
+                "Unable to serialize raw message for {}: {:?}",
+                self.exchange_account_id, error
+            ),
+        }
+    }
+
+    fn send(&self, command: WriterCommand) {
+        match &*self.sender.lock() {
+            Some(sender) => {
+                if sender.send(command).is_err() {
+                    error!(
+                        "RawMessagesRecorder writer thread for {} is stopped so message is lost",
+                        self.exchange_account_id
+                    );
+                }
+            }
+            None => warn!(
+                "RawMessagesRecorder for {} is stopped so message is lost",
+                self.exchange_account_id
+            ),
+        }
+    }
+}
+
+fn write_messages(
+    mut encoder: GzEncoder<File>,
+    file_path: PathBuf,
+    receiver: Receiver<WriterCommand>,
+) {
+    let mut unflushed_messages_count = 0;
+    let mut finished_sender = None;
+    while let Ok(command) = receiver.recv() {
+        match command {
+            WriterCommand::Record(line) => {
+                if let Err(error) = encoder.write_all(line.as_bytes()) {
+                    error!(
+                        "Unable to write raw message to file {}: {:?}",
+                        file_path.display(),
+                        error
+                    );
+                    continue;
+                }
+
+                unflushed_messages_count += 1;
+                if unflushed_messages_count >= FLUSH_MESSAGES_COUNT {
+                    unflushed_messages_count = 0;
+                    if let Err(error) = encoder.flush() {
+                        error!("Unable to flush file {}: {:?}", file_path.display(), error);
+                    }
+                }
+            }
+            WriterCommand::Flush(flushed_sender) => {
+                unflushed_messages_count = 0;
+                let result = encoder
+                    .flush()
+                    .with_context(|| format!("Unable to flush file {}", file_path.display()));
+                let _ = flushed_sender.send(result);
+            }
+            WriterCommand::Finish(sender) => {
+                finished_sender = Some(sender);
+                break;
+            }
+        }
+    }
+
+    let result = encoder
+        .finish()
+        .map(|_| ())
+        .with_context(|| format!("Unable to finish file {}", file_path.display()));
+    match finished_sender {
+        Some(finished_sender) => {
+            let _ = finished_sender.send(result);
+        }
+        None => {
+            if let Err(error) = result {
+                error!("Unable to finish recording of raw messages: {:?}", error);
+            }
+        }
+    }
+}
+
+impl Drop for RawMessagesRecorder {
+    fn drop(&mut self) {
+        // Closing of the channel lets the writer thread write remaining messages and finish the file
+        // if it wasn't stopped on graceful shutdown
+        drop(self.sender.lock().take());
+
+        if let Some(writer_thread) = self.writer_thread.lock().take() {
+            if writer_thread.join().is_err() {
+                error!(
+                    "RawMessagesRecorder writer thread for {} panicked",
+                    self.exchange_account_id
+                );
+            }
+        }
+    }
+}
+
+impl Service for RawMessagesRecorder {
+    fn name(&self) -> &str {
+        "RawMessagesRecorder"
+    }
+
+    fn graceful_shutdown(self: Arc<Self>) -> Option<oneshot::Receiver<Result<()>>> {
+        Some(self.stop())
+    }
+}
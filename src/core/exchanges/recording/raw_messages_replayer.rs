@@ -0,0 +1,173 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use flate2::read::MultiGzDecoder;
+use log::warn;
+
+use super::raw_messages_recorder::{RawMessage, RawMessageKind};
+use crate::core::exchanges::traits::Support;
+
+/// Reads messages recorded by `RawMessagesRecorder` and pushes them back to the exchange client
+pub struct RawMessagesReplayer {
+    messages: Vec<RawMessage>,
+}
+
+impl RawMessagesReplayer {
+    pub fn open(file_path: impl AsRef<Path>) -> Result<Self> {
+        let file_path = file_path.as_ref();
+        let file = File::open(file_path)
+            .with_context(|| format!("Unable to open file {}", file_path.display()))?;
+
+        let mut messages = Vec::new();
+        for line in BufReader::new(MultiGzDecoder::new(file)).lines() {
+            // Tail of the file can be incomplete if application wasn't stopped gracefully
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => {
+                    warn!(
+                        "Stopped reading of raw messages from {} after {} messages: {}",
+                        file_path.display(),
+                        messages.len(),
+                        error
+                    );
+                    break;
+                }
+            };
+
+            match serde_json::from_str(&line) {
+                Ok(message) => messages.push(message),
+                Err(error) => {
+                    warn!(
+                        "Stopped reading of raw messages from {} after {} messages: {}",
+                        file_path.display(),
+                        messages.len(),
+                        error
+                    );
+                    break;
+                }
+            }
+        }
+
+        Ok(RawMessagesReplayer { messages })
+    }
+
+    pub fn messages(&self) -> &[RawMessage] {
+        &self.messages
+    }
+
+    /// Push recorded WebSocket messages to `handle_message` in the order they were received.
+    /// Returns count of replayed messages
+    pub fn replay(&self, mut handle_message: impl FnMut(&str) -> Result<()>) -> usize {
+        let mut replayed_count = 0;
+        for message in &self.messages {
+            if message.kind != RawMessageKind::WebSocket {
+                continue;
+            }
+
+            if let Err(error) = handle_message(&message.content) {
+                warn!(
+                    "Error occurred while replaying websocket message received at {}: {:?}",
+                    message.receive_time, error
+                );
+            }
+            replayed_count += 1;
+        }
+
+        replayed_count
+    }
+
+    pub fn replay_to_client(&self, exchange_client: &dyn Support) -> usize {
+        self.replay(|message| exchange_client.on_websocket_message(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exchanges::common::{ExchangeAccountId, RestRequestOutcome};
+    use crate::core::exchanges::general::request_type::RequestType;
+    use crate::core::exchanges::recording::raw_messages_recorder::RawMessagesRecorder;
+    use awc::http::StatusCode;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    fn temp_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("mmb_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    #[tokio::test]
+    async fn replay_messages_recorded_until_stop() {
+        let directory = temp_directory("replay_raw_messages");
+        let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+
+        let recorder = RawMessagesRecorder::open(&directory, exchange_account_id).expect("in test");
+        recorder.record_websocket_message("first");
+        recorder.record_rest_response(
+            RequestType::CreateOrder,
+            &RestRequestOutcome::new(r#"{"orderId":1}"#.to_owned(), StatusCode::OK),
+        );
+        recorder.record_websocket_message("second");
+        recorder.record_rest_request_completion(RequestType::CancelAllOrders);
+        recorder.stop().await.expect("in test").expect("in test");
+        // Messages after stop aren't written to finished file
+        recorder.record_websocket_message("third");
+
+        let replayer = RawMessagesReplayer::open(recorder.file_path()).expect("in test");
+
+        assert_eq!(replayer.messages().len(), 4);
+        assert_eq!(
+            replayer.messages()[1].kind,
+            RawMessageKind::Rest {
+                request_type: RequestType::CreateOrder,
+                status: 200
+            }
+        );
+        assert_eq!(
+            replayer.messages()[3].kind,
+            RawMessageKind::Rest {
+                request_type: RequestType::CancelAllOrders,
+                status: 200
+            }
+        );
+
+        let mut replayed_messages = Vec::new();
+        let replayed_count = replayer.replay(|message| {
+            replayed_messages.push(message.to_owned());
+            Ok(())
+        });
+        assert_eq!(replayed_count, 2);
+        assert_eq!(replayed_messages, vec!["first", "second"]);
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn incomplete_tail_is_skipped() {
+        let directory = temp_directory("incomplete_raw_messages");
+        let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+        let file_path = {
+            let recorder =
+                RawMessagesRecorder::open(&directory, exchange_account_id).expect("in test");
+            recorder.record_websocket_message("message");
+            recorder.file_path().to_owned()
+        };
+
+        OpenOptions::new()
+            .append(true)
+            .open(&file_path)
+            .expect("in test")
+            .write_all(&[0x1f, 0x8b, 0x08])
+            .expect("in test");
+
+        let replayer = RawMessagesReplayer::open(&file_path).expect("in test");
+        assert_eq!(replayer.messages().len(), 1);
+        assert_eq!(replayer.messages()[0].content, "message");
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
@@ -0,0 +1,333 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use awc::http::Uri;
+use dashmap::DashMap;
+use tokio::time::Duration;
+
+use super::raw_messages_recorder::RawMessagesRecorder;
+use crate::core::connectivity::connectivity_manager::WebSocketRole;
+use crate::core::exchanges::common::{
+    Amount, CurrencyCode, CurrencyId, CurrencyPair, ExchangeAccountId, ExchangeError, Price,
+    RestRequestOutcome, SpecificCurrencyPair,
+};
+use crate::core::exchanges::events::{ExchangeBalancesAndPositions, TradeId};
+use crate::core::exchanges::general::currency_pair_metadata::CurrencyPairMetadata;
+use crate::core::exchanges::general::exchange::BoxExchangeClient;
+use crate::core::exchanges::general::handlers::handle_order_filled::FillEventData;
+use crate::core::exchanges::general::order::get_order_trades::OrderTrade;
+use crate::core::exchanges::general::request_type::RequestType;
+use crate::core::exchanges::timeouts::rate_limit::RateLimitUsage;
+use crate::core::exchanges::traits::{ExchangeClient, Support};
+use crate::core::orders::fill::EventSourceType;
+use crate::core::orders::order::*;
+use crate::core::orders::pool::OrderRef;
+use crate::core::settings::ExchangeSettings;
+use crate::core::DateTime;
+
+/// Exchange client which records all WebSocket messages and REST responses of the wrapped client
+pub struct RecordingExchangeClient {
+    exchange_client: BoxExchangeClient,
+    recorder: Arc<RawMessagesRecorder>,
+}
+
+impl RecordingExchangeClient {
+    pub fn new(exchange_client: BoxExchangeClient, recorder: Arc<RawMessagesRecorder>) -> Self {
+        Self {
+            exchange_client,
+            recorder,
+        }
+    }
+
+    fn record_response(
+        &self,
+        request_type: RequestType,
+        response: Result<RestRequestOutcome>,
+    ) -> Result<RestRequestOutcome> {
+        if let Ok(response) = &response {
+            self.recorder.record_rest_response(request_type, response);
+        }
+
+        response
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for RecordingExchangeClient {
+    async fn request_metadata(&self) -> Result<RestRequestOutcome> {
+        let response = self.exchange_client.request_metadata().await;
+        self.record_response(RequestType::GetMarkets, response)
+    }
+
+    async fn create_order(&self, order: &OrderCreating) -> Result<RestRequestOutcome> {
+        let response = self.exchange_client.create_order(order).await;
+        self.record_response(RequestType::CreateOrder, response)
+    }
+
+    async fn request_cancel_order(&self, order: &OrderCancelling) -> Result<RestRequestOutcome> {
+        let response = self.exchange_client.request_cancel_order(order).await;
+        self.record_response(RequestType::CancelOrder, response)
+    }
+
+    async fn cancel_all_orders(&self, currency_pair: CurrencyPair) -> Result<()> {
+        let result = self.exchange_client.cancel_all_orders(currency_pair).await;
+        if result.is_ok() {
+            self.recorder
+                .record_rest_request_completion(RequestType::CancelAllOrders);
+        }
+
+        result
+    }
+
+    async fn request_cancel_all_orders_countdown(
+        &self,
+        currency_pair: CurrencyPair,
+        countdown: Duration,
+    ) -> Result<RestRequestOutcome> {
+        let response = self
+            .exchange_client
+            .request_cancel_all_orders_countdown(currency_pair, countdown)
+            .await;
+        self.record_response(RequestType::SetCancelAllOrdersCountdown, response)
+    }
+
+    async fn request_open_orders(&self) -> Result<RestRequestOutcome> {
+        let response = self.exchange_client.request_open_orders().await;
+        self.record_response(RequestType::GetOpenOrders, response)
+    }
+
+    async fn request_open_orders_by_currency_pair(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> Result<RestRequestOutcome> {
+        let response = self
+            .exchange_client
+            .request_open_orders_by_currency_pair(currency_pair)
+            .await;
+        self.record_response(RequestType::GetOpenOrders, response)
+    }
+
+    async fn request_order_info(&self, order: &OrderRef) -> Result<RestRequestOutcome> {
+        let response = self.exchange_client.request_order_info(order).await;
+        self.record_response(RequestType::GetOrderInfo, response)
+    }
+
+    async fn request_my_trades(
+        &self,
+        currency_pair_metadata: &CurrencyPairMetadata,
+        last_date_time: Option<DateTime>,
+    ) -> Result<RestRequestOutcome> {
+        let response = self
+            .exchange_client
+            .request_my_trades(currency_pair_metadata, last_date_time)
+            .await;
+        self.record_response(RequestType::GetMyTrades, response)
+    }
+
+    async fn request_balance(&self) -> Result<RestRequestOutcome> {
+        let response = self.exchange_client.request_balance().await;
+        self.record_response(RequestType::GetBalance, response)
+    }
+
+    async fn request_order_book_snapshot(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> Result<RestRequestOutcome> {
+        let response = self
+            .exchange_client
+            .request_order_book_snapshot(currency_pair)
+            .await;
+        self.record_response(RequestType::GetOrderBook, response)
+    }
+}
+
+#[async_trait]
+impl Support for RecordingExchangeClient {
+    fn is_rest_error_code(&self, response: &RestRequestOutcome) -> Result<(), ExchangeError> {
+        self.exchange_client.is_rest_error_code(response)
+    }
+
+    fn get_order_id(&self, response: &RestRequestOutcome) -> Result<ExchangeOrderId> {
+        self.exchange_client.get_order_id(response)
+    }
+
+    fn clarify_error_type(&self, error: &mut ExchangeError) {
+        self.exchange_client.clarify_error_type(error)
+    }
+
+    fn on_websocket_message(&self, msg: &str) -> Result<()> {
+        self.recorder.record_websocket_message(msg);
+        self.exchange_client.on_websocket_message(msg)
+    }
+
+    fn on_connecting(&self) -> Result<()> {
+        self.exchange_client.on_connecting()
+    }
+
+    fn set_order_created_callback(
+        &self,
+        callback: Box<dyn FnMut(ClientOrderId, ExchangeOrderId, EventSourceType) + Send + Sync>,
+    ) {
+        self.exchange_client.set_order_created_callback(callback)
+    }
+
+    fn set_order_cancelled_callback(
+        &self,
+        callback: Box<dyn FnMut(ClientOrderId, ExchangeOrderId, EventSourceType) + Send + Sync>,
+    ) {
+        self.exchange_client.set_order_cancelled_callback(callback)
+    }
+
+    fn set_handle_order_filled_callback(
+        &self,
+        callback: Box<dyn FnMut(FillEventData) + Send + Sync>,
+    ) {
+        self.exchange_client
+            .set_handle_order_filled_callback(callback)
+    }
+
+    fn set_handle_trade_callback(
+        &self,
+        callback: Box<
+            dyn FnMut(&CurrencyPair, TradeId, Price, Amount, OrderSide, DateTime) + Send + Sync,
+        >,
+    ) {
+        self.exchange_client.set_handle_trade_callback(callback)
+    }
+
+    fn set_order_book_snapshot_requested_callback(
+        &self,
+        callback: Box<dyn FnMut(CurrencyPair) + Send + Sync>,
+    ) {
+        self.exchange_client
+            .set_order_book_snapshot_requested_callback(callback)
+    }
+
+    fn set_traded_specific_currencies(&self, currencies: Vec<SpecificCurrencyPair>) {
+        self.exchange_client
+            .set_traded_specific_currencies(currencies)
+    }
+
+    fn is_websocket_enabled(&self, role: WebSocketRole) -> bool {
+        self.exchange_client.is_websocket_enabled(role)
+    }
+
+    async fn create_ws_url(&self, role: WebSocketRole) -> Result<Uri> {
+        self.exchange_client.create_ws_url(role).await
+    }
+
+    fn get_specific_currency_pair(&self, currency_pair: &CurrencyPair) -> SpecificCurrencyPair {
+        self.exchange_client
+            .get_specific_currency_pair(currency_pair)
+    }
+
+    fn get_supported_currencies(&self) -> &DashMap<CurrencyId, CurrencyCode> {
+        self.exchange_client.get_supported_currencies()
+    }
+
+    fn should_log_message(&self, message: &str) -> bool {
+        self.exchange_client.should_log_message(message)
+    }
+
+    fn log_unknown_message(&self, exchange_account_id: ExchangeAccountId, message: &str) {
+        self.exchange_client
+            .log_unknown_message(exchange_account_id, message)
+    }
+
+    fn parse_open_orders(&self, response: &RestRequestOutcome) -> Result<Vec<OrderInfo>> {
+        self.exchange_client.parse_open_orders(response)
+    }
+
+    fn parse_order_info(&self, response: &RestRequestOutcome) -> Result<OrderInfo> {
+        self.exchange_client.parse_order_info(response)
+    }
+
+    fn parse_metadata(
+        &self,
+        response: &RestRequestOutcome,
+    ) -> Result<Vec<Arc<CurrencyPairMetadata>>> {
+        self.exchange_client.parse_metadata(response)
+    }
+
+    fn get_balance_reservation_currency_code(
+        &self,
+        currency_pair_metadata: Arc<CurrencyPairMetadata>,
+        side: OrderSide,
+    ) -> CurrencyCode {
+        self.exchange_client
+            .get_balance_reservation_currency_code(currency_pair_metadata, side)
+    }
+
+    fn parse_get_my_trades(
+        &self,
+        response: &RestRequestOutcome,
+        last_date_time: Option<DateTime>,
+    ) -> Result<Vec<OrderTrade>> {
+        self.exchange_client
+            .parse_get_my_trades(response, last_date_time)
+    }
+
+    fn parse_balance(&self, response: &RestRequestOutcome) -> Result<ExchangeBalancesAndPositions> {
+        self.exchange_client.parse_balance(response)
+    }
+
+    fn parse_rate_limits_usage(&self, response: &RestRequestOutcome) -> Vec<RateLimitUsage> {
+        self.exchange_client.parse_rate_limits_usage(response)
+    }
+
+    fn request_order_book_resync(&self, currency_pair: &CurrencyPair) {
+        self.exchange_client
+            .request_order_book_resync(currency_pair)
+    }
+
+    fn handle_order_book_snapshot_response(
+        &self,
+        currency_pair: &CurrencyPair,
+        response: &RestRequestOutcome,
+    ) -> Result<()> {
+        self.exchange_client
+            .handle_order_book_snapshot_response(currency_pair, response)
+    }
+
+    fn on_order_book_snapshot_failed(&self, currency_pair: &CurrencyPair) {
+        self.exchange_client
+            .on_order_book_snapshot_failed(currency_pair)
+    }
+
+    fn get_settings(&self) -> &ExchangeSettings {
+        self.exchange_client.get_settings()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exchanges::binance::test_helper::{
+        get_binance_with_synced_order_book, get_test_currency_pair,
+    };
+    use crate::core::lifecycle::application_manager::ApplicationManager;
+    use crate::core::lifecycle::cancellation_token::CancellationToken;
+    use tokio::sync::broadcast;
+
+    #[test]
+    fn order_book_resync_is_forwarded() {
+        let (tx, _) = broadcast::channel(10);
+        let application_manager = ApplicationManager::new(CancellationToken::default());
+        let (binance, is_snapshot_requested) =
+            get_binance_with_synced_order_book(tx, application_manager);
+
+        let directory = std::env::temp_dir().join(format!(
+            "mmb_recording_order_book_resync_{}",
+            std::process::id()
+        ));
+        let recorder = RawMessagesRecorder::open(&directory, "Binance0".parse().expect("in test"))
+            .expect("in test");
+        let exchange_client = RecordingExchangeClient::new(Box::new(binance), recorder);
+
+        exchange_client.request_order_book_resync(&get_test_currency_pair());
+        assert!(is_snapshot_requested());
+
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
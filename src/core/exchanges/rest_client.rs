@@ -7,6 +7,7 @@ use std::convert::TryInto;
 
 pub type HttpParams = Vec<(String, String)>;
 
+#[derive(Clone)]
 pub struct RestClient {
     client: Client<HttpsConnector<HttpConnector>>,
 }
@@ -76,8 +77,11 @@ type ResponseType = std::result::Result<Response<Body>, Error>;
 async fn handle_response(response: ResponseType, rest_action: &str) -> Result<RestRequestOutcome> {
     let response = response.with_context(|| format!("Unable to send {} request", rest_action))?;
 
+    let status = response.status();
+    let headers = response.headers().clone();
     Ok(RestRequestOutcome {
-        status: response.status(),
+        status,
+        headers,
         content: std::str::from_utf8(hyper::body::to_bytes(response.into_body()).await?.as_ref())
             .context("Unable to parse content string")?
             .to_owned(),
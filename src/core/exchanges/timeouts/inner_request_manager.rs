@@ -2,9 +2,12 @@ use std::collections::HashMap;
 
 use super::{
     more_or_equals_available_requests_count_trigger_scheduler::MoreOrEqualsAvailableRequestsCountTriggerScheduler,
-    pre_reserved_group::PreReservedGroup, request::Request,
+    pre_reserved_group::PreReservedGroup,
+    rate_limit::{interval_start, RateLimitKind, RateLimitUsage, RateLimitWindow},
+    request::Request,
     triggers::handle_trigger_trait::TriggerHandler,
 };
+use crate::core::data_recorder::recorder::{DataRecordType, DataRecorder};
 use crate::core::exchanges::timeouts::requests_timeout_manager::RequestGroupId;
 use crate::core::{
     exchanges::common::ExchangeAccountId, exchanges::general::request_type::RequestType, DateTime,
@@ -12,10 +15,37 @@ use crate::core::{
 use anyhow::{anyhow, bail, Result};
 use chrono::Duration;
 use log::info;
+use serde::Serialize;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub(super) enum RequestReservationAction {
+    GroupReserved,
+    GroupNotReserved,
+    GroupRemoved,
+    GroupNotFound,
+    RequestReserved,
+    RequestNotReserved,
+}
+
+#[derive(Serialize)]
+struct RequestReservationRecord<'a> {
+    exchange_account_id: &'a ExchangeAccountId,
+    action: RequestReservationAction,
+    request_type: Option<RequestType>,
+    group_id: Option<RequestGroupId>,
+    requests_count: Option<usize>,
+    all_available_requests_count: usize,
+    time: DateTime,
+}
 
 pub(super) struct InnerRequestsTimeoutManager {
     pub(super) requests_per_period: usize,
     pub(super) period_duration: Duration,
+    /// Weights of requests in requests per period. It's 1 for request types which aren't specified
+    pub(super) request_weights: HashMap<RequestType, usize>,
+    /// Exchange limits tracked in addition to requests per period
+    pub(super) additional_windows: Vec<RateLimitWindow>,
     pub(super) exchange_account_id: ExchangeAccountId,
     pub(super) requests: Vec<Request>,
     pub(super) pre_reserved_groups: Vec<PreReservedGroup>,
@@ -29,10 +59,35 @@ pub(super) struct InnerRequestsTimeoutManager {
     pub(super) more_or_equals_available_requests_count_trigger_scheduler:
         MoreOrEqualsAvailableRequestsCountTriggerScheduler,
     pub(super) delay_to_next_time_period: Duration,
-    // data_recorder
+    pub(super) data_recorder: Option<Arc<DataRecorder>>,
 }
 
 impl InnerRequestsTimeoutManager {
+    pub(super) fn save_reservation(
+        &self,
+        action: RequestReservationAction,
+        request_type: Option<RequestType>,
+        group_id: Option<RequestGroupId>,
+        requests_count: Option<usize>,
+        all_available_requests_count: usize,
+        time: DateTime,
+    ) {
+        if let Some(data_recorder) = &self.data_recorder {
+            data_recorder.save(
+                DataRecordType::RequestReservations,
+                &RequestReservationRecord {
+                    exchange_account_id: &self.exchange_account_id,
+                    action,
+                    request_type,
+                    group_id,
+                    requests_count,
+                    all_available_requests_count,
+                    time,
+                },
+            );
+        }
+    }
+
     pub(super) fn try_reserve_request_instant(
         &mut self,
         request_type: RequestType,
@@ -41,11 +96,20 @@ impl InnerRequestsTimeoutManager {
         let current_time = self.get_non_decreasing_time(current_time);
         self.remove_outdated_requests(current_time)?;
 
-        let _all_available_requests_count = self.get_all_available_requests_count();
+        let all_available_requests_count = self.get_all_available_requests_count();
         let available_requests_count = self.get_available_requests_count_at_present(current_time);
 
-        if available_requests_count == 0 {
-            // TODO save to DataRecorder
+        if available_requests_count < self.request_weight(request_type)
+            || !self.is_available_in_additional_windows(request_type, current_time)
+        {
+            self.save_reservation(
+                RequestReservationAction::RequestNotReserved,
+                Some(request_type),
+                None,
+                None,
+                all_available_requests_count,
+                current_time,
+            );
 
             return Ok(false);
         }
@@ -58,7 +122,14 @@ impl InnerRequestsTimeoutManager {
             request_type, current_time
         );
 
-        // TODO save to DataRecorder
+        self.save_reservation(
+            RequestReservationAction::RequestReserved,
+            Some(request_type),
+            None,
+            None,
+            all_available_requests_count,
+            current_time,
+        );
 
         (self.time_has_come_for_request)(request)?;
 
@@ -75,7 +146,7 @@ impl InnerRequestsTimeoutManager {
         let group_id = Some(group_id);
         for request in &self.requests {
             if request.allowed_start_time <= current_time && request.group_id == group_id {
-                count += 1;
+                count += request.weight;
             }
         }
 
@@ -88,7 +159,22 @@ impl InnerRequestsTimeoutManager {
         current_time: DateTime,
         group_id: Option<RequestGroupId>,
     ) -> Result<Request> {
-        let request = Request::new(request_type, current_time, group_id);
+        let weight = self.request_weight(request_type);
+        self.add_request_with_weight(request_type, current_time, group_id, weight)
+    }
+
+    fn add_request_with_weight(
+        &mut self,
+        request_type: RequestType,
+        current_time: DateTime,
+        group_id: Option<RequestGroupId>,
+        weight: usize,
+    ) -> Result<Request> {
+        let request = Request::new(request_type, current_time, group_id, weight);
+        for window in &mut self.additional_windows {
+            let window_weight = window.weight(request_type, weight);
+            window.add(window_weight, current_time);
+        }
 
         let request_index = self
             .requests
@@ -105,6 +191,105 @@ impl InnerRequestsTimeoutManager {
         Ok(request)
     }
 
+    pub(super) fn remove_request(&mut self, request: &Request) {
+        if let Some(position) = self
+            .requests
+            .iter()
+            .position(|stored_request| stored_request == request)
+        {
+            self.requests.remove(position);
+        }
+
+        for window in &mut self.additional_windows {
+            let window_weight = window.weight(request.request_type, request.weight);
+            window.remove(window_weight, request.allowed_start_time);
+        }
+    }
+
+    pub(super) fn request_weight(&self, request_type: RequestType) -> usize {
+        self.request_weights
+            .get(&request_type)
+            .copied()
+            .unwrap_or(1)
+    }
+
+    pub(super) fn is_available_in_additional_windows(
+        &self,
+        request_type: RequestType,
+        current_time: DateTime,
+    ) -> bool {
+        let weight = self.request_weight(request_type);
+        self.additional_windows
+            .iter()
+            .all(|window| window.is_available(window.weight(request_type, weight), current_time))
+    }
+
+    /// Earliest time not before `time` when request is allowed by additional limits
+    pub(super) fn get_available_time_in_additional_windows(
+        &self,
+        request_type: RequestType,
+        time: DateTime,
+    ) -> DateTime {
+        let weight = self.request_weight(request_type);
+        self.additional_windows
+            .iter()
+            .map(|window| {
+                window.earliest_available_time(
+                    window.weight(request_type, weight),
+                    time,
+                    self.delay_to_next_time_period,
+                )
+            })
+            .max()
+            .unwrap_or(time)
+    }
+
+    /// Raise counters of limits up to usage reported by exchange
+    pub(super) fn sync_rate_limits_usage(
+        &mut self,
+        rate_limits_usage: &[RateLimitUsage],
+        current_time: DateTime,
+    ) -> Result<()> {
+        let current_time = self.get_non_decreasing_time(current_time);
+        self.remove_outdated_requests(current_time)?;
+
+        for usage in rate_limits_usage {
+            if usage.kind == RateLimitKind::RequestWeight && usage.period == self.period_duration {
+                // Exchange reports usage of current interval, so unaccounted usage expires on its boundary
+                let interval_start = interval_start(current_time, self.period_duration);
+                let local_used = self.reserved_requests_count_in_period(
+                    current_time,
+                    |request: &Request, time| {
+                        request.allowed_start_time > time
+                            || request.allowed_start_time < interval_start
+                    },
+                );
+                if usage.used > local_used.requests_count {
+                    let unaccounted_weight = usage.used - local_used.requests_count;
+                    info!(
+                        "Synchronised used weight {} for {} with exchange, unaccounted weight {}",
+                        usage.used, self.exchange_account_id, unaccounted_weight
+                    );
+                    self.add_request_with_weight(
+                        RequestType::UnaccountedUsage,
+                        interval_start,
+                        None,
+                        unaccounted_weight,
+                    )?;
+                }
+            }
+
+            self.additional_windows
+                .iter_mut()
+                .filter(|window| {
+                    window.rate_limit.kind == usage.kind && window.rate_limit.period == usage.period
+                })
+                .for_each(|window| window.sync_used(usage.used, current_time));
+        }
+
+        Ok(())
+    }
+
     pub(super) fn handle_all_decreasing_triggers(&mut self) -> Result<()> {
         let available_requests_count = self.get_all_available_requests_count();
 
@@ -208,7 +393,7 @@ impl InnerRequestsTimeoutManager {
                 continue;
             }
 
-            requests_count += 1;
+            requests_count += request.weight;
 
             match request.group_id {
                 None => continue,
@@ -220,9 +405,9 @@ impl InnerRequestsTimeoutManager {
                             continue;
                         }
                         Some(requests_count_tmp) => {
-                            requests_count_in_group += 1;
+                            requests_count_in_group += request.weight;
 
-                            requests_count_tmp.requests_count += 1;
+                            requests_count_tmp.requests_count += request.weight;
                         }
                     }
                 }
@@ -246,8 +431,8 @@ impl InnerRequestsTimeoutManager {
     }
 
     pub(super) fn get_all_available_requests_count(&self) -> usize {
-        let available_requests_number =
-            self.requests_per_period.saturating_sub(self.requests.len());
+        let reserved_weight: usize = self.requests.iter().map(|request| request.weight).sum();
+        let available_requests_number = self.requests_per_period.saturating_sub(reserved_weight);
 
         available_requests_number
     }
@@ -259,6 +444,10 @@ impl InnerRequestsTimeoutManager {
         self.requests
             .retain(|request| request.allowed_start_time >= deadline);
 
+        for window in &mut self.additional_windows {
+            window.remove_outdated(current_time);
+        }
+
         Ok(())
     }
 
@@ -0,0 +1,251 @@
+use chrono::{Duration, TimeZone, Utc};
+
+use crate::core::{exchanges::general::request_type::RequestType, DateTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitKind {
+    /// Sum of weights of all requests
+    RequestWeight,
+    /// Count of order creation requests
+    OrdersCount,
+}
+
+/// Exchange limit of requests in period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub kind: RateLimitKind,
+    pub limit: usize,
+    pub period: Duration,
+}
+
+impl RateLimit {
+    pub fn new(kind: RateLimitKind, limit: usize, period: Duration) -> Self {
+        Self {
+            kind,
+            limit,
+            period,
+        }
+    }
+}
+
+/// Usage of rate limit in period reported by exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitUsage {
+    pub kind: RateLimitKind,
+    pub period: Duration,
+    pub used: usize,
+}
+
+impl RateLimitUsage {
+    pub fn new(kind: RateLimitKind, period: Duration, used: usize) -> Self {
+        Self { kind, period, used }
+    }
+}
+
+/// Start of interval aligned to multiples of period since Unix epoch which contains specified time.
+/// Exchanges report usage of calendar intervals (e.g. current minute), which is reset on their boundaries
+pub(super) fn interval_start(time: DateTime, period: Duration) -> DateTime {
+    let period_millis = period.num_milliseconds();
+    if period_millis <= 0 {
+        return time;
+    }
+
+    let time_millis = time.timestamp_millis();
+    Utc.timestamp_millis(time_millis - time_millis.rem_euclid(period_millis))
+}
+
+/// Reservations of requests for one rate limit
+pub(super) struct RateLimitWindow {
+    pub(super) rate_limit: RateLimit,
+    /// Reservation times with weights sorted by time
+    reservations: Vec<(DateTime, usize)>,
+}
+
+impl RateLimitWindow {
+    pub(super) fn new(rate_limit: RateLimit) -> Self {
+        Self {
+            rate_limit,
+            reservations: Vec::new(),
+        }
+    }
+
+    /// Weight of request in terms of this limit
+    pub(super) fn weight(&self, request_type: RequestType, request_weight: usize) -> usize {
+        match self.rate_limit.kind {
+            RateLimitKind::RequestWeight => request_weight,
+            RateLimitKind::OrdersCount => match request_type {
+                RequestType::CreateOrder => 1,
+                _ => 0,
+            },
+        }
+    }
+
+    fn used_in_period(&self, current_time: DateTime) -> usize {
+        let period_start = current_time - self.rate_limit.period;
+        self.reservations
+            .iter()
+            .filter(|(time, _)| period_start <= *time && *time <= current_time)
+            .map(|(_, weight)| weight)
+            .sum()
+    }
+
+    pub(super) fn is_available(&self, weight: usize, current_time: DateTime) -> bool {
+        weight == 0
+            || self.used_in_period(current_time).saturating_add(weight) <= self.rate_limit.limit
+    }
+
+    /// Earliest time not before `time` when request can be made. Reservations scheduled in future are counted too
+    pub(super) fn earliest_available_time(
+        &self,
+        weight: usize,
+        time: DateTime,
+        delay_to_next_time_period: Duration,
+    ) -> DateTime {
+        if weight == 0 {
+            return time;
+        }
+
+        let period_start = time - self.rate_limit.period;
+        let actual_reservations = self
+            .reservations
+            .iter()
+            .filter(|(reservation_time, _)| period_start <= *reservation_time);
+
+        let mut used: usize = actual_reservations
+            .clone()
+            .map(|(_, reservation_weight)| reservation_weight)
+            .sum();
+        let mut available_time = time;
+        for (reservation_time, reservation_weight) in actual_reservations {
+            if used.saturating_add(weight) <= self.rate_limit.limit {
+                break;
+            }
+
+            used -= reservation_weight;
+            available_time = std::cmp::max(
+                available_time,
+                *reservation_time + self.rate_limit.period + delay_to_next_time_period,
+            );
+        }
+
+        available_time
+    }
+
+    pub(super) fn add(&mut self, weight: usize, time: DateTime) {
+        if weight == 0 {
+            return;
+        }
+
+        let index = self
+            .reservations
+            .partition_point(|(reservation_time, _)| *reservation_time <= time);
+        self.reservations.insert(index, (time, weight));
+    }
+
+    pub(super) fn remove(&mut self, weight: usize, time: DateTime) {
+        if let Some(position) = self
+            .reservations
+            .iter()
+            .position(|reservation| *reservation == (time, weight))
+        {
+            self.reservations.remove(position);
+        }
+    }
+
+    pub(super) fn remove_outdated(&mut self, current_time: DateTime) {
+        let period_start = current_time - self.rate_limit.period;
+        self.reservations
+            .retain(|(reservation_time, _)| period_start <= *reservation_time);
+    }
+
+    /// Raise usage of current interval up to reported by exchange. It isn't lowered because exchange doesn't count requests in flight yet.
+    /// Unaccounted usage is added at interval start, so it expires on interval boundary like on exchange
+    pub(super) fn sync_used(&mut self, used: usize, current_time: DateTime) {
+        let interval_start = interval_start(current_time, self.rate_limit.period);
+        let local_used: usize = self
+            .reservations
+            .iter()
+            .filter(|(time, _)| interval_start <= *time && *time <= current_time)
+            .map(|(_, weight)| weight)
+            .sum();
+        if used > local_used {
+            self.add(used - local_used, interval_start);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn window() -> RateLimitWindow {
+        RateLimitWindow::new(RateLimit::new(
+            RateLimitKind::OrdersCount,
+            3,
+            Duration::seconds(10),
+        ))
+    }
+
+    #[test]
+    fn only_orders_are_counted() {
+        let window = window();
+        assert_eq!(window.weight(RequestType::CreateOrder, 5), 1);
+        assert_eq!(window.weight(RequestType::GetOpenOrders, 5), 0);
+    }
+
+    #[test]
+    fn earliest_available_time_after_oldest_reservations() {
+        let mut window = window();
+        let now = Utc::now();
+        let delay = Duration::milliseconds(1);
+
+        window.add(1, now);
+        window.add(1, now + Duration::seconds(2));
+        assert!(window.is_available(1, now));
+        assert_eq!(window.earliest_available_time(1, now, delay), now);
+
+        window.add(1, now + Duration::seconds(4));
+        assert_eq!(
+            window.earliest_available_time(1, now, delay),
+            now + Duration::seconds(10) + delay
+        );
+        assert_eq!(
+            window.earliest_available_time(2, now, delay),
+            now + Duration::seconds(12) + delay
+        );
+    }
+
+    #[test]
+    fn sync_only_raises_usage() {
+        let mut window = window();
+        let now = Utc.ymd(2021, 1, 1).and_hms(12, 0, 0);
+
+        window.add(1, now);
+        window.sync_used(0, now);
+        assert!(window.is_available(2, now));
+
+        window.sync_used(3, now);
+        assert!(!window.is_available(1, now));
+
+        window.remove_outdated(now + Duration::seconds(11));
+        assert!(window.is_available(3, now + Duration::seconds(11)));
+    }
+
+    #[test]
+    fn synced_usage_expires_on_interval_boundary() {
+        let mut window = window();
+        let now = Utc.ymd(2021, 1, 1).and_hms(12, 0, 8);
+        assert_eq!(
+            interval_start(now, Duration::seconds(10)),
+            Utc.ymd(2021, 1, 1).and_hms(12, 0, 0)
+        );
+
+        window.sync_used(3, now);
+        assert!(!window.is_available(1, now));
+
+        // Exchange resets usage at 12:00:10 rather than 10 seconds after synchronization
+        let next_interval = Utc.ymd(2021, 1, 1).and_hms(12, 0, 10) + Duration::milliseconds(1);
+        assert!(window.is_available(3, next_interval));
+    }
+}
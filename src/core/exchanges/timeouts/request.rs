@@ -6,6 +6,7 @@ pub struct Request {
     pub(crate) request_type: RequestType,
     pub(crate) allowed_start_time: DateTime,
     pub(crate) group_id: Option<RequestGroupId>,
+    pub(crate) weight: usize,
 }
 
 impl Request {
@@ -13,11 +14,13 @@ impl Request {
         request_type: RequestType,
         allowed_start_time: DateTime,
         group_id: Option<RequestGroupId>,
+        weight: usize,
     ) -> Self {
         Self {
             request_type,
             allowed_start_time,
             group_id,
+            weight,
         }
     }
 }
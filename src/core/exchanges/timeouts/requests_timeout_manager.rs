@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::sync::{Arc, Weak};
 
@@ -6,24 +7,28 @@ use chrono::Duration;
 use futures::FutureExt;
 use log::{error, info};
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use uuid::Uuid;
 
 use super::{
-    inner_request_manager::InnerRequestsTimeoutManager,
+    inner_request_manager::{InnerRequestsTimeoutManager, RequestReservationAction},
     more_or_equals_available_requests_count_trigger_scheduler::MoreOrEqualsAvailableRequestsCountTriggerScheduler,
-    pre_reserved_group::PreReservedGroup, request::Request,
+    pre_reserved_group::PreReservedGroup,
+    rate_limit::{RateLimit, RateLimitUsage, RateLimitWindow},
+    request::Request,
     triggers::every_requests_count_change_trigger::EveryRequestsCountChangeTrigger,
     triggers::less_or_equals_requests_count_trigger::LessOrEqualsRequestsCountTrigger,
 };
 use crate::core::{
-    exchanges::common::ExchangeAccountId, exchanges::general::request_type::RequestType,
-    infrastructure::spawn_future, infrastructure::FutureOutcome,
-    lifecycle::cancellation_token::CancellationToken, DateTime, OPERATION_CANCELED_MSG,
+    data_recorder::recorder::DataRecorder, exchanges::common::ExchangeAccountId,
+    exchanges::general::request_type::RequestType, infrastructure::spawn_future,
+    infrastructure::FutureOutcome, lifecycle::cancellation_token::CancellationToken, DateTime,
+    OPERATION_CANCELED_MSG,
 };
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct RequestGroupId(Uuid);
 
 impl RequestGroupId {
@@ -46,12 +51,20 @@ impl RequestsTimeoutManager {
     pub fn new(
         requests_per_period: usize,
         period_duration: Duration,
+        request_weights: HashMap<RequestType, usize>,
+        additional_limits: Vec<RateLimit>,
         exchange_account_id: ExchangeAccountId,
         more_or_equals_available_requests_count_trigger_scheduler: MoreOrEqualsAvailableRequestsCountTriggerScheduler,
+        data_recorder: Option<Arc<DataRecorder>>,
     ) -> Arc<Self> {
         let inner = InnerRequestsTimeoutManager {
             requests_per_period,
             period_duration,
+            request_weights,
+            additional_windows: additional_limits
+                .into_iter()
+                .map(RateLimitWindow::new)
+                .collect(),
             exchange_account_id,
             requests: Default::default(),
             pre_reserved_groups: Default::default(),
@@ -62,6 +75,7 @@ impl RequestsTimeoutManager {
             time_has_come_for_request: Box::new(|_| Ok(())),
             less_or_equals_requests_count_triggers: Default::default(),
             more_or_equals_available_requests_count_trigger_scheduler,
+            data_recorder,
         };
 
         Arc::new(Self {
@@ -81,11 +95,18 @@ impl RequestsTimeoutManager {
         let current_time = inner.get_non_decreasing_time(current_time);
         inner.remove_outdated_requests(current_time)?;
 
-        let _all_available_requests_count = inner.get_all_available_requests_count();
+        let all_available_requests_count = inner.get_all_available_requests_count();
         let available_requests_count = inner.get_available_requests_count_at_present(current_time);
 
         if available_requests_count < requests_count {
-            // TODO save to DataRecorder
+            inner.save_reservation(
+                RequestReservationAction::GroupNotReserved,
+                None,
+                None,
+                Some(requests_count),
+                all_available_requests_count,
+                current_time,
+            );
             return Ok(None);
         }
 
@@ -98,7 +119,14 @@ impl RequestsTimeoutManager {
             group_id, requests_count
         );
 
-        // TODO save to DataRecorder
+        inner.save_reservation(
+            RequestReservationAction::GroupReserved,
+            None,
+            Some(group_id),
+            Some(requests_count),
+            all_available_requests_count,
+            current_time,
+        );
 
         inner.last_time = Some(current_time);
 
@@ -107,10 +135,10 @@ impl RequestsTimeoutManager {
         Ok(Some(group_id))
     }
 
-    pub fn remove_group(&self, group_id: RequestGroupId, _current_time: DateTime) -> Result<bool> {
+    pub fn remove_group(&self, group_id: RequestGroupId, current_time: DateTime) -> Result<bool> {
         let mut inner = self.inner.lock();
 
-        let _all_available_requests_count = inner.get_all_available_requests_count();
+        let all_available_requests_count = inner.get_all_available_requests_count();
         let stored_group = inner
             .pre_reserved_groups
             .iter()
@@ -119,7 +147,14 @@ impl RequestsTimeoutManager {
         match stored_group {
             None => {
                 error!("Cannot find PreReservedGroup {} for removing", { group_id });
-                // TODO save to DataRecorder
+                inner.save_reservation(
+                    RequestReservationAction::GroupNotFound,
+                    None,
+                    Some(group_id),
+                    None,
+                    all_available_requests_count,
+                    current_time,
+                );
 
                 Ok(false)
             }
@@ -133,7 +168,14 @@ impl RequestsTimeoutManager {
                     group_id, pre_reserved_requests_count
                 );
 
-                // TODO save to DataRecorder
+                inner.save_reservation(
+                    RequestReservationAction::GroupRemoved,
+                    None,
+                    Some(group_id),
+                    Some(pre_reserved_requests_count),
+                    all_available_requests_count,
+                    current_time,
+                );
 
                 (inner.group_was_removed)(group)?;
 
@@ -175,7 +217,15 @@ impl RequestsTimeoutManager {
                     pre_reserved_group_id, request_type
                 );
 
-                // TODO save to DataRecorder
+                let all_available_requests_count = inner.get_all_available_requests_count();
+                inner.save_reservation(
+                    RequestReservationAction::GroupNotFound,
+                    Some(request_type),
+                    Some(pre_reserved_group_id),
+                    None,
+                    all_available_requests_count,
+                    current_time,
+                );
 
                 return inner.try_reserve_request_instant(request_type, current_time);
             }
@@ -199,8 +249,17 @@ impl RequestsTimeoutManager {
                 let available_requests_count =
                     available_requests_count_without_group + rest_requests_count_in_group;
 
-                if available_requests_count == 0 {
-                    // TODO save to DataRecorder
+                if available_requests_count < inner.request_weight(request_type)
+                    || !inner.is_available_in_additional_windows(request_type, current_time)
+                {
+                    inner.save_reservation(
+                        RequestReservationAction::RequestNotReserved,
+                        Some(request_type),
+                        Some(pre_reserved_group_id),
+                        None,
+                        all_available_requests_count,
+                        current_time,
+                    );
 
                     return Ok(false);
                 }
@@ -221,6 +280,15 @@ impl RequestsTimeoutManager {
                     current_time
                 );
 
+                inner.save_reservation(
+                    RequestReservationAction::RequestReserved,
+                    Some(request_type),
+                    Some(pre_reserved_group_id),
+                    None,
+                    all_available_requests_count,
+                    current_time,
+                );
+
                 (inner.time_has_come_for_request)(request)?;
 
                 Ok(true)
@@ -256,39 +324,47 @@ impl RequestsTimeoutManager {
         let current_time = inner.get_non_decreasing_time(current_time);
         inner.remove_outdated_requests(current_time)?;
 
-        let _available_requests_count = inner.get_all_available_requests_count();
+        let all_available_requests_count = inner.get_all_available_requests_count();
 
         let mut request_start_time;
-        let delay;
         let available_requests_count_for_period;
-        let request = if inner.requests.is_empty() {
+        if inner.requests.is_empty() {
             request_start_time = current_time;
-            delay = Duration::zero();
             available_requests_count_for_period = inner.requests_per_period;
-            inner.add_request(request_type, current_time, None)?
         } else {
             let last_request = inner.get_last_request()?;
             let last_requests_start_time = last_request.allowed_start_time;
 
             available_requests_count_for_period = inner.get_available_requests_in_last_period()?;
-            request_start_time = if available_requests_count_for_period == 0 {
+            request_start_time = if available_requests_count_for_period
+                < inner.request_weight(request_type)
+            {
                 last_requests_start_time + inner.period_duration + inner.delay_to_next_time_period
             } else {
                 last_requests_start_time
             };
 
             request_start_time = std::cmp::max(request_start_time, current_time);
-            delay = request_start_time - current_time;
-            inner.add_request(request_type, request_start_time, None)?
-        };
+        }
+
+        request_start_time =
+            inner.get_available_time_in_additional_windows(request_type, request_start_time);
+        let delay = request_start_time - current_time;
+        let request = inner.add_request(request_type, request_start_time, None)?;
 
         info!(
             "Request {:?} reserved, available in request_start_time {}",
             request_type, request_start_time
         );
 
-        // TODO save to DataRecorder. Delete drop
-        drop(available_requests_count_for_period);
+        inner.save_reservation(
+            RequestReservationAction::RequestReserved,
+            Some(request_type),
+            None,
+            Some(available_requests_count_for_period),
+            all_available_requests_count,
+            request_start_time,
+        );
 
         inner.last_time = Some(current_time);
 
@@ -334,9 +410,7 @@ impl RequestsTimeoutManager {
                 let strong_self = Self::try_get_strong(weak_self)?;
                 let mut inner = strong_self.inner.lock();
                 (inner.time_has_come_for_request)(request.clone())?;
-                if let Some(position) = inner.requests.iter().position(|stored_request| *stored_request == request) {
-                    inner.requests.remove(position);
-                }
+                inner.remove_request(&request);
 
                 bail!(OPERATION_CANCELED_MSG)
             }
@@ -355,6 +429,17 @@ impl RequestsTimeoutManager {
         })
     }
 
+    /// Synchronise counters of rate limits with usage reported by exchange
+    pub fn sync_rate_limits_usage(
+        &self,
+        rate_limits_usage: &[RateLimitUsage],
+        current_time: DateTime,
+    ) -> Result<()> {
+        self.inner
+            .lock()
+            .sync_rate_limits_usage(rate_limits_usage, current_time)
+    }
+
     pub fn register_trigger_on_more_or_equals(
         &self,
         available_requests_count_threshold: usize,
@@ -404,7 +489,7 @@ mod test {
     use crate::core::exchanges::timeouts::requests_timeout_manager_factory::{
         RequestTimeoutArguments, RequestsTimeoutManagerFactory,
     };
-    use chrono::Utc;
+    use chrono::{TimeZone, Utc};
 
     use super::*;
     use rstest::{fixture, rstest};
@@ -416,6 +501,7 @@ mod test {
         let timeout_manager = RequestsTimeoutManagerFactory::from_requests_per_period(
             RequestTimeoutArguments::from_requests_per_minute(requests_per_period),
             exchange_account_id,
+            None,
         );
 
         timeout_manager
@@ -2293,6 +2379,7 @@ mod test {
             let timeout_manager = RequestsTimeoutManagerFactory::from_requests_per_period(
                 RequestTimeoutArguments::new(requests_per_period, Duration::milliseconds(1)),
                 exchange_account_id,
+                None,
             );
 
             timeout_manager
@@ -2555,4 +2642,182 @@ mod test {
             Ok(())
         }
     }
+
+    mod rate_limits {
+        use super::*;
+        use crate::core::exchanges::timeouts::rate_limit::RateLimitKind;
+        use crate::hashmap;
+
+        #[fixture]
+        fn timeout_manager() -> Arc<RequestsTimeoutManager> {
+            let exchange_account_id = ExchangeAccountId::new("test_exchange_account_id".into(), 0);
+            RequestsTimeoutManagerFactory::from_requests_per_period(
+                RequestTimeoutArguments::from_requests_per_minute(10)
+                    .with_request_weights(hashmap![RequestType::GetOpenOrders => 4])
+                    .with_additional_limit(RateLimit::new(
+                        RateLimitKind::OrdersCount,
+                        2,
+                        Duration::seconds(10),
+                    )),
+                exchange_account_id,
+                None,
+            )
+        }
+
+        #[rstest]
+        fn heavy_requests_consume_weight(
+            timeout_manager: Arc<RequestsTimeoutManager>,
+        ) -> Result<()> {
+            let current_time = Utc::now();
+
+            for _ in 0..2 {
+                assert!(timeout_manager.try_reserve_instant(
+                    RequestType::GetOpenOrders,
+                    current_time,
+                    None
+                )?);
+            }
+            assert!(!timeout_manager.try_reserve_instant(
+                RequestType::GetOpenOrders,
+                current_time,
+                None
+            )?);
+            assert!(timeout_manager.try_reserve_instant(
+                RequestType::GetBalance,
+                current_time,
+                None
+            )?);
+
+            Ok(())
+        }
+
+        #[rstest]
+        fn orders_count_is_limited_separately(
+            timeout_manager: Arc<RequestsTimeoutManager>,
+        ) -> Result<()> {
+            let current_time = Utc::now();
+
+            for _ in 0..2 {
+                assert!(timeout_manager.try_reserve_instant(
+                    RequestType::CreateOrder,
+                    current_time,
+                    None
+                )?);
+            }
+            assert!(!timeout_manager.try_reserve_instant(
+                RequestType::CreateOrder,
+                current_time,
+                None
+            )?);
+            assert!(timeout_manager.try_reserve_instant(
+                RequestType::CancelOrder,
+                current_time,
+                None
+            )?);
+
+            Ok(())
+        }
+
+        #[rstest]
+        #[tokio::test]
+        async fn reserve_when_available_waits_for_orders_limit(
+            timeout_manager: Arc<RequestsTimeoutManager>,
+        ) -> Result<()> {
+            let current_time = Utc::now();
+            for _ in 0..2 {
+                assert!(timeout_manager.try_reserve_instant(
+                    RequestType::CreateOrder,
+                    current_time,
+                    None
+                )?);
+            }
+
+            let (_, request_start_time, _) = timeout_manager.clone().reserve_when_available(
+                RequestType::CreateOrder,
+                current_time,
+                CancellationToken::default(),
+            )?;
+
+            let delay_to_next_time_period = timeout_manager.inner.lock().delay_to_next_time_period;
+            assert_eq!(
+                request_start_time,
+                current_time + Duration::seconds(10) + delay_to_next_time_period
+            );
+
+            Ok(())
+        }
+
+        #[rstest]
+        fn usage_is_synchronised_with_exchange(
+            timeout_manager: Arc<RequestsTimeoutManager>,
+        ) -> Result<()> {
+            let current_time = Utc::now();
+            assert!(timeout_manager.try_reserve_instant(
+                RequestType::GetBalance,
+                current_time,
+                None
+            )?);
+
+            timeout_manager.sync_rate_limits_usage(
+                &[
+                    RateLimitUsage::new(RateLimitKind::RequestWeight, Duration::minutes(1), 9),
+                    RateLimitUsage::new(RateLimitKind::OrdersCount, Duration::seconds(10), 2),
+                ],
+                current_time,
+            )?;
+
+            assert_eq!(
+                timeout_manager
+                    .inner
+                    .lock()
+                    .get_all_available_requests_count(),
+                1
+            );
+            assert!(!timeout_manager.try_reserve_instant(
+                RequestType::CreateOrder,
+                current_time,
+                None
+            )?);
+            assert!(timeout_manager.try_reserve_instant(
+                RequestType::GetBalance,
+                current_time,
+                None
+            )?);
+
+            Ok(())
+        }
+
+        #[rstest]
+        fn synchronised_usage_expires_on_minute_boundary(
+            timeout_manager: Arc<RequestsTimeoutManager>,
+        ) -> Result<()> {
+            let current_time = Utc.ymd(2021, 1, 1).and_hms(12, 0, 50);
+            timeout_manager.sync_rate_limits_usage(
+                &[RateLimitUsage::new(
+                    RateLimitKind::RequestWeight,
+                    Duration::minutes(1),
+                    9,
+                )],
+                current_time,
+            )?;
+
+            assert!(!timeout_manager.try_reserve_instant(
+                RequestType::GetOpenOrders,
+                current_time + Duration::seconds(5),
+                None
+            )?);
+
+            // Exchange resets used weight at the start of minute rather than a minute after synchronisation
+            let next_minute = Utc.ymd(2021, 1, 1).and_hms(12, 1, 1);
+            for _ in 0..2 {
+                assert!(timeout_manager.try_reserve_instant(
+                    RequestType::GetOpenOrders,
+                    next_minute,
+                    None
+                )?);
+            }
+
+            Ok(())
+        }
+    }
 }
@@ -1,15 +1,19 @@
 use std::{
+    collections::HashMap,
     fmt::{self, Display, Formatter},
     sync::Arc,
 };
 
 use chrono::{Duration, Utc};
 
-use crate::core::{exchanges::common::ExchangeAccountId, DateTime};
+use crate::core::{
+    data_recorder::recorder::DataRecorder, exchanges::common::ExchangeAccountId,
+    exchanges::general::request_type::RequestType, DateTime,
+};
 
 use super::{
     more_or_equals_available_requests_count_trigger_scheduler::MoreOrEqualsAvailableRequestsCountTriggerScheduler,
-    requests_timeout_manager::RequestsTimeoutManager,
+    rate_limit::RateLimit, requests_timeout_manager::RequestsTimeoutManager,
 };
 
 pub struct RequestsTimeoutManagerFactory {}
@@ -22,20 +26,29 @@ impl RequestsTimeoutManagerFactory {
     pub fn from_requests_per_period(
         timeout_arguments: RequestTimeoutArguments,
         exchange_account_id: ExchangeAccountId,
+        data_recorder: Option<Arc<DataRecorder>>,
     ) -> Arc<RequestsTimeoutManager> {
         let trigger_scheduler = MoreOrEqualsAvailableRequestsCountTriggerScheduler::new();
         RequestsTimeoutManager::new(
             timeout_arguments.requests_per_period,
             timeout_arguments.period,
+            timeout_arguments.request_weights,
+            timeout_arguments.additional_limits,
             exchange_account_id,
             trigger_scheduler,
+            data_recorder,
         )
     }
 }
 
 pub struct RequestTimeoutArguments {
+    /// Max sum of request weights in period
     pub requests_per_period: usize,
     pub period: Duration,
+    /// Weights of requests by type. Weight is 1 for request types which aren't specified
+    pub request_weights: HashMap<RequestType, usize>,
+    /// Exchange limits which are tracked in addition to requests per period
+    pub additional_limits: Vec<RateLimit>,
 }
 
 impl RequestTimeoutArguments {
@@ -43,9 +56,21 @@ impl RequestTimeoutArguments {
         Self {
             requests_per_period,
             period,
+            request_weights: HashMap::new(),
+            additional_limits: Vec::new(),
         }
     }
 
+    pub fn with_request_weights(mut self, request_weights: HashMap<RequestType, usize>) -> Self {
+        self.request_weights = request_weights;
+        self
+    }
+
+    pub fn with_additional_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.additional_limits.push(rate_limit);
+        self
+    }
+
     pub fn unlimited() -> RequestTimeoutArguments {
         Self::from_requests_per_second(usize::MAX)
     }
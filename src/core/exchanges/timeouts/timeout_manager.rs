@@ -13,6 +13,7 @@ use log::error;
 
 use crate::core::exchanges::common::ExchangeAccountId;
 use crate::core::exchanges::general::request_type::RequestType;
+use crate::core::exchanges::timeouts::rate_limit::RateLimitUsage;
 use crate::core::exchanges::timeouts::requests_timeout_manager::{
     RequestGroupId, RequestsTimeoutManager,
 };
@@ -75,6 +76,14 @@ impl TimeoutManager {
         )
     }
 
+    pub fn sync_rate_limits_usage(
+        &self,
+        exchange_account_id: &ExchangeAccountId,
+        rate_limits_usage: &[RateLimitUsage],
+    ) -> Result<()> {
+        self.inner[exchange_account_id].sync_rate_limits_usage(rate_limits_usage, now())
+    }
+
     pub fn reserve_when_available(
         &self,
         exchange_account_id: &ExchangeAccountId,
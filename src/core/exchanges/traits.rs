@@ -16,9 +16,10 @@ use super::{
     general::currency_pair_metadata::BeforeAfter,
     general::handlers::handle_order_filled::FillEventData,
     general::{currency_pair_metadata::CurrencyPairMetadata, order::get_order_trades::OrderTrade},
+    timeouts::rate_limit::RateLimitUsage,
     timeouts::requests_timeout_manager_factory::RequestTimeoutArguments,
 };
-use crate::core::exchanges::events::ExchangeEvent;
+use crate::core::exchanges::events::{ExchangeBalancesAndPositions, ExchangeEvent};
 use crate::core::exchanges::general::features::ExchangeFeatures;
 use crate::core::lifecycle::application_manager::ApplicationManager;
 use crate::core::orders::fill::EventSourceType;
@@ -31,6 +32,7 @@ use crate::core::{
 };
 use crate::core::{exchanges::general::exchange::BoxExchangeClient, orders::pool::OrderRef};
 use awc::http::Uri;
+use tokio::time::Duration;
 
 // Implementation of rest API client
 #[async_trait]
@@ -43,6 +45,13 @@ pub trait ExchangeClient: Support {
 
     async fn cancel_all_orders(&self, currency_pair: CurrencyPair) -> Result<()>;
 
+    /// Exchange cancels all orders for currency pair if countdown isn't refreshed until it's expired
+    async fn request_cancel_all_orders_countdown(
+        &self,
+        currency_pair: CurrencyPair,
+        countdown: Duration,
+    ) -> Result<RestRequestOutcome>;
+
     async fn request_open_orders(&self) -> Result<RestRequestOutcome>;
 
     async fn request_open_orders_by_currency_pair(
@@ -57,6 +66,13 @@ pub trait ExchangeClient: Support {
         currency_pair_metadata: &CurrencyPairMetadata,
         last_date_time: Option<DateTime>,
     ) -> Result<RestRequestOutcome>;
+
+    async fn request_balance(&self) -> Result<RestRequestOutcome>;
+
+    async fn request_order_book_snapshot(
+        &self,
+        currency_pair: CurrencyPair,
+    ) -> Result<RestRequestOutcome>;
 }
 
 #[async_trait]
@@ -90,6 +106,12 @@ pub trait Support: Send + Sync {
         >,
     );
 
+    /// Exchange client doesn't request order book snapshots itself, so requests are rate limited by Exchange
+    fn set_order_book_snapshot_requested_callback(
+        &self,
+        callback: Box<dyn FnMut(CurrencyPair) + Send + Sync>,
+    );
+
     fn set_traded_specific_currencies(&self, currencies: Vec<SpecificCurrencyPair>);
 
     fn is_websocket_enabled(&self, role: WebSocketRole) -> bool;
@@ -127,6 +149,26 @@ pub trait Support: Send + Sync {
         last_date_time: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<Vec<OrderTrade>>;
 
+    fn parse_balance(&self, response: &RestRequestOutcome) -> Result<ExchangeBalancesAndPositions>;
+
+    /// Usage of rate limits reported by exchange in response headers
+    fn parse_rate_limits_usage(&self, _response: &RestRequestOutcome) -> Vec<RateLimitUsage> {
+        Vec::new()
+    }
+
+    /// Request new order book snapshot for currency pair, because local one is invalid
+    fn request_order_book_resync(&self, currency_pair: &CurrencyPair);
+
+    /// Response for request from order book snapshot requested callback
+    fn handle_order_book_snapshot_response(
+        &self,
+        currency_pair: &CurrencyPair,
+        response: &RestRequestOutcome,
+    ) -> Result<()>;
+
+    /// Order book snapshot request from order book snapshot requested callback is failed
+    fn on_order_book_snapshot_failed(&self, currency_pair: &CurrencyPair);
+
     fn get_settings(&self) -> &ExchangeSettings;
 }
 
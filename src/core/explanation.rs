@@ -1,3 +1,4 @@
+use serde::Serialize;
 use std::fmt::{Debug, Formatter};
 
 pub struct Reason(Option<String>);
@@ -26,7 +27,7 @@ impl From<Option<&str>> for Reason {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct Explanation {
     reasons: Vec<String>,
 }
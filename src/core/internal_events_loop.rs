@@ -2,43 +2,65 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
-use log::warn;
+use log::{error, warn};
 use parking_lot::Mutex;
 use tokio::sync::{broadcast, oneshot};
+use tokio::time::{interval, Duration, Instant};
 
+use crate::core::balance_manager::balance_manager::BalanceManager;
 use crate::core::exchanges::common::ExchangeAccountId;
-use crate::core::exchanges::events::ExchangeEvent;
+use crate::core::exchanges::events::{BalanceUpdateEvent, ExchangeEvent};
 use crate::core::exchanges::general::exchange::{Exchange, OrderBookTop, PriceLevel};
 use crate::core::lifecycle::cancellation_token::CancellationToken;
 use crate::core::lifecycle::trading_engine::Service;
+use crate::core::market_data_watchdog::MarketDataWatchdog;
 use crate::core::order_book::event::OrderBookEvent;
-use crate::core::order_book::local_snapshot_service::LocalSnapshotsService;
-use crate::core::orders::order::OrderType;
+use crate::core::order_book::local_snapshot_service::{LocalSnapshotsService, SnapshotUpdate};
+use crate::core::orders::event::OrderEventType;
+use crate::core::orders::order::{OrderSnapshot, OrderType};
+use crate::core::risk_manager::RiskManager;
+
+// Period of heartbeat updating when there are no events
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
 
 pub(crate) struct InternalEventsLoop {
     work_finished_receiver: Mutex<Option<oneshot::Receiver<Result<()>>>>,
+    last_heartbeat: Mutex<Instant>,
 }
 
 impl InternalEventsLoop {
     pub(crate) fn new() -> Arc<Self> {
         Arc::new(InternalEventsLoop {
             work_finished_receiver: Default::default(),
+            last_heartbeat: Mutex::new(Instant::now()),
         })
     }
 
+    /// Time since the last iteration of events loop. It grows if the loop is stalled
+    pub(crate) fn time_since_heartbeat(&self) -> Duration {
+        self.last_heartbeat.lock().elapsed()
+    }
+
     pub async fn start(
         self: Arc<Self>,
         mut events_receiver: broadcast::Receiver<ExchangeEvent>,
         exchanges_map: HashMap<ExchangeAccountId, Arc<Exchange>>,
+        balance_manager: Arc<Mutex<BalanceManager>>,
+        risk_manager: Option<Arc<RiskManager>>,
+        market_data_watchdog: Option<Arc<MarketDataWatchdog>>,
         cancellation_token: CancellationToken,
     ) -> Result<()> {
         let mut local_snapshots_service = LocalSnapshotsService::default();
         let (work_finished_sender, receiver) = oneshot::channel();
         *self.work_finished_receiver.lock() = Some(receiver);
 
+        let mut heartbeat_interval = interval(HEARTBEAT_INTERVAL);
         loop {
+            *self.last_heartbeat.lock() = Instant::now();
+
             let event = tokio::select! {
                 event_res = events_receiver.recv() => event_res.context("Error during receiving event in InternalEventsLoop::start()")?,
+                _ = heartbeat_interval.tick() => continue,
                 _ = cancellation_token.when_cancelled() => {
                     let _ = work_finished_sender.send(Ok(()));
                     return Ok(());
@@ -51,15 +73,22 @@ impl InternalEventsLoop {
                         order_book_event,
                         &mut local_snapshots_service,
                         &exchanges_map,
+                        &market_data_watchdog,
                     )
                 }
                 ExchangeEvent::OrderEvent(order_event) => {
                     if let OrderType::Liquidation = order_event.order.order_type() {
                         // TODO react on order liquidation
                     }
+
+                    if let (Some(risk_manager), OrderEventType::OrderFilled { cloned_order }) =
+                        (&risk_manager, &order_event.event_type)
+                    {
+                        update_risk_manager_position(risk_manager, cloned_order, &exchanges_map)
+                    }
                 }
-                ExchangeEvent::BalanceUpdate(_) => {
-                    // TODO add update exchange balance
+                ExchangeEvent::BalanceUpdate(balance_update_event) => {
+                    update_exchange_balance(balance_update_event, &balance_manager)
                 }
                 ExchangeEvent::LiquidationPrice(_) => {}
                 ExchangeEvent::Trades(_) => {}
@@ -72,13 +101,28 @@ fn update_order_book_top_for_exchange(
     order_book_event: OrderBookEvent,
     local_snapshots_service: &mut LocalSnapshotsService,
     exchanges_map: &HashMap<ExchangeAccountId, Arc<Exchange>>,
+    market_data_watchdog: &Option<Arc<MarketDataWatchdog>>,
 ) {
-    let trade_place_account = local_snapshots_service.update(order_book_event);
+    let trade_place_account = match local_snapshots_service.apply(order_book_event) {
+        SnapshotUpdate::Updated(trade_place_account) => Some(trade_place_account),
+        SnapshotUpdate::Invalidated(trade_place_account, _) => {
+            if let Some(exchange) = exchanges_map.get(&trade_place_account.exchange_account_id) {
+                exchange.request_order_book_resync(&trade_place_account.currency_pair);
+            }
+            None
+        }
+        SnapshotUpdate::Skipped => None,
+    };
     if let Some(trade_place_account) = &trade_place_account {
+        let trade_place = trade_place_account.trade_place();
         let snapshot = local_snapshots_service
-            .get_snapshot(&trade_place_account.trade_place())
+            .get_snapshot(&trade_place)
             .expect("snapshot should exists because we just added one");
 
+        if let Some(market_data_watchdog) = market_data_watchdog {
+            market_data_watchdog.order_book_updated(&trade_place, snapshot.last_update_time);
+        }
+
         let order_book_top = OrderBookTop {
             ask: snapshot
                 .get_top_ask()
@@ -98,6 +142,41 @@ fn update_order_book_top_for_exchange(
     }
 }
 
+fn update_risk_manager_position(
+    risk_manager: &RiskManager,
+    order: &OrderSnapshot,
+    exchanges_map: &HashMap<ExchangeAccountId, Arc<Exchange>>,
+) {
+    let currency_pair_metadata = exchanges_map
+        .get(&order.header.exchange_account_id)
+        .with_context(|| format!("Unknown exchange {}", order.header.exchange_account_id))
+        .and_then(|exchange| exchange.get_currency_pair_metadata(&order.header.currency_pair));
+
+    match currency_pair_metadata {
+        Ok(currency_pair_metadata) => risk_manager.order_filled(order, &currency_pair_metadata),
+        Err(error) => error!(
+            "Unable to update risk manager position by order {}: {:?}",
+            order.header.client_order_id, error
+        ),
+    }
+}
+
+fn update_exchange_balance(
+    balance_update_event: BalanceUpdateEvent,
+    balance_manager: &Mutex<BalanceManager>,
+) {
+    let update_result = balance_manager.lock().update_exchange_balance(
+        &balance_update_event.exchange_account_id,
+        &balance_update_event.balances_and_positions,
+    );
+    if let Err(error) = update_result {
+        error!(
+            "Unable to update balances for {}: {:?}",
+            balance_update_event.exchange_account_id, error
+        );
+    }
+}
+
 impl Service for InternalEventsLoop {
     fn name(&self) -> &str {
         "InternalEventsLoop"
@@ -117,7 +117,7 @@ mod tests {
         token.cancel();
 
         // we need a little wait while spawned `working_future` react for cancellation
-        tokio::task::yield_now().await;
+        let _ = tokio::task::yield_now().await;
 
         assert_eq!(*signal.lock(), true);
         assert_eq!(token.is_cancellation_requested(), true);
@@ -143,7 +143,7 @@ mod tests {
         token.cancel();
 
         // we need a little wait while spawned `working_future` react for cancellation
-        tokio::task::yield_now().await;
+        let _ = tokio::task::yield_now().await;
 
         assert_eq!(*signal1.lock(), true);
         assert_eq!(*signal2.lock(), true);
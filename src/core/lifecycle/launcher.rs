@@ -1,8 +1,17 @@
-use crate::core::exchanges::common::{ExchangeAccountId, ExchangeId};
+use crate::core::balance_manager::balance_manager::BalanceManager;
+use crate::core::data_recorder::recorder::DataRecorder;
+use crate::core::exchanges::common::{
+    ExchangeAccountId, ExchangeId, TradePlace, TradePlaceAccount,
+};
 use crate::core::exchanges::events::{ExchangeEvent, ExchangeEvents, CHANNEL_MAX_EVENTS_COUNT};
+use crate::core::exchanges::exchange_blocker::ExchangeBlocker;
+use crate::core::exchanges::general::currency_pair_to_metadata_converter::CurrencyPairToMetadataConverter;
+use crate::core::exchanges::general::dead_man_switch::DeadManSwitch;
 use crate::core::exchanges::general::exchange::Exchange;
 use crate::core::exchanges::general::exchange_creation::create_exchange;
 use crate::core::exchanges::general::exchange_creation::create_timeout_manager;
+use crate::core::exchanges::paper::paper_exchange::{PaperExchangeBuilder, PaperExchangeSettings};
+use crate::core::exchanges::recording::raw_messages_recorder::RawMessagesRecorder;
 use crate::core::exchanges::timeouts::timeout_manager::TimeoutManager;
 use crate::core::exchanges::traits::ExchangeClientBuilder;
 use crate::core::internal_events_loop::InternalEventsLoop;
@@ -11,10 +20,17 @@ use crate::core::lifecycle::cancellation_token::CancellationToken;
 use crate::core::lifecycle::trading_engine::{EngineContext, TradingEngine};
 use crate::core::logger::init_logger;
 use crate::core::order_book::local_snapshot_service::LocalSnapshotsService;
-use crate::core::settings::{AppSettings, BaseStrategySettings, CoreSettings};
+use crate::core::orders::order::OrderSnapshot;
+use crate::core::orders::pool::OrderRef;
+use crate::core::service_configuration::configuration_descriptor::ConfigurationDescriptor;
+use crate::core::settings::{
+    AppSettings, BaseStrategySettings, ConfiguredStrategySettings, CoreSettings, ExchangeSettings,
+    OrdersCancellationSettings, RestoredOrdersPolicy, SelfTradePreventionMode,
+    StrategyInstanceSettings, TargetAmountLimitSettings,
+};
 use crate::core::{config::load_settings, statistic_service::StatisticEventHandler};
 use crate::core::{
-    disposition_execution::executor::DispositionExecutorService,
+    disposition_execution::{executor::DispositionExecutorService, validate_price_slots},
     infrastructure::{keep_application_manager, spawn_future},
 };
 use crate::core::{
@@ -22,12 +38,21 @@ use crate::core::{
 };
 use crate::hashmap;
 use crate::rest_api::control_panel::ControlPanel;
-use crate::strategies::disposition_strategy::DispositionStrategy;
-use anyhow::{anyhow, Result};
+use crate::strategies::cross_exchange_market_making::{
+    CrossExchangeMarketMakingSettings, CrossExchangeMarketMakingStrategy,
+};
+use crate::strategies::disposition_strategy::{
+    DispositionStrategy, ExampleStrategy, ExampleStrategySettings,
+};
+use crate::strategies::pure_market_making::{PureMarketMakingSettings, PureMarketMakingStrategy};
+use crate::strategies::strategy_builder::{StrategyBuilder, TypedStrategyBuilder};
+use anyhow::{anyhow, bail, Context, Result};
 use core::fmt::Debug;
 use dashmap::DashMap;
 use futures::{future::join_all, FutureExt};
-use log::info;
+use itertools::Itertools;
+use log::{error, info, warn};
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::collections::HashMap;
@@ -36,21 +61,60 @@ use std::panic::{self, AssertUnwindSafe};
 use std::sync::Arc;
 use tokio::signal;
 use tokio::sync::{broadcast, oneshot};
+use tokio::time::{timeout_at, Duration, Instant};
 
 pub struct EngineBuildConfig {
     pub supported_exchange_clients: HashMap<ExchangeId, Box<dyn ExchangeClientBuilder + 'static>>,
+    /// Strategies by their names for `ConfiguredStrategySettings::strategy_type`
+    pub supported_strategies: HashMap<String, Box<dyn StrategyBuilder + 'static>>,
 }
 
 impl EngineBuildConfig {
     pub fn standard() -> Self {
         let exchange_name = "Binance".into();
-        let supported_exchange_clients =
-            hashmap![exchange_name => Box::new(BinanceBuilder) as Box<dyn ExchangeClientBuilder>];
+        let paper_exchange_name = "PaperBinance".into();
+        let paper_exchange_builder =
+            PaperExchangeBuilder::new(Box::new(BinanceBuilder), PaperExchangeSettings::default());
+        let supported_exchange_clients = hashmap![
+            exchange_name => Box::new(BinanceBuilder) as Box<dyn ExchangeClientBuilder>,
+            paper_exchange_name => Box::new(paper_exchange_builder) as Box<dyn ExchangeClientBuilder>
+        ];
+
+        let supported_strategies = hashmap![
+            "ExampleStrategy".to_owned() => Box::new(TypedStrategyBuilder::new(
+                |settings: ExampleStrategySettings, engine_context| {
+                    Box::new(ExampleStrategy::new(
+                        settings.exchange_account_id,
+                        settings.currency_pair,
+                        settings.spread,
+                        engine_context,
+                    ))
+                },
+            )) as Box<dyn StrategyBuilder>,
+            "PureMarketMaking".to_owned() => Box::new(TypedStrategyBuilder::new(
+                |settings: PureMarketMakingSettings, engine_context| {
+                    Box::new(PureMarketMakingStrategy::new(settings, engine_context))
+                },
+            )) as Box<dyn StrategyBuilder>,
+            "CrossExchangeMarketMaking".to_owned() => Box::new(TypedStrategyBuilder::new(
+                |settings: CrossExchangeMarketMakingSettings, engine_context| {
+                    Box::new(CrossExchangeMarketMakingStrategy::new(settings, engine_context))
+                },
+            )) as Box<dyn StrategyBuilder>
+        ];
 
         EngineBuildConfig {
             supported_exchange_clients,
+            supported_strategies,
         }
     }
+
+    fn get_strategy_builder(&self, strategy_type: &str) -> Result<&dyn StrategyBuilder> {
+        self.supported_strategies
+            .get(strategy_type)
+            .map(|x| x.as_ref())
+            .with_context(|| format!("Strategy type {} isn't supported", strategy_type))
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -72,6 +136,7 @@ async fn before_enging_context_init<'a, StrategySettings>(
     DashMap<ExchangeAccountId, Arc<Exchange>>,
     Arc<EngineContext>,
     oneshot::Receiver<()>,
+    Vec<OrderRef>,
 )>
 where
     StrategySettings: BaseStrategySettings + Clone + Debug + Deserialize<'a> + Serialize,
@@ -87,21 +152,88 @@ where
             load_settings::<StrategySettings>(&config_path, &credentials_path)?
         }
     };
+    // Invalid strategies settings should stop launch before connection to exchanges
+    validate_strategies(&settings.strategies)?;
 
     let application_manager = ApplicationManager::new(CancellationToken::new());
     keep_application_manager(application_manager.clone());
     let (events_sender, events_receiver) = broadcast::channel(CHANNEL_MAX_EVENTS_COUNT);
 
-    let timeout_manager = create_timeout_manager(&settings.core, &build_settings);
-    let exchanges = create_exchanges(
+    // Orders of previous launches should be loaded before new records are written
+    let persisted_orders = settings
+        .core
+        .data_recorder
+        .as_ref()
+        .map(DataRecorder::load_order_snapshots)
+        .transpose()?;
+
+    let data_recorder = settings
+        .core
+        .data_recorder
+        .as_ref()
+        .map(DataRecorder::new)
+        .transpose()?;
+
+    let timeout_manager = create_timeout_manager(&settings.core, &build_settings, &data_recorder);
+    let exchange_blocker = ExchangeBlocker::new(
+        settings
+            .core
+            .exchanges
+            .iter()
+            .map(|x| x.exchange_account_id.clone())
+            .collect_vec(),
+    );
+    let (exchanges, raw_messages_recorders) = create_exchanges(
         &settings.core,
         build_settings,
         events_sender.clone(),
         application_manager.clone(),
         &timeout_manager,
+        &data_recorder,
+        &exchange_blocker,
     )
     .await;
 
+    let restored_orders = match (&settings.core.data_recorder, persisted_orders) {
+        (Some(data_recorder_settings), Some(persisted_orders)) => {
+            let restored_orders = restore_orders(&exchanges, persisted_orders).await?;
+            let strategies_trade_places = settings
+                .strategies
+                .iter()
+                .map(|x| strategy_trade_place(&x.settings))
+                .collect_vec();
+            apply_restored_orders_policy(
+                data_recorder_settings.restored_orders_policy,
+                &settings.core.orders_cancellation,
+                &strategies_trade_places,
+                &exchanges,
+                restored_orders,
+            )
+            .await
+        }
+        _ => Vec::new(),
+    };
+
+    let exchanges_by_id: HashMap<_, _> = exchanges
+        .iter()
+        .map(|exchange| (exchange.exchange_account_id.clone(), exchange.clone()))
+        .collect();
+    let balance_manager = BalanceManager::new(
+        exchanges_by_id.clone(),
+        CurrencyPairToMetadataConverter::new(exchanges_by_id.clone()),
+        data_recorder.clone(),
+    );
+    // Limits are set before any service is started, because they need only exchanges metadata
+    for strategy_settings in &settings.strategies {
+        set_target_amount_limits(
+            &exchanges_by_id,
+            &balance_manager,
+            &Arc::new(strategy_settings.configuration_descriptor()),
+            &strategy_settings.target_amount_limits,
+        )?;
+    }
+    request_balances(&exchanges, &balance_manager).await;
+
     let exchanges_map: DashMap<_, _> = exchanges
         .into_iter()
         .map(|exchange| (exchange.exchange_account_id.clone(), exchange))
@@ -117,7 +249,15 @@ where
         finish_graceful_shutdown_tx,
         timeout_manager,
         application_manager.clone(),
+        data_recorder,
+        balance_manager,
+        exchange_blocker,
     );
+    for raw_messages_recorder in raw_messages_recorders {
+        engine_context
+            .shutdown_service
+            .register_service(raw_messages_recorder);
+    }
 
     Ok((
         events_sender,
@@ -126,9 +266,121 @@ where
         exchanges_map,
         engine_context,
         finish_graceful_shutdown_rx,
+        restored_orders,
     ))
 }
 
+/// Request balances of all exchanges to know them before strategy start
+async fn request_balances(exchanges: &[Arc<Exchange>], balance_manager: &Mutex<BalanceManager>) {
+    let balances = join_all(
+        exchanges
+            .iter()
+            .map(|exchange| exchange.get_balance(CancellationToken::default())),
+    )
+    .await;
+
+    for (exchange, balances_and_positions) in exchanges.iter().zip(balances) {
+        let update_result = balances_and_positions.and_then(|balances_and_positions| {
+            balance_manager
+                .lock()
+                .update_exchange_balance(&exchange.exchange_account_id, &balances_and_positions)
+        });
+        if let Err(error) = update_result {
+            error!(
+                "Unable to get balances for {}: {:?}",
+                exchange.exchange_account_id, error
+            );
+        }
+    }
+}
+
+/// Reconcile orders of previous launches with exchanges and return orders which are still open
+async fn restore_orders(
+    exchanges: &[Arc<Exchange>],
+    persisted_orders: Vec<OrderSnapshot>,
+) -> Result<Vec<OrderRef>> {
+    let restored_orders = join_all(
+        exchanges
+            .iter()
+            .map(|exchange| exchange.restore_orders(persisted_orders.clone())),
+    )
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>>>()?;
+
+    let restored_orders = restored_orders.into_iter().flatten().collect_vec();
+    info!("Restored {} open orders", restored_orders.len());
+
+    Ok(restored_orders)
+}
+
+fn strategy_trade_place(strategy_settings: &dyn BaseStrategySettings) -> TradePlaceAccount {
+    TradePlaceAccount::new(
+        strategy_settings.exchange_account_id(),
+        strategy_settings.currency_pair(),
+    )
+}
+
+/// Cancel restored orders which can't be adopted by any strategy and return orders for adoption.
+/// Cancellation is stopped after deadline of orders cancellation settings
+async fn apply_restored_orders_policy(
+    policy: RestoredOrdersPolicy,
+    cancellation_settings: &OrdersCancellationSettings,
+    strategies_trade_places: &[TradePlaceAccount],
+    exchanges: &[Arc<Exchange>],
+    restored_orders: Vec<OrderRef>,
+) -> Vec<OrderRef> {
+    let (orders_to_adopt, orders_to_cancel): (Vec<_>, Vec<_>) =
+        restored_orders.into_iter().partition(|order| {
+            policy == RestoredOrdersPolicy::Adopt
+                && strategies_trade_places.contains(&order.trade_place_account())
+        });
+
+    let deadline = Instant::now() + Duration::from_secs(cancellation_settings.deadline_secs);
+    let cancellation_token = CancellationToken::new();
+    let cancellations = join_all(orders_to_cancel.into_iter().filter_map(|order| {
+        let exchange = exchanges
+            .iter()
+            .find(|x| x.exchange_account_id == order.exchange_account_id());
+        match exchange {
+            Some(exchange) => {
+                info!("Cancelling restored order {}", order.client_order_id());
+                let cancellation_token = cancellation_token.clone();
+                Some(async move {
+                    let client_order_id = order.client_order_id();
+                    if let Err(error) = exchange
+                        .wait_cancel_order(order, None, true, cancellation_token)
+                        .await
+                    {
+                        error!(
+                            "Unable to cancel restored order {}: {:?}",
+                            client_order_id, error
+                        );
+                    }
+                })
+            }
+            None => {
+                warn!(
+                    "Exchange {} of restored order {} not found",
+                    order.exchange_account_id(),
+                    order.client_order_id()
+                );
+                None
+            }
+        }
+    }));
+
+    if timeout_at(deadline, cancellations).await.is_err() {
+        cancellation_token.cancel();
+        error!(
+            "Restored orders aren't cancelled in {} seconds",
+            cancellation_settings.deadline_secs
+        );
+    }
+
+    orders_to_adopt
+}
+
 fn run_services<'a, StrategySettings>(
     engine_context: Arc<EngineContext>,
     events_sender: broadcast::Sender<ExchangeEvent>,
@@ -136,14 +388,21 @@ fn run_services<'a, StrategySettings>(
     settings: AppSettings<StrategySettings>,
     exchanges_map: DashMap<ExchangeAccountId, Arc<Exchange>>,
     build_strategy: impl Fn(
-        &AppSettings<StrategySettings>,
+        &StrategySettings,
         Arc<EngineContext>,
     ) -> Box<dyn DispositionStrategy + 'static>,
     finish_graceful_shutdown_rx: oneshot::Receiver<()>,
+    restored_orders: Vec<OrderRef>,
 ) -> Result<TradingEngine>
 where
     StrategySettings: BaseStrategySettings + Clone + Debug + Deserialize<'a> + Serialize,
 {
+    if let Some(data_recorder) = &engine_context.data_recorder {
+        engine_context
+            .shutdown_service
+            .register_service(data_recorder.clone());
+    }
+
     let internal_events_loop = InternalEventsLoop::new();
     engine_context
         .shutdown_service
@@ -158,6 +417,8 @@ where
         toml::Value::try_from(settings.clone())?.to_string(),
         engine_context.application_manager.clone(),
         statistic_service.clone(),
+        engine_context.orders_cancellation_report(),
+        engine_context.market_data_watchdog.clone(),
     );
     engine_context
         .shutdown_service
@@ -168,25 +429,94 @@ where
         let action = internal_events_loop.clone().start(
             events_receiver,
             local_exchanges_map,
+            engine_context.balance_manager.clone(),
+            engine_context.risk_manager.clone(),
+            engine_context.market_data_watchdog.clone(),
             engine_context.application_manager.stop_token(),
         );
-        let _ = spawn_future("internal_events_loop start", true, action.boxed());
+        spawn_future("internal_events_loop start", true, action.boxed());
+    }
+
+    if let Some(market_data_watchdog) = &engine_context.market_data_watchdog {
+        engine_context
+            .shutdown_service
+            .register_service(market_data_watchdog.clone());
+
+        let trade_places = engine_context
+            .app_settings
+            .exchanges
+            .iter()
+            .filter(|x| x.subscribe_to_market_data)
+            .filter_map(|x| engine_context.exchanges.get(&x.exchange_account_id))
+            .flat_map(|exchange| {
+                exchange
+                    .symbols
+                    .iter()
+                    .map(|x| {
+                        TradePlace::new(
+                            exchange.exchange_account_id.exchange_id.clone(),
+                            x.key().clone(),
+                        )
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        let action = market_data_watchdog.clone().start(
+            trade_places,
+            engine_context.application_manager.stop_token(),
+        );
+        spawn_future("market_data_watchdog start", true, action.boxed());
+    }
+
+    if let Some(dead_man_switch_settings) = &engine_context.app_settings.dead_man_switch {
+        let dead_man_switch = DeadManSwitch::new(
+            dead_man_switch_settings.clone(),
+            engine_context.app_settings.orders_cancellation.clone(),
+            engine_context
+                .exchanges
+                .iter()
+                .map(|x| x.value().clone())
+                .collect_vec(),
+            internal_events_loop.clone(),
+            engine_context.exchange_blocker.clone(),
+        )?;
+        engine_context
+            .shutdown_service
+            .register_service(dead_man_switch.clone());
+
+        let action = dead_man_switch.start(engine_context.application_manager.stop_token());
+        spawn_future("dead_man_switch start", true, action.boxed());
     }
 
     if let Err(error) = control_panel.clone().start() {
         log::error!("Unable to start rest api: {}", error);
     }
 
-    let disposition_strategy = build_strategy(&settings, engine_context.clone());
-    let disposition_executor_service = create_disposition_executor_service(
-        &settings.strategy,
-        &engine_context,
-        disposition_strategy,
-        &statistic_event_handler.stats,
-    );
-    engine_context
-        .shutdown_service
-        .register_service(disposition_executor_service);
+    let mut restored_orders = restored_orders;
+    for strategy_settings in &settings.strategies {
+        let configuration_descriptor = Arc::new(strategy_settings.configuration_descriptor());
+
+        // Every restored order is adopted by the first strategy instance with its trade place
+        let trade_place = strategy_trade_place(&strategy_settings.settings);
+        let (strategy_restored_orders, other_restored_orders) = restored_orders
+            .into_iter()
+            .partition(|order| order.trade_place_account() == trade_place);
+        restored_orders = other_restored_orders;
+
+        let disposition_strategy =
+            build_strategy(&strategy_settings.settings, engine_context.clone());
+        let disposition_executor_service = create_disposition_executor_service(
+            &strategy_settings.settings,
+            configuration_descriptor,
+            &engine_context,
+            disposition_strategy,
+            &statistic_event_handler.stats,
+            strategy_restored_orders,
+        )?;
+        engine_context
+            .shutdown_service
+            .register_service(disposition_executor_service);
+    }
 
     info!("TradingEngine started");
     Ok(TradingEngine::new(
@@ -227,7 +557,7 @@ pub async fn launch_trading_engine<'a, StrategySettings>(
     build_settings: &EngineBuildConfig,
     init_user_settings: InitSettings<StrategySettings>,
     build_strategy: impl Fn(
-        &AppSettings<StrategySettings>,
+        &StrategySettings,
         Arc<EngineContext>,
     ) -> Box<dyn DispositionStrategy + 'static>,
 ) -> Result<TradingEngine>
@@ -249,6 +579,7 @@ where
         exchanges_map,
         engine_context,
         finish_graceful_shutdown_rx,
+        restored_orders,
     ) = unwrap_or_handle_panic(action_outcome, message_template, None)??;
 
     let cloned_application_manager = engine_context.application_manager.clone();
@@ -262,7 +593,7 @@ where
         Ok(())
     };
 
-    let _ = spawn_future("Start Ctrl-C handler", true, action.boxed());
+    spawn_future("Start Ctrl-C handler", true, action.boxed());
 
     let action_outcome = panic::catch_unwind(AssertUnwindSafe(|| {
         run_services(
@@ -273,6 +604,7 @@ where
             exchanges_map,
             build_strategy,
             finish_graceful_shutdown_rx,
+            restored_orders,
         )
     }));
 
@@ -284,23 +616,115 @@ where
     )?
 }
 
+/// Strategy instances should be distinguishable by their configuration descriptors
+fn validate_strategies<StrategySettings>(
+    strategies: &[StrategyInstanceSettings<StrategySettings>],
+) -> Result<()>
+where
+    StrategySettings: BaseStrategySettings + Clone,
+{
+    let duplicates = strategies
+        .iter()
+        .map(|x| x.configuration_descriptor())
+        .duplicates()
+        .collect_vec();
+    if !duplicates.is_empty() {
+        bail!(
+            "Configuration descriptors of strategies should be unique, but duplicated: {:?}",
+            duplicates
+        );
+    }
+
+    Ok(())
+}
+
+fn set_target_amount_limits(
+    exchanges_by_id: &HashMap<ExchangeAccountId, Arc<Exchange>>,
+    balance_manager: &Mutex<BalanceManager>,
+    configuration_descriptor: &Arc<ConfigurationDescriptor>,
+    target_amount_limits: &[TargetAmountLimitSettings],
+) -> Result<()> {
+    for target_amount_limit in target_amount_limits {
+        let exchange_account_id = &target_amount_limit.exchange_account_id;
+        let currency_pair_metadata = exchanges_by_id
+            .get(exchange_account_id)
+            .with_context(|| {
+                format!(
+                    "Exchange {} of target amount limit not found",
+                    exchange_account_id
+                )
+            })?
+            .get_currency_pair_metadata(&target_amount_limit.currency_pair)?;
+
+        balance_manager.lock().set_target_amount_limit(
+            configuration_descriptor.clone(),
+            exchange_account_id,
+            currency_pair_metadata,
+            target_amount_limit.limit,
+        );
+    }
+
+    Ok(())
+}
+
+/// Launch engine with strategies selected from `EngineBuildConfig::supported_strategies` by their types in settings
+pub async fn launch_configured_trading_engine(
+    build_settings: &EngineBuildConfig,
+    init_user_settings: InitSettings<ConfiguredStrategySettings>,
+) -> Result<TradingEngine> {
+    let mut settings = match init_user_settings {
+        InitSettings::Directly(v) => v,
+        InitSettings::Load(config_path, credentials_path) => {
+            load_settings::<ConfiguredStrategySettings>(&config_path, &credentials_path)?
+        }
+    };
+
+    // Strategy settings are checked before launch, so strategies creation can't fail later
+    for strategy_settings in &mut settings.strategies {
+        let strategy_settings = &mut strategy_settings.settings;
+        strategy_settings.price_slots = build_settings
+            .get_strategy_builder(&strategy_settings.strategy_type)?
+            .get_price_slots(strategy_settings)?;
+    }
+
+    launch_trading_engine(
+        build_settings,
+        InitSettings::Directly(settings),
+        |settings, engine_context| {
+            build_settings
+                .get_strategy_builder(&settings.strategy_type)
+                .and_then(|builder| builder.create_strategy(settings, engine_context))
+                .expect("Strategy settings should be checked before engine launch")
+        },
+    )
+    .await
+}
+
 fn create_disposition_executor_service(
     base_settings: &dyn BaseStrategySettings,
+    configuration_descriptor: Arc<ConfigurationDescriptor>,
     engine_context: &Arc<EngineContext>,
     disposition_strategy: Box<dyn DispositionStrategy>,
     statistics: &Arc<StatisticService>,
-) -> Arc<DispositionExecutorService> {
-    DispositionExecutorService::new(
+    restored_orders: Vec<OrderRef>,
+) -> Result<Arc<DispositionExecutorService>> {
+    let price_slots = base_settings.price_slots();
+    validate_price_slots(&price_slots)?;
+
+    Ok(DispositionExecutorService::new(
         engine_context.clone(),
         engine_context.get_events_channel(),
         LocalSnapshotsService::default(),
         base_settings.exchange_account_id(),
         base_settings.currency_pair(),
         base_settings.max_amount(),
+        price_slots,
+        configuration_descriptor,
         disposition_strategy,
         engine_context.application_manager.stop_token(),
         statistics.clone(),
-    )
+        restored_orders,
+    ))
 }
 
 fn create_statistic_event_handler(
@@ -316,15 +740,119 @@ pub async fn create_exchanges(
     events_channel: broadcast::Sender<ExchangeEvent>,
     application_manager: Arc<ApplicationManager>,
     timeout_manager: &Arc<TimeoutManager>,
-) -> Vec<Arc<Exchange>> {
-    join_all(core_settings.exchanges.iter().map(|x| {
-        create_exchange(
-            x,
-            build_settings,
-            events_channel.clone(),
-            application_manager.clone(),
-            timeout_manager.clone(),
+    data_recorder: &Option<Arc<DataRecorder>>,
+    exchange_blocker: &Arc<ExchangeBlocker>,
+) -> (Vec<Arc<Exchange>>, Vec<Arc<RawMessagesRecorder>>) {
+    let native_self_trade_prevention = core_settings
+        .self_trade_prevention
+        .as_ref()
+        .map(|x| x.mode == SelfTradePreventionMode::Native)
+        .unwrap_or(false);
+
+    let exchanges_settings = core_settings
+        .exchanges
+        .iter()
+        .map(|x| ExchangeSettings {
+            native_self_trade_prevention: x.native_self_trade_prevention
+                || native_self_trade_prevention,
+            ..x.clone()
+        })
+        .collect_vec();
+
+    let (exchanges, raw_messages_recorders): (Vec<_>, Vec<_>) =
+        join_all(exchanges_settings.iter().map(|x| {
+            create_exchange(
+                x,
+                build_settings,
+                events_channel.clone(),
+                application_manager.clone(),
+                timeout_manager.clone(),
+                data_recorder.clone(),
+                exchange_blocker.clone(),
+            )
+        }))
+        .await
+        .into_iter()
+        .unzip();
+
+    (
+        exchanges,
+        raw_messages_recorders.into_iter().flatten().collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::parse_settings;
+    use crate::core::exchanges::common::{Amount, CurrencyPair};
+    use rust_decimal_macros::dec;
+
+    #[derive(Debug, Clone)]
+    struct TestStrategySettings;
+
+    impl BaseStrategySettings for TestStrategySettings {
+        fn exchange_account_id(&self) -> ExchangeAccountId {
+            "Binance0".parse().expect("in test")
+        }
+
+        fn currency_pair(&self) -> CurrencyPair {
+            CurrencyPair::from_codes(&"eth".into(), &"btc".into())
+        }
+
+        fn max_amount(&self) -> Amount {
+            dec!(1)
+        }
+    }
+
+    fn strategy_instance(
+        configuration_key: &str,
+    ) -> StrategyInstanceSettings<TestStrategySettings> {
+        StrategyInstanceSettings {
+            service_name: "TestStrategy".to_owned(),
+            configuration_key: configuration_key.to_owned(),
+            target_amount_limits: Vec::new(),
+            settings: TestStrategySettings,
+        }
+    }
+
+    #[test]
+    fn strategies_with_different_configuration_keys_are_valid() {
+        let strategies = vec![strategy_instance("first"), strategy_instance("second")];
+
+        validate_strategies(&strategies).expect("in test");
+    }
+
+    #[test]
+    fn strategies_with_same_configuration_descriptor_are_invalid() {
+        let strategies = vec![strategy_instance("first"), strategy_instance("first")];
+
+        assert!(validate_strategies(&strategies).is_err());
+    }
+
+    #[test]
+    fn shipped_config_is_valid() {
+        let credentials = r#"
+            [Binance0]
+            api_key = "test_api_key"
+            secret_key = "test_secret_key"
+            "#;
+        let settings = parse_settings::<ConfiguredStrategySettings>(
+            include_str!("../../config.toml"),
+            credentials,
         )
-    }))
-    .await
+        .expect("in test");
+
+        assert!(!settings.strategies.is_empty());
+        validate_strategies(&settings.strategies).expect("in test");
+
+        let build_settings = EngineBuildConfig::standard();
+        for strategy_settings in &settings.strategies {
+            let strategy_settings = &strategy_settings.settings;
+            build_settings
+                .get_strategy_builder(&strategy_settings.strategy_type)
+                .and_then(|builder| builder.get_price_slots(strategy_settings))
+                .expect("in test");
+        }
+    }
 }
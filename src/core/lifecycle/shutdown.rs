@@ -27,6 +27,8 @@ struct ActorInfo {
 #[derive(Default)]
 struct State {
     services: Vec<Arc<dyn Service>>,
+    /// Services which graceful shutdown was run before the whole graceful shutdown
+    stopped_services: Vec<Arc<dyn Service>>,
     actors: Vec<ActorInfo>,
 }
 
@@ -52,6 +54,27 @@ impl ShutdownService {
         self.state.lock().actors.push(ActorInfo { name, actor });
     }
 
+    /// Graceful shutdown of services with specified name before all other ones.
+    /// These services aren't shut down again by `graceful_shutdown`, but are dropped together with other ones
+    pub(crate) async fn graceful_shutdown_services(&self, name: &str) {
+        let finish_receivers = {
+            let mut state_guard = self.state.lock();
+            let (services, other_services): (Vec<_>, Vec<_>) = state_guard
+                .services
+                .drain(..)
+                .partition(|x| x.name() == name);
+            state_guard.services = other_services;
+
+            trace!("Running graceful shutdown for services {} started", name);
+            let finish_receivers = run_services_graceful_shutdown(&services);
+            state_guard.stopped_services.extend(services);
+            finish_receivers
+        };
+
+        wait_finishing(finish_receivers).await;
+        trace!("Running graceful shutdown for services {} finished", name);
+    }
+
     pub(crate) async fn graceful_shutdown(&self) -> Vec<String> {
         let mut finish_receivers = Vec::new();
 
@@ -76,62 +99,11 @@ impl ShutdownService {
             trace!("Running graceful shutdown for actors finished");
 
             trace!("Running graceful shutdown for services started");
-            for service in &state_guard.services {
-                let receiver = service.clone().graceful_shutdown();
-
-                if let Some(receiver) = receiver {
-                    let service_name = format!("service {}", service.name());
-
-                    trace!("Waiting finishing graceful shutdown for {}", service_name);
-                    finish_receivers.push((service_name, receiver));
-                } else {
-                    trace!(
-                        "Service {} not needed waiting graceful shutdown or already finished",
-                        service.name()
-                    )
-                }
-            }
+            finish_receivers.extend(run_services_graceful_shutdown(&state_guard.services));
             trace!("Running graceful shutdown for services finished");
         }
 
-        // log errors when its came
-        let finishing_services_futures = finish_receivers
-            .into_iter()
-            .map(|(service_name, receiver)| {
-                receiver.map(
-                    move |finishing_service_send_result| match finishing_service_send_result {
-                        Err(err) => {
-                            error!(
-                                "Can't receive message for finishing graceful shutdown in {} because of error: {:?}",
-                                service_name,
-                                err
-                            );
-                        },
-                        Ok(finishing_service_result) => match finishing_service_result {
-                            Err(err) => {
-                                error!(
-                                    "{} finished on graceful shutdown with error: {:?}",
-                                    service_name,
-                                    err
-                                );
-                            }
-                            Ok(_) => {
-                                trace!(
-                                    "Graceful shutdown for {} completed successfully",
-                                    service_name
-                                );
-                            },
-                        },
-                    },
-                )
-            })
-            .collect_vec();
-
-        const TIMEOUT: Duration = Duration::from_secs(3);
-        tokio::select! {
-            _ = join_all(finishing_services_futures) => trace!("All services sent finished marker at given time"),
-            _ = sleep(TIMEOUT) => error!("Not all services finished after timeout ({} sec)", TIMEOUT.as_secs()),
-        }
+        wait_finishing(finish_receivers).await;
 
         trace!("Prepare to drop services in ShutdownService finished");
 
@@ -143,9 +115,11 @@ impl ShutdownService {
         let weak_services;
         {
             let mut state_guard = self.state.lock();
+            let stopped_services = state_guard.stopped_services.drain(..).collect_vec();
             weak_services = state_guard
                 .services
                 .drain(..)
+                .chain(stopped_services)
                 .map(|x| Arc::downgrade(&x))
                 .collect_vec();
         }
@@ -179,6 +153,70 @@ impl ShutdownService {
     }
 }
 
+type FinishReceiver = (String, oneshot::Receiver<Result<()>>);
+
+fn run_services_graceful_shutdown(services: &[Arc<dyn Service>]) -> Vec<FinishReceiver> {
+    let mut finish_receivers = Vec::new();
+    for service in services {
+        let receiver = service.clone().graceful_shutdown();
+
+        if let Some(receiver) = receiver {
+            let service_name = format!("service {}", service.name());
+
+            trace!("Waiting finishing graceful shutdown for {}", service_name);
+            finish_receivers.push((service_name, receiver));
+        } else {
+            trace!(
+                "Service {} not needed waiting graceful shutdown or already finished",
+                service.name()
+            )
+        }
+    }
+
+    finish_receivers
+}
+
+async fn wait_finishing(finish_receivers: Vec<FinishReceiver>) {
+    // log errors when its came
+    let finishing_services_futures = finish_receivers
+        .into_iter()
+        .map(|(service_name, receiver)| {
+            receiver.map(
+                move |finishing_service_send_result| match finishing_service_send_result {
+                    Err(err) => {
+                        error!(
+                            "Can't receive message for finishing graceful shutdown in {} because of error: {:?}",
+                            service_name,
+                            err
+                        );
+                    },
+                    Ok(finishing_service_result) => match finishing_service_result {
+                        Err(err) => {
+                            error!(
+                                "{} finished on graceful shutdown with error: {:?}",
+                                service_name,
+                                err
+                            );
+                        }
+                        Ok(_) => {
+                            trace!(
+                                "Graceful shutdown for {} completed successfully",
+                                service_name
+                            );
+                        },
+                    },
+                },
+            )
+        })
+        .collect_vec();
+
+    const TIMEOUT: Duration = Duration::from_secs(3);
+    tokio::select! {
+        _ = join_all(finishing_services_futures) => trace!("All services sent finished marker at given time"),
+        _ = sleep(TIMEOUT) => error!("Not all services finished after timeout ({} sec)", TIMEOUT.as_secs()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,4 +291,55 @@ mod tests {
         let not_dropped_services = shutdown_service.graceful_shutdown().await;
         assert_eq!(not_dropped_services, vec![REF_TEST_SERVICE.to_string()]);
     }
+
+    #[actix_rt::test]
+    pub async fn services_shut_down_first_are_shut_down_once() {
+        init_logger();
+
+        const FIRST_TEST_SERVICE: &str = "FirstTestService";
+        pub struct CountingTestService {
+            name: &'static str,
+            shutdowns_count: Mutex<usize>,
+        }
+
+        impl CountingTestService {
+            pub fn new(name: &'static str) -> Arc<Self> {
+                Arc::new(Self {
+                    name,
+                    shutdowns_count: Mutex::new(0),
+                })
+            }
+        }
+
+        impl Service for CountingTestService {
+            fn name(&self) -> &str {
+                self.name
+            }
+
+            fn graceful_shutdown(self: Arc<Self>) -> Option<Receiver<Result<()>>> {
+                *self.shutdowns_count.lock() += 1;
+                None
+            }
+        }
+
+        let shutdown_service = Arc::new(ShutdownService::default());
+        let first = CountingTestService::new(FIRST_TEST_SERVICE);
+        let other = CountingTestService::new("OtherTestService");
+        shutdown_service.register_service(first.clone());
+        shutdown_service.register_service(other.clone());
+
+        shutdown_service
+            .graceful_shutdown_services(FIRST_TEST_SERVICE)
+            .await;
+        assert_eq!(*first.shutdowns_count.lock(), 1);
+        assert_eq!(*other.shutdowns_count.lock(), 0);
+
+        let weak_first = Arc::downgrade(&first);
+        let weak_other = Arc::downgrade(&other);
+        drop((first, other));
+        let not_dropped_services = shutdown_service.graceful_shutdown().await;
+        assert!(not_dropped_services.is_empty());
+        assert!(weak_first.upgrade().is_none());
+        assert!(weak_other.upgrade().is_none());
+    }
 }
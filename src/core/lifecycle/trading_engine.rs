@@ -1,5 +1,4 @@
 use futures::FutureExt;
-use std::panic;
 use std::panic::AssertUnwindSafe;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
@@ -9,22 +8,29 @@ use anyhow::Result;
 use dashmap::DashMap;
 use futures::future::join_all;
 use itertools::Itertools;
-use log::info;
-use tokio::sync::{broadcast, oneshot};
-use tokio::time::Duration;
-
+use log::{error, info};
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::{broadcast, oneshot, watch};
+use tokio::time::{Duration, Instant};
+
+use crate::core::balance_manager::balance_manager::BalanceManager;
+use crate::core::data_recorder::recorder::DataRecorder;
+use crate::core::disposition_execution::executor::DISPOSITION_EXECUTOR;
 use crate::core::exchanges::block_reasons;
 use crate::core::exchanges::common::ExchangeAccountId;
 use crate::core::exchanges::events::{ExchangeEvent, ExchangeEvents};
 use crate::core::exchanges::exchange_blocker::BlockType;
 use crate::core::exchanges::exchange_blocker::ExchangeBlocker;
 use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::exchanges::general::order::cancel_all::OrdersCancellationOutcome;
 use crate::core::exchanges::timeouts::timeout_manager::TimeoutManager;
 use crate::core::lifecycle::shutdown::ShutdownService;
-use crate::core::settings::CoreSettings;
+use crate::core::market_data_watchdog::MarketDataWatchdog;
+use crate::core::risk_manager::RiskManager;
+use crate::core::settings::{CoreSettings, OrdersCancellationSettings};
 use crate::core::{
     infrastructure::unset_application_manager, lifecycle::application_manager::ApplicationManager,
-    lifecycle::cancellation_token::CancellationToken,
 };
 use parking_lot::Mutex;
 
@@ -43,8 +49,15 @@ pub struct EngineContext {
     pub exchange_blocker: Arc<ExchangeBlocker>,
     pub application_manager: Arc<ApplicationManager>,
     pub timeout_manager: Arc<TimeoutManager>,
+    pub data_recorder: Option<Arc<DataRecorder>>,
+    pub balance_manager: Arc<Mutex<BalanceManager>>,
+    pub risk_manager: Option<Arc<RiskManager>>,
+    pub market_data_watchdog: Option<Arc<MarketDataWatchdog>>,
     is_graceful_shutdown_started: AtomicBool,
     exchange_events: ExchangeEvents,
+    orders_cancellation_report_sender: watch::Sender<Option<OrdersCancellationReport>>,
+    // Receiver should exist for the whole lifetime, otherwise sent report isn't saved in channel
+    orders_cancellation_report_receiver: watch::Receiver<Option<OrdersCancellationReport>>,
     finish_graceful_shutdown_sender: Mutex<Option<oneshot::Sender<()>>>,
 }
 
@@ -56,22 +69,36 @@ impl EngineContext {
         finish_graceful_shutdown_sender: oneshot::Sender<()>,
         timeout_manager: Arc<TimeoutManager>,
         application_manager: Arc<ApplicationManager>,
+        data_recorder: Option<Arc<DataRecorder>>,
+        balance_manager: Arc<Mutex<BalanceManager>>,
+        exchange_blocker: Arc<ExchangeBlocker>,
     ) -> Arc<Self> {
-        let exchange_account_ids = app_settings
-            .exchanges
-            .iter()
-            .map(|x| x.exchange_account_id.clone())
-            .collect_vec();
-
+        let risk_manager = app_settings
+            .risk_manager
+            .clone()
+            .map(|settings| RiskManager::new(settings, exchange_blocker.clone()));
+        let market_data_watchdog = app_settings
+            .market_data_watchdog
+            .clone()
+            .map(MarketDataWatchdog::new);
+
+        let (orders_cancellation_report_sender, orders_cancellation_report_receiver) =
+            watch::channel(None);
         let engine_context = Arc::new(EngineContext {
             app_settings,
             exchanges,
             shutdown_service: Default::default(),
-            exchange_blocker: ExchangeBlocker::new(exchange_account_ids),
+            exchange_blocker,
             application_manager: application_manager.clone(),
             timeout_manager,
+            data_recorder,
+            balance_manager,
+            risk_manager,
+            market_data_watchdog,
             is_graceful_shutdown_started: Default::default(),
             exchange_events,
+            orders_cancellation_report_sender,
+            orders_cancellation_report_receiver,
             finish_graceful_shutdown_sender: Mutex::new(Some(finish_graceful_shutdown_sender)),
         });
 
@@ -101,23 +128,21 @@ impl EngineContext {
 
         self.application_manager.stop_token().cancel();
 
+        // Executors are stopped first, so they can't create orders after cancellation of all orders
+        self.shutdown_service
+            .graceful_shutdown_services(DISPOSITION_EXECUTOR)
+            .await;
+
+        // Orders are cancelled before other services shutdown, so the report can be sent by ControlPanel
+        let orders_cancellation_report =
+            cancel_all_orders(&self.exchanges, &self.app_settings.orders_cancellation).await;
+        let _ = self
+            .orders_cancellation_report_sender
+            .send(Some(orders_cancellation_report));
+
         self.shutdown_service.graceful_shutdown().await;
         self.exchange_blocker.stop_blocker().await;
 
-        let cancellation_token = CancellationToken::default();
-        const TIMEOUT: Duration = Duration::from_secs(5);
-
-        tokio::select! {
-            _ = cancel_opened_orders(&self.exchanges, cancellation_token.clone(), true) => (),
-            _ = tokio::time::sleep(TIMEOUT) => {
-                cancellation_token.cancel();
-                log::error!(
-                    "Timeout {} secs is exceeded: cancel open orders has been stopped",
-                    TIMEOUT.as_secs(),
-                );
-            }
-        }
-
         self.finish_graceful_shutdown_sender
             .lock()
             .take()
@@ -133,22 +158,57 @@ impl EngineContext {
     pub fn get_events_channel(&self) -> broadcast::Receiver<ExchangeEvent> {
         self.exchange_events.get_events_channel()
     }
+
+    /// Receiver of report about orders cancellation, which is sent during graceful shutdown
+    pub fn orders_cancellation_report(&self) -> watch::Receiver<Option<OrdersCancellationReport>> {
+        self.orders_cancellation_report_receiver.clone()
+    }
 }
 
-async fn cancel_opened_orders(
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OrdersCancellationReport {
+    pub outcomes: HashMap<ExchangeAccountId, OrdersCancellationOutcome>,
+}
+
+impl OrdersCancellationReport {
+    pub fn is_successful(&self) -> bool {
+        self.outcomes
+            .values()
+            .all(|x| *x == OrdersCancellationOutcome::Cancelled)
+    }
+}
+
+async fn cancel_all_orders(
     exchanges: &DashMap<ExchangeAccountId, Arc<Exchange>>,
-    cancellation_token: CancellationToken,
-    add_missing_open_orders: bool,
-) {
-    info!("Canceling opened orders started");
-
-    join_all(exchanges.iter().map(|x| {
-        x.clone()
-            .cancel_opened_orders(cancellation_token.clone(), add_missing_open_orders)
-    }))
+    settings: &OrdersCancellationSettings,
+) -> OrdersCancellationReport {
+    info!("Cancelling all orders started");
+
+    let deadline = Instant::now() + Duration::from_secs(settings.deadline_secs);
+    let retry_interval = Duration::from_millis(settings.retry_interval_ms);
+    let exchanges = exchanges.iter().map(|x| x.value().clone()).collect_vec();
+    let outcomes = join_all(
+        exchanges
+            .iter()
+            .map(|x| x.cancel_all_orders_until_deadline(deadline, retry_interval)),
+    )
     .await;
 
-    info!("Canceling opened orders finished");
+    let report = OrdersCancellationReport {
+        outcomes: exchanges
+            .iter()
+            .map(|x| x.exchange_account_id.clone())
+            .zip(outcomes)
+            .collect(),
+    };
+
+    if report.is_successful() {
+        info!("Cancelling all orders finished successfully");
+    } else {
+        error!("Cancelling all orders finished with failures: {:?}", report);
+    }
+
+    report
 }
 
 pub struct TradingEngine {
@@ -0,0 +1,245 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use log::{info, warn};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::sync::{broadcast, oneshot};
+use tokio::time::interval;
+
+use crate::core::exchanges::common::TradePlace;
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::lifecycle::trading_engine::Service;
+use crate::core::settings::MarketDataWatchdogSettings;
+use crate::core::DateTime;
+
+/// Max count of stale episodes kept for control panel
+const MAX_EPISODES_COUNT: usize = 1000;
+
+/// Period when trade place had no fresh order book updates
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StaleEpisode {
+    pub trade_place: TradePlace,
+    pub last_update_time: DateTime,
+    pub detected_at: DateTime,
+    /// None while trade place is still stale
+    pub finished_at: Option<DateTime>,
+}
+
+/// Watchdog which flags trade place as stale if there are no order book updates for too long.
+/// Orders on stale trade places are cancelled by `DispositionExecutor` until data is fresh again
+pub struct MarketDataWatchdog {
+    settings: MarketDataWatchdogSettings,
+    last_update_times: Mutex<HashMap<TradePlace, DateTime>>,
+    /// Stale episodes from the oldest to the newest
+    episodes: Mutex<VecDeque<StaleEpisode>>,
+    stale_trade_places_sender: broadcast::Sender<TradePlace>,
+    work_finished_receiver: Mutex<Option<oneshot::Receiver<Result<()>>>>,
+}
+
+impl MarketDataWatchdog {
+    pub fn new(settings: MarketDataWatchdogSettings) -> Arc<Self> {
+        let (stale_trade_places_sender, _) = broadcast::channel(20);
+        Arc::new(MarketDataWatchdog {
+            settings,
+            last_update_times: Default::default(),
+            episodes: Default::default(),
+            stale_trade_places_sender,
+            work_finished_receiver: Default::default(),
+        })
+    }
+
+    /// Notifications about trade places which became stale
+    pub fn subscribe_stale_trade_places(&self) -> broadcast::Receiver<TradePlace> {
+        self.stale_trade_places_sender.subscribe()
+    }
+
+    pub fn is_stale(&self, trade_place: &TradePlace) -> bool {
+        self.episodes
+            .lock()
+            .iter()
+            .any(|x| x.finished_at.is_none() && &x.trade_place == trade_place)
+    }
+
+    /// Stale episodes from the oldest to the newest
+    pub fn episodes(&self) -> Vec<StaleEpisode> {
+        self.episodes.lock().iter().cloned().collect()
+    }
+
+    pub fn order_book_updated(&self, trade_place: &TradePlace, update_time: DateTime) {
+        let _ = self
+            .last_update_times
+            .lock()
+            .insert(trade_place.clone(), update_time);
+
+        let mut episodes = self.episodes.lock();
+        let stale_episode = episodes
+            .iter_mut()
+            .find(|x| x.finished_at.is_none() && &x.trade_place == trade_place);
+        if let Some(stale_episode) = stale_episode {
+            info!(
+                "Order book on {:?} is fresh again after {}ms",
+                trade_place,
+                (update_time - stale_episode.last_update_time).num_milliseconds()
+            );
+            stale_episode.finished_at = Some(update_time);
+        }
+    }
+
+    /// Trade places are watched since specified time, so they are flagged as stale even without any order book updates
+    fn watch_trade_places(&self, trade_places: Vec<TradePlace>, now: DateTime) {
+        let mut last_update_times = self.last_update_times.lock();
+        for trade_place in trade_places {
+            let _ = last_update_times.entry(trade_place).or_insert(now);
+        }
+    }
+
+    /// Returns trade places which became stale since the previous check
+    fn check(&self, now: DateTime) -> Vec<TradePlace> {
+        let max_order_book_age = Duration::milliseconds(self.settings.max_order_book_age_ms as i64);
+
+        let last_update_times = self.last_update_times.lock();
+        let mut episodes = self.episodes.lock();
+        let mut new_stale_trade_places = Vec::new();
+        for (trade_place, last_update_time) in last_update_times.iter() {
+            if now - *last_update_time < max_order_book_age {
+                continue;
+            }
+
+            let is_already_stale = episodes
+                .iter()
+                .any(|x| x.finished_at.is_none() && &x.trade_place == trade_place);
+            if is_already_stale {
+                continue;
+            }
+
+            warn!(
+                "Order book on {:?} is stale, the last update was at {}",
+                trade_place, last_update_time
+            );
+            episodes.push_back(StaleEpisode {
+                trade_place: trade_place.clone(),
+                last_update_time: *last_update_time,
+                detected_at: now,
+                finished_at: None,
+            });
+            new_stale_trade_places.push(trade_place.clone());
+        }
+
+        while episodes.len() > MAX_EPISODES_COUNT {
+            let _ = episodes.pop_front();
+        }
+
+        new_stale_trade_places
+    }
+
+    /// Subscribed trade places are watched since the start even if there are no order book updates for them
+    pub(crate) async fn start(
+        self: Arc<Self>,
+        subscribed_trade_places: Vec<TradePlace>,
+        cancellation_token: CancellationToken,
+    ) -> Result<()> {
+        let (work_finished_sender, receiver) = oneshot::channel();
+        *self.work_finished_receiver.lock() = Some(receiver);
+
+        self.watch_trade_places(subscribed_trade_places, Utc::now());
+
+        let mut check_interval = interval(tokio::time::Duration::from_millis(
+            self.settings.check_interval_ms,
+        ));
+        loop {
+            tokio::select! {
+                _ = check_interval.tick() => {}
+                _ = cancellation_token.when_cancelled() => {
+                    let _ = work_finished_sender.send(Ok(()));
+                    return Ok(());
+                }
+            }
+
+            for trade_place in self.check(Utc::now()) {
+                // There are no receivers if there are no disposition executors
+                let _ = self.stale_trade_places_sender.send(trade_place);
+            }
+        }
+    }
+}
+
+impl Service for MarketDataWatchdog {
+    fn name(&self) -> &str {
+        "MarketDataWatchdog"
+    }
+
+    fn graceful_shutdown(self: Arc<Self>) -> Option<oneshot::Receiver<Result<()>>> {
+        let work_finished_receiver = self.work_finished_receiver.lock().take();
+        if work_finished_receiver.is_none() {
+            warn!("'work_finished_receiver' wasn't created when started graceful shutdown in MarketDataWatchdog");
+        }
+
+        work_finished_receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exchanges::common::CurrencyPair;
+
+    #[test]
+    fn trade_place_is_stale_until_order_book_update() {
+        let watchdog = MarketDataWatchdog::new(MarketDataWatchdogSettings {
+            max_order_book_age_ms: 1000,
+            check_interval_ms: 100,
+        });
+        let trade_place = TradePlace::new(
+            "Binance".into(),
+            CurrencyPair::from_codes(&"eth".into(), &"btc".into()),
+        );
+        let now = Utc::now();
+
+        watchdog.order_book_updated(&trade_place, now);
+        assert!(watchdog.check(now + Duration::milliseconds(999)).is_empty());
+        assert!(!watchdog.is_stale(&trade_place));
+
+        let detected_at = now + Duration::seconds(1);
+        assert_eq!(watchdog.check(detected_at), vec![trade_place.clone()]);
+        assert!(watchdog.is_stale(&trade_place));
+        // Trade place is reported once per episode
+        assert!(watchdog.check(now + Duration::seconds(2)).is_empty());
+
+        let fresh_at = now + Duration::seconds(3);
+        watchdog.order_book_updated(&trade_place, fresh_at);
+        assert!(!watchdog.is_stale(&trade_place));
+        assert_eq!(
+            watchdog.episodes(),
+            vec![StaleEpisode {
+                trade_place,
+                last_update_time: now,
+                detected_at,
+                finished_at: Some(fresh_at),
+            }]
+        );
+    }
+
+    #[test]
+    fn trade_place_without_order_book_updates_is_stale() {
+        let watchdog = MarketDataWatchdog::new(MarketDataWatchdogSettings {
+            max_order_book_age_ms: 1000,
+            check_interval_ms: 100,
+        });
+        let trade_place = TradePlace::new(
+            "Binance".into(),
+            CurrencyPair::from_codes(&"eth".into(), &"btc".into()),
+        );
+        let now = Utc::now();
+
+        watchdog.watch_trade_places(vec![trade_place.clone()], now);
+        assert!(watchdog.check(now + Duration::milliseconds(999)).is_empty());
+        assert_eq!(
+            watchdog.check(now + Duration::seconds(1)),
+            vec![trade_place.clone()]
+        );
+        assert!(watchdog.is_stale(&trade_place));
+    }
+}
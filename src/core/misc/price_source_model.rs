@@ -1,8 +1,11 @@
+use serde::Serialize;
+
 use crate::core::{
     exchanges::common::{CurrencyPair, ExchangeId, Price},
     DateTime,
 };
 
+#[derive(Serialize)]
 pub(crate) struct PriceSourceModel {
     pub init_time: DateTime,
     pub exchange_id: ExchangeId,
@@ -8,6 +8,7 @@ use crate::core::service_configuration::configuration_descriptor::ConfigurationD
 use crate::hashmap;
 
 use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
 
 pub(crate) type ConfigurationKeyByServiceName =
     HashMap<String, ExchangeAccountIdByConfigurationKey>;
@@ -23,7 +24,7 @@ pub(crate) type ValueByCurrencyCode = HashMap<CurrencyCode, Amount>;
 ///     NOTE: there is storing all balances by ServiceNames(strategy name),
 ///     that will contain several configuration keys for strategies, next layer is one or more accounts for
 ///     selected ServiceName and here stored CurrencyCodes by CurrencyPairs and amount for every currency code.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServiceValueTree {
     tree: ConfigurationKeyByServiceName,
 }
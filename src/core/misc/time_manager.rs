@@ -1,6 +1,42 @@
+use std::cell::Cell;
+
 #[cfg(test)]
 use mockall::automock;
 
+use crate::core::DateTime;
+
+thread_local! {
+    static VIRTUAL_NOW: Cell<Option<DateTime>> = const { Cell::new(None) };
+}
+
+/// Virtual time returned by `time_manager::now()` instead of the real one on the current thread.
+/// It is used for replaying historical data (e.g. in backtesting) and reset when dropped
+pub(crate) struct VirtualClock {
+    _private: (),
+}
+
+impl VirtualClock {
+    pub(crate) fn start(now: DateTime) -> Self {
+        VIRTUAL_NOW.with(|x| x.set(Some(now)));
+
+        VirtualClock { _private: () }
+    }
+
+    pub(crate) fn set_now(&self, now: DateTime) {
+        VIRTUAL_NOW.with(|x| x.set(Some(now)));
+    }
+}
+
+impl Drop for VirtualClock {
+    fn drop(&mut self) {
+        VIRTUAL_NOW.with(|x| x.set(None));
+    }
+}
+
+fn virtual_now() -> Option<DateTime> {
+    VIRTUAL_NOW.with(|x| x.get())
+}
+
 /// If you'll use this mod in some tests, mocks object should be created.
 /// Automock doesn't support default implementation.
 /// NOTE: you need to avoid using mock objects in a parallel way https://docs.rs/mockall/0.10.2/mockall/#static-methods
@@ -9,8 +45,8 @@ use mockall::automock;
 pub(crate) mod time_manager {
     use crate::core::DateTime;
 
-    /// Return current date in UTC
+    /// Return current date in UTC or virtual time if VirtualClock is started on the current thread
     pub(crate) fn now() -> DateTime {
-        chrono::Utc::now()
+        super::virtual_now().unwrap_or_else(chrono::Utc::now)
     }
 }
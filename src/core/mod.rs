@@ -1,9 +1,11 @@
 use chrono::Utc;
 
+pub mod backtesting;
 pub(crate) mod balance_changes;
 pub mod balance_manager;
 mod balances;
 pub mod connectivity;
+pub mod data_recorder;
 pub mod exchanges;
 pub mod infrastructure;
 pub mod logger;
@@ -19,8 +21,10 @@ pub(crate) mod events;
 pub mod explanation;
 pub(crate) mod internal_events_loop;
 pub mod lifecycle;
+pub mod market_data_watchdog;
 pub mod math;
 pub mod order_book;
+pub mod risk_manager;
 pub(crate) mod services;
 pub mod settings;
 pub mod text;
@@ -47,6 +47,14 @@ impl OrderBookEvent {
         }
     }
 
+    pub fn event_type(&self) -> EventType {
+        self.event_type
+    }
+
+    pub fn data(&self) -> &OrderBookData {
+        &self.data
+    }
+
     /// Update inner OrderBookData
     pub fn apply_data_update(&mut self, updates: Vec<OrderBookData>) {
         self.data.update(updates);
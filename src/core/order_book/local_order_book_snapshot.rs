@@ -1,3 +1,5 @@
+use itertools::Either;
+use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
 use crate::core::exchanges::common::*;
@@ -90,6 +92,17 @@ impl LocalOrderBookSnapshot {
         self.bids.iter().rev()
     }
 
+    /// Return values of asks or bids starting from the top
+    pub fn get_price_levels(
+        &self,
+        book_side: OrderSide,
+    ) -> impl Iterator<Item = (&Price, &Amount)> {
+        match book_side {
+            OrderSide::Buy => Either::Left(self.get_bids_price_levels()),
+            OrderSide::Sell => Either::Right(self.get_asks_price_levels()),
+        }
+    }
+
     fn try_remove_order(&mut self, order: DataToExcludeOrder) {
         let book_side = self.get_order_book_side(order.side);
 
@@ -146,11 +159,102 @@ impl LocalOrderBookSnapshot {
 
         Some((top_ask + top_bid) * dec!(0.5))
     }
+
+    /// Consume amount from the top of book side. Returns price of the last consumed level and total cost.
+    /// Returns None if amount isn't positive or there isn't enough liquidity
+    fn consume(&self, book_side: OrderSide, amount: Amount) -> Option<(Price, Decimal)> {
+        if amount <= dec!(0) {
+            return None;
+        }
+
+        let mut remaining_amount = amount;
+        let mut cost = dec!(0);
+        for (price, level_amount) in self.get_price_levels(book_side) {
+            let consumed_amount = remaining_amount.min(*level_amount);
+            cost += consumed_amount * price;
+            remaining_amount -= consumed_amount;
+
+            if remaining_amount.is_zero() {
+                return Some((*price, cost));
+            }
+        }
+
+        None
+    }
+
+    /// Volume weighted average price of consuming amount from book side (Buy - bids, Sell - asks)
+    pub fn calculate_vwap(&self, book_side: OrderSide, amount: Amount) -> Option<Price> {
+        self.consume(book_side, amount)
+            .map(|(_, cost)| cost / amount)
+    }
+
+    /// Price of the last level reached after consuming amount from book side (Buy - bids, Sell - asks)
+    pub fn calculate_price_after_consuming(
+        &self,
+        book_side: OrderSide,
+        amount: Amount,
+    ) -> Option<Price> {
+        self.consume(book_side, amount).map(|(price, _)| price)
+    }
+
+    /// Cumulative amount of book side levels with prices within percent from middle price
+    pub fn calculate_depth(&self, book_side: OrderSide, percent: Decimal) -> Option<Amount> {
+        let prices = self.get_top_prices();
+        let middle_price = (prices.top_ask? + prices.top_bid?) * dec!(0.5);
+        let deviation = middle_price * percent / dec!(100);
+
+        let depth = self
+            .get_price_levels(book_side)
+            .take_while(|(price, _)| match book_side {
+                OrderSide::Buy => **price >= middle_price - deviation,
+                OrderSide::Sell => **price <= middle_price + deviation,
+            })
+            .map(|(_, amount)| amount)
+            .sum();
+
+        Some(depth)
+    }
+
+    /// Imbalance of amounts on top levels of bids and asks from -1 (only asks) to 1 (only bids)
+    pub fn calculate_imbalance(&self, levels_count: usize) -> Option<Decimal> {
+        let bids_amount: Amount = self
+            .get_bids_price_levels()
+            .take(levels_count)
+            .map(|(_, amount)| amount)
+            .sum();
+        let asks_amount: Amount = self
+            .get_asks_price_levels()
+            .take(levels_count)
+            .map(|(_, amount)| amount)
+            .sum();
+
+        let total_amount = bids_amount + asks_amount;
+        if total_amount.is_zero() {
+            return None;
+        }
+
+        Some((bids_amount - asks_amount) / total_amount)
+    }
+
+    /// Middle price weighted by amounts of top levels, so it's closer to the side with less amount
+    pub fn calculate_weighted_middle_price(&self) -> Option<Price> {
+        let (top_bid_price, top_bid_amount) = self.get_top_bid()?;
+        let (top_ask_price, top_ask_amount) = self.get_top_ask()?;
+        if (top_bid_amount + top_ask_amount).is_zero() {
+            return None;
+        }
+
+        Some(
+            (top_bid_price * top_ask_amount + top_ask_price * top_bid_amount)
+                / (top_bid_amount + top_ask_amount),
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::order_book_data;
     use chrono::Utc;
 
     #[test]
@@ -314,4 +418,50 @@ mod tests {
         // Still exists
         assert_eq!(asks.next().expect("in test"), (&dec!(3.0), &dec!(4.2)));
     }
+
+    #[test]
+    fn analytics_queries() {
+        let order_book_snapshot = order_book_data![
+            dec!(11) => dec!(1),
+            dec!(12) => dec!(2),
+            dec!(13) => dec!(3),
+            ;
+            dec!(9) => dec!(3),
+            dec!(8) => dec!(5),
+        ]
+        .to_local_order_book_snapshot();
+
+        assert_eq!(
+            order_book_snapshot.calculate_vwap(OrderSide::Sell, dec!(2)),
+            Some(dec!(11.5))
+        );
+        assert_eq!(
+            order_book_snapshot.calculate_price_after_consuming(OrderSide::Sell, dec!(3.5)),
+            Some(dec!(13))
+        );
+        assert_eq!(
+            order_book_snapshot.calculate_vwap(OrderSide::Sell, dec!(7)),
+            None
+        );
+        assert_eq!(
+            order_book_snapshot.calculate_vwap(OrderSide::Buy, dec!(4)),
+            Some(dec!(8.75))
+        );
+
+        // Middle price is 10
+        assert_eq!(
+            order_book_snapshot.calculate_depth(OrderSide::Sell, dec!(20)),
+            Some(dec!(3))
+        );
+        assert_eq!(
+            order_book_snapshot.calculate_depth(OrderSide::Buy, dec!(20)),
+            Some(dec!(8))
+        );
+
+        assert_eq!(order_book_snapshot.calculate_imbalance(1), Some(dec!(0.5)));
+        assert_eq!(
+            order_book_snapshot.calculate_weighted_middle_price(),
+            Some(dec!(10.5))
+        );
+    }
 }
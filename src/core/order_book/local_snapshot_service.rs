@@ -1,25 +1,116 @@
 use crate::core::exchanges::common::*;
+use crate::core::exchanges::general::currency_pair_metadata::{CurrencyPairMetadata, Round};
 use crate::core::order_book::local_order_book_snapshot::LocalOrderBookSnapshot;
+use crate::core::order_book::order_book_data::OrderBookData;
 use crate::core::order_book::*;
-use std::collections::HashMap;
+use crate::core::orders::order::OrderSide;
+use crate::core::DateTime;
+use anyhow::Result;
+use log::warn;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+
+/// Reason why local order book snapshot is considered invalid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderBookInconsistency {
+    /// Top bid is not lower than top ask
+    CrossedBook { top_bid: Price, top_ask: Price },
+    /// Price level with amount which isn't positive. Zero amount is allowed only in updates to remove price level
+    InvalidAmount { price: Price, amount: Amount },
+    /// Event is older than the last applied one
+    OutOfOrderEvent {
+        last_update_time: DateTime,
+        event_time: DateTime,
+    },
+}
+
+/// Result of applying order book event to local snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotUpdate {
+    /// Snapshot is updated and it's valid
+    Updated(TradePlaceAccount),
+    /// Event made snapshot invalid, so order book should be resynchronized from exchange
+    Invalidated(TradePlaceAccount, OrderBookInconsistency),
+    /// There is no snapshot to update or it's still invalid
+    Skipped,
+}
+
+fn find_invalid_amount(
+    order_book_data: &OrderBookData,
+    is_zero_allowed: bool,
+) -> Option<OrderBookInconsistency> {
+    order_book_data
+        .asks
+        .iter()
+        .chain(order_book_data.bids.iter())
+        .find(|(_, amount)| amount.is_sign_negative() || (!is_zero_allowed && amount.is_zero()))
+        .map(|(price, amount)| OrderBookInconsistency::InvalidAmount {
+            price: *price,
+            amount: *amount,
+        })
+}
+
+fn find_crossing(snapshot: &LocalOrderBookSnapshot) -> Option<OrderBookInconsistency> {
+    let top_prices = snapshot.get_top_prices();
+    match (top_prices.top_bid, top_prices.top_ask) {
+        (Some(top_bid), Some(top_ask)) if top_bid >= top_ask => {
+            Some(OrderBookInconsistency::CrossedBook { top_bid, top_ask })
+        }
+        _ => None,
+    }
+}
+
+/// Taker gets lower prices on bids and higher prices on asks
+fn round_taker_price(
+    currency_pair_metadata: &CurrencyPairMetadata,
+    price: Price,
+    book_side: OrderSide,
+) -> Result<Price> {
+    match book_side {
+        OrderSide::Buy => currency_pair_metadata.price_round(price, Round::Floor),
+        OrderSide::Sell => currency_pair_metadata.price_round(price, Round::Ceiling),
+    }
+}
 
 /// Produce and actualize current logical state of order book snapshot according to logical time of handled order book events
 pub struct LocalSnapshotsService {
     local_snapshots: HashMap<TradePlace, LocalOrderBookSnapshot>,
+    /// Snapshots which are hidden from consumers until valid snapshot event is received
+    invalid_trade_places: HashSet<TradePlace>,
 }
 
 impl LocalSnapshotsService {
     pub fn new(local_snapshots: HashMap<TradePlace, LocalOrderBookSnapshot>) -> Self {
-        Self { local_snapshots }
+        Self {
+            local_snapshots,
+            invalid_trade_places: HashSet::new(),
+        }
     }
 
+    /// Returns None if there is no snapshot or it's invalid
     pub fn get_snapshot(&self, trade_place: &TradePlace) -> Option<&LocalOrderBookSnapshot> {
+        if self.is_invalid(trade_place) {
+            return None;
+        }
+
         self.local_snapshots.get(trade_place)
     }
 
+    pub fn is_invalid(&self, trade_place: &TradePlace) -> bool {
+        self.invalid_trade_places.contains(trade_place)
+    }
+
     /// Create snapshot if it does not exist
     /// Update snapshot if suitable data arrive
     pub fn update(&mut self, order_book_event: event::OrderBookEvent) -> Option<TradePlaceAccount> {
+        match self.apply(order_book_event) {
+            SnapshotUpdate::Updated(trade_place_account) => Some(trade_place_account),
+            SnapshotUpdate::Invalidated(..) | SnapshotUpdate::Skipped => None,
+        }
+    }
+
+    /// Apply event and validate snapshot after it. Snapshot stays invalid until the next valid snapshot event
+    pub fn apply(&mut self, order_book_event: event::OrderBookEvent) -> SnapshotUpdate {
         // Extract all field
         let (_, creation_time, exchange_account_id, currency_pair, _, event_type, event_data) =
             order_book_event.dissolve();
@@ -28,23 +119,129 @@ impl LocalSnapshotsService {
             exchange_account_id.exchange_id.clone(),
             currency_pair.clone(),
         );
+        let trade_place_account = TradePlaceAccount::new(exchange_account_id, currency_pair);
+
+        if let Some(snapshot) = self.local_snapshots.get(&trade_place) {
+            if creation_time < snapshot.last_update_time {
+                let inconsistency = OrderBookInconsistency::OutOfOrderEvent {
+                    last_update_time: snapshot.last_update_time,
+                    event_time: creation_time,
+                };
+                return self.invalidate(trade_place, trade_place_account, inconsistency);
+            }
+        }
 
-        match event_type {
+        let inconsistency = match event_type {
             event::EventType::Snapshot => {
-                let _ = self.local_snapshots.insert(
-                    trade_place.clone(),
-                    event_data.to_local_order_book_snapshot(),
-                );
+                let inconsistency = find_invalid_amount(&event_data, false);
+                let snapshot =
+                    LocalOrderBookSnapshot::new(event_data.asks, event_data.bids, creation_time);
+                let inconsistency = inconsistency.or_else(|| find_crossing(&snapshot));
+
+                let _ = self.local_snapshots.insert(trade_place.clone(), snapshot);
+                let _ = self.invalid_trade_places.remove(&trade_place);
 
-                Some(TradePlaceAccount::new(exchange_account_id, currency_pair))
+                inconsistency
             }
             event::EventType::Update => {
-                self.local_snapshots.get_mut(&trade_place).map(|snapshot| {
-                    snapshot.apply_update(event_data, creation_time);
-                    TradePlaceAccount::new(exchange_account_id, currency_pair)
-                })
+                let snapshot = match self.local_snapshots.get_mut(&trade_place) {
+                    Some(snapshot) => snapshot,
+                    None => return SnapshotUpdate::Skipped,
+                };
+
+                let inconsistency = find_invalid_amount(&event_data, true);
+                snapshot.apply_update(event_data, creation_time);
+
+                inconsistency.or_else(|| find_crossing(snapshot))
             }
+        };
+
+        if let Some(inconsistency) = inconsistency {
+            return self.invalidate(trade_place, trade_place_account, inconsistency);
+        }
+
+        match self.is_invalid(&trade_place) {
+            true => SnapshotUpdate::Skipped,
+            false => SnapshotUpdate::Updated(trade_place_account),
+        }
+    }
+
+    /// Volume weighted average price of consuming amount from book side (Buy - bids, Sell - asks).
+    /// Price is rounded to the worse side for taker
+    pub fn get_vwap(
+        &self,
+        trade_place: &TradePlace,
+        currency_pair_metadata: &CurrencyPairMetadata,
+        book_side: OrderSide,
+        amount: Amount,
+    ) -> Result<Option<Price>> {
+        self.get_snapshot(trade_place)
+            .and_then(|x| x.calculate_vwap(book_side, amount))
+            .map(|x| round_taker_price(currency_pair_metadata, x, book_side))
+            .transpose()
+    }
+
+    /// Price of the last level reached after consuming amount from book side (Buy - bids, Sell - asks)
+    pub fn get_price_after_consuming(
+        &self,
+        trade_place: &TradePlace,
+        currency_pair_metadata: &CurrencyPairMetadata,
+        book_side: OrderSide,
+        amount: Amount,
+    ) -> Result<Option<Price>> {
+        self.get_snapshot(trade_place)
+            .and_then(|x| x.calculate_price_after_consuming(book_side, amount))
+            .map(|x| round_taker_price(currency_pair_metadata, x, book_side))
+            .transpose()
+    }
+
+    /// Cumulative amount of book side levels with prices within percent from middle price rounded down
+    pub fn get_depth(
+        &self,
+        trade_place: &TradePlace,
+        currency_pair_metadata: &CurrencyPairMetadata,
+        book_side: OrderSide,
+        percent: Decimal,
+    ) -> Result<Option<Amount>> {
+        self.get_snapshot(trade_place)
+            .and_then(|x| x.calculate_depth(book_side, percent))
+            .map(|x| currency_pair_metadata.amount_round(x, Round::Floor))
+            .transpose()
+    }
+
+    /// Imbalance of amounts on top levels from -1 (only asks) to 1 (only bids).
+    /// It's a ratio, so it isn't rounded by currency pair metadata
+    pub fn get_imbalance(&self, trade_place: &TradePlace, levels_count: usize) -> Option<Decimal> {
+        self.get_snapshot(trade_place)?
+            .calculate_imbalance(levels_count)
+    }
+
+    /// Middle price weighted by amounts of top levels rounded to the nearest price
+    pub fn get_weighted_middle_price(
+        &self,
+        trade_place: &TradePlace,
+        currency_pair_metadata: &CurrencyPairMetadata,
+    ) -> Result<Option<Price>> {
+        self.get_snapshot(trade_place)
+            .and_then(|x| x.calculate_weighted_middle_price())
+            .map(|x| currency_pair_metadata.price_round(x, Round::ToNearest))
+            .transpose()
+    }
+
+    fn invalidate(
+        &mut self,
+        trade_place: TradePlace,
+        trade_place_account: TradePlaceAccount,
+        inconsistency: OrderBookInconsistency,
+    ) -> SnapshotUpdate {
+        if self.invalid_trade_places.insert(trade_place) {
+            warn!(
+                "Order book snapshot for {:?} is invalid: {:?}",
+                trade_place_account, inconsistency
+            );
         }
+
+        SnapshotUpdate::Invalidated(trade_place_account, inconsistency)
     }
 }
 
@@ -57,6 +254,8 @@ impl Default for LocalSnapshotsService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::exchanges::general::currency_pair_metadata::Precision;
+    use crate::hashmap;
     use crate::order_book_data;
     use chrono::Utc;
     use rust_decimal_macros::*;
@@ -65,7 +264,7 @@ mod tests {
         exchange_id: ExchangeId,
         currency_pair: CurrencyPair,
         event_type: event::EventType,
-        order_book_data: order_book_data::OrderBookData,
+        order_book_data: OrderBookData,
     ) -> event::OrderBookEvent {
         event::OrderBookEvent::new(
             Utc::now(),
@@ -87,8 +286,8 @@ mod tests {
             dec!(1.0) => dec!(2.1),
             dec!(3.0) => dec!(4.2),
             ;
-            dec!(2.9) => dec!(7.8),
-            dec!(3.4) => dec!(1.2),
+            dec!(0.9) => dec!(7.8),
+            dec!(0.4) => dec!(1.2),
         ];
 
         // Construct update
@@ -117,8 +316,8 @@ mod tests {
         // Check all snapshot returned values
         assert_eq!(updated_asks.get(&dec!(1.0)), Some(&dec!(2.1)));
         assert_eq!(updated_asks.get(&dec!(3.0)), Some(&dec!(4.2)));
-        assert_eq!(updated_bids.get(&dec!(2.9)), Some(&dec!(7.8)));
-        assert_eq!(updated_bids.get(&dec!(3.4)), Some(&dec!(1.2)));
+        assert_eq!(updated_bids.get(&dec!(0.9)), Some(&dec!(7.8)));
+        assert_eq!(updated_bids.get(&dec!(0.4)), Some(&dec!(1.2)));
     }
 
     #[test]
@@ -131,8 +330,8 @@ mod tests {
             dec!(1.0) => dec!(2.1),
             dec!(3.0) => dec!(4.2),
             ;
-            dec!(2.9) => dec!(7.8),
-            dec!(3.4) => dec!(1.2),
+            dec!(0.9) => dec!(7.8),
+            dec!(0.4) => dec!(1.2),
         ];
 
         // Construct update
@@ -164,8 +363,8 @@ mod tests {
             dec!(1.0) => dec!(0.1),
             dec!(3.0) => dec!(4.2),
             ;
-            dec!(2.9) => dec!(7.8),
-            dec!(3.4) => dec!(1.2),
+            dec!(0.9) => dec!(7.8),
+            dec!(0.4) => dec!(1.2),
         ]
         .to_local_order_book_snapshot();
 
@@ -180,8 +379,8 @@ mod tests {
         let order_book_data = order_book_data![
             dec!(1.0) => dec!(2.1),
             ;
-            dec!(2.9) => dec!(7.8),
-            dec!(3.4) => dec!(0),
+            dec!(0.9) => dec!(7.8),
+            dec!(0.4) => dec!(0),
         ];
 
         // Construct update
@@ -220,14 +419,179 @@ mod tests {
             Some(&dec!(4.2))
         );
         assert_eq!(
-            updated_bids.get(&dec!(2.9)),
+            updated_bids.get(&dec!(0.9)),
             // Updated
             Some(&dec!(7.8))
         );
         assert_eq!(
-            updated_bids.get(&dec!(3.4)),
+            updated_bids.get(&dec!(0.4)),
             // Deleted
             None
         );
     }
+
+    #[test]
+    fn crossed_book_is_hidden_until_valid_snapshot() {
+        let mut snapshot_service = LocalSnapshotsService::default();
+        let currency_pair = CurrencyPair::from_codes(&"base".into(), &"quote".into());
+        let trade_place = TradePlace::new("exchange_id".into(), currency_pair.clone());
+        let event = |event_type, order_book_data| {
+            create_order_book_event_for_tests(
+                "exchange_id".into(),
+                currency_pair.clone(),
+                event_type,
+                order_book_data,
+            )
+        };
+
+        let update = snapshot_service.apply(event(
+            event::EventType::Snapshot,
+            order_book_data![dec!(2) => dec!(1), ; dec!(1) => dec!(1),],
+        ));
+        assert!(matches!(update, SnapshotUpdate::Updated(_)));
+
+        let update = snapshot_service.apply(event(
+            event::EventType::Update,
+            order_book_data![; dec!(2) => dec!(1),],
+        ));
+        assert!(matches!(
+            update,
+            SnapshotUpdate::Invalidated(_, OrderBookInconsistency::CrossedBook { .. })
+        ));
+        assert!(snapshot_service.get_snapshot(&trade_place).is_none());
+
+        // Update doesn't make snapshot valid again even if book isn't crossed after it
+        let update = snapshot_service.apply(event(
+            event::EventType::Update,
+            order_book_data![; dec!(2) => dec!(0),],
+        ));
+        assert_eq!(update, SnapshotUpdate::Skipped);
+        assert!(snapshot_service.get_snapshot(&trade_place).is_none());
+
+        let update = snapshot_service.apply(event(
+            event::EventType::Snapshot,
+            order_book_data![dec!(3) => dec!(1), ; dec!(2) => dec!(1),],
+        ));
+        assert!(matches!(update, SnapshotUpdate::Updated(_)));
+        assert!(snapshot_service.get_snapshot(&trade_place).is_some());
+    }
+
+    #[test]
+    fn invalid_amounts_and_out_of_order_events() {
+        let mut snapshot_service = LocalSnapshotsService::default();
+        let currency_pair = CurrencyPair::from_codes(&"base".into(), &"quote".into());
+        let event = |event_type, order_book_data| {
+            create_order_book_event_for_tests(
+                "exchange_id".into(),
+                currency_pair.clone(),
+                event_type,
+                order_book_data,
+            )
+        };
+
+        let update = snapshot_service.apply(event(
+            event::EventType::Snapshot,
+            order_book_data![dec!(2) => dec!(0), ; dec!(1) => dec!(1),],
+        ));
+        assert_eq!(
+            update,
+            SnapshotUpdate::Invalidated(
+                TradePlaceAccount::new(
+                    ExchangeAccountId::new("exchange_id".into(), 0),
+                    currency_pair.clone()
+                ),
+                OrderBookInconsistency::InvalidAmount {
+                    price: dec!(2),
+                    amount: dec!(0)
+                }
+            )
+        );
+
+        let old_event = event(
+            event::EventType::Snapshot,
+            order_book_data![dec!(2) => dec!(1), ; dec!(1) => dec!(1),],
+        );
+        let update = snapshot_service.apply(event(
+            event::EventType::Snapshot,
+            order_book_data![dec!(2) => dec!(1), ; dec!(1) => dec!(1),],
+        ));
+        assert!(matches!(update, SnapshotUpdate::Updated(_)));
+
+        let update = snapshot_service.apply(old_event);
+        assert!(matches!(
+            update,
+            SnapshotUpdate::Invalidated(_, OrderBookInconsistency::OutOfOrderEvent { .. })
+        ));
+    }
+
+    #[test]
+    fn analytics_are_rounded_by_metadata() {
+        let currency_pair_metadata = CurrencyPairMetadata::new(
+            false,
+            false,
+            "base".into(),
+            "base".into(),
+            "quote".into(),
+            "quote".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            "base".into(),
+            None,
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(1) },
+        );
+        let trade_place_account = TradePlaceAccount::new(
+            ExchangeAccountId::new("exchange_id".into(), 0),
+            currency_pair_metadata.currency_pair(),
+        );
+        let trade_place = trade_place_account.trade_place();
+        let snapshot = order_book_data![
+            dec!(10.1) => dec!(1),
+            dec!(10.2) => dec!(2.5),
+            ;
+            dec!(9.9) => dec!(2),
+        ]
+        .to_local_order_book_snapshot();
+        let snapshot_service =
+            LocalSnapshotsService::new(hashmap![trade_place.clone() => snapshot]);
+
+        // 10.1666.. for asks is rounded up
+        assert_eq!(
+            snapshot_service
+                .get_vwap(
+                    &trade_place,
+                    &currency_pair_metadata,
+                    OrderSide::Sell,
+                    dec!(3)
+                )
+                .expect("in test"),
+            Some(dec!(10.2))
+        );
+        // 3.5 is rounded down
+        assert_eq!(
+            snapshot_service
+                .get_depth(
+                    &trade_place,
+                    &currency_pair_metadata,
+                    OrderSide::Sell,
+                    dec!(5)
+                )
+                .expect("in test"),
+            Some(dec!(3))
+        );
+        // 10.0333.. is rounded to the nearest
+        assert_eq!(
+            snapshot_service
+                .get_weighted_middle_price(&trade_place, &currency_pair_metadata)
+                .expect("in test"),
+            Some(dec!(10.0))
+        );
+
+        let other_trade_place =
+            TradePlace::new("other_exchange_id".into(), trade_place.currency_pair);
+        assert_eq!(snapshot_service.get_imbalance(&other_trade_place, 1), None);
+    }
 }
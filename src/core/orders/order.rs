@@ -39,7 +39,7 @@ impl OrderSide {
 }
 
 impl Display for OrderSide {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let side = match self {
             OrderSide::Buy => "Buy",
             OrderSide::Sell => "Sell",
@@ -435,13 +435,13 @@ pub struct SystemInternalOrderProps {
     pub last_order_cancellation_status_request_time: Option<DateTime>,
     pub last_cancellation_error: Option<ExchangeErrorType>,
 
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub is_canceling_from_wait_cancel_order: bool,
 
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub canceled_not_from_wait_cancel_order: bool,
 
-    #[serde(skip_serializing)]
+    #[serde(skip)]
     pub was_cancellation_event_raised: bool,
 
     pub last_order_trades_request_time: Option<DateTime>,
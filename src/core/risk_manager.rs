@@ -0,0 +1,461 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::Duration;
+use log::warn;
+use parking_lot::Mutex;
+use rust_decimal_macros::dec;
+
+use crate::core::balance_changes::balance_change_usd_periodic_calculator::BalanceChangeUsdPeriodicCalculator;
+use crate::core::balance_changes::profit_loss_balance_change::ProfitLossBalanceChange;
+use crate::core::balance_manager::balance_request::BalanceRequest;
+use crate::core::exchanges::block_reasons;
+use crate::core::exchanges::common::{Amount, Price, TradePlaceAccount};
+use crate::core::exchanges::exchange_blocker::{BlockType, ExchangeBlocker};
+use crate::core::exchanges::general::currency_pair_metadata::CurrencyPairMetadata;
+use crate::core::explanation::Explanation;
+use crate::core::orders::order::{ClientOrderFillId, OrderSide, OrderSnapshot};
+use crate::core::orders::pool::OrderRef;
+use crate::core::service_configuration::configuration_descriptor::ConfigurationDescriptor;
+use crate::core::settings::{RiskLimitsSettings, RiskManagerSettings};
+
+const RISK_MANAGER_SERVICE_NAME: &str = "RiskManager";
+
+/// Position accumulated by fills with average entry price
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Position {
+    /// Positive for long position and negative for short one
+    amount: Amount,
+    average_price: Price,
+}
+
+impl Position {
+    /// Returns realised profit in quote currency, it's negative for loss
+    fn apply_fill(&mut self, side: OrderSide, amount: Amount, price: Price) -> Amount {
+        let signed_amount = match side {
+            OrderSide::Buy => amount,
+            OrderSide::Sell => -amount,
+        };
+
+        if self.amount.is_zero()
+            || self.amount.is_sign_positive() == signed_amount.is_sign_positive()
+        {
+            let new_amount = self.amount + signed_amount;
+            self.average_price =
+                (self.amount.abs() * self.average_price + amount * price) / new_amount.abs();
+            self.amount = new_amount;
+            return dec!(0);
+        }
+
+        let closed_amount = amount.min(self.amount.abs());
+        let price_change = match self.amount.is_sign_positive() {
+            true => price - self.average_price,
+            false => self.average_price - price,
+        };
+        let realised_profit = closed_amount * price_change;
+
+        self.amount += signed_amount;
+        if self.amount.is_zero() {
+            self.average_price = dec!(0);
+        } else if self.amount.is_sign_positive() == signed_amount.is_sign_positive() {
+            // Position is reversed, so the rest of fill opens new one
+            self.average_price = price;
+        }
+
+        realised_profit
+    }
+}
+
+/// Checks orders before creation against limits of position, open notional and realised loss per trade place.
+/// Exchange is blocked for a while when any limit is breached, so no orders are created on it until unblocking
+pub struct RiskManager {
+    settings: RiskManagerSettings,
+    limits: HashMap<TradePlaceAccount, RiskLimitsSettings>,
+    exchange_blocker: Arc<ExchangeBlocker>,
+    positions: Mutex<HashMap<TradePlaceAccount, Position>>,
+    balance_change_usd_periodic_calculator: Mutex<BalanceChangeUsdPeriodicCalculator>,
+}
+
+impl RiskManager {
+    pub fn new(settings: RiskManagerSettings, exchange_blocker: Arc<ExchangeBlocker>) -> Arc<Self> {
+        let limits = settings
+            .limits
+            .iter()
+            .map(|x| {
+                let trade_place =
+                    TradePlaceAccount::new(x.exchange_account_id.clone(), x.currency_pair.clone());
+                (trade_place, x.clone())
+            })
+            .collect();
+
+        let loss_period = Duration::seconds(settings.loss_period_secs as i64);
+        Arc::new(RiskManager {
+            settings,
+            limits,
+            exchange_blocker,
+            positions: Default::default(),
+            balance_change_usd_periodic_calculator: Mutex::new(
+                BalanceChangeUsdPeriodicCalculator::new(loss_period, None),
+            ),
+        })
+    }
+
+    /// Returns false if order breaches limits of its trade place. Decision is added to explanation
+    pub fn check_order(
+        &self,
+        trade_place: &TradePlaceAccount,
+        side: OrderSide,
+        price: Price,
+        amount: Amount,
+        open_orders: &[OrderRef],
+        explanation: &mut Explanation,
+    ) -> bool {
+        let limits = match self.limits.get(trade_place) {
+            Some(limits) => limits,
+            None => {
+                explanation.add_reason("Risk manager: there are no limits for trade place");
+                return true;
+            }
+        };
+
+        match self.find_breach(limits, trade_place, side, price, amount, open_orders) {
+            Some(breach) => {
+                warn!(
+                    "Risk manager rejected order on {:?}: {}",
+                    trade_place, breach
+                );
+                explanation.add_reason(format!("Risk manager rejected order: {}", breach));
+
+                self.exchange_blocker.block(
+                    &trade_place.exchange_account_id,
+                    block_reasons::RISK_LIMIT_BREACH,
+                    BlockType::Timed(tokio::time::Duration::from_secs(
+                        self.settings.block_duration_secs,
+                    )),
+                );
+
+                false
+            }
+            None => {
+                explanation.add_reason("Risk manager approved order");
+                true
+            }
+        }
+    }
+
+    fn find_breach(
+        &self,
+        limits: &RiskLimitsSettings,
+        trade_place: &TradePlaceAccount,
+        side: OrderSide,
+        price: Price,
+        amount: Amount,
+        open_orders: &[OrderRef],
+    ) -> Option<String> {
+        let remaining_amount = |order: &OrderRef| order.amount() - order.filled_amount();
+        let position = self.position(trade_place);
+
+        if let Some(max_position) = limits.max_position {
+            let open_amount: Amount = open_orders
+                .iter()
+                .filter(|x| x.side() == side)
+                .map(remaining_amount)
+                .sum();
+            let new_position = match side {
+                OrderSide::Buy => position + open_amount + amount,
+                OrderSide::Sell => position - open_amount - amount,
+            };
+
+            // Orders which reduce position are allowed even if limit is exceeded already
+            if new_position.abs() > max_position && new_position.abs() > position.abs() {
+                return Some(format!(
+                    "position {} including open orders exceeds max position {}",
+                    new_position, max_position
+                ));
+            }
+        }
+
+        if let Some(max_open_notional) = limits.max_open_notional {
+            let open_notional: Amount = open_orders
+                .iter()
+                .map(|x| remaining_amount(x) * x.price())
+                .sum::<Amount>()
+                + amount * price;
+
+            if open_notional > max_open_notional {
+                return Some(format!(
+                    "open notional {} exceeds max open notional {}",
+                    open_notional, max_open_notional
+                ));
+            }
+        }
+
+        if let Some(max_loss) = limits.max_loss_in_usd {
+            let realised_loss = -self
+                .balance_change_usd_periodic_calculator
+                .lock()
+                .calculate_raw_usd_change(trade_place);
+
+            if realised_loss > max_loss {
+                return Some(format!(
+                    "realised loss {} USD over {}s exceeds max loss {} USD",
+                    realised_loss, self.settings.loss_period_secs, max_loss
+                ));
+            }
+        }
+
+        None
+    }
+
+    fn position(&self, trade_place: &TradePlaceAccount) -> Amount {
+        self.positions
+            .lock()
+            .get(trade_place)
+            .map(|x| x.amount)
+            .unwrap_or_default()
+    }
+
+    /// Update position with the last fill of order and register its realised profit or loss
+    pub fn order_filled(
+        &self,
+        order: &OrderSnapshot,
+        currency_pair_metadata: &CurrencyPairMetadata,
+    ) {
+        let order_fill = match order.fills.fills.last() {
+            Some(order_fill) => order_fill,
+            None => return,
+        };
+
+        let trade_place = TradePlaceAccount::new(
+            order.header.exchange_account_id.clone(),
+            order.header.currency_pair.clone(),
+        );
+        let price = order_fill.price();
+        let commission = match order_fill.converted_commission_currency_code() {
+            code if code == &currency_pair_metadata.quote_currency_code => {
+                order_fill.converted_commission_amount()
+            }
+            code if code == &currency_pair_metadata.base_currency_code => {
+                order_fill.converted_commission_amount() * price
+            }
+            _ => dec!(0),
+        };
+
+        let realised_profit = self
+            .positions
+            .lock()
+            .entry(trade_place.clone())
+            .or_default()
+            .apply_fill(order.header.side, order_fill.amount(), price)
+            - commission;
+
+        if realised_profit.is_zero() {
+            return;
+        }
+
+        let quote_currency_usd_price = match self.limits.get(&trade_place) {
+            Some(limits) => limits.quote_currency_usd_price,
+            None => return,
+        };
+
+        let balance_request = BalanceRequest::new(
+            Arc::new(ConfigurationDescriptor::new(
+                RISK_MANAGER_SERVICE_NAME.to_owned(),
+                format!(
+                    "{};{}",
+                    trade_place.exchange_account_id, trade_place.currency_pair
+                ),
+            )),
+            trade_place.exchange_account_id.clone(),
+            trade_place.currency_pair.clone(),
+            currency_pair_metadata.quote_currency_code.clone(),
+        );
+        let balance_change = ProfitLossBalanceChange::new(
+            balance_request,
+            trade_place.exchange_account_id.exchange_id.clone(),
+            order_fill
+                .client_order_fill_id()
+                .clone()
+                .unwrap_or_else(ClientOrderFillId::unique_id),
+            order_fill.receive_time(),
+            realised_profit,
+            realised_profit * quote_currency_usd_price,
+        );
+
+        self.balance_change_usd_periodic_calculator
+            .lock()
+            .add_balance_change(&balance_change);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::exchanges::common::{CurrencyPair, ExchangeAccountId};
+
+    #[test]
+    fn position_realised_profit() {
+        let mut position = Position::default();
+
+        assert_eq!(
+            position.apply_fill(OrderSide::Buy, dec!(2), dec!(10)),
+            dec!(0)
+        );
+        assert_eq!(
+            position.apply_fill(OrderSide::Buy, dec!(2), dec!(20)),
+            dec!(0)
+        );
+        assert_eq!(position.average_price, dec!(15));
+
+        assert_eq!(
+            position.apply_fill(OrderSide::Sell, dec!(1), dec!(12)),
+            dec!(-3)
+        );
+        assert_eq!(position.amount, dec!(3));
+
+        // Reversal closes long position and opens short one with the rest of fill
+        assert_eq!(
+            position.apply_fill(OrderSide::Sell, dec!(5), dec!(17)),
+            dec!(6)
+        );
+        assert_eq!(
+            position,
+            Position {
+                amount: dec!(-2),
+                average_price: dec!(17)
+            }
+        );
+
+        assert_eq!(
+            position.apply_fill(OrderSide::Buy, dec!(2), dec!(18)),
+            dec!(-2)
+        );
+        assert_eq!(position, Position::default());
+    }
+
+    #[tokio::test]
+    async fn reject_order_exceeding_position_and_block_exchange() {
+        let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+        let currency_pair = CurrencyPair::from_codes(&"eth".into(), &"btc".into());
+        let exchange_blocker = ExchangeBlocker::new(vec![exchange_account_id.clone()]);
+        let risk_manager = RiskManager::new(
+            RiskManagerSettings {
+                loss_period_secs: 60,
+                block_duration_secs: 60,
+                limits: vec![RiskLimitsSettings {
+                    exchange_account_id: exchange_account_id.clone(),
+                    currency_pair: currency_pair.clone(),
+                    max_position: Some(dec!(5)),
+                    max_open_notional: None,
+                    max_loss_in_usd: None,
+                    quote_currency_usd_price: dec!(60000),
+                }],
+            },
+            exchange_blocker.clone(),
+        );
+        let trade_place = TradePlaceAccount::new(exchange_account_id.clone(), currency_pair);
+        let _ = risk_manager.positions.lock().insert(
+            trade_place.clone(),
+            Position {
+                amount: dec!(2),
+                average_price: dec!(1),
+            },
+        );
+
+        let mut explanation = Explanation::default();
+        assert!(risk_manager.check_order(
+            &trade_place,
+            OrderSide::Buy,
+            dec!(1),
+            dec!(3),
+            &[],
+            &mut explanation
+        ));
+        assert!(!exchange_blocker.is_blocked(&exchange_account_id));
+
+        assert!(!risk_manager.check_order(
+            &trade_place,
+            OrderSide::Sell,
+            dec!(1),
+            dec!(8),
+            &[],
+            &mut explanation
+        ));
+        assert!(exchange_blocker
+            .is_blocked_by_reason(&exchange_account_id, block_reasons::RISK_LIMIT_BREACH));
+
+        exchange_blocker.stop_blocker().await;
+    }
+
+    #[tokio::test]
+    async fn reject_order_after_realised_loss_in_usd() {
+        let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+        let currency_pair = CurrencyPair::from_codes(&"eth".into(), &"btc".into());
+        let exchange_blocker = ExchangeBlocker::new(vec![exchange_account_id.clone()]);
+        let risk_manager = RiskManager::new(
+            RiskManagerSettings {
+                loss_period_secs: 60,
+                block_duration_secs: 60,
+                limits: vec![RiskLimitsSettings {
+                    exchange_account_id: exchange_account_id.clone(),
+                    currency_pair: currency_pair.clone(),
+                    max_position: None,
+                    max_open_notional: None,
+                    max_loss_in_usd: Some(dec!(30000)),
+                    quote_currency_usd_price: dec!(60000),
+                }],
+            },
+            exchange_blocker.clone(),
+        );
+        let trade_place = TradePlaceAccount::new(exchange_account_id.clone(), currency_pair);
+
+        let add_realised_profit = |realised_profit| {
+            let balance_request = BalanceRequest::new(
+                Arc::new(ConfigurationDescriptor::new(
+                    RISK_MANAGER_SERVICE_NAME.to_owned(),
+                    "test".to_owned(),
+                )),
+                trade_place.exchange_account_id.clone(),
+                trade_place.currency_pair.clone(),
+                "btc".into(),
+            );
+            let balance_change = ProfitLossBalanceChange::new(
+                balance_request,
+                trade_place.exchange_account_id.exchange_id.clone(),
+                ClientOrderFillId::unique_id(),
+                chrono::Utc::now(),
+                realised_profit,
+                realised_profit * dec!(60000),
+            );
+            risk_manager
+                .balance_change_usd_periodic_calculator
+                .lock()
+                .add_balance_change(&balance_change);
+        };
+
+        add_realised_profit(dec!(-0.4));
+        let mut explanation = Explanation::default();
+        assert!(risk_manager.check_order(
+            &trade_place,
+            OrderSide::Buy,
+            dec!(1),
+            dec!(1),
+            &[],
+            &mut explanation
+        ));
+
+        add_realised_profit(dec!(-0.2));
+        assert!(!risk_manager.check_order(
+            &trade_place,
+            OrderSide::Buy,
+            dec!(1),
+            dec!(1),
+            &[],
+            &mut explanation
+        ));
+        assert!(exchange_blocker
+            .is_blocked_by_reason(&exchange_account_id, block_reasons::RISK_LIMIT_BREACH));
+
+        exchange_blocker.stop_blocker().await;
+    }
+}
@@ -1,7 +1,8 @@
+use serde::{Deserialize, Serialize};
 use std::hash::Hash;
 
 /// Entity needed to describe a configuration of trading strategy, which helps to determine which strategy the balance change refers.
-#[derive(Hash, Debug, Clone, Eq, PartialEq)]
+#[derive(Hash, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ConfigurationDescriptor {
     /// Trading strategy name
     pub service_name: String,
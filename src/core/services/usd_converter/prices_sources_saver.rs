@@ -1,4 +1,7 @@
+use std::sync::Arc;
+
 use crate::core::{
+    data_recorder::recorder::{DataRecordType, DataRecorder},
     exchanges::common::TradePlace,
     misc::{
         price_by_order_side::PriceByOrderSide, price_source_model::PriceSourceModel,
@@ -7,26 +10,27 @@ use crate::core::{
 };
 
 pub struct PriceSourcesSaver {
-    // TODO: implement when DataRecorder will be added
-// data_recorder: DataRecorder;
+    data_recorder: Option<Arc<DataRecorder>>,
 }
 
 impl PriceSourcesSaver {
-    pub fn new(// data_recorder: DataRecorder
-    ) -> Self {
-        Self{
-            // data_recorder
-        }
+    pub fn new(data_recorder: Option<Arc<DataRecorder>>) -> Self {
+        Self { data_recorder }
     }
 
     pub fn save(&mut self, trade_place: TradePlace, prices: PriceByOrderSide) {
-        let _prices_source = PriceSourceModel::new(
+        let data_recorder = match &self.data_recorder {
+            Some(data_recorder) => data_recorder,
+            None => return,
+        };
+
+        let price_source = PriceSourceModel::new(
             time_manager::now(),
             trade_place.exchange_id,
             trade_place.currency_pair,
             prices.top_bid,
             prices.top_ask,
         );
-        //     _dataRecorder.Save(priceSource);
+        data_recorder.save(DataRecordType::PriceSources, &price_source);
     }
 }
@@ -1,10 +1,25 @@
-use crate::core::exchanges::common::{Amount, CurrencyCode, CurrencyPair, ExchangeAccountId};
-use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::core::disposition_execution::PriceSlotId;
+use crate::core::exchanges::common::{
+    Amount, CurrencyCode, CurrencyPair, ExchangeAccountId, Price,
+};
+use crate::core::exchanges::general::commission::Percent;
+use crate::core::service_configuration::configuration_descriptor::ConfigurationDescriptor;
+use anyhow::{Context, Result};
+use rust_decimal::Decimal;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 pub trait BaseStrategySettings {
     fn exchange_account_id(&self) -> ExchangeAccountId;
     fn currency_pair(&self) -> CurrencyPair;
     fn max_amount(&self) -> Amount;
+
+    /// Price slots created on every side of order book. Each slot keeps orders of single level,
+    /// so estimation of trading context with the same level index is applied to it
+    fn price_slots(&self) -> Vec<PriceSlotId> {
+        vec![PriceSlotId::new("PriceSlotId".into(), 0)]
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
@@ -12,15 +27,240 @@ pub struct AppSettings<StrategySettings>
 where
     StrategySettings: BaseStrategySettings + Clone,
 {
-    pub strategy: StrategySettings,
+    /// Strategy instances which are run in the same engine with shared exchanges and events
+    pub strategies: Vec<StrategyInstanceSettings<StrategySettings>>,
     pub core: CoreSettings,
 }
 
+// Field order are matter for serialization (see ExchangeSettings)
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct StrategyInstanceSettings<StrategySettings>
+where
+    StrategySettings: BaseStrategySettings + Clone,
+{
+    /// Service name of `ConfigurationDescriptor` of strategy instance
+    pub service_name: String,
+    /// Service configuration key of `ConfigurationDescriptor`, which distinguishes instances with the same service name
+    pub configuration_key: String,
+    /// Limits of position by trade places which are set with `BalanceManager::set_target_amount_limit`
+    #[serde(default)]
+    pub target_amount_limits: Vec<TargetAmountLimitSettings>,
+    pub settings: StrategySettings,
+}
+
+impl<StrategySettings> StrategyInstanceSettings<StrategySettings>
+where
+    StrategySettings: BaseStrategySettings + Clone,
+{
+    pub fn configuration_descriptor(&self) -> ConfigurationDescriptor {
+        ConfigurationDescriptor::new(self.service_name.clone(), self.configuration_key.clone())
+    }
+}
+
+/// Settings of strategy which type is selected by name from strategies supported by `EngineBuildConfig`
+// Field order are matter for serialization (see ExchangeSettings)
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ConfiguredStrategySettings {
+    /// Name of strategy in `EngineBuildConfig::supported_strategies`
+    pub strategy_type: String,
+    pub exchange_account_id: ExchangeAccountId,
+    pub currency_pair: CurrencyPair,
+    pub max_amount: Amount,
+    /// Settings specific for strategy type
+    #[serde(default)]
+    pub parameters: toml::value::Table,
+    /// Filled according to strategy type by `EngineBuildConfig` before engine launch
+    #[serde(skip)]
+    pub price_slots: Vec<PriceSlotId>,
+}
+
+impl ConfiguredStrategySettings {
+    /// Parse settings of concrete strategy type from common fields and parameters
+    pub fn parse<StrategySettings>(&self) -> Result<StrategySettings>
+    where
+        StrategySettings: DeserializeOwned,
+    {
+        let mut settings = self.parameters.clone();
+        let _ = settings.insert(
+            "exchange_account_id".to_owned(),
+            toml::Value::try_from(&self.exchange_account_id)?,
+        );
+        let _ = settings.insert(
+            "currency_pair".to_owned(),
+            toml::Value::try_from(&self.currency_pair)?,
+        );
+        let _ = settings.insert(
+            "max_amount".to_owned(),
+            toml::Value::try_from(self.max_amount)?,
+        );
+
+        toml::Value::Table(settings).try_into().with_context(|| {
+            format!(
+                "Unable parse settings of strategy type {}",
+                self.strategy_type
+            )
+        })
+    }
+}
+
+impl BaseStrategySettings for ConfiguredStrategySettings {
+    fn exchange_account_id(&self) -> ExchangeAccountId {
+        self.exchange_account_id.clone()
+    }
+
+    fn currency_pair(&self) -> CurrencyPair {
+        self.currency_pair.clone()
+    }
+
+    fn max_amount(&self) -> Amount {
+        self.max_amount
+    }
+
+    fn price_slots(&self) -> Vec<PriceSlotId> {
+        self.price_slots.clone()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TargetAmountLimitSettings {
+    pub exchange_account_id: ExchangeAccountId,
+    pub currency_pair: CurrencyPair,
+    pub limit: Amount,
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct CoreSettings {
+    /// Persisting of orders, fills, balances and other trading data. Disabled if not specified
+    pub data_recorder: Option<DataRecorderSettings>,
+    /// Cancellation of all orders during graceful shutdown
+    #[serde(default)]
+    pub orders_cancellation: OrdersCancellationSettings,
+    /// Cancellation of all orders if engine hangs or loses connection to exchange. Disabled if not specified
+    pub dead_man_switch: Option<DeadManSwitchSettings>,
+    /// Limits checked before creation of orders. Disabled if not specified
+    pub risk_manager: Option<RiskManagerSettings>,
+    /// Pre-trade checks of order prices and amounts. Disabled if not specified
+    pub order_protection: Option<OrderProtectionSettings>,
+    /// Prevention of crossing own orders of all strategies and accounts on the same trade place. Disabled if not specified
+    pub self_trade_prevention: Option<SelfTradePreventionSettings>,
+    /// Cancellation of orders on trade places without fresh order book updates. Disabled if not specified
+    pub market_data_watchdog: Option<MarketDataWatchdogSettings>,
     pub exchanges: Vec<ExchangeSettings>,
 }
 
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct OrdersCancellationSettings {
+    /// Max duration of cancellation and checking that there are no open orders on exchanges
+    pub deadline_secs: u64,
+    /// Delay before next attempt if open orders are still received from exchange
+    pub retry_interval_ms: u64,
+}
+
+impl Default for OrdersCancellationSettings {
+    fn default() -> Self {
+        OrdersCancellationSettings {
+            deadline_secs: 5,
+            retry_interval_ms: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DeadManSwitchSettings {
+    /// Duration of events loop stalling or exchange connectivity outage after which all orders are cancelled
+    /// and exchange is blocked until recovering.
+    /// Also it's used as countdown for exchanges which support cancellation of all orders on their side
+    pub timeout_secs: u64,
+    /// Period of checks. Countdown is refreshed on checks a few times per timeout
+    pub check_interval_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RiskManagerSettings {
+    /// Rolling window for calculation of realised loss
+    pub loss_period_secs: u64,
+    /// Duration of exchange blocking after limit breach
+    pub block_duration_secs: u64,
+    pub limits: Vec<RiskLimitsSettings>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RiskLimitsSettings {
+    pub exchange_account_id: ExchangeAccountId,
+    pub currency_pair: CurrencyPair,
+    /// Max absolute position accumulated by fills including amounts of open orders
+    pub max_position: Option<Amount>,
+    /// Max total cost of open orders in quote currency
+    pub max_open_notional: Option<Amount>,
+    /// Max realised loss in USD over loss period
+    pub max_loss_in_usd: Option<Amount>,
+    /// Price of quote currency in USD for conversion of realised profit and loss
+    pub quote_currency_usd_price: Price,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct OrderProtectionSettings {
+    /// Max deviation of order price in percents from mid price of order book or from last trade price
+    pub max_price_deviation_percent: Decimal,
+    pub price_band_action: PriceBandAction,
+    #[serde(default)]
+    pub max_order_amounts: Vec<MaxOrderAmountSettings>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PriceBandAction {
+    /// Don't create orders priced outside of band
+    Reject,
+    /// Move price of orders to the nearest band bound
+    Clamp,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MaxOrderAmountSettings {
+    pub exchange_account_id: ExchangeAccountId,
+    pub currency_pair: CurrencyPair,
+    pub max_amount: Amount,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SelfTradePreventionSettings {
+    pub mode: SelfTradePreventionMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SelfTradePreventionMode {
+    /// Cancel own resting orders crossed by new order, new order is created after their cancellation
+    CancelResting,
+    /// Don't create orders which cross own resting orders
+    SkipNew,
+    /// Rely on self-trade prevention of exchange. Own resting orders are expired by exchange when crossed.
+    /// New orders are skipped on exchanges without such support
+    Native,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MarketDataWatchdogSettings {
+    /// Duration without order book updates after which trade place is considered stale
+    pub max_order_book_age_ms: u64,
+    /// Period of checks of order book update times
+    pub check_interval_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DataRecorderSettings {
+    pub database_path: String,
+    /// What to do with orders which are still open on exchanges after restart
+    pub restored_orders_policy: RestoredOrdersPolicy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RestoredOrdersPolicy {
+    /// Pass orders to `DispositionExecutor` so it continues to manage them
+    Adopt,
+    /// Cancel orders before strategy start
+    Cancel,
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct CurrencyPairSetting {
     pub base: CurrencyCode,
@@ -41,9 +281,33 @@ pub struct ExchangeSettings {
     pub is_margin_trading: bool,
     pub request_trades: bool,
     pub is_reducing_market_data: Option<bool>,
+    /// Directory for recording of raw WebSocket messages and REST responses. Recording is disabled if not specified
+    pub raw_messages_directory: Option<String>,
+    /// Request self-trade prevention of exchange for created orders. It's set by engine for Native self-trade prevention mode
+    #[serde(default)]
+    pub native_self_trade_prevention: bool,
     pub subscribe_to_market_data: bool,
+    /// Timeout of receiving order book snapshots after websockets reconnection. Exchange stays blocked
+    /// and snapshots are requested again after it. Default one is used if not specified
+    pub order_book_snapshots_timeout_secs: Option<u64>,
     pub websocket_channels: Vec<String>,
     pub currency_pairs: Option<Vec<CurrencyPairSetting>>,
+    /// Simulation parameters for paper trading exchanges. Default ones of paper exchange are used if not specified
+    pub paper_trading: Option<PaperTradingSettings>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PaperTradingSettings {
+    /// Fee of maker fills in percents
+    pub maker_fee: Percent,
+    /// Fee of taker fills in percents
+    pub taker_fee: Percent,
+    /// Delay before order creation or cancellation takes effect
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Balances reported by the simulated account
+    #[serde(default)]
+    pub balances: HashMap<CurrencyCode, Amount>,
 }
 
 impl ExchangeSettings {
@@ -63,7 +327,11 @@ impl ExchangeSettings {
             websocket_channels: vec![],
             currency_pairs: None,
             subscribe_to_market_data: true,
+            order_book_snapshots_timeout_secs: None,
             is_reducing_market_data: None,
+            raw_messages_directory: None,
+            native_self_trade_prevention: false,
+            paper_trading: None,
         }
     }
 }
@@ -86,7 +354,11 @@ impl Default for ExchangeSettings {
             websocket_channels: vec![],
             currency_pairs: None,
             subscribe_to_market_data: true,
+            order_book_snapshots_timeout_secs: None,
             is_reducing_market_data: None,
+            raw_messages_directory: None,
+            native_self_trade_prevention: false,
+            paper_trading: None,
         }
     }
 }
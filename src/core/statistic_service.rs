@@ -26,6 +26,8 @@ pub struct TradePlaceAccountStatistic {
     canceled_orders_count: u64,
     partially_filled_orders_count: u64,
     fully_filled_orders_count: u64,
+    // Orders rejected by pre-trade checks
+    rejected_orders_count: u64,
     // Calculated only for completely filled orders
     summary_filled_amount: Amount,
     // Calculated only for completely filled orders
@@ -41,6 +43,10 @@ impl TradePlaceAccountStatistic {
         self.canceled_orders_count += 1;
     }
 
+    fn register_rejected_order(&mut self) {
+        self.rejected_orders_count += 1;
+    }
+
     fn increment_partially_filled_orders(&mut self) {
         self.partially_filled_orders_count += 1;
     }
@@ -109,6 +115,14 @@ impl StatisticServiceState {
             .register_canceled_order();
     }
 
+    pub(crate) fn register_rejected_order(&self, trade_place_account: &TradePlaceAccount) {
+        self.trade_place_stats
+            .write()
+            .entry(trade_place_account.clone())
+            .or_default()
+            .register_rejected_order();
+    }
+
     pub(crate) fn register_partially_filled_order(&self, trade_place_account: &TradePlaceAccount) {
         self.trade_place_stats
             .write()
@@ -192,6 +206,11 @@ impl StatisticService {
         self.remove_filled_order_if_exist(&trade_place_account, &client_order_id);
     }
 
+    pub(crate) fn register_rejected_order(&self, trade_place_account: &TradePlaceAccount) {
+        self.statistic_service_state
+            .register_rejected_order(trade_place_account);
+    }
+
     pub(crate) fn register_partially_filled_order(
         &self,
         trade_place_account: &TradePlaceAccount,
@@ -278,7 +297,7 @@ impl StatisticEventHandler {
         }
     }
 
-    fn handle_event(&self, event: ExchangeEvent) -> Result<()> {
+    pub(crate) fn handle_event(&self, event: ExchangeEvent) -> Result<()> {
         match event {
             ExchangeEvent::OrderEvent(order_event) => {
                 let trade_place_account = TradePlaceAccount::new(
@@ -6,10 +6,15 @@ use std::{sync::mpsc, sync::mpsc::Sender, sync::Arc, thread};
 
 use super::endpoints;
 use actix_web::{dev::Server, rt, App, HttpServer};
-use tokio::sync::oneshot;
+use tokio::runtime::Handle;
+use tokio::sync::{oneshot, watch};
 
 use crate::core::{
-    lifecycle::{application_manager::ApplicationManager, trading_engine::Service},
+    lifecycle::{
+        application_manager::ApplicationManager,
+        trading_engine::{OrdersCancellationReport, Service},
+    },
+    market_data_watchdog::MarketDataWatchdog,
     statistic_service::StatisticService,
 };
 use actix_web::web::Data;
@@ -22,6 +27,8 @@ pub(crate) struct ControlPanel {
     work_finished_sender: Arc<Mutex<Option<oneshot::Sender<Result<()>>>>>,
     work_finished_receiver: Arc<Mutex<Option<oneshot::Receiver<Result<()>>>>>,
     statistics: Arc<StatisticService>,
+    orders_cancellation_report: watch::Receiver<Option<OrdersCancellationReport>>,
+    market_data_watchdog: Option<Arc<MarketDataWatchdog>>,
 }
 
 impl ControlPanel {
@@ -30,6 +37,8 @@ impl ControlPanel {
         engine_settings: String,
         application_manager: Arc<ApplicationManager>,
         statistics: Arc<StatisticService>,
+        orders_cancellation_report: watch::Receiver<Option<OrdersCancellationReport>>,
+        market_data_watchdog: Option<Arc<MarketDataWatchdog>>,
     ) -> Arc<Self> {
         let (work_finished_sender, work_finished_receiver) = oneshot::channel();
         Arc::new(Self {
@@ -40,6 +49,8 @@ impl ControlPanel {
             work_finished_sender: Arc::new(Mutex::new(Some(work_finished_sender))),
             work_finished_receiver: Arc::new(Mutex::new(Some(work_finished_receiver))),
             statistics,
+            orders_cancellation_report,
+            market_data_watchdog,
         })
     }
 
@@ -62,15 +73,22 @@ impl ControlPanel {
         let engine_settings = self.engine_settings.clone();
         let application_manager = self.application_manager.clone();
         let statistics = self.statistics.clone();
+        let orders_cancellation_report = self.orders_cancellation_report.clone();
+        let market_data_watchdog = self.market_data_watchdog.clone();
+        // Server is stopped during graceful shutdown, so engine runtime is needed to run it from endpoints
+        let engine_runtime = Handle::current();
         let server = HttpServer::new(move || {
             App::new()
-                .app_data(Data::new(server_stopper_tx.clone()))
                 .app_data(Data::new(engine_settings.clone()))
                 .app_data(Data::new(application_manager.clone()))
                 .app_data(Data::new(statistics.clone()))
+                .app_data(Data::new(orders_cancellation_report.clone()))
+                .app_data(Data::new(engine_runtime.clone()))
+                .app_data(Data::new(market_data_watchdog.clone()))
                 .service(endpoints::health)
                 .service(endpoints::stop)
                 .service(endpoints::stats)
+                .service(endpoints::stale_market_data)
                 .service(endpoints::get_config)
                 .service(endpoints::set_config)
         })
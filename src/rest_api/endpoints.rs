@@ -1,10 +1,14 @@
 use actix_web::{error, get, post, web, Error, HttpResponse, Responder};
-use log::{error, warn};
-use std::sync::{mpsc::Sender, Arc};
+use log::{error, info, warn};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tokio::sync::watch;
 
 use crate::core::{
     config::save_settings, config::CONFIG_PATH, config::CREDENTIALS_PATH,
-    lifecycle::application_manager::ApplicationManager, statistic_service::StatisticService,
+    lifecycle::application_manager::ApplicationManager,
+    lifecycle::trading_engine::OrdersCancellationReport, market_data_watchdog::MarketDataWatchdog,
+    statistic_service::StatisticService,
 };
 
 // New endpoints have to be added as a service for actix server. Look at super::control_panel::start_server()
@@ -14,13 +18,42 @@ pub(super) async fn health() -> impl Responder {
     HttpResponse::Ok().body("Bot is working")
 }
 
+/// Stop trading engine and respond with report about cancellation of all orders
 #[post("/stop")]
-pub(super) async fn stop(server_stopper_tx: web::Data<Sender<()>>) -> impl Responder {
-    if let Err(error) = server_stopper_tx.send(()) {
-        error!("Unable to send signal to stop actix server: {}", error);
-    }
+pub(super) async fn stop(
+    application_manager: web::Data<Arc<ApplicationManager>>,
+    engine_runtime: web::Data<Handle>,
+    orders_cancellation_report: web::Data<watch::Receiver<Option<OrdersCancellationReport>>>,
+) -> Result<HttpResponse, Error> {
+    let application_manager = application_manager.get_ref().clone();
+    let graceful_shutdown = engine_runtime.spawn(async move {
+        application_manager
+            .run_graceful_shutdown("Engine stopped via ControlPanel")
+            .await
+    });
+    // Response is sent before the end of graceful shutdown, so its outcome is only logged
+    engine_runtime.spawn(async move {
+        match graceful_shutdown.await {
+            Ok(()) => info!("Graceful shutdown requested via ControlPanel finished"),
+            Err(error) => error!(
+                "Graceful shutdown requested via ControlPanel failed: {:?}",
+                error
+            ),
+        }
+    });
 
-    HttpResponse::Ok().body("ControlPanel turned off")
+    let mut orders_cancellation_report = orders_cancellation_report.get_ref().clone();
+    loop {
+        if let Some(report) = orders_cancellation_report.borrow().clone() {
+            return Ok(HttpResponse::Ok().json(report));
+        }
+
+        if orders_cancellation_report.changed().await.is_err() {
+            let error_message = "Engine was stopped without report about orders cancellation";
+            error!("{}", error_message);
+            return Err(error::ErrorInternalServerError(error_message));
+        }
+    }
 }
 
 #[get("/config")]
@@ -61,3 +94,17 @@ pub(super) async fn stats(
 
     Ok(HttpResponse::Ok().body(&json_statistic))
 }
+
+/// Episodes when trade places had no fresh order book updates. Empty if market data watchdog is disabled
+#[get("/stale_market_data")]
+pub(super) async fn stale_market_data(
+    market_data_watchdog: web::Data<Option<Arc<MarketDataWatchdog>>>,
+) -> impl Responder {
+    let episodes = market_data_watchdog
+        .get_ref()
+        .as_ref()
+        .map(|x| x.episodes())
+        .unwrap_or_default();
+
+    HttpResponse::Ok().json(episodes)
+}
@@ -0,0 +1,638 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::Duration;
+use enum_map::EnumMap;
+use log::{error, info, warn};
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+use crate::core::disposition_execution::{
+    PriceSlot, TradeCycle, TradeDisposition, TradingContext, TradingContextBySide,
+};
+use crate::core::exchanges::common::{
+    Amount, CurrencyPair, ExchangeAccountId, Price, TradePlace, TradePlaceAccount,
+};
+use crate::core::exchanges::general::commission::Percent;
+use crate::core::exchanges::general::currency_pair_metadata::{CurrencyPairMetadata, Round};
+use crate::core::exchanges::general::exchange::Exchange;
+use crate::core::explanation::{Explanation, WithExplanation};
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::lifecycle::trading_engine::EngineContext;
+use crate::core::math::ConvertPercentToRate;
+use crate::core::order_book::local_snapshot_service::LocalSnapshotsService;
+use crate::core::orders::order::{ClientOrderId, OrderRole, OrderSide, OrderSnapshot};
+use crate::core::settings::BaseStrategySettings;
+use crate::core::DateTime;
+use crate::strategies::disposition_strategy::DispositionStrategy;
+
+static STRATEGY_NAME: &str = "CrossExchangeMarketMaking";
+const HEDGE_ATTEMPTS_COUNT: usize = 3;
+
+fn hedge_retry_delay() -> Duration {
+    Duration::seconds(1)
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CrossExchangeMarketMakingSettings {
+    /// Exchange where maker orders are placed
+    pub maker_exchange_account_id: ExchangeAccountId,
+    /// Exchange which order book is used for pricing and where fills are hedged
+    pub taker_exchange_account_id: ExchangeAccountId,
+    pub currency_pair: CurrencyPair,
+    /// Max total amount of orders on each side
+    pub max_amount: Amount,
+    pub order_amount: Amount,
+    /// Minimal profit of maker order after hedging on taker exchange with taker fee in percents
+    pub min_profitability: Percent,
+}
+
+impl BaseStrategySettings for CrossExchangeMarketMakingSettings {
+    fn exchange_account_id(&self) -> ExchangeAccountId {
+        self.maker_exchange_account_id.clone()
+    }
+
+    fn currency_pair(&self) -> CurrencyPair {
+        self.currency_pair.clone()
+    }
+
+    fn max_amount(&self) -> Amount {
+        self.max_amount
+    }
+}
+
+/// Hedging of maker fills of one side
+#[derive(Debug, Default)]
+struct HedgeState {
+    /// Amount of maker fills which isn't hedged and isn't being hedged by taker order now
+    unhedged_amount: Amount,
+    /// Price of the last maker fill, which is used as estimation of hedge order price
+    price: Price,
+    /// Count of hedge orders in a row which weren't filled completely
+    failed_attempts: usize,
+    /// Failed hedge is retried not earlier than this time
+    retry_time: Option<DateTime>,
+}
+
+/// Strategy which quotes on maker exchange with prices of taker exchange order book
+/// and hedges every maker fill by market order on taker exchange,
+/// like cross_exchange_market_making strategy of hummingbot.
+/// Hedge orders are created by disposition executor as taker orders of the strategy
+pub struct CrossExchangeMarketMakingStrategy {
+    settings: CrossExchangeMarketMakingSettings,
+    engine_context: Arc<EngineContext>,
+    /// Filled amounts of maker orders which are already hedged
+    hedged_fill_amounts: Mutex<HashMap<ClientOrderId, Amount>>,
+    /// Hedging state by side of maker orders
+    hedges: Mutex<EnumMap<OrderSide, HedgeState>>,
+}
+
+impl CrossExchangeMarketMakingStrategy {
+    pub fn new(
+        settings: CrossExchangeMarketMakingSettings,
+        engine_context: Arc<EngineContext>,
+    ) -> Self {
+        CrossExchangeMarketMakingStrategy {
+            settings,
+            engine_context,
+            hedged_fill_amounts: Default::default(),
+            hedges: Default::default(),
+        }
+    }
+
+    /// Amount of maker fills on specified side which isn't hedged on taker exchange
+    /// and isn't being hedged now
+    pub fn unhedged_amount(&self, maker_side: OrderSide) -> Amount {
+        self.hedges.lock()[maker_side].unhedged_amount
+    }
+
+    fn maker_trade_place_account(&self) -> TradePlaceAccount {
+        TradePlaceAccount::new(
+            self.settings.maker_exchange_account_id.clone(),
+            self.settings.currency_pair.clone(),
+        )
+    }
+
+    fn taker_trade_place_account(&self) -> TradePlaceAccount {
+        TradePlaceAccount::new(
+            self.settings.taker_exchange_account_id.clone(),
+            self.settings.currency_pair.clone(),
+        )
+    }
+
+    fn taker_trade_place(&self) -> TradePlace {
+        self.taker_trade_place_account().trade_place()
+    }
+
+    fn exchange(&self, exchange_account_id: &ExchangeAccountId) -> Option<Arc<Exchange>> {
+        self.engine_context
+            .exchanges
+            .get(exchange_account_id)
+            .map(|x| x.value().clone())
+    }
+
+    fn calc_trading_context_by_side(
+        &self,
+        side: OrderSide,
+        max_amount: Amount,
+        market: &MarketState,
+        explanation: &Explanation,
+    ) -> TradingContextBySide {
+        let mut explanation = explanation.clone();
+        let trade_cycle = self.estimate_side(side, market, &mut explanation);
+
+        TradingContextBySide {
+            max_amount,
+            estimating: vec![WithExplanation {
+                value: trade_cycle,
+                explanation,
+            }],
+        }
+    }
+
+    fn estimate_side(
+        &self,
+        side: OrderSide,
+        market: &MarketState,
+        explanation: &mut Explanation,
+    ) -> Option<TradeCycle> {
+        let currency_pair_metadata = &market.currency_pair_metadata;
+
+        // Maker buy is hedged by selling to taker bids and vice versa
+        let (hedge_price, hedge_liquidity) = match side {
+            OrderSide::Buy => market.taker_bid,
+            OrderSide::Sell => market.taker_ask,
+        };
+
+        let price = calculate_maker_price(
+            side,
+            hedge_price,
+            self.settings.min_profitability.percent_to_rate(),
+            market.taker_fee,
+        );
+        let price = match side {
+            OrderSide::Buy => currency_pair_metadata.price_round(price, Round::Floor),
+            OrderSide::Sell => currency_pair_metadata.price_round(price, Round::Ceiling),
+        }
+        .ok()?;
+
+        let is_crossing_book = match side {
+            OrderSide::Buy => price >= market.maker_ask,
+            OrderSide::Sell => price <= market.maker_bid,
+        };
+        if is_crossing_book {
+            explanation.add_reason(format!(
+                "Price {} {} crosses maker order book top",
+                price, side
+            ));
+            return None;
+        }
+
+        let amount = currency_pair_metadata
+            .amount_round(
+                self.settings.order_amount.min(hedge_liquidity),
+                Round::Floor,
+            )
+            .ok()?;
+        if amount.is_zero() {
+            explanation.add_reason(format!(
+                "Amount {} is zero because of taker order book liquidity {}",
+                side, hedge_liquidity
+            ));
+            return None;
+        }
+
+        explanation.add_reason(format!(
+            "{}: price {} amount {} hedge price {}",
+            side, price, amount, hedge_price
+        ));
+
+        Some(TradeCycle {
+            order_role: OrderRole::Maker,
+            strategy_name: STRATEGY_NAME.to_string(),
+            disposition: TradeDisposition::new(
+                self.maker_trade_place_account(),
+                side,
+                price,
+                amount,
+            ),
+        })
+    }
+
+    /// Returns amount of maker order which was filled since the previous call for this order
+    fn take_new_filled_amount(&self, cloned_order: &OrderSnapshot) -> Amount {
+        let client_order_id = &cloned_order.header.client_order_id;
+        let filled_amount = cloned_order.filled_amount();
+
+        let mut hedged_fill_amounts = self.hedged_fill_amounts.lock();
+        let hedged_amount = hedged_fill_amounts
+            .insert(client_order_id.clone(), filled_amount)
+            .unwrap_or_default();
+
+        if cloned_order.status().is_finished() {
+            let _ = hedged_fill_amounts.remove(client_order_id);
+        }
+
+        filled_amount - hedged_amount
+    }
+
+    /// Taker order for unhedged amount of maker fills on specified side,
+    /// if it's not waiting for retry and attempts aren't over
+    fn take_hedge(
+        &self,
+        maker_side: OrderSide,
+        hedge_state: &mut HedgeState,
+        now: DateTime,
+    ) -> Option<TradeCycle> {
+        if hedge_state.failed_attempts >= HEDGE_ATTEMPTS_COUNT
+            || hedge_state.retry_time.is_some_and(|x| now < x)
+        {
+            return None;
+        }
+
+        let hedge_amount = match self
+            .exchange(&self.settings.taker_exchange_account_id)?
+            .get_currency_pair_metadata(&self.settings.currency_pair)
+            .and_then(|x| x.amount_round(hedge_state.unhedged_amount, Round::Floor))
+        {
+            Ok(hedge_amount) => hedge_amount,
+            Err(error) => {
+                error!("Unable to round hedge amount: {:?}", error);
+                return None;
+            }
+        };
+        // Whole amount stays unhedged if it can't be rounded
+        if hedge_amount <= dec!(0) {
+            return None;
+        }
+        hedge_state.unhedged_amount -= hedge_amount;
+
+        let hedge_side = maker_side.change_side();
+        info!(
+            "Hedging maker fills by {} {} on {}",
+            hedge_side, hedge_amount, self.settings.taker_exchange_account_id
+        );
+
+        Some(TradeCycle {
+            order_role: OrderRole::Taker,
+            strategy_name: STRATEGY_NAME.to_string(),
+            disposition: TradeDisposition::new(
+                self.taker_trade_place_account(),
+                hedge_side,
+                hedge_state.price,
+                hedge_amount,
+            ),
+        })
+    }
+}
+
+impl DispositionStrategy for CrossExchangeMarketMakingStrategy {
+    fn calculate_trading_context(
+        &mut self,
+        max_amount: Decimal,
+        _now: DateTime,
+        local_snapshots_service: &LocalSnapshotsService,
+        explanation: &mut Explanation,
+    ) -> Option<TradingContext> {
+        let maker_snapshot = local_snapshots_service
+            .get_snapshot(&self.maker_trade_place_account().trade_place())?;
+        let taker_snapshot = local_snapshots_service.get_snapshot(&self.taker_trade_place())?;
+        let maker_exchange = self.exchange(&self.settings.maker_exchange_account_id)?;
+        let taker_exchange = self.exchange(&self.settings.taker_exchange_account_id)?;
+
+        let market = MarketState {
+            maker_ask: maker_snapshot.get_top_ask()?.0,
+            maker_bid: maker_snapshot.get_top_bid()?.0,
+            taker_ask: taker_snapshot.get_top_ask()?,
+            taker_bid: taker_snapshot.get_top_bid()?,
+            currency_pair_metadata: maker_exchange
+                .get_currency_pair_metadata(&self.settings.currency_pair)
+                .ok()?,
+            taker_fee: taker_exchange
+                .commission()
+                .get_commission(OrderRole::Taker)
+                .fee
+                .percent_to_rate(),
+        };
+
+        let buy_trading_ctx =
+            self.calc_trading_context_by_side(OrderSide::Buy, max_amount, &market, explanation);
+        let sell_trading_ctx =
+            self.calc_trading_context_by_side(OrderSide::Sell, max_amount, &market, explanation);
+
+        Some(TradingContext::new(buy_trading_ctx, sell_trading_ctx))
+    }
+
+    /// New filled amount of maker order is hedged together with previously unhedged amount of the same side
+    fn handle_order_fill(
+        &self,
+        cloned_order: &Arc<OrderSnapshot>,
+        _price_slot: &PriceSlot,
+        _target_eai: &ExchangeAccountId,
+        _cancellation_token: CancellationToken,
+    ) -> Result<()> {
+        if cloned_order.header.exchange_account_id != self.settings.maker_exchange_account_id {
+            return Ok(());
+        }
+
+        let new_filled_amount = self.take_new_filled_amount(cloned_order);
+        if new_filled_amount <= dec!(0) {
+            return Ok(());
+        }
+
+        info!(
+            "Maker order {} is filled by {}, it will be hedged on {}",
+            cloned_order.header.client_order_id,
+            new_filled_amount,
+            self.settings.taker_exchange_account_id
+        );
+
+        let mut hedges = self.hedges.lock();
+        let hedge_state = &mut hedges[cloned_order.header.side];
+        hedge_state.unhedged_amount += new_filled_amount;
+        hedge_state.price = cloned_order.price();
+        // New fill is hedged immediately even if attempts for previous ones are over
+        hedge_state.failed_attempts = 0;
+        hedge_state.retry_time = None;
+
+        Ok(())
+    }
+
+    fn take_taker_orders(&mut self, now: DateTime) -> Vec<TradeCycle> {
+        let mut hedges = self.hedges.lock();
+        hedges
+            .iter_mut()
+            .filter_map(|(maker_side, hedge_state)| self.take_hedge(maker_side, hedge_state, now))
+            .collect()
+    }
+
+    /// Not filled amount of hedge order is retried after delay. Amount which isn't hedged
+    /// after all attempts is hedged together with the next maker fill of the same side
+    fn handle_taker_order_finished(
+        &mut self,
+        trade_cycle: &TradeCycle,
+        unfilled_amount: Amount,
+        now: DateTime,
+    ) {
+        let disposition = &trade_cycle.disposition;
+        let maker_side = disposition.side().change_side();
+        let mut hedges = self.hedges.lock();
+        let hedge_state = &mut hedges[maker_side];
+
+        if unfilled_amount <= dec!(0) {
+            hedge_state.failed_attempts = 0;
+            return;
+        }
+
+        hedge_state.unhedged_amount += unfilled_amount;
+        hedge_state.failed_attempts += 1;
+        hedge_state.retry_time = Some(now + hedge_retry_delay());
+
+        if hedge_state.failed_attempts < HEDGE_ATTEMPTS_COUNT {
+            warn!(
+                "Hedge attempt {} of {} for {} {} isn't filled by {}, it will be retried",
+                hedge_state.failed_attempts,
+                HEDGE_ATTEMPTS_COUNT,
+                disposition.side(),
+                disposition.amount(),
+                unfilled_amount
+            );
+        } else {
+            error!(
+                "Hedge of {} {} failed after {} attempts, unhedged amount {} is tracked",
+                disposition.side(),
+                disposition.amount(),
+                HEDGE_ATTEMPTS_COUNT,
+                hedge_state.unhedged_amount
+            );
+        }
+    }
+}
+
+/// Market data of both exchanges used for estimation
+struct MarketState {
+    maker_ask: Price,
+    maker_bid: Price,
+    taker_ask: (Price, Amount),
+    taker_bid: (Price, Amount),
+    /// Metadata of maker exchange
+    currency_pair_metadata: Arc<CurrencyPairMetadata>,
+    /// Taker fee as rate
+    taker_fee: Decimal,
+}
+
+/// Price of maker order which gives required profit after hedging by taker order with specified price
+fn calculate_maker_price(
+    maker_side: OrderSide,
+    hedge_price: Price,
+    min_profitability: Decimal,
+    taker_fee: Decimal,
+) -> Price {
+    match maker_side {
+        OrderSide::Buy => hedge_price * (dec!(1) - min_profitability - taker_fee),
+        OrderSide::Sell => hedge_price * (dec!(1) + min_profitability + taker_fee),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use chrono::Utc;
+    use tokio::sync::{broadcast, oneshot};
+
+    use super::*;
+    use crate::core::balance_manager::balance_manager::BalanceManager;
+    use crate::core::disposition_execution::{CompositeOrder, PriceSlotId};
+    use crate::core::exchanges::events::ExchangeEvents;
+    use crate::core::exchanges::exchange_blocker::ExchangeBlocker;
+    use crate::core::exchanges::general::currency_pair_metadata::Precision;
+    use crate::core::exchanges::general::currency_pair_to_metadata_converter::CurrencyPairToMetadataConverter;
+    use crate::core::exchanges::general::test_helper::get_test_exchange_with_currency_pair_metadata_and_id;
+    use crate::core::exchanges::timeouts::timeout_manager::TimeoutManager;
+    use crate::core::lifecycle::application_manager::ApplicationManager;
+    use crate::core::orders::order::{OrderStatus, OrderType};
+    use crate::core::settings::CoreSettings;
+
+    /// Strategy with maker and taker exchanges
+    fn strategy() -> CrossExchangeMarketMakingStrategy {
+        let base_currency_code = "eth".into();
+        let quote_currency_code = "btc".into();
+        let currency_pair_metadata = Arc::new(CurrencyPairMetadata::new(
+            false,
+            false,
+            "eth".into(),
+            base_currency_code,
+            "btc".into(),
+            quote_currency_code,
+            None,
+            None,
+            None,
+            None,
+            None,
+            "eth".into(),
+            Some("eth".into()),
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0.001) },
+        ));
+        let settings = CrossExchangeMarketMakingSettings {
+            maker_exchange_account_id: ExchangeAccountId::new("maker".into(), 0),
+            taker_exchange_account_id: ExchangeAccountId::new("taker".into(), 0),
+            currency_pair: currency_pair_metadata.currency_pair(),
+            max_amount: dec!(10),
+            order_amount: dec!(1),
+            min_profitability: dec!(0.1),
+        };
+
+        let exchanges_by_id: HashMap<_, _> = [
+            &settings.maker_exchange_account_id,
+            &settings.taker_exchange_account_id,
+        ]
+        .iter()
+        .map(|&exchange_account_id| {
+            let (exchange, _) = get_test_exchange_with_currency_pair_metadata_and_id(
+                currency_pair_metadata.clone(),
+                exchange_account_id,
+            );
+            (exchange_account_id.clone(), exchange)
+        })
+        .collect();
+
+        let balance_manager = BalanceManager::new(
+            exchanges_by_id.clone(),
+            CurrencyPairToMetadataConverter::new(exchanges_by_id.clone()),
+            None,
+        );
+
+        let engine_context = EngineContext::new(
+            CoreSettings::default(),
+            exchanges_by_id.clone().into_iter().collect(),
+            ExchangeEvents::new(broadcast::channel(10).0),
+            oneshot::channel().0,
+            TimeoutManager::new(HashMap::new()),
+            ApplicationManager::new(CancellationToken::new()),
+            None,
+            balance_manager,
+            ExchangeBlocker::new(exchanges_by_id.keys().cloned().collect()),
+        );
+
+        CrossExchangeMarketMakingStrategy::new(settings, engine_context)
+    }
+
+    fn fill_maker_order(
+        strategy: &CrossExchangeMarketMakingStrategy,
+        side: OrderSide,
+        filled_amount: Amount,
+    ) {
+        let mut order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderType::Limit,
+            Some(OrderRole::Maker),
+            strategy.settings.maker_exchange_account_id.clone(),
+            strategy.settings.currency_pair.clone(),
+            dec!(100),
+            filled_amount,
+            side,
+            None,
+            STRATEGY_NAME,
+        );
+        order.fills.filled_amount = filled_amount;
+        order.props.status = OrderStatus::Completed;
+        let price_slot = PriceSlot {
+            id: PriceSlotId::new(STRATEGY_NAME.into(), 0),
+            estimating: RefCell::new(None),
+            order: RefCell::new(CompositeOrder::new(side)),
+        };
+
+        strategy
+            .handle_order_fill(
+                &Arc::new(order),
+                &price_slot,
+                &strategy.settings.maker_exchange_account_id,
+                CancellationToken::new(),
+            )
+            .expect("in test");
+    }
+
+    fn take_hedge(strategy: &mut CrossExchangeMarketMakingStrategy, now: DateTime) -> TradeCycle {
+        let mut taker_orders = strategy.take_taker_orders(now);
+        assert_eq!(taker_orders.len(), 1);
+        taker_orders.remove(0)
+    }
+
+    #[tokio::test]
+    async fn maker_fill_is_hedged_by_taker_order() {
+        let mut strategy = strategy();
+        let now = Utc::now();
+        fill_maker_order(&strategy, OrderSide::Sell, dec!(1));
+
+        let hedge = take_hedge(&mut strategy, now);
+        assert_eq!(hedge.order_role, OrderRole::Taker);
+        assert_eq!(
+            hedge.disposition,
+            TradeDisposition::new(
+                strategy.taker_trade_place_account(),
+                OrderSide::Buy,
+                dec!(100),
+                dec!(1)
+            )
+        );
+        // Amount which is being hedged isn't hedged again
+        assert!(strategy.take_taker_orders(now).is_empty());
+
+        strategy.handle_taker_order_finished(&hedge, dec!(0), now);
+        assert!(strategy.take_taker_orders(now).is_empty());
+        assert_eq!(strategy.unhedged_amount(OrderSide::Sell), dec!(0));
+    }
+
+    #[tokio::test]
+    async fn unfilled_hedge_amount_is_retried_after_delay() {
+        let mut strategy = strategy();
+        let now = Utc::now();
+        fill_maker_order(&strategy, OrderSide::Sell, dec!(1));
+
+        let hedge = take_hedge(&mut strategy, now);
+        strategy.handle_taker_order_finished(&hedge, dec!(0.4), now);
+        assert_eq!(strategy.unhedged_amount(OrderSide::Sell), dec!(0.4));
+        assert!(strategy.take_taker_orders(now).is_empty());
+
+        let now = now + hedge_retry_delay();
+        let hedge = take_hedge(&mut strategy, now);
+        assert_eq!(hedge.disposition.amount(), dec!(0.4));
+
+        strategy.handle_taker_order_finished(&hedge, dec!(0), now);
+        assert_eq!(strategy.unhedged_amount(OrderSide::Sell), dec!(0));
+    }
+
+    #[tokio::test]
+    async fn failed_hedge_amount_is_hedged_with_next_fill() {
+        let mut strategy = strategy();
+        let mut now = Utc::now();
+        fill_maker_order(&strategy, OrderSide::Sell, dec!(1));
+
+        for _ in 0..HEDGE_ATTEMPTS_COUNT {
+            let hedge = take_hedge(&mut strategy, now);
+            strategy.handle_taker_order_finished(&hedge, hedge.disposition.amount(), now);
+            now = now + hedge_retry_delay();
+        }
+        assert!(strategy.take_taker_orders(now).is_empty());
+        assert_eq!(strategy.unhedged_amount(OrderSide::Sell), dec!(1));
+        assert_eq!(strategy.unhedged_amount(OrderSide::Buy), dec!(0));
+
+        fill_maker_order(&strategy, OrderSide::Sell, dec!(0.5));
+        let hedge = take_hedge(&mut strategy, now);
+        assert_eq!(hedge.disposition.amount(), dec!(1.5));
+    }
+
+    #[test]
+    fn maker_prices_include_profitability_and_taker_fee() {
+        let buy_price = calculate_maker_price(OrderSide::Buy, dec!(100), dec!(0.01), dec!(0.001));
+        assert_eq!(buy_price, dec!(98.9));
+
+        let sell_price = calculate_maker_price(OrderSide::Sell, dec!(100), dec!(0.01), dec!(0.001));
+        assert_eq!(sell_price, dec!(101.1));
+    }
+}
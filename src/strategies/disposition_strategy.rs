@@ -3,12 +3,13 @@ use std::sync::Arc;
 use anyhow::Result;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
 
 use crate::core::disposition_execution::{
     PriceSlot, TradeCycle, TradeDisposition, TradingContext, TradingContextBySide,
 };
 use crate::core::exchanges::common::{
-    CurrencyPair, ExchangeAccountId, TradePlace, TradePlaceAccount,
+    Amount, CurrencyPair, ExchangeAccountId, TradePlace, TradePlaceAccount,
 };
 use crate::core::exchanges::general::currency_pair_metadata::Round;
 use crate::core::explanation::{Explanation, WithExplanation};
@@ -16,6 +17,7 @@ use crate::core::lifecycle::cancellation_token::CancellationToken;
 use crate::core::lifecycle::trading_engine::EngineContext;
 use crate::core::order_book::local_snapshot_service::LocalSnapshotsService;
 use crate::core::orders::order::{OrderRole, OrderSide, OrderSnapshot};
+use crate::core::settings::BaseStrategySettings;
 use crate::core::DateTime;
 
 pub trait DispositionStrategy: Send + Sync + 'static {
@@ -34,6 +36,45 @@ pub trait DispositionStrategy: Send + Sync + 'static {
         target_eai: &ExchangeAccountId,
         cancellation_token: CancellationToken,
     ) -> Result<()>;
+
+    /// Trade cycles which should be executed immediately by market orders (e.g. hedges of maker fills).
+    /// Disposition executor takes them after every event and creates orders with the same checks
+    /// and balance reservation as orders of price slots. Orders for blocked exchange or stale market data
+    /// are kept by executor until exchange is unblocked and market data is fresh
+    fn take_taker_orders(&mut self, _now: DateTime) -> Vec<TradeCycle> {
+        Vec::new()
+    }
+
+    /// Called when order of taker trade cycle is finished or it isn't created at all
+    fn handle_taker_order_finished(
+        &mut self,
+        _trade_cycle: &TradeCycle,
+        _unfilled_amount: Amount,
+        _now: DateTime,
+    ) {
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ExampleStrategySettings {
+    pub exchange_account_id: ExchangeAccountId,
+    pub currency_pair: CurrencyPair,
+    pub max_amount: Amount,
+    pub spread: Decimal,
+}
+
+impl BaseStrategySettings for ExampleStrategySettings {
+    fn exchange_account_id(&self) -> ExchangeAccountId {
+        self.exchange_account_id.clone()
+    }
+
+    fn currency_pair(&self) -> CurrencyPair {
+        self.currency_pair.clone()
+    }
+
+    fn max_amount(&self) -> Amount {
+        self.max_amount
+    }
 }
 
 pub struct ExampleStrategy {
@@ -1 +1,4 @@
+pub mod cross_exchange_market_making;
 pub mod disposition_strategy;
+pub mod pure_market_making;
+pub mod strategy_builder;
@@ -0,0 +1,759 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use enum_map::{enum_map, EnumMap};
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+use crate::core::disposition_execution::{
+    PriceSlot, PriceSlotId, TradeCycle, TradeDisposition, TradingContext, TradingContextBySide,
+};
+use crate::core::exchanges::common::{
+    Amount, CurrencyPair, ExchangeAccountId, Price, TradePlace, TradePlaceAccount,
+};
+use crate::core::exchanges::general::commission::Percent;
+use crate::core::exchanges::general::currency_pair_metadata::{CurrencyPairMetadata, Round};
+use crate::core::explanation::{Explanation, WithExplanation};
+use crate::core::lifecycle::cancellation_token::CancellationToken;
+use crate::core::lifecycle::trading_engine::EngineContext;
+use crate::core::math::ConvertPercentToRate;
+use crate::core::order_book::local_snapshot_service::LocalSnapshotsService;
+use crate::core::orders::order::{OrderRole, OrderSide, OrderSnapshot, OrderStatus};
+use crate::core::settings::BaseStrategySettings;
+use crate::core::DateTime;
+use crate::strategies::disposition_strategy::DispositionStrategy;
+
+static STRATEGY_NAME: &str = "PureMarketMaking";
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PureMarketMakingSettings {
+    pub exchange_account_id: ExchangeAccountId,
+    pub currency_pair: CurrencyPair,
+    /// Max total amount of orders on each side
+    pub max_amount: Amount,
+    /// Distance from mid price to the first bid level in percents
+    pub bid_spread: Percent,
+    /// Distance from mid price to the first ask level in percents
+    pub ask_spread: Percent,
+    /// Amount of orders on the first level
+    pub order_amount: Amount,
+    /// Count of orders on each side
+    pub order_levels: usize,
+    /// Additional distance from mid price for every next level in percents
+    pub order_level_spread: Percent,
+    /// Additional amount for every next level
+    pub order_level_amount: Amount,
+    /// Order amounts are skewed to keep inventory near target if specified
+    pub inventory_skew: Option<InventorySkewSettings>,
+    /// Orders aren't recreated while new price differs from their price less than this value in percents
+    pub order_refresh_tolerance: Percent,
+    /// Minimal distance from mid price minus maker fee in percents which order should have
+    pub min_profitability: Percent,
+    /// After an order is completed on one side, the same count of levels on this side
+    /// isn't quoted until an order on the other side is completed
+    pub ping_pong_enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct InventorySkewSettings {
+    /// Desired share of base currency in total value of base and quote balances in percents
+    pub target_base_percent: Percent,
+    /// Deviation from target in total amounts of orders on both sides
+    /// at which orders are placed only on one side
+    pub range_multiplier: Decimal,
+}
+
+impl BaseStrategySettings for PureMarketMakingSettings {
+    fn exchange_account_id(&self) -> ExchangeAccountId {
+        self.exchange_account_id.clone()
+    }
+
+    fn currency_pair(&self) -> CurrencyPair {
+        self.currency_pair.clone()
+    }
+
+    fn max_amount(&self) -> Amount {
+        self.max_amount
+    }
+
+    fn price_slots(&self) -> Vec<PriceSlotId> {
+        (0..self.order_levels)
+            .map(|level_index| PriceSlotId::new(STRATEGY_NAME.into(), level_index))
+            .collect()
+    }
+}
+
+/// Strategy which keeps ladders of maker orders on both sides around mid price,
+/// like pure_market_making strategy of hummingbot
+pub struct PureMarketMakingStrategy {
+    settings: PureMarketMakingSettings,
+    engine_context: Arc<EngineContext>,
+    /// Prices of the last estimation by levels to apply order refresh tolerance
+    last_prices: EnumMap<OrderSide, Vec<Option<Price>>>,
+    /// Count of completed buy orders minus count of completed sell orders,
+    /// limited by count of levels because more levels can't be skipped on a side
+    ping_pong_balance: Mutex<i64>,
+}
+
+impl PureMarketMakingStrategy {
+    pub fn new(settings: PureMarketMakingSettings, engine_context: Arc<EngineContext>) -> Self {
+        let order_levels = settings.order_levels;
+        PureMarketMakingStrategy {
+            settings,
+            engine_context,
+            last_prices: enum_map! {
+                _ => vec![None; order_levels],
+            },
+            ping_pong_balance: Mutex::new(0),
+        }
+    }
+
+    fn trade_place_account(&self) -> TradePlaceAccount {
+        TradePlaceAccount::new(
+            self.settings.exchange_account_id.clone(),
+            self.settings.currency_pair.clone(),
+        )
+    }
+
+    fn trade_place(&self) -> TradePlace {
+        self.trade_place_account().trade_place()
+    }
+
+    /// Ratios to multiply bid and ask amounts by according to current inventory
+    fn calculate_amount_ratios(
+        &self,
+        currency_pair_metadata: &Arc<CurrencyPairMetadata>,
+        mid_price: Price,
+        explanation: &mut Explanation,
+    ) -> (Decimal, Decimal) {
+        let inventory_skew = match &self.settings.inventory_skew {
+            None => return (dec!(1), dec!(1)),
+            Some(inventory_skew) => inventory_skew,
+        };
+
+        let (base_balance, quote_balance) = {
+            let balance_manager = self.engine_context.balance_manager.lock();
+            let get_balance = |currency_code| {
+                balance_manager.get_exchange_balance(
+                    &self.settings.exchange_account_id,
+                    currency_pair_metadata.clone(),
+                    &currency_code,
+                )
+            };
+
+            match (
+                get_balance(currency_pair_metadata.base_currency_code()),
+                get_balance(currency_pair_metadata.quote_currency_code()),
+            ) {
+                (Some(base_balance), Some(quote_balance)) => (base_balance, quote_balance),
+                _ => {
+                    explanation
+                        .add_reason("Inventory skew isn't applied because balances are unknown");
+                    return (dec!(1), dec!(1));
+                }
+            }
+        };
+
+        let total_order_amount = (0..self.settings.order_levels)
+            .map(|level_index| self.level_amount(level_index))
+            .sum::<Amount>()
+            * dec!(2);
+
+        let (bid_ratio, ask_ratio) = calculate_inventory_skew(
+            base_balance,
+            quote_balance,
+            mid_price,
+            total_order_amount,
+            inventory_skew,
+        );
+        explanation.add_reason(format!(
+            "Inventory skew: base balance {} quote balance {} bid ratio {} ask ratio {}",
+            base_balance, quote_balance, bid_ratio, ask_ratio
+        ));
+
+        (bid_ratio, ask_ratio)
+    }
+
+    fn level_amount(&self, level_index: usize) -> Amount {
+        self.settings.order_amount + self.settings.order_level_amount * Decimal::from(level_index)
+    }
+
+    fn level_spread(&self, side: OrderSide, level_index: usize) -> Percent {
+        let spread = match side {
+            OrderSide::Buy => self.settings.bid_spread,
+            OrderSide::Sell => self.settings.ask_spread,
+        };
+
+        spread + self.settings.order_level_spread * Decimal::from(level_index)
+    }
+
+    /// Count of first levels on the side which shouldn't be quoted in ping-pong mode
+    fn ping_pong_skipped_levels(&self, side: OrderSide) -> usize {
+        if !self.settings.ping_pong_enabled {
+            return 0;
+        }
+
+        let ping_pong_balance = *self.ping_pong_balance.lock();
+        let skipped_levels = match side {
+            OrderSide::Buy => ping_pong_balance,
+            OrderSide::Sell => -ping_pong_balance,
+        };
+
+        skipped_levels.max(0) as usize
+    }
+
+    fn calc_trading_context_by_side(
+        &mut self,
+        side: OrderSide,
+        max_amount: Amount,
+        market: &MarketState,
+        amount_ratio: Decimal,
+        explanation: &Explanation,
+    ) -> TradingContextBySide {
+        let skipped_levels = self.ping_pong_skipped_levels(side);
+
+        let estimating = (0..self.settings.order_levels)
+            .map(|level_index| {
+                let mut explanation = explanation.clone();
+                let trade_cycle = if level_index < skipped_levels {
+                    explanation.add_reason(format!(
+                        "Level {} {} is skipped in ping-pong mode",
+                        level_index, side
+                    ));
+                    None
+                } else {
+                    self.estimate_level(side, level_index, market, amount_ratio, &mut explanation)
+                };
+
+                WithExplanation {
+                    value: trade_cycle,
+                    explanation,
+                }
+            })
+            .collect();
+
+        TradingContextBySide {
+            max_amount,
+            estimating,
+        }
+    }
+
+    fn estimate_level(
+        &mut self,
+        side: OrderSide,
+        level_index: usize,
+        market: &MarketState,
+        amount_ratio: Decimal,
+        explanation: &mut Explanation,
+    ) -> Option<TradeCycle> {
+        let currency_pair_metadata = &market.currency_pair_metadata;
+        let mid_price = market.mid_price();
+        let spread_rate = self.level_spread(side, level_index).percent_to_rate();
+        let price = match side {
+            OrderSide::Buy => currency_pair_metadata
+                .price_round(mid_price * (dec!(1) - spread_rate), Round::Floor)
+                .ok()?,
+            OrderSide::Sell => currency_pair_metadata
+                .price_round(mid_price * (dec!(1) + spread_rate), Round::Ceiling)
+                .ok()?,
+        };
+
+        let price = apply_refresh_tolerance(
+            price,
+            self.last_prices[side][level_index],
+            self.settings.order_refresh_tolerance,
+        );
+
+        let is_crossing_book = match side {
+            OrderSide::Buy => price >= market.ask,
+            OrderSide::Sell => price <= market.bid,
+        };
+        if is_crossing_book {
+            explanation.add_reason(format!(
+                "Price {} of level {} {} crosses order book top",
+                price, level_index, side
+            ));
+            return None;
+        }
+
+        let profitability = (price - mid_price).abs() / mid_price - market.maker_fee;
+        if profitability < self.settings.min_profitability.percent_to_rate() {
+            explanation.add_reason(format!(
+                "Profitability {} of level {} {} with price {} is less than minimal",
+                profitability, level_index, side, price
+            ));
+            return None;
+        }
+
+        let amount = currency_pair_metadata
+            .amount_round(self.level_amount(level_index) * amount_ratio, Round::Floor)
+            .ok()?;
+        if amount.is_zero() {
+            explanation.add_reason(format!(
+                "Amount of level {} {} is zero after inventory skew",
+                level_index, side
+            ));
+            return None;
+        }
+
+        explanation.add_reason(format!(
+            "Level {} {}: price {} amount {}",
+            level_index, side, price, amount
+        ));
+
+        // Only the price of an emitted quote is kept, so rejected prices don't hold the next quote
+        self.last_prices[side][level_index] = Some(price);
+
+        Some(TradeCycle {
+            order_role: OrderRole::Maker,
+            strategy_name: STRATEGY_NAME.to_string(),
+            disposition: TradeDisposition::new(self.trade_place_account(), side, price, amount),
+        })
+    }
+}
+
+impl DispositionStrategy for PureMarketMakingStrategy {
+    fn calculate_trading_context(
+        &mut self,
+        max_amount: Decimal,
+        _now: DateTime,
+        local_snapshots_service: &LocalSnapshotsService,
+        explanation: &mut Explanation,
+    ) -> Option<TradingContext> {
+        let snapshot = local_snapshots_service.get_snapshot(&self.trade_place())?;
+        let exchange = self
+            .engine_context
+            .exchanges
+            .get(&self.settings.exchange_account_id)?
+            .clone();
+        let market = MarketState {
+            ask: snapshot.get_top_ask()?.0,
+            bid: snapshot.get_top_bid()?.0,
+            currency_pair_metadata: exchange
+                .get_currency_pair_metadata(&self.settings.currency_pair)
+                .ok()?,
+            maker_fee: exchange
+                .commission()
+                .get_commission(OrderRole::Maker)
+                .fee
+                .percent_to_rate(),
+        };
+
+        let (bid_ratio, ask_ratio) = self.calculate_amount_ratios(
+            &market.currency_pair_metadata,
+            market.mid_price(),
+            explanation,
+        );
+
+        let buy_trading_ctx = self.calc_trading_context_by_side(
+            OrderSide::Buy,
+            max_amount,
+            &market,
+            bid_ratio,
+            explanation,
+        );
+
+        let sell_trading_ctx = self.calc_trading_context_by_side(
+            OrderSide::Sell,
+            max_amount,
+            &market,
+            ask_ratio,
+            explanation,
+        );
+
+        Some(TradingContext::new(buy_trading_ctx, sell_trading_ctx))
+    }
+
+    fn handle_order_fill(
+        &self,
+        cloned_order: &Arc<OrderSnapshot>,
+        _price_slot: &PriceSlot,
+        _target_eai: &ExchangeAccountId,
+        _cancellation_token: CancellationToken,
+    ) -> Result<()> {
+        if self.settings.ping_pong_enabled && cloned_order.status() == OrderStatus::Completed {
+            let max_balance = self.settings.order_levels as i64;
+            let mut ping_pong_balance = self.ping_pong_balance.lock();
+            *ping_pong_balance = match cloned_order.header.side {
+                OrderSide::Buy => (*ping_pong_balance + 1).min(max_balance),
+                OrderSide::Sell => (*ping_pong_balance - 1).max(-max_balance),
+            };
+        }
+
+        Ok(())
+    }
+}
+
+/// Market data used for estimation of all levels
+struct MarketState {
+    ask: Price,
+    bid: Price,
+    currency_pair_metadata: Arc<CurrencyPairMetadata>,
+    /// Maker fee as rate
+    maker_fee: Decimal,
+}
+
+impl MarketState {
+    fn mid_price(&self) -> Price {
+        (self.ask + self.bid) * dec!(0.5)
+    }
+}
+
+/// Keep the last price if new one differs from it less than tolerance,
+/// so orders aren't recreated on every small move of order book
+fn apply_refresh_tolerance(
+    new_price: Price,
+    last_price: Option<Price>,
+    tolerance: Percent,
+) -> Price {
+    match last_price {
+        Some(last_price)
+            if ((new_price - last_price) / last_price).abs() <= tolerance.percent_to_rate() =>
+        {
+            last_price
+        }
+        _ => new_price,
+    }
+}
+
+/// Calculate ratios for bid and ask amounts in range [0, 2], which are both 1 when
+/// base currency value equals to target share of portfolio value
+fn calculate_inventory_skew(
+    base_balance: Amount,
+    quote_balance: Amount,
+    mid_price: Price,
+    total_order_amount: Amount,
+    inventory_skew: &InventorySkewSettings,
+) -> (Decimal, Decimal) {
+    let base_value = base_balance * mid_price;
+    let total_value = base_value + quote_balance;
+    let range_value = (total_order_amount * inventory_skew.range_multiplier * mid_price)
+        .min(total_value * dec!(0.5));
+    if range_value.is_zero() {
+        return (dec!(1), dec!(1));
+    }
+
+    let target_base_value = total_value * inventory_skew.target_base_percent.percent_to_rate();
+    let left_limit = (target_base_value - range_value).max(dec!(0));
+    let right_limit = target_base_value + range_value;
+
+    let base_ratio = ((base_value - left_limit) / (right_limit - left_limit))
+        .max(dec!(0))
+        .min(dec!(1));
+
+    let bid_ratio = (dec!(1) - base_ratio) * dec!(2);
+    (bid_ratio, dec!(2) - bid_ratio)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use chrono::Utc;
+    use tokio::sync::{broadcast, oneshot};
+
+    use super::*;
+    use crate::core::balance_manager::balance_manager::BalanceManager;
+    use crate::core::balance_manager::tests::balance_manager_base::BalanceManagerBase;
+    use crate::core::disposition_execution::CompositeOrder;
+    use crate::core::exchanges::events::{
+        ExchangeBalance, ExchangeBalancesAndPositions, ExchangeEvents,
+    };
+    use crate::core::exchanges::exchange_blocker::ExchangeBlocker;
+    use crate::core::exchanges::general::currency_pair_metadata::Precision;
+    use crate::core::exchanges::general::currency_pair_to_metadata_converter::CurrencyPairToMetadataConverter;
+    use crate::core::exchanges::general::test_helper::get_test_exchange_with_currency_pair_metadata_and_id;
+    use crate::core::exchanges::timeouts::timeout_manager::TimeoutManager;
+    use crate::core::lifecycle::application_manager::ApplicationManager;
+    use crate::core::order_book::event::{EventType, OrderBookEvent};
+    use crate::core::orders::order::{ClientOrderId, OrderType};
+    use crate::core::settings::CoreSettings;
+    use crate::order_book_data;
+
+    fn inventory_skew() -> InventorySkewSettings {
+        InventorySkewSettings {
+            target_base_percent: dec!(50),
+            range_multiplier: dec!(1),
+        }
+    }
+
+    fn settings(exchange_account_id: ExchangeAccountId) -> PureMarketMakingSettings {
+        PureMarketMakingSettings {
+            exchange_account_id,
+            currency_pair: CurrencyPair::from_codes(
+                &BalanceManagerBase::eth(),
+                &BalanceManagerBase::btc(),
+            ),
+            max_amount: dec!(10),
+            bid_spread: dec!(1),
+            ask_spread: dec!(1),
+            order_amount: dec!(1),
+            order_levels: 1,
+            order_level_spread: dec!(0),
+            order_level_amount: dec!(0),
+            inventory_skew: None,
+            order_refresh_tolerance: dec!(0),
+            min_profitability: dec!(0),
+            ping_pong_enabled: false,
+        }
+    }
+
+    /// Engine context with a single exchange trading ETH/BTC with specified balances
+    fn engine_context(
+        exchange_account_id: &ExchangeAccountId,
+        base_balance: Amount,
+        quote_balance: Amount,
+    ) -> Arc<EngineContext> {
+        let base_currency_code = BalanceManagerBase::eth();
+        let quote_currency_code = BalanceManagerBase::btc();
+        let currency_pair_metadata = Arc::new(CurrencyPairMetadata::new(
+            false,
+            false,
+            base_currency_code.as_str().into(),
+            base_currency_code.clone(),
+            quote_currency_code.as_str().into(),
+            quote_currency_code.clone(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            base_currency_code.clone(),
+            Some(base_currency_code.clone()),
+            Precision::ByTick { tick: dec!(0.1) },
+            Precision::ByTick { tick: dec!(0.001) },
+        ));
+        let (exchange, _) = get_test_exchange_with_currency_pair_metadata_and_id(
+            currency_pair_metadata,
+            exchange_account_id,
+        );
+        let exchanges_by_id = HashMap::from([(exchange_account_id.clone(), exchange.clone())]);
+
+        let balance_manager = BalanceManager::new(
+            exchanges_by_id.clone(),
+            CurrencyPairToMetadataConverter::new(exchanges_by_id),
+            None,
+        );
+        balance_manager
+            .lock()
+            .update_exchange_balance(
+                exchange_account_id,
+                &ExchangeBalancesAndPositions {
+                    balances: vec![
+                        ExchangeBalance {
+                            currency_code: base_currency_code,
+                            balance: base_balance,
+                        },
+                        ExchangeBalance {
+                            currency_code: quote_currency_code,
+                            balance: quote_balance,
+                        },
+                    ],
+                    positions: None,
+                },
+            )
+            .expect("in test");
+
+        EngineContext::new(
+            CoreSettings::default(),
+            vec![(exchange_account_id.clone(), exchange)]
+                .into_iter()
+                .collect(),
+            ExchangeEvents::new(broadcast::channel(10).0),
+            oneshot::channel().0,
+            TimeoutManager::new(HashMap::new()),
+            ApplicationManager::new(CancellationToken::new()),
+            None,
+            balance_manager,
+            ExchangeBlocker::new(vec![exchange_account_id.clone()]),
+        )
+    }
+
+    fn local_snapshots_service(
+        exchange_account_id: &ExchangeAccountId,
+        currency_pair: &CurrencyPair,
+    ) -> LocalSnapshotsService {
+        let mut local_snapshots_service = LocalSnapshotsService::default();
+        let _ = local_snapshots_service.update(OrderBookEvent::new(
+            Utc::now(),
+            exchange_account_id.clone(),
+            currency_pair.clone(),
+            "1".to_owned(),
+            EventType::Snapshot,
+            order_book_data![dec!(101) => dec!(10), ; dec!(99) => dec!(10),],
+        ));
+        local_snapshots_service
+    }
+
+    fn completed_order(
+        exchange_account_id: &ExchangeAccountId,
+        currency_pair: &CurrencyPair,
+        side: OrderSide,
+    ) -> Arc<OrderSnapshot> {
+        let mut order = OrderSnapshot::with_params(
+            ClientOrderId::unique_id(),
+            OrderType::Limit,
+            Some(OrderRole::Maker),
+            exchange_account_id.clone(),
+            currency_pair.clone(),
+            dec!(100),
+            dec!(1),
+            side,
+            None,
+            STRATEGY_NAME,
+        );
+        order.props.status = OrderStatus::Completed;
+        Arc::new(order)
+    }
+
+    fn level_quote(trading_context: &TradingContext, side: OrderSide) -> Option<(Price, Amount)> {
+        trading_context.by_side[side].estimating[0]
+            .value
+            .as_ref()
+            .map(|x| (x.disposition.price(), x.disposition.amount()))
+    }
+
+    #[tokio::test]
+    async fn trading_context_with_inventory_skew() {
+        let _balance_manager_base = BalanceManagerBase::new();
+        let exchange_account_id = ExchangeAccountId::new("local_exchange_account_id".into(), 0);
+        let mut settings = settings(exchange_account_id.clone());
+        settings.inventory_skew = Some(inventory_skew());
+        let local_snapshots_service =
+            local_snapshots_service(&exchange_account_id, &settings.currency_pair);
+        // Base value 1100 is in the middle between target 1000 and upper limit 1200
+        let engine_context = engine_context(&exchange_account_id, dec!(11), dec!(900));
+        let mut strategy = PureMarketMakingStrategy::new(settings, engine_context);
+
+        let trading_context = strategy
+            .calculate_trading_context(
+                dec!(10),
+                Utc::now(),
+                &local_snapshots_service,
+                &mut Explanation::default(),
+            )
+            .expect("in test");
+
+        assert_eq!(
+            level_quote(&trading_context, OrderSide::Buy),
+            Some((dec!(99), dec!(0.5)))
+        );
+        assert_eq!(
+            level_quote(&trading_context, OrderSide::Sell),
+            Some((dec!(101), dec!(1.5)))
+        );
+    }
+
+    #[tokio::test]
+    async fn ping_pong_balance_is_limited_by_order_levels() {
+        let _balance_manager_base = BalanceManagerBase::new();
+        let exchange_account_id = ExchangeAccountId::new("local_exchange_account_id".into(), 0);
+        let mut settings = settings(exchange_account_id.clone());
+        settings.ping_pong_enabled = true;
+        let currency_pair = settings.currency_pair.clone();
+        let local_snapshots_service = local_snapshots_service(&exchange_account_id, &currency_pair);
+        let engine_context = engine_context(&exchange_account_id, dec!(10), dec!(1000));
+        let mut strategy = PureMarketMakingStrategy::new(settings, engine_context);
+        let price_slot = PriceSlot {
+            id: PriceSlotId::new(STRATEGY_NAME.into(), 0),
+            estimating: RefCell::new(None),
+            order: RefCell::new(CompositeOrder::new(OrderSide::Buy)),
+        };
+
+        for _ in 0..3 {
+            let buy_order = completed_order(&exchange_account_id, &currency_pair, OrderSide::Buy);
+            strategy
+                .handle_order_fill(
+                    &buy_order,
+                    &price_slot,
+                    &exchange_account_id,
+                    CancellationToken::new(),
+                )
+                .expect("in test");
+        }
+        let sell_order = completed_order(&exchange_account_id, &currency_pair, OrderSide::Sell);
+        strategy
+            .handle_order_fill(
+                &sell_order,
+                &price_slot,
+                &exchange_account_id,
+                CancellationToken::new(),
+            )
+            .expect("in test");
+
+        // A single sell completion is enough to quote buy level again after several buy completions
+        let trading_context = strategy
+            .calculate_trading_context(
+                dec!(10),
+                Utc::now(),
+                &local_snapshots_service,
+                &mut Explanation::default(),
+            )
+            .expect("in test");
+        assert!(level_quote(&trading_context, OrderSide::Buy).is_some());
+        assert!(level_quote(&trading_context, OrderSide::Sell).is_some());
+    }
+
+    #[test]
+    fn inventory_skew_is_neutral_on_target() {
+        let ratios =
+            calculate_inventory_skew(dec!(10), dec!(1000), dec!(100), dec!(2), &inventory_skew());
+
+        assert_eq!(ratios, (dec!(1), dec!(1)));
+    }
+
+    #[test]
+    fn inventory_skew_with_excess_of_base_currency() {
+        // Base value 1100 is in the middle between target 1000 and upper limit 1200
+        let (bid_ratio, ask_ratio) =
+            calculate_inventory_skew(dec!(11), dec!(900), dec!(100), dec!(2), &inventory_skew());
+
+        assert_eq!(bid_ratio, dec!(0.5));
+        assert_eq!(ask_ratio, dec!(1.5));
+    }
+
+    #[test]
+    fn inventory_skew_out_of_range() {
+        let ratios =
+            calculate_inventory_skew(dec!(0), dec!(2000), dec!(100), dec!(2), &inventory_skew());
+        assert_eq!(ratios, (dec!(2), dec!(0)));
+
+        let ratios =
+            calculate_inventory_skew(dec!(20), dec!(0), dec!(100), dec!(2), &inventory_skew());
+        assert_eq!(ratios, (dec!(0), dec!(2)));
+    }
+
+    #[test]
+    fn refresh_tolerance() {
+        let tolerance = dec!(1);
+
+        assert_eq!(
+            apply_refresh_tolerance(dec!(100), None, tolerance),
+            dec!(100)
+        );
+        assert_eq!(
+            apply_refresh_tolerance(dec!(100.5), Some(dec!(100)), tolerance),
+            dec!(100)
+        );
+        assert_eq!(
+            apply_refresh_tolerance(dec!(98), Some(dec!(100)), tolerance),
+            dec!(98)
+        );
+    }
+
+    #[test]
+    fn price_slots_by_order_levels() {
+        let mut settings = settings("Binance0".parse().expect("in test"));
+        settings.order_levels = 3;
+
+        let level_indexes = settings
+            .price_slots()
+            .into_iter()
+            .map(|x| x.level_index)
+            .collect::<Vec<_>>();
+        assert_eq!(level_indexes, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+
+use crate::core::disposition_execution::PriceSlotId;
+use crate::core::lifecycle::trading_engine::EngineContext;
+use crate::core::settings::{BaseStrategySettings, ConfiguredStrategySettings};
+use crate::strategies::disposition_strategy::DispositionStrategy;
+
+pub trait StrategyBuilder {
+    /// Price slots of strategy with specified settings. Fails if settings can't be parsed for this strategy
+    fn get_price_slots(&self, settings: &ConfiguredStrategySettings) -> Result<Vec<PriceSlotId>>;
+
+    fn create_strategy(
+        &self,
+        settings: &ConfiguredStrategySettings,
+        engine_context: Arc<EngineContext>,
+    ) -> Result<Box<dyn DispositionStrategy>>;
+}
+
+/// Builder of strategy which settings are parsed from `ConfiguredStrategySettings` into `StrategySettings`
+pub struct TypedStrategyBuilder<StrategySettings> {
+    create: fn(StrategySettings, Arc<EngineContext>) -> Box<dyn DispositionStrategy>,
+}
+
+impl<StrategySettings> TypedStrategyBuilder<StrategySettings> {
+    pub fn new(
+        create: fn(StrategySettings, Arc<EngineContext>) -> Box<dyn DispositionStrategy>,
+    ) -> Self {
+        TypedStrategyBuilder { create }
+    }
+}
+
+impl<StrategySettings> StrategyBuilder for TypedStrategyBuilder<StrategySettings>
+where
+    StrategySettings: BaseStrategySettings + DeserializeOwned,
+{
+    fn get_price_slots(&self, settings: &ConfiguredStrategySettings) -> Result<Vec<PriceSlotId>> {
+        Ok(settings.parse::<StrategySettings>()?.price_slots())
+    }
+
+    fn create_strategy(
+        &self,
+        settings: &ConfiguredStrategySettings,
+        engine_context: Arc<EngineContext>,
+    ) -> Result<Box<dyn DispositionStrategy>> {
+        Ok((self.create)(settings.parse()?, engine_context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::pure_market_making::{
+        PureMarketMakingSettings, PureMarketMakingStrategy,
+    };
+    use rust_decimal_macros::dec;
+
+    fn pure_market_making_builder() -> TypedStrategyBuilder<PureMarketMakingSettings> {
+        TypedStrategyBuilder::new(|settings, engine_context| {
+            Box::new(PureMarketMakingStrategy::new(settings, engine_context))
+        })
+    }
+
+    fn configured_settings(parameters: &str) -> ConfiguredStrategySettings {
+        let settings = format!(
+            r#"
+            strategy_type = "PureMarketMaking"
+            exchange_account_id = "Binance0"
+            currency_pair = "eth/btc"
+            max_amount = "10"
+
+            [parameters]
+            {}
+            "#,
+            parameters
+        );
+
+        toml::from_str(&settings).expect("in test")
+    }
+
+    #[test]
+    fn parse_typed_settings_from_configured() {
+        let settings = configured_settings(
+            r#"
+            bid_spread = 0.1
+            ask_spread = 0.2
+            order_amount = 1
+            order_levels = 2
+            order_level_spread = 0.05
+            order_level_amount = 0.5
+            order_refresh_tolerance = 0
+            min_profitability = 0
+            ping_pong_enabled = false
+            "#,
+        );
+
+        let typed_settings = settings
+            .parse::<PureMarketMakingSettings>()
+            .expect("in test");
+        assert_eq!(
+            typed_settings.exchange_account_id,
+            settings.exchange_account_id
+        );
+        assert_eq!(typed_settings.max_amount, dec!(10));
+        assert_eq!(typed_settings.ask_spread, dec!(0.2));
+
+        let level_indexes = pure_market_making_builder()
+            .get_price_slots(&settings)
+            .expect("in test")
+            .into_iter()
+            .map(|x| x.level_index)
+            .collect::<Vec<_>>();
+        assert_eq!(level_indexes, vec![0, 1]);
+    }
+
+    #[test]
+    fn parse_typed_settings_without_required_parameters() {
+        let settings = configured_settings("bid_spread = 0.1");
+
+        assert!(pure_market_making_builder()
+            .get_price_slots(&settings)
+            .is_err());
+    }
+}
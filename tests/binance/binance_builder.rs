@@ -84,6 +84,8 @@ impl BinanceBuilder {
             application_manager,
             timeout_manager,
             commission,
+            None,
+            None,
         ); // TODO: change to mmb_lib::core::exchanges::general::exchange_creation::create_exchange::create_exchange() when it will be ready
         exchange.clone().connect().await;
         exchange.build_metadata().await;
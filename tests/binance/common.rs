@@ -59,6 +59,7 @@ pub(crate) fn get_timeout_manager(exchange_account_id: &ExchangeAccountId) -> Ar
     let request_timeout_manager = RequestsTimeoutManagerFactory::from_requests_per_period(
         timeout_arguments,
         exchange_account_id.clone(),
+        None,
     );
 
     TimeoutManager::new(hashmap![exchange_account_id.clone() => request_timeout_manager])
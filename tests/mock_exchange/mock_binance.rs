@@ -0,0 +1,1012 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use actix::{Actor, ActorContext, Addr, AsyncContext, Handler, Message, StreamHandler};
+use actix_web::dev::Server;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use actix_web_actors::ws;
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use hmac::{Hmac, Mac, NewMac};
+use itertools::Itertools;
+use mmb_lib::core::exchanges::common::{Amount, Price};
+use mmb_lib::core::orders::order::{ClientOrderId, OrderSide};
+use mmb_lib::core::settings::Hosts;
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+pub const MOCK_API_KEY: &str = "mock_api_key";
+pub const MOCK_SECRET_KEY: &str = "mock_secret_key";
+const MOCK_LISTEN_KEY: &str = "mock_listen_key";
+
+/// Symbol description which will be returned from `/api/v3/exchangeInfo`
+#[derive(Debug, Clone)]
+pub struct MockSymbol {
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub price_tick: Price,
+    pub amount_tick: Amount,
+    pub min_price: Price,
+    pub max_price: Price,
+    pub min_amount: Amount,
+    pub max_amount: Amount,
+    pub min_notional: Amount,
+}
+
+impl MockSymbol {
+    pub fn new(base_asset: &str, quote_asset: &str) -> Self {
+        Self {
+            base_asset: base_asset.to_uppercase(),
+            quote_asset: quote_asset.to_uppercase(),
+            price_tick: dec!(0.00000001),
+            amount_tick: dec!(1),
+            min_price: dec!(0.00000001),
+            max_price: dec!(1000),
+            min_amount: dec!(1),
+            max_amount: dec!(90000000),
+            min_notional: dec!(0.0001),
+        }
+    }
+
+    pub fn symbol(&self) -> String {
+        format!("{}{}", self.base_asset, self.quote_asset)
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "symbol": self.symbol(),
+            "status": "TRADING",
+            "baseAsset": self.base_asset,
+            "quoteAsset": self.quote_asset,
+            "filters": [
+                {
+                    "filterType": "PRICE_FILTER",
+                    "minPrice": self.min_price.to_string(),
+                    "maxPrice": self.max_price.to_string(),
+                    "tickSize": self.price_tick.to_string(),
+                },
+                {
+                    "filterType": "LOT_SIZE",
+                    "minQty": self.min_amount.to_string(),
+                    "maxQty": self.max_amount.to_string(),
+                    "stepSize": self.amount_tick.to_string(),
+                },
+                {
+                    "filterType": "MIN_NOTIONAL",
+                    "minNotional": self.min_notional.to_string(),
+                },
+            ],
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MockOrderStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+}
+
+impl MockOrderStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MockOrderStatus::New => "NEW",
+            MockOrderStatus::PartiallyFilled => "PARTIALLY_FILLED",
+            MockOrderStatus::Filled => "FILLED",
+            MockOrderStatus::Canceled => "CANCELED",
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        matches!(
+            self,
+            MockOrderStatus::New | MockOrderStatus::PartiallyFilled
+        )
+    }
+}
+
+/// Order as it is stored on the mock exchange side
+#[derive(Debug, Clone)]
+pub struct MockOrder {
+    pub order_id: u64,
+    pub client_order_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub order_type: String,
+    pub time_in_force: String,
+    pub price: Price,
+    pub amount: Amount,
+    pub filled_amount: Amount,
+    pub status: MockOrderStatus,
+}
+
+impl MockOrder {
+    fn to_json(&self) -> Value {
+        json!({
+            "symbol": self.symbol,
+            "orderId": self.order_id,
+            "clientOrderId": self.client_order_id,
+            "price": self.price.to_string(),
+            "origQty": self.amount.to_string(),
+            "executedQty": self.filled_amount.to_string(),
+            "status": self.status.as_str(),
+            "timeInForce": self.time_in_force,
+            "type": self.order_type,
+            "side": self.side,
+            "transactTime": Utc::now().timestamp_millis(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MockTrade {
+    trade_id: u64,
+    order_id: u64,
+    symbol: String,
+    price: Price,
+    amount: Amount,
+    commission: Amount,
+    commission_asset: String,
+    time: i64,
+    is_maker: bool,
+}
+
+/// Error which will be returned instead of handling the next request of some type
+#[derive(Debug, Clone)]
+pub struct MockReject {
+    pub code: i64,
+    pub message: String,
+}
+
+impl MockReject {
+    pub fn new(code: i64, message: &str) -> Self {
+        Self {
+            code,
+            message: message.to_owned(),
+        }
+    }
+
+    fn to_response(&self) -> HttpResponse {
+        error_response(self.code, &self.message)
+    }
+}
+
+#[derive(Default)]
+struct MockBinanceState {
+    symbols: Vec<MockSymbol>,
+    orders: Vec<MockOrder>,
+    trades: Vec<MockTrade>,
+    last_order_id: u64,
+    last_trade_id: u64,
+    last_update_id: u64,
+    order_creation_rejects: VecDeque<MockReject>,
+    order_cancellation_rejects: VecDeque<MockReject>,
+    // Retry-After in seconds of responses with exceeded rate limit to the next open orders requests
+    open_orders_rate_limits: VecDeque<u64>,
+    open_orders_requests_count: usize,
+    user_data_sessions: Vec<Addr<MockWebSocketSession>>,
+    market_data_sessions: Vec<Addr<MockWebSocketSession>>,
+}
+
+impl MockBinanceState {
+    fn get_symbol(&self, symbol: &str) -> Option<&MockSymbol> {
+        self.symbols.iter().find(|x| x.symbol() == symbol)
+    }
+
+    fn send_user_data(&self, message: Value) {
+        let message = message.to_string();
+        for session in &self.user_data_sessions {
+            session.do_send(SendText(message.clone()));
+        }
+    }
+
+    fn send_market_data(&self, stream: &str, data: Value) {
+        let message = json!({ "stream": stream, "data": data }).to_string();
+        for session in &self.market_data_sessions {
+            session.do_send(SendStreamText {
+                stream: stream.to_owned(),
+                text: message.clone(),
+            });
+        }
+    }
+}
+
+type SharedState = Arc<Mutex<MockBinanceState>>;
+
+/// Local stand-in for Binance spot REST API and WebSocket streams.
+/// It implements only that subset of the API which is used by Binance exchange client,
+/// so the whole order lifecycle can be checked without network access and real credentials.
+///
+/// ```no_run
+/// let mock = MockBinance::start(vec![MockSymbol::new("phb", "btc")]).expect("in test");
+/// mock.reject_next_order_creation(MockReject::new(-2010, "Account has insufficient balance for requested action."));
+/// // ... create order through Exchange and check the error
+/// mock.fill_order(&client_order_id, None, dec!(10)).expect("in test");
+/// mock.disconnect_websockets();
+/// ```
+pub struct MockBinance {
+    state: SharedState,
+    server: Server,
+    address: SocketAddr,
+}
+
+impl MockBinance {
+    /// Start server on random local port. Have to be called inside actix system
+    pub fn start(symbols: Vec<MockSymbol>) -> Result<Self> {
+        let state = Arc::new(Mutex::new(MockBinanceState {
+            symbols,
+            ..Default::default()
+        }));
+
+        let app_state = state.clone();
+        let http_server = HttpServer::new(move || {
+            App::new()
+                .app_data(web::Data::new(app_state.clone()))
+                .route("/api/v3/exchangeInfo", web::get().to(exchange_info))
+                .route("/api/v3/userDataStream", web::post().to(user_data_stream))
+                .route("/api/v3/order", web::post().to(create_order))
+                .route("/api/v3/order", web::delete().to(cancel_order))
+                .route("/api/v3/order", web::get().to(order_info))
+                .route("/api/v3/openOrders", web::get().to(open_orders))
+                .route("/api/v3/openOrders", web::delete().to(cancel_all_orders))
+                .route("/api/v3/myTrades", web::get().to(my_trades))
+                .route("/ws/{listen_key}", web::get().to(user_data_websocket))
+                .route("/stream", web::get().to(market_data_websocket))
+        })
+        .workers(1)
+        .disable_signals()
+        .shutdown_timeout(1)
+        .bind(("127.0.0.1", 0))
+        .context("Unable to bind mock Binance server")?;
+
+        let address = *http_server
+            .addrs()
+            .first()
+            .context("Mock Binance server has no bound address")?;
+
+        let server = http_server.run();
+
+        Ok(Self {
+            state,
+            server,
+            address,
+        })
+    }
+
+    pub fn hosts(&self) -> Hosts {
+        Hosts {
+            web_socket_host: format!("ws://{}", self.address),
+            web_socket2_host: format!("ws://{}", self.address),
+            rest_host: format!("http://{}", self.address),
+        }
+    }
+
+    /// Next order creation request will be answered with specified error
+    pub fn reject_next_order_creation(&self, reject: MockReject) {
+        self.state.lock().order_creation_rejects.push_back(reject);
+    }
+
+    /// Next order cancellation request will be answered with specified error
+    pub fn reject_next_order_cancellation(&self, reject: MockReject) {
+        self.state
+            .lock()
+            .order_cancellation_rejects
+            .push_back(reject);
+    }
+
+    /// Next open orders request will be answered with 429 Too Many Requests and specified Retry-After
+    pub fn rate_limit_next_open_orders_request(&self, retry_after_secs: u64) {
+        self.state
+            .lock()
+            .open_orders_rate_limits
+            .push_back(retry_after_secs);
+    }
+
+    /// Count of open orders requests received by server including rate limited ones
+    pub fn open_orders_requests_count(&self) -> usize {
+        self.state.lock().open_orders_requests_count
+    }
+
+    /// Fill open order by specified amount and notify user data stream about that.
+    /// Order price is used as fill price if `price` is not specified
+    pub fn fill_order(
+        &self,
+        client_order_id: &ClientOrderId,
+        price: Option<Price>,
+        amount: Amount,
+    ) -> Result<()> {
+        let mut state = self.state.lock();
+
+        state.last_trade_id += 1;
+        let trade_id = state.last_trade_id;
+
+        let order = state
+            .orders
+            .iter_mut()
+            .find(|x| x.client_order_id == client_order_id.as_str())
+            .with_context(|| format!("Order {} not found on mock exchange", client_order_id))?;
+
+        if !order.status.is_open() {
+            bail!(
+                "Unable to fill order {} with status {:?}",
+                client_order_id,
+                order.status
+            );
+        }
+
+        let rest_amount = order.amount - order.filled_amount;
+        if amount > rest_amount {
+            bail!(
+                "Unable to fill order {} by {} cause only {} is left",
+                client_order_id,
+                amount,
+                rest_amount
+            );
+        }
+
+        order.filled_amount += amount;
+        order.status = match order.filled_amount == order.amount {
+            true => MockOrderStatus::Filled,
+            false => MockOrderStatus::PartiallyFilled,
+        };
+        let order = order.clone();
+
+        let commission_asset = state
+            .get_symbol(&order.symbol)
+            .map(|x| x.quote_asset.clone())
+            .unwrap_or_default();
+        let trade = MockTrade {
+            trade_id,
+            order_id: order.order_id,
+            symbol: order.symbol.clone(),
+            price: price.unwrap_or(order.price),
+            amount,
+            commission: Decimal::ZERO,
+            commission_asset,
+            time: Utc::now().timestamp_millis(),
+            is_maker: true,
+        };
+
+        state.send_user_data(execution_report(&order, "TRADE", Some(&trade), ""));
+        state.trades.push(trade);
+
+        Ok(())
+    }
+
+    /// Push order book snapshot to `<symbol>@depth20` market data stream
+    pub fn send_order_book_snapshot(
+        &self,
+        symbol: &str,
+        bids: &[(Price, Amount)],
+        asks: &[(Price, Amount)],
+    ) {
+        let mut state = self.state.lock();
+        state.last_update_id += 1;
+
+        let to_levels = |levels: &[(Price, Amount)]| {
+            levels
+                .iter()
+                .map(|(price, amount)| json!([price.to_string(), amount.to_string()]))
+                .collect_vec()
+        };
+        let data = json!({
+            "lastUpdateId": state.last_update_id,
+            "bids": to_levels(bids),
+            "asks": to_levels(asks),
+        });
+
+        state.send_market_data(&format!("{}@depth20", symbol.to_lowercase()), data);
+    }
+
+    /// Push public trade to `<symbol>@trade` market data stream
+    pub fn send_trade(&self, symbol: &str, price: Price, amount: Amount, side: OrderSide) {
+        let mut state = self.state.lock();
+        state.last_trade_id += 1;
+
+        let data = json!({
+            "e": "trade",
+            "s": symbol.to_uppercase(),
+            "t": state.last_trade_id,
+            "p": price.to_string(),
+            "q": amount.to_string(),
+            "T": Utc::now().timestamp_millis(),
+            "m": side == OrderSide::Sell,
+        });
+
+        state.send_market_data(&format!("{}@trade", symbol.to_lowercase()), data);
+    }
+
+    /// Close all opened websocket connections from the server side
+    pub fn disconnect_websockets(&self) {
+        let mut state = self.state.lock();
+        let mut sessions = state.user_data_sessions.drain(..).collect_vec();
+        sessions.append(&mut state.market_data_sessions);
+
+        for session in sessions {
+            session.do_send(Disconnect);
+        }
+    }
+
+    pub fn websocket_connections_count(&self) -> usize {
+        let state = self.state.lock();
+        state
+            .user_data_sessions
+            .iter()
+            .chain(state.market_data_sessions.iter())
+            .filter(|x| x.connected())
+            .count()
+    }
+
+    pub fn get_order(&self, client_order_id: &ClientOrderId) -> Option<MockOrder> {
+        self.state
+            .lock()
+            .orders
+            .iter()
+            .find(|x| x.client_order_id == client_order_id.as_str())
+            .cloned()
+    }
+
+    pub fn open_orders(&self) -> Vec<MockOrder> {
+        self.state
+            .lock()
+            .orders
+            .iter()
+            .filter(|x| x.status.is_open())
+            .cloned()
+            .collect_vec()
+    }
+}
+
+impl Drop for MockBinance {
+    fn drop(&mut self) {
+        // Stop command is sent immediately, so there is no need to wait for completion
+        let _ = self.server.stop(false);
+    }
+}
+
+fn error_response(code: i64, message: &str) -> HttpResponse {
+    HttpResponse::BadRequest().json(json!({ "code": code, "msg": message }))
+}
+
+fn execution_report(
+    order: &MockOrder,
+    execution_type: &str,
+    trade: Option<&MockTrade>,
+    original_client_order_id: &str,
+) -> Value {
+    let (trade_id, last_price, last_amount, commission, commission_asset, is_maker) = match trade {
+        Some(trade) => (
+            trade.trade_id as i64,
+            trade.price,
+            trade.amount,
+            trade.commission,
+            Value::from(trade.commission_asset.clone()),
+            trade.is_maker,
+        ),
+        None => (
+            -1,
+            Decimal::ZERO,
+            Decimal::ZERO,
+            Decimal::ZERO,
+            Value::Null,
+            false,
+        ),
+    };
+
+    json!({
+        "e": "executionReport",
+        "E": Utc::now().timestamp_millis(),
+        "s": order.symbol,
+        "c": order.client_order_id,
+        "S": order.side,
+        "o": order.order_type,
+        "f": order.time_in_force,
+        "q": order.amount.to_string(),
+        "p": order.price.to_string(),
+        "x": execution_type,
+        "X": order.status.as_str(),
+        "r": "NONE",
+        "i": order.order_id,
+        "l": last_amount.to_string(),
+        "z": order.filled_amount.to_string(),
+        "L": last_price.to_string(),
+        "n": commission.to_string(),
+        "N": commission_asset,
+        "T": Utc::now().timestamp_millis(),
+        "t": trade_id,
+        "m": is_maker,
+        "C": original_client_order_id,
+    })
+}
+
+/// Check that request was signed with mock secret key the same way as Binance expects
+fn parse_signed_params(raw_params: &str) -> Result<HashMap<String, String>, HttpResponse> {
+    let (payload, signature) = match raw_params.rsplit_once("&signature=") {
+        Some(parts) => parts,
+        None => {
+            return Err(error_response(
+                -1102,
+                "Mandatory parameter 'signature' was not sent, was empty/null, or malformed.",
+            ))
+        }
+    };
+
+    let mut hmac = Hmac::<Sha256>::new_from_slice(MOCK_SECRET_KEY.as_bytes())
+        .expect("HMAC can take key of any size");
+    hmac.update(payload.as_bytes());
+    if hex::encode(hmac.finalize().into_bytes()) != signature {
+        return Err(error_response(
+            -1022,
+            "Signature for this request is not valid.",
+        ));
+    }
+
+    let params: HashMap<String, String> = form_urlencoded::parse(payload.as_bytes())
+        .into_owned()
+        .collect();
+    if !params.contains_key("timestamp") {
+        return Err(error_response(
+            -1102,
+            "Mandatory parameter 'timestamp' was not sent, was empty/null, or malformed.",
+        ));
+    }
+
+    Ok(params)
+}
+
+fn check_api_key(req: &HttpRequest) -> Result<(), HttpResponse> {
+    match req.headers().get("X-MBX-APIKEY") {
+        Some(api_key) if api_key == MOCK_API_KEY => Ok(()),
+        _ => Err(HttpResponse::Unauthorized().json(json!({
+            "code": -2015,
+            "msg": "Invalid API-key, IP, or permissions for action.",
+        }))),
+    }
+}
+
+fn check_signed_request(
+    req: &HttpRequest,
+    raw_params: &str,
+) -> Result<HashMap<String, String>, HttpResponse> {
+    check_api_key(req)?;
+    parse_signed_params(raw_params)
+}
+
+fn get_param<'a>(
+    params: &'a HashMap<String, String>,
+    name: &str,
+) -> Result<&'a String, HttpResponse> {
+    params.get(name).ok_or_else(|| {
+        error_response(
+            -1102,
+            &format!(
+                "Mandatory parameter '{}' was not sent, was empty/null, or malformed.",
+                name
+            ),
+        )
+    })
+}
+
+async fn exchange_info(state: web::Data<SharedState>) -> HttpResponse {
+    let symbols = state
+        .lock()
+        .symbols
+        .iter()
+        .map(|x| x.to_json())
+        .collect_vec();
+
+    HttpResponse::Ok().json(json!({
+        "timezone": "UTC",
+        "serverTime": Utc::now().timestamp_millis(),
+        "symbols": symbols,
+    }))
+}
+
+async fn user_data_stream(req: HttpRequest) -> HttpResponse {
+    if let Err(response) = check_api_key(&req) {
+        return response;
+    }
+
+    HttpResponse::Ok().json(json!({ "listenKey": MOCK_LISTEN_KEY }))
+}
+
+async fn create_order(
+    state: web::Data<SharedState>,
+    req: HttpRequest,
+    body: web::Bytes,
+) -> HttpResponse {
+    let params = match check_signed_request(&req, &String::from_utf8_lossy(&body)) {
+        Ok(params) => params,
+        Err(response) => return response,
+    };
+
+    let mut state = state.lock();
+    if let Some(reject) = state.order_creation_rejects.pop_front() {
+        return reject.to_response();
+    }
+
+    let order = match parse_order_to_create(&state, &params) {
+        Ok(order) => order,
+        Err(response) => return response,
+    };
+
+    if state
+        .orders
+        .iter()
+        .any(|x| x.client_order_id == order.client_order_id && x.status.is_open())
+    {
+        return error_response(-2010, "Duplicate order sent.");
+    }
+
+    state.last_order_id += 1;
+    let order = MockOrder {
+        order_id: state.last_order_id,
+        ..order
+    };
+
+    state.send_user_data(execution_report(&order, "NEW", None, ""));
+    state.orders.push(order.clone());
+
+    HttpResponse::Ok().json(order.to_json())
+}
+
+fn parse_order_to_create(
+    state: &MockBinanceState,
+    params: &HashMap<String, String>,
+) -> Result<MockOrder, HttpResponse> {
+    let symbol = get_param(params, "symbol")?;
+    let mock_symbol = state
+        .get_symbol(symbol)
+        .ok_or_else(|| error_response(-1121, "Invalid symbol."))?;
+
+    let side = get_param(params, "side")?;
+    if side != "BUY" && side != "SELL" {
+        return Err(error_response(-1117, "Invalid side."));
+    }
+
+    let order_type = get_param(params, "type")?;
+    let price = match order_type.as_str() {
+        "LIMIT" => get_param(params, "price")?
+            .parse::<Price>()
+            .map_err(|_| error_response(-1100, "Illegal characters found in parameter 'price'."))?,
+        "MARKET" => Decimal::ZERO,
+        _ => return Err(error_response(-1116, "Invalid orderType.")),
+    };
+
+    let amount = get_param(params, "quantity")?
+        .parse::<Amount>()
+        .map_err(|_| error_response(-1100, "Illegal characters found in parameter 'quantity'."))?;
+
+    if order_type == "LIMIT" {
+        if (price / mock_symbol.price_tick).fract() != Decimal::ZERO {
+            return Err(error_response(
+                -1111,
+                "Precision is over the maximum defined for this asset.",
+            ));
+        }
+
+        if price < mock_symbol.min_price || price > mock_symbol.max_price {
+            return Err(error_response(-1013, "Filter failure: PRICE_FILTER"));
+        }
+
+        if price * amount < mock_symbol.min_notional {
+            return Err(error_response(-1013, "Filter failure: MIN_NOTIONAL"));
+        }
+    }
+
+    if (amount / mock_symbol.amount_tick).fract() != Decimal::ZERO
+        || amount < mock_symbol.min_amount
+        || amount > mock_symbol.max_amount
+    {
+        return Err(error_response(-1013, "Filter failure: LOT_SIZE"));
+    }
+
+    Ok(MockOrder {
+        order_id: 0,
+        client_order_id: get_param(params, "newClientOrderId")?.clone(),
+        symbol: symbol.clone(),
+        side: side.clone(),
+        order_type: order_type.clone(),
+        time_in_force: params
+            .get("timeInForce")
+            .cloned()
+            .unwrap_or_else(|| "GTC".to_owned()),
+        price,
+        amount,
+        filled_amount: Decimal::ZERO,
+        status: MockOrderStatus::New,
+    })
+}
+
+async fn cancel_order(state: web::Data<SharedState>, req: HttpRequest) -> HttpResponse {
+    let params = match check_signed_request(&req, req.query_string()) {
+        Ok(params) => params,
+        Err(response) => return response,
+    };
+
+    let mut state = state.lock();
+    if let Some(reject) = state.order_cancellation_rejects.pop_front() {
+        return reject.to_response();
+    }
+
+    let order_id = match get_param(&params, "orderId") {
+        Ok(order_id) => order_id.clone(),
+        Err(response) => return response,
+    };
+
+    let order = state
+        .orders
+        .iter_mut()
+        .find(|x| x.order_id.to_string() == order_id && x.status.is_open());
+    let order = match order {
+        Some(order) => {
+            order.status = MockOrderStatus::Canceled;
+            order.clone()
+        }
+        None => return error_response(-2011, "Unknown order sent."),
+    };
+
+    send_order_cancelled(&state, &order);
+
+    HttpResponse::Ok().json(order.to_json())
+}
+
+async fn cancel_all_orders(state: web::Data<SharedState>, req: HttpRequest) -> HttpResponse {
+    let params = match check_signed_request(&req, req.query_string()) {
+        Ok(params) => params,
+        Err(response) => return response,
+    };
+
+    let symbol = match get_param(&params, "symbol") {
+        Ok(symbol) => symbol.clone(),
+        Err(response) => return response,
+    };
+
+    let mut state = state.lock();
+    let cancelled_orders = state
+        .orders
+        .iter_mut()
+        .filter(|x| x.symbol == symbol && x.status.is_open())
+        .map(|order| {
+            order.status = MockOrderStatus::Canceled;
+            order.clone()
+        })
+        .collect_vec();
+
+    for order in &cancelled_orders {
+        send_order_cancelled(&state, order);
+    }
+
+    HttpResponse::Ok().json(cancelled_orders.iter().map(|x| x.to_json()).collect_vec())
+}
+
+fn send_order_cancelled(state: &MockBinanceState, order: &MockOrder) {
+    let cancel_client_order_id = format!("cancel_{}", order.order_id);
+    let report = execution_report(
+        &MockOrder {
+            client_order_id: cancel_client_order_id,
+            ..order.clone()
+        },
+        "CANCELED",
+        None,
+        &order.client_order_id,
+    );
+
+    state.send_user_data(report);
+}
+
+async fn order_info(state: web::Data<SharedState>, req: HttpRequest) -> HttpResponse {
+    let params = match check_signed_request(&req, req.query_string()) {
+        Ok(params) => params,
+        Err(response) => return response,
+    };
+
+    let client_order_id = match get_param(&params, "origClientOrderId") {
+        Ok(client_order_id) => client_order_id.clone(),
+        Err(response) => return response,
+    };
+
+    let state = state.lock();
+    match state
+        .orders
+        .iter()
+        .find(|x| x.client_order_id == client_order_id)
+    {
+        Some(order) => HttpResponse::Ok().json(order.to_json()),
+        None => error_response(-2013, "Order does not exist."),
+    }
+}
+
+async fn open_orders(state: web::Data<SharedState>, req: HttpRequest) -> HttpResponse {
+    let params = match check_signed_request(&req, req.query_string()) {
+        Ok(params) => params,
+        Err(response) => return response,
+    };
+
+    let mut state = state.lock();
+    state.open_orders_requests_count += 1;
+    if let Some(retry_after_secs) = state.open_orders_rate_limits.pop_front() {
+        return HttpResponse::TooManyRequests()
+            .insert_header(("Retry-After", retry_after_secs.to_string()))
+            .json(
+                json!({ "code": -1003, "msg": "Too many requests; current limit is exceeded." }),
+            );
+    }
+
+    let symbol = params.get("symbol");
+    let orders = state
+        .orders
+        .iter()
+        .filter(|x| x.status.is_open())
+        .filter(|x| symbol.map(|symbol| &x.symbol == symbol).unwrap_or(true))
+        .map(|x| x.to_json())
+        .collect_vec();
+
+    HttpResponse::Ok().json(orders)
+}
+
+async fn my_trades(state: web::Data<SharedState>, req: HttpRequest) -> HttpResponse {
+    let params = match check_signed_request(&req, req.query_string()) {
+        Ok(params) => params,
+        Err(response) => return response,
+    };
+
+    let symbol = match get_param(&params, "symbol") {
+        Ok(symbol) => symbol.clone(),
+        Err(response) => return response,
+    };
+
+    let trades = state
+        .lock()
+        .trades
+        .iter()
+        .filter(|x| x.symbol == symbol)
+        .map(|x| {
+            json!({
+                "symbol": x.symbol,
+                "id": x.trade_id,
+                "orderId": x.order_id,
+                "price": x.price.to_string(),
+                "qty": x.amount.to_string(),
+                "commission": x.commission.to_string(),
+                "commissionAsset": x.commission_asset,
+                "time": x.time,
+                "isMaker": x.is_maker,
+            })
+        })
+        .collect_vec();
+
+    HttpResponse::Ok().json(trades)
+}
+
+async fn user_data_websocket(
+    state: web::Data<SharedState>,
+    req: HttpRequest,
+    listen_key: web::Path<String>,
+    stream: web::Payload,
+) -> actix_web::Result<HttpResponse> {
+    if listen_key.as_str() != MOCK_LISTEN_KEY {
+        return Ok(HttpResponse::BadRequest().finish());
+    }
+
+    let session = MockWebSocketSession {
+        state: state.get_ref().clone(),
+        kind: SessionKind::UserData,
+    };
+    ws::start(session, &req, stream)
+}
+
+async fn market_data_websocket(
+    state: web::Data<SharedState>,
+    req: HttpRequest,
+    query: web::Query<HashMap<String, String>>,
+    stream: web::Payload,
+) -> actix_web::Result<HttpResponse> {
+    let streams = query
+        .get("streams")
+        .map(|x| {
+            x.split('/')
+                .filter(|x| !x.is_empty())
+                .map(|x| x.to_owned())
+                .collect_vec()
+        })
+        .unwrap_or_default();
+
+    let session = MockWebSocketSession {
+        state: state.get_ref().clone(),
+        kind: SessionKind::MarketData { streams },
+    };
+    ws::start(session, &req, stream)
+}
+
+enum SessionKind {
+    UserData,
+    MarketData { streams: Vec<String> },
+}
+
+pub struct MockWebSocketSession {
+    state: SharedState,
+    kind: SessionKind,
+}
+
+impl Actor for MockWebSocketSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let mut state = self.state.lock();
+        match self.kind {
+            SessionKind::UserData => state.user_data_sessions.push(ctx.address()),
+            SessionKind::MarketData { .. } => state.market_data_sessions.push(ctx.address()),
+        }
+    }
+
+    fn stopped(&mut self, ctx: &mut Self::Context) {
+        let address = ctx.address();
+        let mut state = self.state.lock();
+        state.user_data_sessions.retain(|x| *x != address);
+        state.market_data_sessions.retain(|x| *x != address);
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+struct SendText(String);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+struct SendStreamText {
+    stream: String,
+    text: String,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Disconnect;
+
+impl Handler<SendText> for MockWebSocketSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: SendText, ctx: &mut Self::Context) -> Self::Result {
+        ctx.text(msg.0);
+    }
+}
+
+impl Handler<SendStreamText> for MockWebSocketSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: SendStreamText, ctx: &mut Self::Context) -> Self::Result {
+        if let SessionKind::MarketData { streams } = &self.kind {
+            if streams.contains(&msg.stream) {
+                ctx.text(msg.text);
+            }
+        }
+    }
+}
+
+impl Handler<Disconnect> for MockWebSocketSession {
+    type Result = ();
+
+    fn handle(&mut self, _msg: Disconnect, ctx: &mut Self::Context) -> Self::Result {
+        ctx.close(Some(ws::CloseCode::Away.into()));
+        ctx.stop();
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for MockWebSocketSession {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Ok(_) => {}
+            Err(_) => ctx.stop(),
+        }
+    }
+}
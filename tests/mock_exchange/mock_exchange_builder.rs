@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use mmb_lib::core::exchanges::binance::binance::{Binance, BinanceBuilder};
+use mmb_lib::core::exchanges::common::*;
+use mmb_lib::core::exchanges::events::{AllowedEventSourceType, ExchangeEvent};
+use mmb_lib::core::exchanges::exchange_blocker::ExchangeBlocker;
+use mmb_lib::core::exchanges::general::commission::Commission;
+use mmb_lib::core::exchanges::general::exchange::*;
+use mmb_lib::core::exchanges::general::exchange_creation::get_symbols;
+use mmb_lib::core::exchanges::general::features::*;
+use mmb_lib::core::exchanges::paper::paper_exchange::{PaperExchange, PaperExchangeSettings};
+use mmb_lib::core::exchanges::traits::ExchangeClientBuilder;
+use mmb_lib::core::lifecycle::application_manager::ApplicationManager;
+use mmb_lib::core::lifecycle::cancellation_token::CancellationToken;
+use mmb_lib::core::settings::{CurrencyPairSetting, ExchangeSettings};
+
+use anyhow::Result;
+use tokio::sync::broadcast;
+
+use crate::binance::common::get_timeout_manager;
+use crate::mock_exchange::mock_binance::{MockBinance, MockSymbol, MOCK_API_KEY, MOCK_SECRET_KEY};
+
+/// Creates Binance based `Exchange` connected to the local `MockBinance` server
+pub struct MockExchangeBuilder {
+    pub mock: MockBinance,
+    pub exchange: Arc<Exchange>,
+    pub tx: broadcast::Sender<ExchangeEvent>,
+    pub rx: broadcast::Receiver<ExchangeEvent>,
+    pub exchange_blocker: Arc<ExchangeBlocker>,
+}
+
+impl MockExchangeBuilder {
+    pub async fn try_new(
+        exchange_account_id: ExchangeAccountId,
+        cancellation_token: CancellationToken,
+        symbols: Vec<MockSymbol>,
+    ) -> Result<MockExchangeBuilder> {
+        Self::try_new_with_client(
+            exchange_account_id,
+            cancellation_token,
+            symbols,
+            |binance, _, _| Box::new(binance),
+        )
+        .await
+    }
+
+    /// Creates `PaperExchange` which takes metadata and market data from `MockBinance`
+    pub async fn try_new_paper(
+        exchange_account_id: ExchangeAccountId,
+        cancellation_token: CancellationToken,
+        symbols: Vec<MockSymbol>,
+        paper_settings: PaperExchangeSettings,
+    ) -> Result<MockExchangeBuilder> {
+        Self::try_new_with_client(
+            exchange_account_id,
+            cancellation_token,
+            symbols,
+            |binance, tx, application_manager| {
+                Box::new(PaperExchange::new(
+                    binance.id.clone(),
+                    binance.settings.clone(),
+                    paper_settings,
+                    Box::new(binance),
+                    tx,
+                    application_manager,
+                ))
+            },
+        )
+        .await
+    }
+
+    async fn try_new_with_client(
+        exchange_account_id: ExchangeAccountId,
+        cancellation_token: CancellationToken,
+        symbols: Vec<MockSymbol>,
+        create_client: impl FnOnce(
+            Binance,
+            broadcast::Sender<ExchangeEvent>,
+            Arc<ApplicationManager>,
+        ) -> BoxExchangeClient,
+    ) -> Result<MockExchangeBuilder> {
+        let mock = MockBinance::start(symbols.clone())?;
+
+        let mut settings = ExchangeSettings::new_short(
+            exchange_account_id.clone(),
+            MOCK_API_KEY.to_owned(),
+            MOCK_SECRET_KEY.to_owned(),
+            false,
+        );
+        settings.websocket_channels = vec!["depth20".into(), "trade".into()];
+        settings.currency_pairs = Some(
+            symbols
+                .iter()
+                .map(|x| CurrencyPairSetting {
+                    base: x.base_asset.as_str().into(),
+                    quote: x.quote_asset.as_str().into(),
+                    currency_pair: None,
+                })
+                .collect(),
+        );
+
+        let application_manager = ApplicationManager::new(cancellation_token);
+        let (tx, rx) = broadcast::channel(100);
+
+        let mut binance = Binance::new(
+            exchange_account_id.clone(),
+            settings.clone(),
+            tx.clone(),
+            application_manager.clone(),
+            false,
+        );
+        binance.hosts = mock.hosts();
+
+        let exchange_blocker = ExchangeBlocker::new(vec![exchange_account_id.clone()]);
+
+        let exchange = Exchange::new(
+            exchange_account_id.clone(),
+            create_client(binance, tx.clone(), application_manager.clone()),
+            Self::default_features(),
+            BinanceBuilder.get_timeout_argments(),
+            tx.clone(),
+            application_manager,
+            get_timeout_manager(&exchange_account_id),
+            Commission::default(),
+            None,
+            Some(exchange_blocker.clone()),
+        );
+        exchange.build_metadata().await;
+
+        if let Some(currency_pairs) = &settings.currency_pairs {
+            exchange.set_symbols(get_symbols(&exchange, &currency_pairs[..]));
+        }
+
+        exchange.clone().connect().await;
+
+        Ok(MockExchangeBuilder {
+            mock,
+            exchange,
+            tx,
+            rx,
+            exchange_blocker,
+        })
+    }
+
+    pub fn default_features() -> ExchangeFeatures {
+        ExchangeFeatures::new(
+            OpenOrdersType::AllCurrencyPair,
+            RestFillsFeatures::default(),
+            OrderFeatures::default(),
+            OrderTradeOption::default(),
+            WebSocketOptions::default(),
+            // Binance answers with an empty array when there are no open orders
+            true,
+            true,
+            AllowedEventSourceType::default(),
+            AllowedEventSourceType::default(),
+        )
+    }
+}
@@ -0,0 +1,5 @@
+pub mod mock_binance;
+pub mod mock_exchange_builder;
+pub mod order_lifecycle;
+pub mod paper_exchange;
+pub mod websocket_connection;
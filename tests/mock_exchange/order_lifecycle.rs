@@ -0,0 +1,420 @@
+use std::time::Duration;
+
+use mmb_lib::core::exchanges::block_reasons;
+use mmb_lib::core::exchanges::common::*;
+use mmb_lib::core::exchanges::events::ExchangeEvent;
+use mmb_lib::core::exchanges::general::exchange::RequestResult;
+use mmb_lib::core::exchanges::general::order::cancel_all::OrdersCancellationOutcome;
+use mmb_lib::core::lifecycle::cancellation_token::CancellationToken;
+use mmb_lib::core::logger::init_logger;
+use mmb_lib::core::orders::event::{OrderEvent, OrderEventType};
+use mmb_lib::core::orders::order::{OrderSide, OrderStatus};
+use rust_decimal_macros::dec;
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+
+use crate::core::misc::with_timeout::with_timeout;
+use crate::core::order::OrderProxy;
+use crate::mock_exchange::mock_binance::{MockOrderStatus, MockReject, MockSymbol};
+use crate::mock_exchange::mock_exchange_builder::MockExchangeBuilder;
+
+const EVENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn create_builder(exchange_account_id: &ExchangeAccountId) -> MockExchangeBuilder {
+    MockExchangeBuilder::try_new(
+        exchange_account_id.clone(),
+        CancellationToken::default(),
+        vec![MockSymbol::new("phb", "btc")],
+    )
+    .await
+    .expect("in test")
+}
+
+/// Skip market data events and return next order event
+async fn next_order_event(rx: &mut broadcast::Receiver<ExchangeEvent>) -> OrderEvent {
+    with_timeout(EVENT_TIMEOUT, async {
+        loop {
+            if let ExchangeEvent::OrderEvent(order_event) = rx.recv().await? {
+                return Ok(order_event);
+            }
+        }
+    })
+    .await
+    .expect("in test")
+}
+
+#[actix_rt::test]
+async fn create_successfully() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    let mut builder = create_builder(&exchange_account_id).await;
+
+    let order_proxy = OrderProxy::new(
+        exchange_account_id.clone(),
+        Some("FromMockCreateSuccessfullyTest".to_owned()),
+        CancellationToken::default(),
+    );
+
+    let order_ref = order_proxy
+        .create_order(builder.exchange.clone())
+        .await
+        .expect("in test");
+
+    let order_event = next_order_event(&mut builder.rx).await;
+    assert!(matches!(
+        order_event.event_type,
+        OrderEventType::CreateOrderSucceeded
+    ));
+
+    assert_eq!(order_ref.status(), OrderStatus::Created);
+    let mock_order = builder
+        .mock
+        .get_order(&order_proxy.client_order_id)
+        .expect("in test");
+    assert_eq!(mock_order.status, MockOrderStatus::New);
+    assert_eq!(mock_order.amount, order_proxy.amount);
+    assert_eq!(mock_order.price, order_proxy.price);
+    assert_eq!(
+        order_ref.exchange_order_id().expect("in test").as_str(),
+        mock_order.order_id.to_string()
+    );
+}
+
+#[actix_rt::test]
+async fn create_rejected() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    let mut builder = create_builder(&exchange_account_id).await;
+
+    builder.mock.reject_next_order_creation(MockReject::new(
+        -2010,
+        "Account has insufficient balance for requested action.",
+    ));
+
+    let order_proxy = OrderProxy::new(
+        exchange_account_id.clone(),
+        Some("FromMockCreateRejectedTest".to_owned()),
+        CancellationToken::default(),
+    );
+
+    let error = order_proxy
+        .create_order(builder.exchange.clone())
+        .await
+        .expect_err("in test");
+    assert_eq!(
+        error.to_string(),
+        "Exchange error: Account has insufficient balance for requested action."
+    );
+
+    let order_event = next_order_event(&mut builder.rx).await;
+    assert!(matches!(
+        order_event.event_type,
+        OrderEventType::CreateOrderFailed
+    ));
+    assert_eq!(order_event.order.status(), OrderStatus::FailedToCreate);
+    assert_eq!(
+        order_event
+            .order
+            .fn_ref(|x| x.internal_props.last_creation_error_type.clone()),
+        Some(ExchangeErrorType::InsufficientFunds)
+    );
+    assert!(builder.mock.open_orders().is_empty());
+}
+
+#[actix_rt::test]
+async fn create_with_invalid_precision() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    let builder = create_builder(&exchange_account_id).await;
+
+    let mut order_proxy = OrderProxy::new(
+        exchange_account_id.clone(),
+        Some("FromMockInvalidPrecisionTest".to_owned()),
+        CancellationToken::default(),
+    );
+    order_proxy.amount = dec!(1);
+    order_proxy.price = dec!(0.0000000000000000001);
+
+    let error = order_proxy
+        .create_order(builder.exchange.clone())
+        .await
+        .expect_err("in test");
+    assert_eq!(
+        error.to_string(),
+        "Exchange error: Precision is over the maximum defined for this asset."
+    );
+}
+
+#[actix_rt::test]
+async fn cancel_successfully() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    let mut builder = create_builder(&exchange_account_id).await;
+
+    let order_proxy = OrderProxy::new(
+        exchange_account_id.clone(),
+        Some("FromMockCancelSuccessfullyTest".to_owned()),
+        CancellationToken::default(),
+    );
+    let order_ref = order_proxy
+        .create_order(builder.exchange.clone())
+        .await
+        .expect("in test");
+    let _ = next_order_event(&mut builder.rx).await;
+
+    order_proxy
+        .cancel_order_or_fail(&order_ref, builder.exchange.clone())
+        .await;
+
+    let order_event = next_order_event(&mut builder.rx).await;
+    assert!(matches!(
+        order_event.event_type,
+        OrderEventType::CancelOrderSucceeded
+    ));
+    assert_eq!(order_ref.status(), OrderStatus::Canceled);
+    assert_eq!(
+        builder
+            .mock
+            .get_order(&order_proxy.client_order_id)
+            .expect("in test")
+            .status,
+        MockOrderStatus::Canceled
+    );
+}
+
+#[actix_rt::test]
+async fn cancel_rejected() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    let mut builder = create_builder(&exchange_account_id).await;
+
+    let order_proxy = OrderProxy::new(
+        exchange_account_id.clone(),
+        Some("FromMockCancelRejectedTest".to_owned()),
+        CancellationToken::default(),
+    );
+    let order_ref = order_proxy
+        .create_order(builder.exchange.clone())
+        .await
+        .expect("in test");
+    let _ = next_order_event(&mut builder.rx).await;
+
+    builder.mock.reject_next_order_cancellation(MockReject::new(
+        -1000,
+        "An unknown error occurred while processing the request.",
+    ));
+
+    let order_to_cancel = order_ref.to_order_cancelling().expect("in test");
+    let cancel_outcome = builder
+        .exchange
+        .cancel_order(&order_to_cancel, CancellationToken::default())
+        .await
+        .expect("in test")
+        .expect("in test");
+
+    match cancel_outcome.outcome {
+        RequestResult::Error(error) => assert_eq!(error.error_type, ExchangeErrorType::Unknown),
+        RequestResult::Success(_) => panic!("Order cancellation should fail"),
+    }
+
+    let order_event = next_order_event(&mut builder.rx).await;
+    assert!(matches!(
+        order_event.event_type,
+        OrderEventType::CancelOrderFailed
+    ));
+    assert_eq!(order_ref.status(), OrderStatus::FailedToCancel);
+    assert_eq!(builder.mock.open_orders().len(), 1);
+}
+
+#[actix_rt::test]
+async fn fill_partially_and_completely() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    let mut builder = create_builder(&exchange_account_id).await;
+
+    let order_proxy = OrderProxy::new(
+        exchange_account_id.clone(),
+        Some("FromMockFillTest".to_owned()),
+        CancellationToken::default(),
+    );
+    let order_ref = order_proxy
+        .create_order(builder.exchange.clone())
+        .await
+        .expect("in test");
+    let _ = next_order_event(&mut builder.rx).await;
+
+    builder
+        .mock
+        .fill_order(&order_proxy.client_order_id, None, dec!(400))
+        .expect("in test");
+
+    let order_event = next_order_event(&mut builder.rx).await;
+    assert!(matches!(
+        order_event.event_type,
+        OrderEventType::OrderFilled { .. }
+    ));
+    assert_eq!(order_ref.filled_amount(), dec!(400));
+    assert_eq!(order_ref.status(), OrderStatus::Created);
+
+    builder
+        .mock
+        .fill_order(&order_proxy.client_order_id, None, dec!(600))
+        .expect("in test");
+
+    let order_event = next_order_event(&mut builder.rx).await;
+    assert!(matches!(
+        order_event.event_type,
+        OrderEventType::OrderFilled { .. }
+    ));
+    let order_event = next_order_event(&mut builder.rx).await;
+    assert!(matches!(
+        order_event.event_type,
+        OrderEventType::OrderCompleted { .. }
+    ));
+
+    assert_eq!(order_ref.filled_amount(), order_proxy.amount);
+    assert_eq!(order_ref.status(), OrderStatus::Completed);
+    assert!(builder.mock.open_orders().is_empty());
+}
+
+#[actix_rt::test]
+async fn get_open_orders_and_cancel_them() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    let mut builder = create_builder(&exchange_account_id).await;
+
+    for side in [OrderSide::Buy, OrderSide::Sell] {
+        let mut order_proxy = OrderProxy::new(
+            exchange_account_id.clone(),
+            Some("FromMockGetOpenOrdersTest".to_owned()),
+            CancellationToken::default(),
+        );
+        order_proxy.side = side;
+        order_proxy
+            .create_order(builder.exchange.clone())
+            .await
+            .expect("in test");
+        let _ = next_order_event(&mut builder.rx).await;
+    }
+
+    let open_orders = builder
+        .exchange
+        .get_open_orders(false)
+        .await
+        .expect("in test");
+    assert_eq!(open_orders.len(), 2);
+
+    builder
+        .exchange
+        .clone()
+        .cancel_opened_orders(CancellationToken::default(), false)
+        .await;
+
+    assert!(builder.mock.open_orders().is_empty());
+    let open_orders = builder
+        .exchange
+        .get_open_orders(false)
+        .await
+        .expect("in test");
+    assert!(open_orders.is_empty());
+}
+
+#[actix_rt::test]
+async fn cancel_all_orders_until_deadline() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    let mut builder = create_builder(&exchange_account_id).await;
+
+    for side in [OrderSide::Buy, OrderSide::Sell] {
+        let mut order_proxy = OrderProxy::new(
+            exchange_account_id.clone(),
+            Some("FromMockCancelAllOrdersUntilDeadlineTest".to_owned()),
+            CancellationToken::default(),
+        );
+        order_proxy.side = side;
+        order_proxy
+            .create_order(builder.exchange.clone())
+            .await
+            .expect("in test");
+        let _ = next_order_event(&mut builder.rx).await;
+    }
+    assert_eq!(builder.mock.open_orders().len(), 2);
+
+    // Mock exchange doesn't support cancellation of all orders, so they are cancelled one by one.
+    // Retry interval exceeds the deadline, so orders should be cancelled on the first attempt
+    let outcome = builder
+        .exchange
+        .cancel_all_orders_until_deadline(Instant::now() + EVENT_TIMEOUT, EVENT_TIMEOUT * 2)
+        .await;
+
+    assert_eq!(outcome, OrdersCancellationOutcome::Cancelled);
+    assert!(builder.mock.open_orders().is_empty());
+}
+
+#[actix_rt::test]
+async fn get_order_info() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    let mut builder = create_builder(&exchange_account_id).await;
+
+    let order_proxy = OrderProxy::new(
+        exchange_account_id.clone(),
+        Some("FromMockGetOrderInfoTest".to_owned()),
+        CancellationToken::default(),
+    );
+    let order_ref = order_proxy
+        .create_order(builder.exchange.clone())
+        .await
+        .expect("in test");
+    let _ = next_order_event(&mut builder.rx).await;
+
+    let order_info = builder
+        .exchange
+        .get_order_info(&order_ref)
+        .await
+        .expect("in test");
+
+    assert_eq!(order_info.client_order_id, order_proxy.client_order_id);
+    assert_eq!(order_info.order_status, OrderStatus::Created);
+    assert_eq!(order_info.price, order_proxy.price);
+    assert_eq!(order_info.amount, order_proxy.amount);
+}
+
+#[actix_rt::test]
+async fn requests_wait_for_end_of_rest_rate_limit_ban() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    let builder = create_builder(&exchange_account_id).await;
+    // Longer than delay between retries of get_open_orders
+    let retry_after = Duration::from_secs(3);
+    builder
+        .mock
+        .rate_limit_next_open_orders_request(retry_after.as_secs());
+
+    let exchange = builder.exchange.clone();
+    let request = tokio::spawn(async move { exchange.get_open_orders(false).await });
+
+    // Retry of rate limited request isn't sent until the end of the ban
+    tokio::time::sleep(retry_after * 2 / 3).await;
+    assert!(builder
+        .exchange_blocker
+        .is_blocked_by_reason(&exchange_account_id, block_reasons::REST_RATE_LIMIT));
+    assert_eq!(builder.mock.open_orders_requests_count(), 1);
+
+    let open_orders = with_timeout(EVENT_TIMEOUT, async { Ok(request.await?) })
+        .await
+        .expect("in test")
+        .expect("in test");
+    assert!(open_orders.is_empty());
+    assert_eq!(builder.mock.open_orders_requests_count(), 2);
+}
@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use mmb_lib::core::exchanges::common::*;
+use mmb_lib::core::exchanges::events::ExchangeEvent;
+use mmb_lib::core::exchanges::general::commission::{Commission, CommissionForType};
+use mmb_lib::core::exchanges::paper::paper_exchange::PaperExchangeSettings;
+use mmb_lib::core::lifecycle::cancellation_token::CancellationToken;
+use mmb_lib::core::logger::init_logger;
+use mmb_lib::core::orders::event::{OrderEvent, OrderEventType};
+use mmb_lib::core::orders::order::{OrderExecutionType, OrderRole, OrderSide, OrderStatus};
+use rust_decimal_macros::dec;
+use tokio::sync::broadcast;
+
+use crate::core::misc::with_timeout::with_timeout;
+use crate::core::order::OrderProxy;
+use crate::mock_exchange::mock_binance::MockSymbol;
+use crate::mock_exchange::mock_exchange_builder::MockExchangeBuilder;
+
+const EVENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn create_builder(exchange_account_id: &ExchangeAccountId) -> MockExchangeBuilder {
+    let fee = |fee| CommissionForType::new(fee, dec!(0));
+    let paper_settings = PaperExchangeSettings {
+        commission: Commission::new(fee(dec!(0.1)), fee(dec!(0.2))),
+        latency: Duration::from_millis(10),
+        // Enough to fund any order of the tests
+        balances: vec![("phb".into(), dec!(1000000)), ("btc".into(), dec!(1000000))]
+            .into_iter()
+            .collect(),
+    };
+
+    MockExchangeBuilder::try_new_paper(
+        exchange_account_id.clone(),
+        CancellationToken::default(),
+        vec![MockSymbol::new("phb", "btc")],
+        paper_settings,
+    )
+    .await
+    .expect("in test")
+}
+
+/// Skip market data events and return next order event
+async fn next_order_event(rx: &mut broadcast::Receiver<ExchangeEvent>) -> OrderEvent {
+    with_timeout(EVENT_TIMEOUT, async {
+        loop {
+            if let ExchangeEvent::OrderEvent(order_event) = rx.recv().await? {
+                return Ok(order_event);
+            }
+        }
+    })
+    .await
+    .expect("in test")
+}
+
+#[actix_rt::test]
+async fn create_and_cancel_order() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "PaperBinance0".parse().expect("in test");
+    let mut builder = create_builder(&exchange_account_id).await;
+
+    let order_proxy = OrderProxy::new(
+        exchange_account_id.clone(),
+        Some("FromPaperCreateAndCancelTest".to_owned()),
+        CancellationToken::default(),
+    );
+    let order_ref = order_proxy
+        .create_order(builder.exchange.clone())
+        .await
+        .expect("in test");
+
+    let order_event = next_order_event(&mut builder.rx).await;
+    assert!(matches!(
+        order_event.event_type,
+        OrderEventType::CreateOrderSucceeded
+    ));
+    assert_eq!(order_ref.status(), OrderStatus::Created);
+    // Order mustn't be sent to the real exchange
+    assert!(builder.mock.open_orders().is_empty());
+
+    let open_orders = builder
+        .exchange
+        .get_open_orders(false)
+        .await
+        .expect("in test");
+    assert_eq!(open_orders.len(), 1);
+
+    order_proxy
+        .cancel_order_or_fail(&order_ref, builder.exchange.clone())
+        .await;
+
+    let order_event = next_order_event(&mut builder.rx).await;
+    assert!(matches!(
+        order_event.event_type,
+        OrderEventType::CancelOrderSucceeded
+    ));
+    assert_eq!(order_ref.status(), OrderStatus::Canceled);
+}
+
+#[actix_rt::test]
+async fn fill_order_by_market_data() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "PaperBinance0".parse().expect("in test");
+    let mut builder = create_builder(&exchange_account_id).await;
+
+    let order_proxy = OrderProxy::new(
+        exchange_account_id.clone(),
+        Some("FromPaperFillTest".to_owned()),
+        CancellationToken::default(),
+    );
+    let order_ref = order_proxy
+        .create_order(builder.exchange.clone())
+        .await
+        .expect("in test");
+    let _ = next_order_event(&mut builder.rx).await;
+
+    builder.mock.send_order_book_snapshot(
+        "PHBBTC",
+        &[(dec!(0.00000008), dec!(5000))],
+        &[(dec!(0.00000009), dec!(400)), (dec!(0.0000001), dec!(5000))],
+    );
+
+    let order_event = next_order_event(&mut builder.rx).await;
+    assert!(matches!(
+        order_event.event_type,
+        OrderEventType::OrderFilled { .. }
+    ));
+    let order_event = next_order_event(&mut builder.rx).await;
+    assert!(matches!(
+        order_event.event_type,
+        OrderEventType::OrderFilled { .. }
+    ));
+    let order_event = next_order_event(&mut builder.rx).await;
+    assert!(matches!(
+        order_event.event_type,
+        OrderEventType::OrderCompleted { .. }
+    ));
+
+    assert_eq!(order_ref.status(), OrderStatus::Completed);
+    assert_eq!(order_ref.filled_amount(), order_proxy.amount);
+
+    let (fills, _) = order_ref.get_fills();
+    assert_eq!(fills.len(), 2);
+    for fill in fills {
+        // Order didn't cross the book when it was placed
+        assert_eq!(OrderRole::from(fill.role()), OrderRole::Maker);
+        assert_eq!(fill.price(), order_proxy.price);
+        assert_eq!(fill.commission_amount(), fill.amount() * dec!(0.001));
+    }
+}
+
+#[actix_rt::test]
+async fn reject_maker_only_order_crossing_book() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "PaperBinance0".parse().expect("in test");
+    let mut builder = create_builder(&exchange_account_id).await;
+
+    builder.mock.send_order_book_snapshot(
+        "PHBBTC",
+        &[(dec!(0.00000008), dec!(5000))],
+        &[(dec!(0.00000009), dec!(5000))],
+    );
+    with_timeout(EVENT_TIMEOUT, async {
+        loop {
+            if let ExchangeEvent::OrderBookEvent(_) = builder.rx.recv().await? {
+                return Ok(());
+            }
+        }
+    })
+    .await
+    .expect("in test");
+    // Let PaperExchange apply the same order book event
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut order_proxy = OrderProxy::new(
+        exchange_account_id.clone(),
+        Some("FromPaperMakerOnlyTest".to_owned()),
+        CancellationToken::default(),
+    );
+    order_proxy.side = OrderSide::Buy;
+    order_proxy.execution_type = OrderExecutionType::MakerOnly;
+
+    let error = order_proxy
+        .create_order(builder.exchange.clone())
+        .await
+        .expect_err("in test");
+    assert_eq!(
+        error.to_string(),
+        "Exchange error: Order would immediately match and take."
+    );
+}
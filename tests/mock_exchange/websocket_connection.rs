@@ -0,0 +1,179 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::Future;
+use mmb_lib::core::connectivity::connectivity_manager::ConnectivityManager;
+use mmb_lib::core::connectivity::websocket_actor::WebSocketParams;
+use mmb_lib::core::exchanges::block_reasons;
+use mmb_lib::core::exchanges::common::{CurrencyPair, ExchangeAccountId};
+use mmb_lib::core::exchanges::events::ExchangeEvent;
+use mmb_lib::core::lifecycle::cancellation_token::CancellationToken;
+use mmb_lib::core::logger::init_logger;
+use mmb_lib::core::orders::order::OrderSide;
+use parking_lot::Mutex;
+use rust_decimal_macros::dec;
+use tokio::sync::oneshot;
+use tokio::time::sleep;
+
+use crate::core::misc::with_timeout::with_timeout;
+use crate::mock_exchange::mock_binance::MockSymbol;
+use crate::mock_exchange::mock_exchange_builder::MockExchangeBuilder;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn wait_until(condition: impl Fn() -> bool) {
+    with_timeout(TIMEOUT, async {
+        while !condition() {
+            sleep(Duration::from_millis(10)).await;
+        }
+        Ok(())
+    })
+    .await
+    .expect("in test");
+}
+
+#[actix_rt::test]
+async fn receive_market_data() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    let mut builder = MockExchangeBuilder::try_new(
+        exchange_account_id.clone(),
+        CancellationToken::default(),
+        vec![MockSymbol::new("phb", "btc")],
+    )
+    .await
+    .expect("in test");
+
+    // Public trades aren't forwarded as events when exchange is subscribed to market data,
+    // so the next event has to be the order book snapshot
+    builder
+        .mock
+        .send_trade("PHBBTC", dec!(0.00000011), dec!(10), OrderSide::Sell);
+    builder.mock.send_order_book_snapshot(
+        "PHBBTC",
+        &[(dec!(0.00000010), dec!(100))],
+        &[(dec!(0.00000012), dec!(200))],
+    );
+
+    let event = with_timeout(TIMEOUT, async { Ok(builder.rx.recv().await?) })
+        .await
+        .expect("in test");
+    match event {
+        ExchangeEvent::OrderBookEvent(order_book_event) => {
+            assert_eq!(order_book_event.exchange_account_id, exchange_account_id);
+            assert_eq!(
+                order_book_event.currency_pair,
+                CurrencyPair::from_codes(&"phb".into(), &"btc".into())
+            );
+        }
+        _ => panic!("Should receive OrderBookEvent"),
+    }
+}
+
+#[actix_rt::test]
+async fn reconnect_after_server_disconnect() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    let builder = MockExchangeBuilder::try_new(
+        exchange_account_id.clone(),
+        CancellationToken::default(),
+        vec![MockSymbol::new("phb", "btc")],
+    )
+    .await
+    .expect("in test");
+
+    let exchange_weak = Arc::downgrade(&builder.exchange);
+    let connectivity_manager = ConnectivityManager::new(exchange_account_id.clone());
+
+    let connected_count = Arc::new(Mutex::new(0));
+    {
+        let connected_count = connected_count.clone();
+        connectivity_manager.set_callback_connected(Box::new(move || *connected_count.lock() += 1));
+    }
+
+    let (disconnected_sender, disconnected_receiver) = oneshot::channel();
+    let disconnected_sender = Mutex::new(Some(disconnected_sender));
+    connectivity_manager.set_callback_disconnected(Box::new(move |_| {
+        if let Some(sender) = disconnected_sender.lock().take() {
+            let _ = sender.send(());
+        }
+    }));
+
+    let get_websocket_params = Box::new(move |websocket_role| {
+        let exchange = exchange_weak.upgrade().expect("in test");
+        let params = exchange.get_websocket_params(websocket_role);
+        Box::pin(params) as Pin<Box<dyn Future<Output = Result<WebSocketParams>>>>
+    });
+
+    assert!(
+        connectivity_manager
+            .clone()
+            .connect(false, get_websocket_params.clone())
+            .await,
+        "websocket should connect successfully"
+    );
+
+    // 2 connections from exchange itself and 1 from the test connectivity manager
+    assert_eq!(builder.mock.websocket_connections_count(), 3);
+
+    builder.mock.disconnect_websockets();
+    with_timeout(TIMEOUT, async { Ok(disconnected_receiver.await?) })
+        .await
+        .expect("in test");
+
+    assert!(
+        connectivity_manager
+            .clone()
+            .connect(false, get_websocket_params.clone())
+            .await,
+        "websocket should reconnect successfully"
+    );
+    assert_eq!(*connected_count.lock(), 2);
+
+    connectivity_manager.clone().disconnect().await;
+}
+
+#[actix_rt::test]
+async fn exchange_is_blocked_until_order_book_snapshot_after_reconnect() {
+    init_logger();
+
+    let exchange_account_id: ExchangeAccountId = "Binance0".parse().expect("in test");
+    let builder = MockExchangeBuilder::try_new(
+        exchange_account_id.clone(),
+        CancellationToken::default(),
+        vec![MockSymbol::new("phb", "btc")],
+    )
+    .await
+    .expect("in test");
+    let is_blocked_by_reconnect = || {
+        builder.exchange_blocker.is_blocked_by_reason(
+            &exchange_account_id,
+            block_reasons::CONNECTIVITY_MANAGER_RECONNECT,
+        )
+    };
+
+    builder.mock.disconnect_websockets();
+    wait_until(|| builder.exchange.is_websocket_reconnecting()).await;
+    assert!(is_blocked_by_reconnect());
+
+    // Both public and private streams are resubscribed
+    wait_until(|| {
+        !builder.exchange.is_websocket_reconnecting()
+            && builder.mock.websocket_connections_count() == 2
+    })
+    .await;
+    assert!(is_blocked_by_reconnect());
+
+    builder.mock.send_order_book_snapshot(
+        "PHBBTC",
+        &[(dec!(0.00000010), dec!(100))],
+        &[(dec!(0.00000012), dec!(200))],
+    );
+    wait_until(|| !is_blocked_by_reconnect()).await;
+
+    builder.exchange_blocker.stop_blocker().await;
+}
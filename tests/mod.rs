@@ -2,3 +2,4 @@ pub mod binance;
 pub mod control_panel;
 pub mod core;
 pub mod lifecycle;
+pub mod mock_exchange;